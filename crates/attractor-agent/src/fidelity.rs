@@ -1,4 +1,56 @@
 //! Context fidelity modes control how conversation history is managed as it grows.
+//!
+//! `AgentSession` applies its [`SessionConfig::fidelity`](crate::SessionConfig)
+//! to the history on every request:
+//!   - `full`: every turn, verbatim
+//!   - `truncate:N`: the last N turns, widened so that no tool result loses
+//!     the call it answers and the latest user input is always kept
+//!   - `truncate_chars:N`: every turn; the mode cuts the prior results in a
+//!     pipeline prompt to their last N characters, not the conversation
+//!   - `compact`: every turn, but the tool outputs of earlier exchanges are
+//!     dropped; the assistant's text and tool calls (its decisions) stay
+//!   - `summary:low|medium|high`: before each new input the earlier
+//!     conversation is replaced with an LLM-written summary of it
+
+use crate::Turn;
+
+/// How much detail a `summary` fidelity keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SummaryDetail {
+    Low,
+    #[default]
+    Medium,
+    High,
+}
+
+impl SummaryDetail {
+    /// Parse "low", "medium" or "high".
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+
+    /// Rough length of a summary at this level, in words.
+    pub fn word_budget(&self) -> usize {
+        match self {
+            Self::Low => 150,
+            Self::Medium => 400,
+            Self::High => 1000,
+        }
+    }
+}
 
 /// Fidelity mode for managing conversation context.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -8,10 +60,12 @@ pub enum FidelityMode {
     Full,
     /// Truncate old messages, keeping the most recent N messages.
     Truncate { keep_last: usize },
-    /// Compact old messages into a summary.
+    /// Keep the last N characters of each prior result in a pipeline prompt.
+    TruncateChars { keep_last: usize },
+    /// Keep every message but drop the tool outputs of earlier exchanges.
     Compact,
     /// Replace history with an LLM-generated summary.
-    Summary,
+    Summary { detail: SummaryDetail },
 }
 
 impl FidelityMode {
@@ -20,15 +74,23 @@ impl FidelityMode {
         match s.to_lowercase().as_str() {
             "full" => FidelityMode::Full,
             "compact" => FidelityMode::Compact,
-            "summary" => FidelityMode::Summary,
+            s if s.starts_with("summary") => {
+                // Parse "summary:low" or "summary(high)"
+                let detail = argument(s, "summary");
+                FidelityMode::Summary {
+                    detail: SummaryDetail::parse(detail).unwrap_or_default(),
+                }
+            }
+            s if s.starts_with("truncate_chars") => {
+                // Parse "truncate_chars:N" or "truncate_chars(N)"
+                let n = argument(s, "truncate_chars")
+                    .parse::<usize>()
+                    .unwrap_or(2000);
+                FidelityMode::TruncateChars { keep_last: n }
+            }
             s if s.starts_with("truncate") => {
                 // Parse "truncate:N" or "truncate(N)"
-                let n = s
-                    .trim_start_matches("truncate")
-                    .trim_start_matches(|c: char| c == ':' || c == '(' || c.is_whitespace())
-                    .trim_end_matches(')')
-                    .parse::<usize>()
-                    .unwrap_or(50);
+                let n = argument(s, "truncate").parse::<usize>().unwrap_or(50);
                 FidelityMode::Truncate { keep_last: n }
             }
             _ => FidelityMode::Full,
@@ -36,23 +98,126 @@ impl FidelityMode {
     }
 }
 
-/// Apply fidelity mode to a message history.
-/// Returns the processed messages (does NOT modify in place).
-pub fn apply_fidelity<T: Clone>(messages: &[T], mode: &FidelityMode) -> Vec<T> {
+/// The argument of a `mode:arg` or `mode(arg)` value.
+fn argument<'a>(s: &'a str, mode: &str) -> &'a str {
+    s.trim_start_matches(mode)
+        .trim_start_matches(|c: char| c == ':' || c == '(' || c.is_whitespace())
+        .trim_end_matches(')')
+}
+
+/// Apply fidelity mode to a conversation history.
+/// Returns the processed turns (does NOT modify in place).
+///
+/// `Summary` needs an LLM call, so it is applied by the session before each
+/// input (see [`summary_prompt`]); here it keeps every turn.
+pub fn apply_fidelity(history: &[Turn], mode: &FidelityMode) -> Vec<Turn> {
     match mode {
-        FidelityMode::Full => messages.to_vec(),
+        FidelityMode::Full | FidelityMode::Summary { .. } | FidelityMode::TruncateChars { .. } => {
+            history.to_vec()
+        }
         FidelityMode::Truncate { keep_last } => {
-            if messages.len() <= *keep_last {
-                messages.to_vec()
-            } else {
-                messages[messages.len() - keep_last..].to_vec()
+            if history.len() <= *keep_last {
+                return history.to_vec();
             }
+            let last_user = history
+                .iter()
+                .rposition(|turn| matches!(turn, Turn::User { .. }));
+            let mut start = history.len() - keep_last;
+            match last_user {
+                // The cut falls inside the current exchange: keep its input
+                // and start at a whole tool round
+                Some(user) if start > user => {
+                    while matches!(history[start], Turn::ToolResults { .. }) {
+                        start -= 1;
+                    }
+                    let mut kept = vec![history[user].clone()];
+                    kept.extend_from_slice(&history[start..]);
+                    kept
+                }
+                // Otherwise start at the beginning of an exchange
+                _ => {
+                    while start > 0 && !matches!(history[start], Turn::User { .. }) {
+                        start -= 1;
+                    }
+                    history[start..].to_vec()
+                }
+            }
+        }
+        FidelityMode::Compact => {
+            let current = history
+                .iter()
+                .rposition(|turn| matches!(turn, Turn::User { .. }))
+                .unwrap_or(0);
+            history
+                .iter()
+                .enumerate()
+                .map(|(i, turn)| match turn {
+                    Turn::ToolResults { results } if i < current => Turn::ToolResults {
+                        results: results
+                            .iter()
+                            .map(|result| crate::ToolResultEntry {
+                                content: format!(
+                                    "[{} output dropped: {} characters]",
+                                    result.tool_name,
+                                    result.content.len()
+                                ),
+                                ..result.clone()
+                            })
+                            .collect(),
+                    },
+                    _ => turn.clone(),
+                })
+                .collect()
         }
-        FidelityMode::Compact | FidelityMode::Summary => {
-            // These would need LLM calls; for now just keep all
-            messages.to_vec()
+    }
+}
+
+/// The request asking an LLM to summarize `material` (describing `subject`)
+/// at `detail`.
+pub fn summary_prompt(subject: &str, material: &str, detail: SummaryDetail) -> String {
+    format!(
+        "Summarize {} below in at most {} words. Keep the decisions made, their \
+         reasons, the files and commands involved, what succeeded, what failed \
+         and anything left to do. Leave out raw tool output. Reply with the \
+         summary only.\n\n{}",
+        subject,
+        detail.word_budget(),
+        material
+    )
+}
+
+/// A plain-text rendering of `history`, as summarization input.
+pub(crate) fn transcript(history: &[Turn]) -> String {
+    let mut text = String::new();
+    for turn in history {
+        match turn {
+            Turn::User { content } => text.push_str(&format!("User: {}\n\n", content)),
+            Turn::Assistant {
+                content,
+                tool_calls,
+            } => {
+                if !content.is_empty() {
+                    text.push_str(&format!("Assistant: {}\n\n", content));
+                }
+                for tc in tool_calls {
+                    text.push_str(&format!("Tool call: {}({})\n\n", tc.name, tc.arguments));
+                }
+            }
+            Turn::ToolResults { results } => {
+                for result in results {
+                    let output: String = result.content.chars().take(2000).collect();
+                    text.push_str(&format!(
+                        "Tool result ({}): {}\n\n",
+                        result.tool_name, output
+                    ));
+                }
+            }
+            Turn::System { content } | Turn::Steering { content } => {
+                text.push_str(&format!("System: {}\n\n", content))
+            }
         }
     }
+    text
 }
 
 // ---------------------------------------------------------------------------
@@ -62,6 +227,57 @@ pub fn apply_fidelity<T: Clone>(messages: &[T], mode: &FidelityMode) -> Vec<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ToolResultEntry;
+    use attractor_llm::ToolCallResult;
+
+    fn user(content: &str) -> Turn {
+        Turn::User {
+            content: content.into(),
+        }
+    }
+
+    fn call(id: &str) -> Turn {
+        Turn::Assistant {
+            content: format!("calling {id}"),
+            tool_calls: vec![ToolCallResult {
+                id: id.into(),
+                name: "read_file".into(),
+                arguments: serde_json::json!({}),
+            }],
+        }
+    }
+
+    fn output(id: &str) -> Turn {
+        Turn::ToolResults {
+            results: vec![ToolResultEntry {
+                tool_call_id: id.into(),
+                tool_name: "read_file".into(),
+                content: format!("contents of {id}"),
+                is_error: false,
+            }],
+        }
+    }
+
+    fn answer(content: &str) -> Turn {
+        Turn::Assistant {
+            content: content.into(),
+            tool_calls: vec![],
+        }
+    }
+
+    /// A compact label per turn, for comparing histories.
+    fn labels(history: &[Turn]) -> Vec<String> {
+        history
+            .iter()
+            .map(|turn| match turn {
+                Turn::User { content } | Turn::System { content } | Turn::Steering { content } => {
+                    content.clone()
+                }
+                Turn::Assistant { content, .. } => content.clone(),
+                Turn::ToolResults { results } => results[0].content.clone(),
+            })
+            .collect()
+    }
 
     #[test]
     fn from_str_parses_full() {
@@ -78,8 +294,28 @@ mod tests {
 
     #[test]
     fn from_str_parses_summary() {
-        assert_eq!(FidelityMode::parse("summary"), FidelityMode::Summary);
-        assert_eq!(FidelityMode::parse("Summary"), FidelityMode::Summary);
+        let medium = FidelityMode::Summary {
+            detail: SummaryDetail::Medium,
+        };
+        assert_eq!(FidelityMode::parse("summary"), medium);
+        assert_eq!(FidelityMode::parse("Summary"), medium);
+        assert_eq!(FidelityMode::parse("summary:medium"), medium);
+    }
+
+    #[test]
+    fn from_str_parses_summary_detail() {
+        assert_eq!(
+            FidelityMode::parse("summary:low"),
+            FidelityMode::Summary {
+                detail: SummaryDetail::Low
+            }
+        );
+        assert_eq!(
+            FidelityMode::parse("summary(high)"),
+            FidelityMode::Summary {
+                detail: SummaryDetail::High
+            }
+        );
     }
 
     #[test]
@@ -107,6 +343,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn from_str_parses_truncate_chars() {
+        assert_eq!(
+            FidelityMode::parse("truncate_chars:500"),
+            FidelityMode::TruncateChars { keep_last: 500 }
+        );
+        assert_eq!(
+            FidelityMode::parse("truncate_chars"),
+            FidelityMode::TruncateChars { keep_last: 2000 }
+        );
+        // Character truncation leaves the conversation itself alone
+        let history = vec![user("a"), answer("b"), user("c"), answer("d")];
+        let result = apply_fidelity(&history, &FidelityMode::TruncateChars { keep_last: 1 });
+        assert_eq!(labels(&result), vec!["a", "b", "c", "d"]);
+    }
+
     #[test]
     fn from_str_unknown_defaults_to_full() {
         assert_eq!(FidelityMode::parse("garbage"), FidelityMode::Full);
@@ -115,37 +367,101 @@ mod tests {
 
     #[test]
     fn apply_fidelity_full_keeps_all() {
-        let msgs = vec![1, 2, 3, 4, 5];
-        let result = apply_fidelity(&msgs, &FidelityMode::Full);
-        assert_eq!(result, vec![1, 2, 3, 4, 5]);
+        let history = vec![user("a"), answer("b"), user("c"), answer("d")];
+        let result = apply_fidelity(&history, &FidelityMode::Full);
+        assert_eq!(labels(&result), vec!["a", "b", "c", "d"]);
     }
 
     #[test]
     fn apply_fidelity_truncate_keeps_last_n() {
-        let msgs = vec![1, 2, 3, 4, 5];
-        let result = apply_fidelity(&msgs, &FidelityMode::Truncate { keep_last: 3 });
-        assert_eq!(result, vec![3, 4, 5]);
+        let history = vec![user("a"), answer("b"), user("c"), answer("d")];
+        let result = apply_fidelity(&history, &FidelityMode::Truncate { keep_last: 2 });
+        assert_eq!(labels(&result), vec!["c", "d"]);
     }
 
     #[test]
     fn apply_fidelity_truncate_with_fewer_messages() {
-        let msgs = vec![1, 2];
-        let result = apply_fidelity(&msgs, &FidelityMode::Truncate { keep_last: 10 });
-        assert_eq!(result, vec![1, 2]);
+        let history = vec![user("a"), answer("b")];
+        let result = apply_fidelity(&history, &FidelityMode::Truncate { keep_last: 10 });
+        assert_eq!(labels(&result), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn apply_fidelity_truncate_keeps_tool_rounds_whole() {
+        // Cutting at the second turn would orphan a tool result: widen to
+        // the start of the exchange
+        let history = vec![
+            user("a"),
+            call("1"),
+            output("1"),
+            answer("b"),
+            user("c"),
+            answer("d"),
+        ];
+        let result = apply_fidelity(&history, &FidelityMode::Truncate { keep_last: 4 });
+        assert_eq!(
+            labels(&result),
+            vec!["a", "calling 1", "contents of 1", "b", "c", "d"]
+        );
+
+        // Inside a long exchange the input stays and the cut moves back to
+        // the call
+        let history = vec![
+            user("a"),
+            answer("b"),
+            user("task"),
+            call("1"),
+            output("1"),
+            call("2"),
+            output("2"),
+        ];
+        let result = apply_fidelity(&history, &FidelityMode::Truncate { keep_last: 1 });
+        assert_eq!(labels(&result), vec!["task", "calling 2", "contents of 2"]);
+    }
+
+    #[test]
+    fn apply_fidelity_compact_drops_earlier_tool_outputs() {
+        let history = vec![
+            user("a"),
+            call("1"),
+            output("1"),
+            answer("b"),
+            user("c"),
+            call("2"),
+            output("2"),
+        ];
+        let result = apply_fidelity(&history, &FidelityMode::Compact);
+        assert_eq!(
+            labels(&result),
+            vec![
+                "a",
+                "calling 1",
+                "[read_file output dropped: 13 characters]",
+                "b",
+                "c",
+                "calling 2",
+                "contents of 2"
+            ]
+        );
     }
 
     #[test]
-    fn apply_fidelity_compact_keeps_all_for_now() {
-        let msgs = vec![1, 2, 3];
-        let result = apply_fidelity(&msgs, &FidelityMode::Compact);
-        assert_eq!(result, vec![1, 2, 3]);
+    fn apply_fidelity_summary_keeps_all() {
+        let history = vec![user("a"), call("1"), output("1")];
+        let mode = FidelityMode::Summary {
+            detail: SummaryDetail::Low,
+        };
+        assert_eq!(labels(&apply_fidelity(&history, &mode)), labels(&history));
     }
 
     #[test]
-    fn apply_fidelity_summary_keeps_all_for_now() {
-        let msgs = vec![1, 2, 3];
-        let result = apply_fidelity(&msgs, &FidelityMode::Summary);
-        assert_eq!(result, vec![1, 2, 3]);
+    fn summary_prompt_scales_with_detail() {
+        let material = transcript(&[user("Plan it"), call("1"), output("1")]);
+        assert!(material.contains("User: Plan it"));
+        assert!(material.contains("Tool call: read_file({})"));
+        let prompt = summary_prompt("the conversation so far", &material, SummaryDetail::High);
+        assert!(prompt.contains("at most 1000 words"));
+        assert!(prompt.ends_with(&material));
     }
 
     #[test]
@@ -9,16 +9,20 @@ pub mod prompt_builder;
 pub mod subagent;
 #[cfg(test)]
 mod test_utils;
-pub use fidelity::{apply_fidelity, FidelityMode};
+pub use fidelity::{apply_fidelity, FidelityMode, SummaryDetail};
 pub use loop_detection::{LoopDetector, SteeringInjector};
 pub use prompt_builder::{discover_project_docs, ProjectDoc, SystemPromptBuilder};
 pub use subagent::{SubagentConfig, SubagentManager, SubagentStatus};
 
 use std::collections::VecDeque;
+use std::sync::Arc;
 
-use attractor_llm::{ContentPart, Message, Request, ToolCallResult};
+use attractor_llm::{
+    ContentPart, Message, Request, Response, StreamAccumulator, StreamEvent, ToolCallResult, Usage,
+};
 use attractor_tools::{ExecutionEnvironment, ToolRegistry};
 use attractor_types::AttractorError;
+use serde::{Deserialize, Serialize};
 
 // ---------------------------------------------------------------------------
 // SessionConfig
@@ -28,6 +32,8 @@ use attractor_types::AttractorError;
 #[derive(Debug, Clone)]
 pub struct SessionConfig {
     pub model: String,
+    /// Provider to route requests to; `None` resolves it from the model.
+    pub provider: Option<String>,
     pub system_prompt: String,
     /// Maximum number of user turns (0 = unlimited).
     pub max_turns: usize,
@@ -39,18 +45,22 @@ pub struct SessionConfig {
     pub enable_loop_detection: bool,
     /// Window size for loop detection (consecutive identical calls).
     pub loop_detection_window: usize,
+    /// How much of the history each request carries (see [`fidelity`]).
+    pub fidelity: FidelityMode,
 }
 
 impl Default for SessionConfig {
     fn default() -> Self {
         Self {
             model: "claude-sonnet-4-5-20250929".to_string(),
+            provider: None,
             system_prompt: "You are a helpful coding assistant.".to_string(),
             max_turns: 0,
             max_tool_rounds: 200,
             default_command_timeout_ms: 10_000,
             enable_loop_detection: true,
             loop_detection_window: 10,
+            fidelity: FidelityMode::Full,
         }
     }
 }
@@ -73,7 +83,8 @@ pub enum SessionState {
 // ---------------------------------------------------------------------------
 
 /// A single turn in the conversation history.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum Turn {
     User {
         content: String,
@@ -94,7 +105,7 @@ pub enum Turn {
 }
 
 /// Result of executing a single tool call.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolResultEntry {
     pub tool_call_id: String,
     pub tool_name: String,
@@ -108,6 +119,9 @@ pub struct ToolResultEntry {
 
 const MAX_TOOL_OUTPUT_LEN: usize = 30_000;
 
+/// Callback receiving assistant text as it streams from the LLM.
+pub type TextDeltaHandler = Arc<dyn Fn(&str) + Send + Sync>;
+
 /// The core agent session that coordinates LLM calls, tool execution, and state.
 pub struct AgentSession {
     id: String,
@@ -123,6 +137,10 @@ pub struct AgentSession {
     followup_queue: VecDeque<String>,
     /// Running count of user turns (for max_turns enforcement).
     user_turn_count: usize,
+    /// When set, LLM calls stream and text deltas are forwarded here.
+    on_text_delta: Option<TextDeltaHandler>,
+    /// Token usage summed over every LLM call in the session.
+    usage: Usage,
 }
 
 impl AgentSession {
@@ -146,9 +164,24 @@ impl AgentSession {
             steering_queue: Vec::new(),
             followup_queue: VecDeque::new(),
             user_turn_count: 0,
+            on_text_delta: None,
+            usage: Usage::default(),
         }
     }
 
+    /// Continue an earlier conversation: `history` is sent ahead of the next
+    /// input.
+    pub fn with_history(mut self, history: Vec<Turn>) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Stream LLM responses, passing assistant text to `handler` token by token.
+    pub fn with_text_delta_handler(mut self, handler: TextDeltaHandler) -> Self {
+        self.on_text_delta = Some(handler);
+        self
+    }
+
     /// Returns the session ID.
     pub fn id(&self) -> &str {
         &self.id
@@ -164,6 +197,11 @@ impl AgentSession {
         &self.history
     }
 
+    /// Returns the token usage summed over all LLM calls so far.
+    pub fn usage(&self) -> &Usage {
+        &self.usage
+    }
+
     /// Push a steering message to be injected at the next tool round boundary.
     pub fn steer(&mut self, message: String) {
         let preview: String = message.chars().take(80).collect();
@@ -223,6 +261,10 @@ impl AgentSession {
 
         self.state = SessionState::Processing;
 
+        if let FidelityMode::Summary { detail } = self.config.fidelity {
+            self.summarize_history(detail).await?;
+        }
+
         // Append user turn
         self.history.push(Turn::User {
             content: user_input.to_string(),
@@ -241,7 +283,7 @@ impl AgentSession {
             let request = self.build_request();
 
             // Call LLM
-            let response = self.llm_client.complete(&request).await?;
+            let response = self.call_llm(&request).await?;
 
             tracing::info!(
                 round,
@@ -251,6 +293,7 @@ impl AgentSession {
                 tool_calls = response.tool_calls.len(),
                 "LLM response received"
             );
+            self.add_usage(&response.usage);
 
             // Record assistant turn
             last_assistant_text = response.text.clone();
@@ -288,6 +331,78 @@ impl AgentSession {
         Ok(last_assistant_text)
     }
 
+    /// Replace the conversation so far with an LLM-written summary of it.
+    /// A history that is already just a summary is left alone.
+    async fn summarize_history(&mut self, detail: SummaryDetail) -> attractor_types::Result<()> {
+        if !self.history.iter().any(|t| matches!(t, Turn::User { .. })) {
+            return Ok(());
+        }
+        let prompt = fidelity::summary_prompt(
+            "the conversation so far",
+            &fidelity::transcript(&self.history),
+            detail,
+        );
+        let request = Request {
+            model: self.config.model.clone(),
+            messages: vec![Message::user(prompt)],
+            tools: vec![],
+            tool_choice: None,
+            max_tokens: None,
+            temperature: None,
+            stop_sequences: vec![],
+            reasoning_effort: None,
+            provider: self.config.provider.clone(),
+            provider_options: None,
+        };
+        let response = self.llm_client.complete(&request).await?;
+        self.add_usage(&response.usage);
+        tracing::info!(
+            turns = self.history.len(),
+            detail = detail.as_str(),
+            "Summarized conversation history"
+        );
+        self.history = vec![Turn::System {
+            content: format!(
+                "Summary of the conversation so far:\n{}",
+                response.text.trim()
+            ),
+        }];
+        Ok(())
+    }
+
+    fn add_usage(&mut self, usage: &Usage) {
+        let total = &mut self.usage;
+        total.input_tokens += usage.input_tokens;
+        total.output_tokens += usage.output_tokens;
+        total.total_tokens += usage.total_tokens;
+        if let Some(n) = usage.reasoning_tokens {
+            *total.reasoning_tokens.get_or_insert(0) += n;
+        }
+        if let Some(n) = usage.cache_read_tokens {
+            *total.cache_read_tokens.get_or_insert(0) += n;
+        }
+        if let Some(n) = usage.cache_write_tokens {
+            *total.cache_write_tokens.get_or_insert(0) += n;
+        }
+    }
+
+    /// Send a request to the LLM, streaming when a text delta handler is set.
+    async fn call_llm(&self, request: &Request) -> attractor_types::Result<Response> {
+        let Some(ref on_text_delta) = self.on_text_delta else {
+            return self.llm_client.complete(request).await;
+        };
+
+        let mut stream = self.llm_client.stream(request)?;
+        let mut acc = StreamAccumulator::new();
+        while let Some(event) = tokio_stream::StreamExt::next(&mut stream).await {
+            if let StreamEvent::ContentDelta { ref text } = event {
+                on_text_delta(text);
+            }
+            acc.push(&event);
+        }
+        acc.finish()
+    }
+
     /// Build an LLM Request from the conversation history.
     fn build_request(&self) -> Request {
         let mut messages = Vec::new();
@@ -298,7 +413,7 @@ impl AgentSession {
         }
 
         // Map each Turn to LLM messages
-        for turn in &self.history {
+        for turn in &apply_fidelity(&self.history, &self.config.fidelity) {
             match turn {
                 Turn::User { content } => {
                     messages.push(Message::user(content));
@@ -366,7 +481,7 @@ impl AgentSession {
             temperature: None,
             stop_sequences: vec![],
             reasoning_effort: None,
-            provider: None,
+            provider: self.config.provider.clone(),
             provider_options: None,
         }
     }
@@ -539,6 +654,109 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn text_delta_handler_streams_tool_rounds() {
+        let responses = vec![
+            Response {
+                id: "resp-1".into(),
+                text: "Checking.".into(),
+                tool_calls: vec![ToolCallResult {
+                    id: "tc-1".into(),
+                    name: "echo".into(),
+                    arguments: serde_json::json!({"text": "ping"}),
+                }],
+                reasoning: None,
+                usage: Usage::default(),
+                model: "mock-model".into(),
+                finish_reason: FinishReason::ToolUse,
+            },
+            Response {
+                id: "resp-2".into(),
+                text: "The echo returned: ping".into(),
+                tool_calls: vec![],
+                reasoning: None,
+                usage: Usage::default(),
+                model: "mock-model".into(),
+                finish_reason: FinishReason::EndTurn,
+            },
+        ];
+
+        let deltas = Arc::new(std::sync::Mutex::new(Vec::<String>::new()));
+        let sink = deltas.clone();
+
+        let client = make_client(SequenceMockProvider::new(responses));
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool);
+        let mut session = AgentSession::new(
+            client,
+            registry,
+            Box::new(MockEnv),
+            SessionConfig::default(),
+        )
+        .with_text_delta_handler(Arc::new(move |text| {
+            sink.lock().unwrap().push(text.to_string())
+        }));
+        let result = session.process_input("Echo ping for me").await.unwrap();
+
+        assert_eq!(result, "The echo returned: ping");
+        assert!(matches!(
+            &session.history()[2],
+            Turn::ToolResults { results } if results[0].content == "ping"
+        ));
+        let deltas = deltas.lock().unwrap();
+        assert_eq!(deltas.len(), 5);
+        assert_eq!(deltas.concat(), "Checking.The echo returned: ping");
+    }
+
+    #[tokio::test]
+    async fn usage_summed_across_rounds() {
+        let usage = |input, output| Usage {
+            input_tokens: input,
+            output_tokens: output,
+            total_tokens: input + output,
+            ..Usage::default()
+        };
+        let responses = vec![
+            Response {
+                id: "resp-1".into(),
+                text: String::new(),
+                tool_calls: vec![ToolCallResult {
+                    id: "tc-1".into(),
+                    name: "echo".into(),
+                    arguments: serde_json::json!({"text": "ping"}),
+                }],
+                reasoning: None,
+                usage: usage(100, 10),
+                model: "mock-model".into(),
+                finish_reason: FinishReason::ToolUse,
+            },
+            Response {
+                id: "resp-2".into(),
+                text: "done".into(),
+                tool_calls: vec![],
+                reasoning: None,
+                usage: usage(150, 20),
+                model: "mock-model".into(),
+                finish_reason: FinishReason::EndTurn,
+            },
+        ];
+
+        let client = make_client(SequenceMockProvider::new(responses));
+        let mut registry = ToolRegistry::new();
+        registry.register(EchoTool);
+        let mut session = AgentSession::new(
+            client,
+            registry,
+            Box::new(MockEnv),
+            SessionConfig::default(),
+        );
+        session.process_input("Echo ping").await.unwrap();
+
+        assert_eq!(session.usage().input_tokens, 250);
+        assert_eq!(session.usage().output_tokens, 30);
+        assert_eq!(session.usage().total_tokens, 280);
+    }
+
     // -----------------------------------------------------------------------
     // Test 4: Steering queue drained between rounds
     // -----------------------------------------------------------------------
@@ -830,4 +1048,142 @@ mod tests {
         assert!(results[0].content.contains("[WARNING: Output truncated."));
         assert!(results[0].content.contains("20000 characters removed"));
     }
+
+    // -----------------------------------------------------------------------
+    // Test 9: Continuing a saved conversation
+    // -----------------------------------------------------------------------
+
+    #[tokio::test]
+    async fn saved_history_continues_in_a_new_session() {
+        let mut first = AgentSession::new(
+            make_client(SequenceMockProvider::single_text("Planned")),
+            ToolRegistry::new(),
+            Box::new(MockEnv),
+            SessionConfig::default(),
+        );
+        first.process_input("Plan it").await.unwrap();
+        let saved = serde_json::to_string(first.history()).unwrap();
+
+        let history: Vec<Turn> = serde_json::from_str(&saved).unwrap();
+        let mut second = AgentSession::new(
+            make_client(SequenceMockProvider::single_text("Fixed")),
+            ToolRegistry::new(),
+            Box::new(MockEnv),
+            SessionConfig::default(),
+        )
+        .with_history(history);
+        second.process_input("Fix it").await.unwrap();
+
+        assert_eq!(second.history().len(), 4);
+        assert!(matches!(&second.history()[0], Turn::User { content } if content == "Plan it"));
+        // System prompt plus all four turns
+        assert_eq!(second.build_request().messages.len(), 5);
+    }
+
+    // -----------------------------------------------------------------------
+    // Test 10: Context fidelity
+    // -----------------------------------------------------------------------
+
+    fn text_response(text: &str) -> Response {
+        Response {
+            id: format!("resp-{text}"),
+            text: text.to_string(),
+            tool_calls: vec![],
+            reasoning: None,
+            usage: Usage {
+                input_tokens: 10,
+                output_tokens: 5,
+                total_tokens: 15,
+                ..Default::default()
+            },
+            model: "mock-model".into(),
+            finish_reason: FinishReason::EndTurn,
+        }
+    }
+
+    fn earlier_history() -> Vec<Turn> {
+        vec![
+            Turn::User {
+                content: "Plan it".into(),
+            },
+            Turn::Assistant {
+                content: "Reading".into(),
+                tool_calls: vec![ToolCallResult {
+                    id: "c1".into(),
+                    name: "read_file".into(),
+                    arguments: serde_json::json!({"path": "a.rs"}),
+                }],
+            },
+            Turn::ToolResults {
+                results: vec![ToolResultEntry {
+                    tool_call_id: "c1".into(),
+                    tool_name: "read_file".into(),
+                    content: "fn main() {}".into(),
+                    is_error: false,
+                }],
+            },
+            Turn::Assistant {
+                content: "Planned".into(),
+                tool_calls: vec![],
+            },
+        ]
+    }
+
+    #[tokio::test]
+    async fn compact_fidelity_drops_earlier_tool_outputs_from_requests() {
+        let config = SessionConfig {
+            fidelity: FidelityMode::Compact,
+            ..Default::default()
+        };
+        let mut session = AgentSession::new(
+            make_client(SequenceMockProvider::single_text("Fixed")),
+            ToolRegistry::new(),
+            Box::new(MockEnv),
+            config,
+        )
+        .with_history(earlier_history());
+        session.process_input("Fix it").await.unwrap();
+
+        // The history keeps the output; only the request leaves it out
+        assert!(
+            matches!(&session.history()[2], Turn::ToolResults { results } if results[0].content == "fn main() {}")
+        );
+        let request = session.build_request();
+        let tool_message = &request.messages[3];
+        assert!(matches!(
+            &tool_message.content[0],
+            ContentPart::ToolResult { content, .. } if content.contains("output dropped")
+        ));
+    }
+
+    #[tokio::test]
+    async fn summary_fidelity_replaces_earlier_history_with_a_summary() {
+        let config = SessionConfig {
+            fidelity: FidelityMode::Summary {
+                detail: SummaryDetail::Low,
+            },
+            ..Default::default()
+        };
+        let provider = SequenceMockProvider::new(vec![
+            text_response("Planned from a.rs"),
+            text_response("Fixed"),
+        ]);
+        let mut session = AgentSession::new(
+            make_client(provider),
+            ToolRegistry::new(),
+            Box::new(MockEnv),
+            config,
+        )
+        .with_history(earlier_history());
+        let text = session.process_input("Fix it").await.unwrap();
+
+        assert_eq!(text, "Fixed");
+        assert_eq!(session.history().len(), 3);
+        assert!(matches!(
+            &session.history()[0],
+            Turn::System { content } if content.ends_with("Planned from a.rs")
+        ));
+        // The summary call counts towards the session's usage
+        assert_eq!(session.usage().input_tokens, 20);
+    }
 }
@@ -90,6 +90,19 @@ impl SequenceMockProvider {
         }
     }
 
+    fn next_response(&self) -> Response {
+        let mut queue = self.responses.lock().unwrap();
+        queue.pop_front().unwrap_or_else(|| Response {
+            id: "resp-fallback".into(),
+            text: "No more responses".into(),
+            tool_calls: vec![],
+            reasoning: None,
+            usage: Usage::default(),
+            model: "mock-model".into(),
+            finish_reason: FinishReason::EndTurn,
+        })
+    }
+
     pub fn single_text(text: &str) -> Self {
         Self::new(vec![Response {
             id: "resp-1".into(),
@@ -106,22 +119,40 @@ impl SequenceMockProvider {
 #[async_trait]
 impl ProviderAdapter for SequenceMockProvider {
     async fn complete(&self, _request: &Request) -> Result<Response, AttractorError> {
-        let mut queue = self.responses.lock().unwrap();
-        match queue.pop_front() {
-            Some(resp) => Ok(resp),
-            None => Ok(Response {
-                id: "resp-fallback".into(),
-                text: "No more responses".into(),
-                tool_calls: vec![],
-                reasoning: None,
-                usage: Usage::default(),
-                model: "mock-model".into(),
-                finish_reason: FinishReason::EndTurn,
-            }),
-        }
+        Ok(self.next_response())
     }
+    /// Replays the next response as stream events, splitting text on spaces.
     fn stream(&self, _request: &Request) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>> {
-        Box::pin(tokio_stream::empty::<StreamEvent>())
+        let resp = self.next_response();
+        let mut events = vec![
+            StreamEvent::MessageStart {
+                id: resp.id.clone(),
+                model: resp.model.clone(),
+            },
+            StreamEvent::ContentStart,
+        ];
+        for word in resp.text.split_inclusive(' ') {
+            events.push(StreamEvent::ContentDelta {
+                text: word.to_string(),
+            });
+        }
+        events.push(StreamEvent::ContentEnd);
+        for tc in &resp.tool_calls {
+            events.push(StreamEvent::ToolCallStart {
+                id: tc.id.clone(),
+                name: tc.name.clone(),
+            });
+            events.push(StreamEvent::ToolCallDelta {
+                id: tc.id.clone(),
+                json_chunk: tc.arguments.to_string(),
+            });
+            events.push(StreamEvent::ToolCallEnd { id: tc.id.clone() });
+        }
+        events.push(StreamEvent::MessageEnd {
+            usage: resp.usage,
+            finish_reason: resp.finish_reason,
+        });
+        Box::pin(tokio_stream::iter(events))
     }
     fn name(&self) -> &str {
         "mock"
@@ -133,7 +164,7 @@ impl ProviderAdapter for SequenceMockProvider {
         true
     }
     fn supports_streaming(&self) -> bool {
-        false
+        true
     }
     fn supports_reasoning(&self) -> bool {
         false
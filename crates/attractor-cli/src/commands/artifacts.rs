@@ -0,0 +1,63 @@
+use std::path::Path;
+
+use attractor_pipeline::ArtifactStore;
+
+use super::run::resolve_logs_dir;
+
+fn open_store(pipeline: &Path, logs: Option<&Path>) -> anyhow::Result<ArtifactStore> {
+    Ok(ArtifactStore::for_logs(&resolve_logs_dir(pipeline, logs))?)
+}
+
+/// List the artifacts stored by a pipeline's runs.
+pub async fn cmd_artifacts_list(pipeline: &Path, logs: Option<&Path>) -> anyhow::Result<()> {
+    let store = open_store(pipeline, logs)?;
+    let artifacts = store.list().await?;
+    if artifacts.is_empty() {
+        println!("No artifacts in {}", store.root().display());
+        return Ok(());
+    }
+
+    println!("Artifacts in {}:", store.root().display());
+    println!(
+        "{:<32}  {:<16}  {:>10}  {:<12}  MEDIA TYPE",
+        "NAME", "NODE", "SIZE", "HASH"
+    );
+    for artifact in artifacts {
+        println!(
+            "{:<32}  {:<16}  {:>10}  {:<12}  {}",
+            artifact.name,
+            artifact.node_id,
+            artifact.size,
+            &artifact.hash[..12],
+            artifact.media_type
+        );
+    }
+    println!(
+        "\nFetch one with: pas artifacts get {} <NAME>",
+        pipeline.display()
+    );
+    Ok(())
+}
+
+/// Print an artifact's content, or copy it to `output`.
+pub async fn cmd_artifacts_get(
+    pipeline: &Path,
+    name_or_hash: &str,
+    output: Option<&Path>,
+    logs: Option<&Path>,
+) -> anyhow::Result<()> {
+    let artifact = open_store(pipeline, logs)?.find(name_or_hash).await?;
+    match output {
+        Some(path) => {
+            tokio::fs::copy(&artifact.path, path).await?;
+            println!(
+                "Wrote {} ({} bytes) to {}",
+                artifact.name,
+                artifact.size,
+                path.display()
+            );
+        }
+        None => print!("{}", artifact.read_text().await?),
+    }
+    Ok(())
+}
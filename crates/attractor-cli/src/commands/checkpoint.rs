@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use super::run::resolve_logs_dir;
+
+/// List the checkpoint journal of a pipeline's latest run, oldest first.
+pub async fn cmd_checkpoint_list(pipeline: &Path, logs: Option<&Path>) -> anyhow::Result<()> {
+    let logs_dir = resolve_logs_dir(pipeline, logs);
+    let journal = attractor_pipeline::load_journal(&logs_dir).await?;
+    if journal.is_empty() {
+        println!("No checkpoints in {}", logs_dir.display());
+        return Ok(());
+    }
+
+    println!("Checkpoints in {}:", logs_dir.display());
+    println!(
+        "{:>4}  {:<24}  {:>9}  {:>9}  TAKEN",
+        "SEQ", "BEFORE NODE", "COMPLETED", "COST"
+    );
+    for entry in journal {
+        let cp = entry.checkpoint;
+        let cost: f64 = cp
+            .context_snapshot
+            .iter()
+            .filter(|(k, _)| k.ends_with(".cost_usd"))
+            .filter_map(|(_, v)| v.as_f64())
+            .sum();
+        println!(
+            "{:>4}  {:<24}  {:>9}  {:>9}  {}",
+            entry.seq,
+            cp.current_node_id,
+            cp.completed_nodes.len(),
+            if cost > 0.0 {
+                format!("${:.4}", cost)
+            } else {
+                "-".to_string()
+            },
+            cp.timestamp
+        );
+    }
+    println!(
+        "\nRe-run from a node with: pas run {} --from <NODE>",
+        pipeline.display()
+    );
+    Ok(())
+}
+
+/// Rewind a pipeline's checkpoint so the next `pas run` resumes just before
+/// `node_id`.
+pub async fn cmd_checkpoint_restore(
+    pipeline: &Path,
+    node_id: &str,
+    logs: Option<&Path>,
+) -> anyhow::Result<()> {
+    let graph = crate::load_pipeline(pipeline)?;
+    if graph.node(node_id).is_none() {
+        anyhow::bail!("Node '{}' not found in {}", node_id, pipeline.display());
+    }
+
+    let logs_dir = resolve_logs_dir(pipeline, logs);
+    let cp = attractor_pipeline::restore_checkpoint(&logs_dir, node_id).await?;
+    println!(
+        "Restored checkpoint before '{}' ({} completed node(s), taken {})",
+        node_id,
+        cp.completed_nodes.len(),
+        cp.timestamp
+    );
+    println!("Resume with: pas run {}", pipeline.display());
+    Ok(())
+}
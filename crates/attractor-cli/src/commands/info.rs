@@ -1,4 +1,5 @@
 use anyhow;
+use attractor_dot::AttributeValue;
 
 pub fn cmd_info(path: &std::path::Path) -> anyhow::Result<()> {
     let graph = crate::load_pipeline(path)?;
@@ -30,5 +31,34 @@ pub fn cmd_info(path: &std::path::Path) -> anyhow::Result<()> {
         );
     }
 
+    // List clusters with the attributes they scope over their members
+    if !graph.clusters().is_empty() {
+        println!("\nClusters:");
+        for cluster in graph.clusters() {
+            let mut line = format!("  {}", cluster.name);
+            if let Some(label) = &cluster.label {
+                line.push_str(&format!(" [{}]", label));
+            }
+            if cluster.goal_gate {
+                line.push_str(" goal_gate");
+            }
+            for (key, value) in cluster.scoped_attrs() {
+                line.push_str(&format!(" {}={}", key, format_attr(value)));
+            }
+            println!("{}", line);
+            println!("    nodes: {}", cluster.nodes.join(", "));
+        }
+    }
+
     Ok(())
 }
+
+fn format_attr(value: &AttributeValue) -> String {
+    match value {
+        AttributeValue::String(s) => s.clone(),
+        AttributeValue::Integer(i) => i.to_string(),
+        AttributeValue::Float(f) => f.to_string(),
+        AttributeValue::Boolean(b) => b.to_string(),
+        AttributeValue::Duration(d) => format!("{}s", d.as_secs_f64()),
+    }
+}
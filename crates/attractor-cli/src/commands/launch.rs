@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 /// End-to-end: discover PRD+spec pairs → generate .dot files → validate → run.
+#[allow(clippy::too_many_arguments)]
 pub async fn cmd_launch(
     docs_dir: &std::path::Path,
     output_dir: Option<&std::path::Path>,
@@ -9,6 +11,8 @@ pub async fn cmd_launch(
     max_budget_usd: Option<f64>,
     max_steps: u64,
     fresh: bool,
+    on_drift: attractor_pipeline::DriftPolicy,
+    vars: &HashMap<String, String>,
     verbose: bool,
 ) -> anyhow::Result<()> {
     let pipelines_dir = output_dir
@@ -78,6 +82,9 @@ pub async fn cmd_launch(
         max_budget_usd,
         max_steps,
         fresh,
+        on_drift,
+        None,
+        vars,
     )
     .await?;
 
@@ -1,17 +1,23 @@
+pub mod artifacts;
+pub mod checkpoint;
 pub mod decompose;
 pub mod generate;
 pub mod info;
 pub mod launch;
 pub mod plan;
 pub mod run;
+pub mod runs;
 pub mod scaffold;
 pub mod validate;
 
+pub use artifacts::{cmd_artifacts_get, cmd_artifacts_list};
+pub use checkpoint::{cmd_checkpoint_list, cmd_checkpoint_restore};
 pub use decompose::{cmd_decompose, validate_decomposition};
 pub use generate::{cmd_generate, cmd_generate_dir};
 pub use info::cmd_info;
 pub use launch::cmd_launch;
 pub use plan::cmd_plan;
 pub use run::{cmd_run, cmd_run_dir};
+pub use runs::{cmd_runs_list, cmd_runs_show};
 pub use scaffold::cmd_scaffold;
 pub use validate::cmd_validate;
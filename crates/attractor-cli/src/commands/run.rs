@@ -1,13 +1,37 @@
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
+use std::str::FromStr;
 
 use anyhow;
+use attractor_pipeline::{RunWorktree, WorktreeAction};
+
+/// How `--worktree` settles the run's branch once the pipeline completes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorktreeFinish {
+    /// Show the run's commits and prompt (keeps the branch when not on a TTY).
+    Ask,
+    Action(WorktreeAction),
+}
+
+impl FromStr for WorktreeFinish {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("ask") {
+            Ok(Self::Ask)
+        } else {
+            s.parse().map(Self::Action)
+        }
+    }
+}
 
 /// Generate a deterministic logs directory name from the pipeline file path.
 /// Format: `.pas/logs/<stem>-<8hex>` e.g. `.pas/logs/phase-01-spec-a3f1b2c9`
 ///
 /// The hash is derived from the canonical file path so re-running the same
 /// pipeline always finds the same logs dir (and its checkpoint).
-fn stable_logs_dir(pipeline_path: &std::path::Path) -> PathBuf {
+pub(crate) fn stable_logs_dir(pipeline_path: &std::path::Path) -> PathBuf {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
@@ -26,6 +50,18 @@ fn stable_logs_dir(pipeline_path: &std::path::Path) -> PathBuf {
     PathBuf::from(format!(".pas/logs/{}-{:08x}", stem, hash as u32))
 }
 
+/// The logs directory for a pipeline: `logs` if given, else its stable one.
+pub(crate) fn resolve_logs_dir(
+    pipeline_path: &std::path::Path,
+    logs: Option<&std::path::Path>,
+) -> PathBuf {
+    match logs {
+        Some(l) => l.to_path_buf(),
+        None => stable_logs_dir(pipeline_path),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn cmd_run(
     path: &std::path::Path,
     workdir: Option<&std::path::Path>,
@@ -34,20 +70,31 @@ pub async fn cmd_run(
     max_budget_usd: Option<f64>,
     max_steps: u64,
     fresh: bool,
+    from: Option<&str>,
+    on_drift: attractor_pipeline::DriftPolicy,
+    worktree: Option<WorktreeFinish>,
+    vars: &HashMap<String, String>,
 ) -> anyhow::Result<()> {
-    let graph = crate::load_pipeline(path)?;
+    let graph = crate::load_pipeline_with_vars(path, vars)?;
 
     // Resolve logs directory: explicit flag or deterministic from path
-    let logs_dir = match logs {
-        Some(l) => l.to_path_buf(),
-        None => stable_logs_dir(path),
-    };
+    let logs_dir = resolve_logs_dir(path, logs);
 
     // --fresh: clear any existing checkpoint before starting
     if fresh {
         attractor_pipeline::clear_checkpoint(&logs_dir).await?;
     }
 
+    // --from: rewind to the journaled checkpoint taken just before that node
+    if let Some(node_id) = from {
+        let cp = attractor_pipeline::restore_checkpoint(&logs_dir, node_id).await?;
+        println!(
+            "Rewound to before '{}' ({} completed node(s))",
+            node_id,
+            cp.completed_nodes.len()
+        );
+    }
+
     // Check for existing checkpoint
     let has_checkpoint = logs_dir.join("checkpoint.json").exists();
 
@@ -90,11 +137,33 @@ pub async fn cmd_run(
     println!("Step limit: {}", max_steps);
 
     let interviewer = std::sync::Arc::new(attractor_pipeline::ConsoleInterviewer);
-    let registry = attractor_pipeline::default_registry_with_interviewer(interviewer);
-    let executor = attractor_pipeline::PipelineExecutor::new(registry);
+    let emitter = attractor_pipeline::EventEmitter::default();
+    let mut registry = attractor_pipeline::default_registry_with_interviewer(interviewer);
+    registry
+        .register(attractor_pipeline::AgentHandler::from_env().with_event_emitter(emitter.clone()));
+    tokio::spawn(print_text_deltas(emitter.subscribe()));
+    let executor = attractor_pipeline::PipelineExecutor::new(registry)
+        .with_event_emitter(emitter.clone())
+        .with_drift_policy(on_drift)
+        .with_worktree(worktree.is_some());
+
+    // Record the run in the history database (dry runs are not recorded)
+    let recorder = if dry_run {
+        None
+    } else {
+        start_recorder(path, &graph.name, &emitter).await
+    };
+
     let result = executor
         .run_with_checkpoint(&graph, context, &logs_dir)
-        .await?;
+        .await;
+    if let Some(recorder) = recorder {
+        match recorder.finish(&result).await {
+            Ok(run_id) => println!("Run recorded as #{} (pas runs show {})", run_id, run_id),
+            Err(e) => tracing::warn!("Failed to finish run record: {}", e),
+        }
+    }
+    let result = result?;
 
     println!("\nPipeline completed");
     println!("Completed nodes: {:?}", result.completed_nodes);
@@ -110,11 +179,127 @@ pub async fn cmd_run(
         println!("Total cost: ${:.4}", total_cost);
     }
 
+    if let Some(run_worktree) = RunWorktree::from_context(&result.final_context) {
+        finish_worktree(&run_worktree, worktree.unwrap_or(WorktreeFinish::Ask)).await?;
+    }
+
+    Ok(())
+}
+
+/// Merge, squash, discard or keep a finished run's worktree branch,
+/// prompting first for [`WorktreeFinish::Ask`].
+async fn finish_worktree(worktree: &RunWorktree, finish: WorktreeFinish) -> anyhow::Result<()> {
+    let commits = worktree.log().await?;
+    println!(
+        "\nWorktree branch {} ({} commit(s)):",
+        worktree.branch,
+        commits.len()
+    );
+    for commit in &commits {
+        println!("  {}", commit);
+    }
+
+    let action = match finish {
+        WorktreeFinish::Action(action) => action,
+        WorktreeFinish::Ask if !std::io::stdin().is_terminal() => WorktreeAction::Keep,
+        WorktreeFinish::Ask => loop {
+            print!("[m]erge, [s]quash, [d]iscard or [k]eep? ");
+            std::io::stdout().flush()?;
+            // Human gates may have left a read pending; share their reader
+            let Some(line) = attractor_pipeline::stdin_lines().next_line().await else {
+                break WorktreeAction::Keep;
+            };
+            match line.trim().to_ascii_lowercase().as_str() {
+                "m" | "merge" => break WorktreeAction::Merge,
+                "s" | "squash" => break WorktreeAction::Squash,
+                "d" | "discard" => break WorktreeAction::Discard,
+                "k" | "keep" | "" => break WorktreeAction::Keep,
+                _ => continue,
+            }
+        },
+    };
+
+    worktree.finish(action).await?;
+    match action {
+        WorktreeAction::Merge => println!(
+            "Merged {} into {}",
+            worktree.branch,
+            worktree.repo.display()
+        ),
+        WorktreeAction::Squash => println!(
+            "Squashed {} into {}",
+            worktree.branch,
+            worktree.repo.display()
+        ),
+        WorktreeAction::Discard => println!("Discarded {}", worktree.branch),
+        WorktreeAction::Keep => println!(
+            "Kept {} at {} (finish later with git merge / git worktree remove)",
+            worktree.branch,
+            worktree.path.display()
+        ),
+    }
     Ok(())
 }
 
+/// Echo agent nodes' replies to stdout as their LLM streams them.
+async fn print_text_deltas(
+    mut rx: tokio::sync::broadcast::Receiver<attractor_pipeline::PipelineEvent>,
+) {
+    use attractor_pipeline::PipelineEvent;
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut mid_line = false;
+    loop {
+        match rx.recv().await {
+            Ok(PipelineEvent::TextDelta { text, .. }) => {
+                print!("{}", text);
+                let _ = std::io::stdout().flush();
+                mid_line = !text.ends_with('\n');
+            }
+            Ok(PipelineEvent::StageCompleted { .. } | PipelineEvent::StageFailed { .. })
+                if mid_line =>
+            {
+                println!();
+                mid_line = false;
+            }
+            Ok(_) | Err(RecvError::Lagged(_)) => {}
+            Err(RecvError::Closed) => break,
+        }
+    }
+}
+
+/// Open the run history store and start recording this run. Failures only
+/// warn: a broken history database must not stop a pipeline.
+async fn start_recorder(
+    path: &std::path::Path,
+    pipeline_name: &str,
+    emitter: &attractor_pipeline::EventEmitter,
+) -> Option<attractor_pipeline::RunRecorder> {
+    let source = std::fs::read_to_string(path).ok()?;
+    let canonical = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let started = async {
+        let store = attractor_pipeline::RunStore::open_default().await?;
+        attractor_pipeline::RunRecorder::start(
+            store,
+            emitter,
+            pipeline_name,
+            &canonical.to_string_lossy(),
+            &source,
+        )
+        .await
+    };
+    match started.await {
+        Ok(recorder) => Some(recorder),
+        Err(e) => {
+            tracing::warn!("Run history disabled: {}", e);
+            None
+        }
+    }
+}
+
 /// Run a directory of .dot files sequentially with a cross-file manifest.
 /// Files are sorted lexically — use zero-padded names (phase-01, phase-02).
+#[allow(clippy::too_many_arguments)]
 pub async fn cmd_run_dir(
     dir: &std::path::Path,
     workdir: Option<&std::path::Path>,
@@ -122,12 +307,15 @@ pub async fn cmd_run_dir(
     max_budget_usd: Option<f64>,
     max_steps: u64,
     fresh: bool,
+    on_drift: attractor_pipeline::DriftPolicy,
+    worktree: Option<WorktreeFinish>,
+    vars: &HashMap<String, String>,
 ) -> anyhow::Result<()> {
     // Collect and sort .dot files
     let mut dot_files: Vec<PathBuf> = std::fs::read_dir(dir)?
         .filter_map(|e| e.ok())
         .map(|e| e.path())
-        .filter(|p| p.extension().map_or(false, |ext| ext == "dot"))
+        .filter(|p| p.extension().is_some_and(|ext| ext == "dot"))
         .collect();
     dot_files.sort();
 
@@ -196,6 +384,10 @@ pub async fn cmd_run_dir(
             max_budget_usd,
             max_steps,
             false, // don't clear per-pipeline checkpoints during batch
+            None,
+            on_drift,
+            worktree,
+            vars,
         )
         .await?;
 
@@ -0,0 +1,95 @@
+use attractor_pipeline::RunStore;
+
+/// List recorded runs, newest first. `pipeline` filters by pipeline name or
+/// .dot path.
+pub async fn cmd_runs_list(pipeline: Option<&str>, limit: i64) -> anyhow::Result<()> {
+    let store = RunStore::open_default().await?;
+
+    // Paths are stored canonicalized
+    let filter = pipeline.map(|p| {
+        std::fs::canonicalize(p)
+            .map(|c| c.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| p.to_string())
+    });
+    let runs = store.list_runs(filter.as_deref(), limit).await?;
+
+    if runs.is_empty() {
+        println!("No runs recorded in {}", RunStore::default_path().display());
+        return Ok(());
+    }
+
+    println!(
+        "{:>5}  {:<8}  {:<19}  {:>9}  {:<8}  PIPELINE",
+        "ID", "STATUS", "STARTED", "COST", "HASH"
+    );
+    for run in runs {
+        println!(
+            "{:>5}  {:<8}  {:<19}  {:>9}  {:<8}  {}",
+            run.id,
+            run.status,
+            short_time(&run.started_at),
+            format!("${:.4}", run.total_cost_usd),
+            &run.pipeline_hash[..run.pipeline_hash.len().min(8)],
+            run.pipeline_name
+        );
+    }
+    Ok(())
+}
+
+/// Show a run and its per-node records.
+pub async fn cmd_runs_show(run_id: i64) -> anyhow::Result<()> {
+    let store = RunStore::open_default().await?;
+    let Some(run) = store.get_run(run_id).await? else {
+        anyhow::bail!("No run with id {}", run_id);
+    };
+
+    println!("Run #{}: {}", run.id, run.pipeline_name);
+    println!("Pipeline: {}", run.pipeline_path);
+    println!("Hash: {}", run.pipeline_hash);
+    println!("Status: {}", run.status);
+    println!("Started: {}", run.started_at);
+    if let Some(ref finished) = run.finished_at {
+        println!("Finished: {}", finished);
+    }
+    println!("Total cost: ${:.4}", run.total_cost_usd);
+    if let Some(ref error) = run.error {
+        println!("Error: {}", error);
+    }
+
+    let nodes = store.run_nodes(run_id).await?;
+    println!("\nNodes ({}):", nodes.len());
+    for node in nodes {
+        let cost = node
+            .cost_usd
+            .map(|c| format!("${:.4}", c))
+            .unwrap_or_else(|| "-".into());
+        let turns = node
+            .turns
+            .map(|t| t.to_string())
+            .unwrap_or_else(|| "-".into());
+        println!(
+            "  {:<24} {:<15} {:>8}ms  cost={}  turns={}",
+            node.node_id, node.status, node.duration_ms, cost, turns
+        );
+        let first_line = node.result.lines().find(|l| !l.trim().is_empty());
+        if let Some(line) = first_line {
+            println!("      {}", truncate(line.trim(), 100));
+        }
+    }
+    Ok(())
+}
+
+/// `2026-01-02T03:04:05.123+00:00` → `2026-01-02 03:04:05`
+fn short_time(rfc3339: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(rfc3339)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|_| rfc3339.to_string())
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        s.to_string()
+    } else {
+        format!("{}…", s.chars().take(max_chars).collect::<String>())
+    }
+}
@@ -2,12 +2,14 @@
 
 mod commands;
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use clap::{Parser, Subcommand};
 use commands::{
+    cmd_artifacts_get, cmd_artifacts_list, cmd_checkpoint_list, cmd_checkpoint_restore,
     cmd_decompose, cmd_generate, cmd_generate_dir, cmd_info, cmd_launch, cmd_plan, cmd_run,
-    cmd_run_dir, cmd_scaffold, cmd_validate, validate_decomposition,
+    cmd_run_dir, cmd_runs_list, cmd_runs_show, cmd_scaffold, cmd_validate, validate_decomposition,
 };
 
 #[derive(Parser)]
@@ -35,7 +37,8 @@ enum Commands {
     ///
     /// Checkpoints are saved automatically after each node. If a run is
     /// interrupted, re-running the same command resumes from the last
-    /// completed node. Use --fresh to discard checkpoints and start over.
+    /// completed node. Use --fresh to discard checkpoints and start over, or
+    /// --from <node> to re-run from an earlier node with the context it saw.
     Run {
         /// Path to a .dot file, or a directory of .dot files (sorted lexically)
         pipeline: PathBuf,
@@ -63,6 +66,49 @@ enum Commands {
         /// Ignore checkpoint and start fresh
         #[arg(long)]
         fresh: bool,
+
+        /// Re-run from this node, restoring the checkpoint taken just before it
+        #[arg(long, value_name = "NODE_ID", conflicts_with = "fresh")]
+        from: Option<String>,
+
+        /// What to do when resuming a checkpoint of an edited pipeline:
+        /// refuse, continue, or invalidate (re-run changed completed nodes)
+        #[arg(long, value_name = "POLICY", default_value = "refuse")]
+        on_drift: attractor_pipeline::DriftPolicy,
+
+        /// Run in a fresh git worktree and branch, committing after each
+        /// successful node. At the end: ask (default), merge, squash, discard
+        /// or keep the branch
+        #[arg(
+            long,
+            value_name = "ACTION",
+            num_args = 0..=1,
+            default_missing_value = "ask"
+        )]
+        worktree: Option<commands::run::WorktreeFinish>,
+
+        /// Override a graph variable (repeatable). Replaces the graph attribute
+        /// `key` and feeds `${key}` and `${ctx.key}` expansion.
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_key_val)]
+        vars: Vec<(String, String)>,
+    },
+
+    /// List or restore the checkpoints journaled for a pipeline
+    Checkpoint {
+        #[command(subcommand)]
+        command: CheckpointCommand,
+    },
+
+    /// List or fetch the artifacts stored by a pipeline's runs
+    Artifacts {
+        #[command(subcommand)]
+        command: ArtifactsCommand,
+    },
+
+    /// Inspect the run history (~/.pas/runs.db, or $PAS_RUNS_DB)
+    Runs {
+        #[command(subcommand)]
+        command: RunsCommand,
     },
 
     /// Validate a pipeline .dot file
@@ -193,9 +239,104 @@ enum Commands {
         /// Ignore checkpoints and start fresh
         #[arg(long)]
         fresh: bool,
+
+        /// What to do when resuming a checkpoint of an edited pipeline:
+        /// refuse, continue, or invalidate (re-run changed completed nodes)
+        #[arg(long, value_name = "POLICY", default_value = "refuse")]
+        on_drift: attractor_pipeline::DriftPolicy,
+
+        /// Override a graph variable in every pipeline run (repeatable)
+        #[arg(long = "set", value_name = "KEY=VALUE", value_parser = parse_key_val)]
+        vars: Vec<(String, String)>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RunsCommand {
+    /// List recorded runs, newest first
+    List {
+        /// Only runs of this pipeline (name or .dot path)
+        #[arg(short, long)]
+        pipeline: Option<String>,
+
+        /// Maximum number of runs to show
+        #[arg(short = 'n', long, default_value = "20")]
+        limit: i64,
+    },
+
+    /// Show a run with its per-node outcomes, durations and costs
+    Show {
+        /// Run ID from `pas runs list`
+        id: i64,
+    },
+}
+
+#[derive(Subcommand)]
+enum ArtifactsCommand {
+    /// List stored artifacts with their node, size and hash
+    List {
+        /// Path to the pipeline .dot file
+        pipeline: PathBuf,
+
+        /// Logs directory (default: .pas/logs/<pipeline>-<hash>)
+        #[arg(short, long)]
+        logs: Option<PathBuf>,
+    },
+
+    /// Print an artifact, or save it with --output
+    Get {
+        /// Path to the pipeline .dot file
+        pipeline: PathBuf,
+
+        /// Artifact name (e.g. plan.result) or hash prefix
+        artifact: String,
+
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Logs directory (default: .pas/logs/<pipeline>-<hash>)
+        #[arg(short, long)]
+        logs: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CheckpointCommand {
+    /// List journaled checkpoints, oldest first
+    List {
+        /// Path to the pipeline .dot file
+        pipeline: PathBuf,
+
+        /// Logs directory (default: .pas/logs/<pipeline>-<hash>)
+        #[arg(short, long)]
+        logs: Option<PathBuf>,
+    },
+
+    /// Rewind so the next `pas run` resumes just before a node
+    Restore {
+        /// Path to the pipeline .dot file
+        pipeline: PathBuf,
+
+        /// Node to re-run from
+        node: String,
+
+        /// Logs directory (default: .pas/logs/<pipeline>-<hash>)
+        #[arg(short, long)]
+        logs: Option<PathBuf>,
     },
 }
 
+/// Parse a `--set key=value` argument.
+fn parse_key_val(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got '{}'", s)),
+    }
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
@@ -213,8 +354,16 @@ async fn main() -> anyhow::Result<()> {
             max_budget_usd,
             max_steps,
             fresh,
+            from,
+            on_drift,
+            worktree,
+            vars,
         } => {
+            let vars: HashMap<String, String> = vars.into_iter().collect();
             if pipeline.is_dir() {
+                if from.is_some() {
+                    anyhow::bail!("--from needs a single .dot pipeline, not a directory");
+                }
                 cmd_run_dir(
                     &pipeline,
                     workdir.as_deref(),
@@ -222,6 +371,9 @@ async fn main() -> anyhow::Result<()> {
                     max_budget_usd,
                     max_steps,
                     fresh,
+                    on_drift,
+                    worktree,
+                    &vars,
                 )
                 .await?;
             } else {
@@ -233,10 +385,47 @@ async fn main() -> anyhow::Result<()> {
                     max_budget_usd,
                     max_steps,
                     fresh,
+                    from.as_deref(),
+                    on_drift,
+                    worktree,
+                    &vars,
                 )
                 .await?;
             }
         }
+        Commands::Checkpoint { command } => match command {
+            CheckpointCommand::List { pipeline, logs } => {
+                cmd_checkpoint_list(&pipeline, logs.as_deref()).await?;
+            }
+            CheckpointCommand::Restore {
+                pipeline,
+                node,
+                logs,
+            } => {
+                cmd_checkpoint_restore(&pipeline, &node, logs.as_deref()).await?;
+            }
+        },
+        Commands::Artifacts { command } => match command {
+            ArtifactsCommand::List { pipeline, logs } => {
+                cmd_artifacts_list(&pipeline, logs.as_deref()).await?;
+            }
+            ArtifactsCommand::Get {
+                pipeline,
+                artifact,
+                output,
+                logs,
+            } => {
+                cmd_artifacts_get(&pipeline, &artifact, output.as_deref(), logs.as_deref()).await?;
+            }
+        },
+        Commands::Runs { command } => match command {
+            RunsCommand::List { pipeline, limit } => {
+                cmd_runs_list(pipeline.as_deref(), limit).await?;
+            }
+            RunsCommand::Show { id } => {
+                cmd_runs_show(id).await?;
+            }
+        },
         Commands::Validate { pipeline } => {
             cmd_validate(&pipeline)?;
         }
@@ -318,7 +507,10 @@ async fn main() -> anyhow::Result<()> {
             max_budget_usd,
             max_steps,
             fresh,
+            on_drift,
+            vars,
         } => {
+            let vars: HashMap<String, String> = vars.into_iter().collect();
             cmd_launch(
                 &docs_dir,
                 output.as_deref(),
@@ -327,6 +519,8 @@ async fn main() -> anyhow::Result<()> {
                 max_budget_usd,
                 max_steps,
                 fresh,
+                on_drift,
+                &vars,
                 cli.verbose,
             )
             .await?;
@@ -339,8 +533,14 @@ async fn main() -> anyhow::Result<()> {
 pub(crate) fn load_pipeline(
     path: &std::path::Path,
 ) -> anyhow::Result<attractor_pipeline::PipelineGraph> {
-    let source = std::fs::read_to_string(path)?;
-    let dot = attractor_dot::parse(&source)?;
-    let graph = attractor_pipeline::PipelineGraph::from_dot(dot)?;
-    Ok(graph)
+    load_pipeline_with_vars(path, &HashMap::new())
+}
+
+/// Load a pipeline and run the transform pass (model stylesheet and `${key}`
+/// expansion), with `vars` overriding graph attributes of the same name.
+pub(crate) fn load_pipeline_with_vars(
+    path: &std::path::Path,
+    vars: &HashMap<String, String>,
+) -> anyhow::Result<attractor_pipeline::PipelineGraph> {
+    Ok(attractor_pipeline::PipelineGraph::load(path, vars)?)
 }
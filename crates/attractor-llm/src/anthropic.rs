@@ -1,8 +1,11 @@
 use async_trait::async_trait;
 use futures_core::Stream;
 use serde_json::json;
+use std::collections::HashMap;
 use std::pin::Pin;
+use std::sync::Arc;
 
+use crate::sse::{self, SseEvent, SseHandler};
 use crate::{
     ContentPart, FinishReason, Message, ProviderAdapter, Request, Response, Role, StreamEvent,
     ToolCallResult, ToolDefinition, Usage,
@@ -40,6 +43,15 @@ impl AnthropicAdapter {
         })?;
         Ok(Self::new(key))
     }
+
+    fn messages_request(&self, body: &serde_json::Value) -> reqwest::RequestBuilder {
+        self.client
+            .post(format!("{}/v1/messages", self.base_url))
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(body)
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -249,13 +261,7 @@ fn parse_response(body: &serde_json::Value) -> Result<Response, AttractorError>
     let id = body["id"].as_str().unwrap_or("").to_string();
     let model = body["model"].as_str().unwrap_or("").to_string();
 
-    let stop_reason = match body["stop_reason"].as_str() {
-        Some("end_turn") => FinishReason::EndTurn,
-        Some("max_tokens") => FinishReason::MaxTokens,
-        Some("stop_sequence") => FinishReason::StopSequence,
-        Some("tool_use") => FinishReason::ToolUse,
-        _ => FinishReason::EndTurn,
-    };
+    let stop_reason = parse_stop_reason(body["stop_reason"].as_str());
 
     let mut text_parts: Vec<String> = Vec::new();
     let mut tool_calls: Vec<ToolCallResult> = Vec::new();
@@ -313,6 +319,151 @@ fn parse_response(body: &serde_json::Value) -> Result<Response, AttractorError>
     })
 }
 
+fn parse_stop_reason(reason: Option<&str>) -> FinishReason {
+    match reason {
+        Some("max_tokens") => FinishReason::MaxTokens,
+        Some("stop_sequence") => FinishReason::StopSequence,
+        Some("tool_use") => FinishReason::ToolUse,
+        _ => FinishReason::EndTurn,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streaming (Anthropic SSE → StreamEvent)
+// ---------------------------------------------------------------------------
+
+/// Content block kinds, tracked by index so `content_block_stop` can emit the
+/// matching end event.
+enum BlockKind {
+    Text,
+    ToolUse(String),
+    Other,
+}
+
+#[derive(Default)]
+struct StreamState {
+    blocks: HashMap<u64, BlockKind>,
+    usage: Usage,
+    stop_reason: Option<String>,
+    finished: bool,
+}
+
+impl SseHandler for StreamState {
+    fn on_event(&mut self, event: &SseEvent) -> Vec<StreamEvent> {
+        if event.event.as_deref() == Some("ping") {
+            return Vec::new();
+        }
+        let data = match sse::parse_data("anthropic", event) {
+            Ok(data) => data,
+            Err(err) => return vec![err],
+        };
+
+        match data["type"].as_str().unwrap_or_default() {
+            "message_start" => {
+                let message = &data["message"];
+                self.usage.input_tokens = message["usage"]["input_tokens"].as_u64().unwrap_or(0);
+                self.usage.cache_read_tokens = message["usage"]["cache_read_input_tokens"].as_u64();
+                self.usage.cache_write_tokens =
+                    message["usage"]["cache_creation_input_tokens"].as_u64();
+                vec![StreamEvent::MessageStart {
+                    id: message["id"].as_str().unwrap_or("").to_string(),
+                    model: message["model"].as_str().unwrap_or("").to_string(),
+                }]
+            }
+            "content_block_start" => {
+                let index = data["index"].as_u64().unwrap_or(0);
+                let block = &data["content_block"];
+                match block["type"].as_str() {
+                    Some("text") => {
+                        self.blocks.insert(index, BlockKind::Text);
+                        vec![StreamEvent::ContentStart]
+                    }
+                    Some("tool_use") => {
+                        let id = block["id"].as_str().unwrap_or("").to_string();
+                        self.blocks.insert(index, BlockKind::ToolUse(id.clone()));
+                        vec![StreamEvent::ToolCallStart {
+                            id,
+                            name: block["name"].as_str().unwrap_or("").to_string(),
+                        }]
+                    }
+                    _ => {
+                        self.blocks.insert(index, BlockKind::Other);
+                        Vec::new()
+                    }
+                }
+            }
+            "content_block_delta" => {
+                let index = data["index"].as_u64().unwrap_or(0);
+                let delta = &data["delta"];
+                match delta["type"].as_str() {
+                    Some("text_delta") => vec![StreamEvent::ContentDelta {
+                        text: delta["text"].as_str().unwrap_or("").to_string(),
+                    }],
+                    Some("thinking_delta") => vec![StreamEvent::ThinkingDelta {
+                        text: delta["thinking"].as_str().unwrap_or("").to_string(),
+                    }],
+                    Some("input_json_delta") => match self.blocks.get(&index) {
+                        Some(BlockKind::ToolUse(id)) => vec![StreamEvent::ToolCallDelta {
+                            id: id.clone(),
+                            json_chunk: delta["partial_json"].as_str().unwrap_or("").to_string(),
+                        }],
+                        _ => Vec::new(),
+                    },
+                    _ => Vec::new(),
+                }
+            }
+            "content_block_stop" => {
+                let index = data["index"].as_u64().unwrap_or(0);
+                match self.blocks.remove(&index) {
+                    Some(BlockKind::Text) => vec![StreamEvent::ContentEnd],
+                    Some(BlockKind::ToolUse(id)) => vec![StreamEvent::ToolCallEnd { id }],
+                    _ => Vec::new(),
+                }
+            }
+            "message_delta" => {
+                if let Some(reason) = data["delta"]["stop_reason"].as_str() {
+                    self.stop_reason = Some(reason.to_string());
+                }
+                if let Some(output) = data["usage"]["output_tokens"].as_u64() {
+                    self.usage.output_tokens = output;
+                }
+                Vec::new()
+            }
+            "message_stop" => {
+                self.finished = true;
+                self.usage.total_tokens = self.usage.input_tokens + self.usage.output_tokens;
+                vec![StreamEvent::MessageEnd {
+                    usage: self.usage.clone(),
+                    finish_reason: parse_stop_reason(self.stop_reason.as_deref()),
+                }]
+            }
+            "error" => {
+                self.finished = true;
+                let error = &data["error"];
+                let message = error["message"].as_str().unwrap_or("").to_string();
+                let retryable = error["type"].as_str() == Some("overloaded_error");
+                vec![StreamEvent::Error(Arc::new(
+                    AttractorError::ProviderError {
+                        provider: "anthropic".into(),
+                        status: 0,
+                        message,
+                        retryable,
+                    },
+                ))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        if self.finished {
+            Vec::new()
+        } else {
+            vec![sse::truncated_stream_error("anthropic")]
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Error mapping
 // ---------------------------------------------------------------------------
@@ -372,21 +523,14 @@ impl ProviderAdapter for AnthropicAdapter {
     async fn complete(&self, request: &Request) -> Result<Response, AttractorError> {
         let body = build_request_body(request);
 
-        let resp = self
-            .client
-            .post(format!("{}/v1/messages", self.base_url))
-            .header("x-api-key", &self.api_key)
-            .header("anthropic-version", "2023-06-01")
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AttractorError::ProviderError {
+        let resp = self.messages_request(&body).send().await.map_err(|e| {
+            AttractorError::ProviderError {
                 provider: "anthropic".into(),
                 status: 0,
                 message: e.to_string(),
                 retryable: true,
-            })?;
+            }
+        })?;
 
         let status = resp.status();
         let response_body = resp
@@ -414,8 +558,16 @@ impl ProviderAdapter for AnthropicAdapter {
         parse_response(&json)
     }
 
-    fn stream(&self, _request: &Request) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>> {
-        Box::pin(tokio_stream::empty::<StreamEvent>())
+    fn stream(&self, request: &Request) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>> {
+        let mut body = build_request_body(request);
+        body["stream"] = json!(true);
+
+        sse::spawn_stream(
+            self.messages_request(&body),
+            "anthropic",
+            map_error,
+            StreamState::default(),
+        )
     }
 
     fn name(&self) -> &str {
@@ -686,4 +838,113 @@ mod tests {
         assert_eq!(content[0]["type"], "tool_result");
         assert_eq!(content[0]["tool_use_id"], "tc_1");
     }
+
+    // ---- streaming ----
+
+    const ANTHROPIC_SSE: &str = "event: message_start\n\
+data: {\"type\":\"message_start\",\"message\":{\"id\":\"msg_1\",\"model\":\"claude-sonnet-4-5-20250929\",\"usage\":{\"input_tokens\":12,\"output_tokens\":1}}}\n\n\
+event: ping\ndata: {\"type\":\"ping\"}\n\n\
+event: content_block_start\n\
+data: {\"type\":\"content_block_start\",\"index\":0,\"content_block\":{\"type\":\"text\",\"text\":\"\"}}\n\n\
+event: content_block_delta\n\
+data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"Hel\"}}\n\n\
+event: content_block_delta\n\
+data: {\"type\":\"content_block_delta\",\"index\":0,\"delta\":{\"type\":\"text_delta\",\"text\":\"lo\"}}\n\n\
+event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":0}\n\n\
+event: content_block_start\n\
+data: {\"type\":\"content_block_start\",\"index\":1,\"content_block\":{\"type\":\"tool_use\",\"id\":\"toolu_1\",\"name\":\"read_file\",\"input\":{}}}\n\n\
+event: content_block_delta\n\
+data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"{\\\"path\\\":\"}}\n\n\
+event: content_block_delta\n\
+data: {\"type\":\"content_block_delta\",\"index\":1,\"delta\":{\"type\":\"input_json_delta\",\"partial_json\":\"\\\"a.rs\\\"}\"}}\n\n\
+event: content_block_stop\ndata: {\"type\":\"content_block_stop\",\"index\":1}\n\n\
+event: message_delta\n\
+data: {\"type\":\"message_delta\",\"delta\":{\"stop_reason\":\"tool_use\"},\"usage\":{\"output_tokens\":30}}\n\n\
+event: message_stop\ndata: {\"type\":\"message_stop\"}\n\n";
+
+    #[tokio::test]
+    async fn stream_translates_sse_events() {
+        use tokio_stream::StreamExt;
+
+        let (base_url, request) =
+            crate::sse::mock_server(200, "text/event-stream", ANTHROPIC_SSE).await;
+        let adapter = AnthropicAdapter::new("test-key".into()).with_base_url(base_url);
+        let events: Vec<StreamEvent> = adapter.stream(&make_basic_request()).collect().await;
+
+        let raw_request = request.await.unwrap();
+        assert!(raw_request.starts_with("POST /v1/messages"));
+        assert!(raw_request.contains("\"stream\":true"));
+
+        assert!(matches!(
+            &events[0],
+            StreamEvent::MessageStart { id, .. } if id == "msg_1"
+        ));
+        assert!(matches!(events[1], StreamEvent::ContentStart));
+        let text: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ContentDelta { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, "Hello");
+        assert!(matches!(events[4], StreamEvent::ContentEnd));
+        assert!(matches!(
+            &events[5],
+            StreamEvent::ToolCallStart { id, name } if id == "toolu_1" && name == "read_file"
+        ));
+        let args: String = events
+            .iter()
+            .filter_map(|e| match e {
+                StreamEvent::ToolCallDelta { id, json_chunk } if id == "toolu_1" => {
+                    Some(json_chunk.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(args, r#"{"path":"a.rs"}"#);
+        assert!(matches!(
+            &events[8],
+            StreamEvent::ToolCallEnd { id } if id == "toolu_1"
+        ));
+        match events.last().unwrap() {
+            StreamEvent::MessageEnd {
+                usage,
+                finish_reason,
+            } => {
+                assert_eq!(usage.input_tokens, 12);
+                assert_eq!(usage.output_tokens, 30);
+                assert_eq!(usage.total_tokens, 42);
+                assert_eq!(*finish_reason, FinishReason::ToolUse);
+            }
+            other => panic!("expected MessageEnd, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_maps_http_errors() {
+        use tokio_stream::StreamExt;
+
+        let (base_url, _request) = crate::sse::mock_server(
+            529,
+            "application/json",
+            r#"{"error":{"type":"overloaded_error","message":"Overloaded"}}"#,
+        )
+        .await;
+        let adapter = AnthropicAdapter::new("test-key".into()).with_base_url(base_url);
+        let events: Vec<StreamEvent> = adapter.stream(&make_basic_request()).collect().await;
+
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            StreamEvent::Error(err) => assert!(err.is_retryable()),
+            other => panic!("expected Error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn stream_without_message_stop_reports_truncation() {
+        let mut state = StreamState::default();
+        let events = state.finish();
+        assert!(matches!(events.as_slice(), [StreamEvent::Error(_)]));
+    }
 }
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use attractor_types::AttractorError;
+use futures_core::Stream;
 
-use crate::{DynProvider, ProviderAdapter, Request, Response};
+use crate::{DynProvider, ProviderAdapter, Request, Response, StreamEvent, Usage};
 
 // ---------------------------------------------------------------------------
 // Middleware
@@ -93,6 +95,10 @@ pub struct ModelInfo {
     pub context_window: usize,
     pub supports_tools: bool,
     pub supports_reasoning: bool,
+    /// USD per million input tokens.
+    pub input_cost_per_mtok: f64,
+    /// USD per million output tokens.
+    pub output_cost_per_mtok: f64,
 }
 
 pub struct ModelCatalog {
@@ -104,10 +110,10 @@ impl ModelCatalog {
         let mut models = HashMap::new();
 
         // Claude models
-        for (id, ctx, reasoning) in [
-            ("claude-opus-4-6", 200_000, true),
-            ("claude-sonnet-4-5-20250929", 200_000, true),
-            ("claude-haiku-4-5-20251001", 200_000, false),
+        for (id, ctx, reasoning, input_cost, output_cost) in [
+            ("claude-opus-4-6", 200_000, true, 5.0, 25.0),
+            ("claude-sonnet-4-5-20250929", 200_000, true, 3.0, 15.0),
+            ("claude-haiku-4-5-20251001", 200_000, false, 1.0, 5.0),
         ] {
             models.insert(
                 id.to_string(),
@@ -117,16 +123,18 @@ impl ModelCatalog {
                     context_window: ctx,
                     supports_tools: true,
                     supports_reasoning: reasoning,
+                    input_cost_per_mtok: input_cost,
+                    output_cost_per_mtok: output_cost,
                 },
             );
         }
 
         // GPT models
-        for (id, ctx, reasoning) in [
-            ("gpt-4o", 128_000, false),
-            ("gpt-4o-mini", 128_000, false),
-            ("o1", 200_000, true),
-            ("o3-mini", 200_000, true),
+        for (id, ctx, reasoning, input_cost, output_cost) in [
+            ("gpt-4o", 128_000, false, 2.5, 10.0),
+            ("gpt-4o-mini", 128_000, false, 0.15, 0.6),
+            ("o1", 200_000, true, 15.0, 60.0),
+            ("o3-mini", 200_000, true, 1.1, 4.4),
         ] {
             models.insert(
                 id.to_string(),
@@ -136,14 +144,16 @@ impl ModelCatalog {
                     context_window: ctx,
                     supports_tools: true,
                     supports_reasoning: reasoning,
+                    input_cost_per_mtok: input_cost,
+                    output_cost_per_mtok: output_cost,
                 },
             );
         }
 
         // Gemini models
-        for (id, ctx) in [
-            ("gemini-2.5-pro", 1_000_000),
-            ("gemini-2.5-flash", 1_000_000),
+        for (id, ctx, input_cost, output_cost) in [
+            ("gemini-2.5-pro", 1_000_000, 1.25, 10.0),
+            ("gemini-2.5-flash", 1_000_000, 0.3, 2.5),
         ] {
             models.insert(
                 id.to_string(),
@@ -153,6 +163,8 @@ impl ModelCatalog {
                     context_window: ctx,
                     supports_tools: true,
                     supports_reasoning: true,
+                    input_cost_per_mtok: input_cost,
+                    output_cost_per_mtok: output_cost,
                 },
             );
         }
@@ -167,6 +179,15 @@ impl ModelCatalog {
     pub fn provider_for_model(&self, model: &str) -> Option<&str> {
         self.models.get(model).map(|m| m.provider.as_str())
     }
+
+    /// Estimate the USD cost of `usage` on `model`; `None` for unknown models.
+    pub fn estimate_cost(&self, model: &str, usage: &Usage) -> Option<f64> {
+        self.models.get(model).map(|m| {
+            (usage.input_tokens as f64 * m.input_cost_per_mtok
+                + usage.output_tokens as f64 * m.output_cost_per_mtok)
+                / 1_000_000.0
+        })
+    }
 }
 
 impl Default for ModelCatalog {
@@ -225,6 +246,25 @@ impl LlmClient {
         Ok(resp)
     }
 
+    /// Stream a response, routing to a provider the same way as [`complete`](Self::complete).
+    ///
+    /// Middleware `before` hooks run on the request; `after` hooks need a full
+    /// [`Response`] and are not called. Provider and transport failures arrive
+    /// as [`StreamEvent::Error`] items on the stream.
+    pub fn stream(
+        &self,
+        request: &Request,
+    ) -> Result<Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>>, AttractorError> {
+        let provider = self.resolve_provider(request)?;
+        let mut req = request.clone();
+
+        for m in &self.middleware {
+            m.before(&mut req);
+        }
+
+        Ok(provider.stream(&req))
+    }
+
     fn resolve_provider(&self, request: &Request) -> Result<&DynProvider, AttractorError> {
         // 1. Explicit provider field
         if let Some(ref provider_name) = request.provider {
@@ -291,11 +331,10 @@ impl Default for LlmClient {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{FinishReason, Message, ProviderAdapter, StreamEvent, Usage};
+    use crate::{FinishReason, Message, ProviderAdapter, StreamEvent};
     use async_trait::async_trait;
-    use futures_core::Stream;
-    use std::pin::Pin;
     use std::sync::atomic::AtomicUsize;
+    use tokio_stream::StreamExt;
 
     struct MockProvider {
         call_count: Arc<AtomicUsize>,
@@ -333,9 +372,17 @@ mod tests {
 
         fn stream(
             &self,
-            _request: &Request,
+            request: &Request,
         ) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>> {
-            Box::pin(tokio_stream::empty::<StreamEvent>())
+            Box::pin(tokio_stream::iter(vec![
+                StreamEvent::MessageStart {
+                    id: "mock-stream".into(),
+                    model: request.model.clone(),
+                },
+                StreamEvent::ContentDelta {
+                    text: "Hello from mock".into(),
+                },
+            ]))
         }
 
         fn name(&self) -> &str {
@@ -390,6 +437,38 @@ mod tests {
         assert_eq!(resp.text, "Hello from mock");
     }
 
+    #[tokio::test]
+    async fn stream_routes_to_provider_and_applies_before_middleware() {
+        struct RenameModel;
+        impl Middleware for RenameModel {
+            fn before(&self, request: &mut Request) {
+                request.model = "renamed-model".into();
+            }
+        }
+
+        let mut client = LlmClient::new().with_middleware(RenameModel);
+        client.register_provider(MockProvider::new());
+
+        let req = make_request("mock-model", Some("mock"));
+        let events: Vec<StreamEvent> = client.stream(&req).unwrap().collect().await;
+        assert_eq!(events.len(), 2);
+        assert!(matches!(
+            &events[0],
+            StreamEvent::MessageStart { model, .. } if model == "renamed-model"
+        ));
+        assert!(matches!(
+            &events[1],
+            StreamEvent::ContentDelta { text } if text == "Hello from mock"
+        ));
+    }
+
+    #[test]
+    fn stream_unknown_provider_errors() {
+        let client = LlmClient::new();
+        let req = make_request("mock-model", Some("missing"));
+        assert!(client.stream(&req).is_err());
+    }
+
     // Test 2: model catalog lookup
     #[test]
     fn model_catalog_lookup() {
@@ -585,4 +664,20 @@ mod tests {
         assert_eq!(catalog.provider_for_model("gemini-2.5-pro"), Some("google"));
         assert_eq!(catalog.provider_for_model("unknown"), None);
     }
+
+    // Test: model catalog cost estimate
+    #[test]
+    fn model_catalog_estimate_cost() {
+        let catalog = ModelCatalog::new();
+        let usage = Usage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            ..Usage::default()
+        };
+        let cost = catalog
+            .estimate_cost("claude-sonnet-4-5-20250929", &usage)
+            .unwrap();
+        assert!((cost - 4.5).abs() < 1e-9);
+        assert_eq!(catalog.estimate_cost("unknown", &usage), None);
+    }
 }
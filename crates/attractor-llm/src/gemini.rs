@@ -3,6 +3,7 @@ use futures_core::Stream;
 use serde_json::json;
 use std::pin::Pin;
 
+use crate::sse::{self, SseEvent, SseHandler};
 use crate::{
     ContentPart, FinishReason, Message, ProviderAdapter, Request, Response, Role, StreamEvent,
     ToolCallResult, ToolDefinition, Usage,
@@ -123,14 +124,7 @@ impl GeminiAdapter {
                 retryable: false,
             })?;
 
-        // Parse finish reason
-        let finish_reason = match candidate["finishReason"].as_str() {
-            Some("STOP") => FinishReason::EndTurn,
-            Some("MAX_TOKENS") => FinishReason::MaxTokens,
-            Some("SAFETY") => FinishReason::EndTurn,
-            Some("STOP_SEQUENCE") => FinishReason::StopSequence,
-            _ => FinishReason::EndTurn,
-        };
+        let finish_reason = parse_finish_reason(candidate["finishReason"].as_str());
 
         // Parse content parts
         let mut text_parts: Vec<String> = Vec::new();
@@ -153,22 +147,7 @@ impl GeminiAdapter {
             }
         }
 
-        // Parse usage
-        let usage_meta = &json["usageMetadata"];
-        let input_tokens = usage_meta["promptTokenCount"].as_u64().unwrap_or(0);
-        let output_tokens = usage_meta["candidatesTokenCount"].as_u64().unwrap_or(0);
-        let total_tokens = usage_meta["totalTokenCount"]
-            .as_u64()
-            .unwrap_or(input_tokens + output_tokens);
-
-        let usage = Usage {
-            input_tokens,
-            output_tokens,
-            reasoning_tokens: None,
-            cache_read_tokens: None,
-            cache_write_tokens: None,
-            total_tokens,
-        };
+        let usage = parse_usage(&json["usageMetadata"]);
 
         // Determine finish reason override for tool calls
         let final_finish_reason = if !tool_calls.is_empty() {
@@ -189,6 +168,141 @@ impl GeminiAdapter {
     }
 }
 
+fn parse_finish_reason(reason: Option<&str>) -> FinishReason {
+    match reason {
+        Some("MAX_TOKENS") => FinishReason::MaxTokens,
+        Some("STOP_SEQUENCE") => FinishReason::StopSequence,
+        _ => FinishReason::EndTurn,
+    }
+}
+
+fn parse_usage(usage_meta: &serde_json::Value) -> Usage {
+    let input_tokens = usage_meta["promptTokenCount"].as_u64().unwrap_or(0);
+    let output_tokens = usage_meta["candidatesTokenCount"].as_u64().unwrap_or(0);
+    let total_tokens = usage_meta["totalTokenCount"]
+        .as_u64()
+        .unwrap_or(input_tokens + output_tokens);
+
+    Usage {
+        input_tokens,
+        output_tokens,
+        reasoning_tokens: usage_meta["thoughtsTokenCount"].as_u64(),
+        cache_read_tokens: None,
+        cache_write_tokens: None,
+        total_tokens,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streaming (Gemini SSE → StreamEvent)
+// ---------------------------------------------------------------------------
+
+/// Gemini streams whole `GenerateContentResponse` chunks with no event names
+/// and no explicit end marker, so message boundaries are synthesised here.
+struct StreamState {
+    model: String,
+    started: bool,
+    text_open: bool,
+    saw_tool_call: bool,
+    finish_reason: Option<String>,
+    usage: Usage,
+}
+
+impl StreamState {
+    fn new(model: String) -> Self {
+        Self {
+            model,
+            started: false,
+            text_open: false,
+            saw_tool_call: false,
+            finish_reason: None,
+            usage: Usage::default(),
+        }
+    }
+}
+
+impl SseHandler for StreamState {
+    fn on_event(&mut self, event: &SseEvent) -> Vec<StreamEvent> {
+        let data = match sse::parse_data("google", event) {
+            Ok(data) => data,
+            Err(err) => return vec![err],
+        };
+
+        let mut out = Vec::new();
+        if !self.started {
+            self.started = true;
+            out.push(StreamEvent::MessageStart {
+                id: uuid::Uuid::new_v4().to_string(),
+                model: self.model.clone(),
+            });
+        }
+
+        let candidate = &data["candidates"][0];
+        if let Some(parts) = candidate["content"]["parts"].as_array() {
+            for part in parts {
+                if let Some(text) = part["text"].as_str() {
+                    if part["thought"].as_bool() == Some(true) {
+                        out.push(StreamEvent::ThinkingDelta {
+                            text: text.to_string(),
+                        });
+                        continue;
+                    }
+                    if !self.text_open {
+                        self.text_open = true;
+                        out.push(StreamEvent::ContentStart);
+                    }
+                    out.push(StreamEvent::ContentDelta {
+                        text: text.to_string(),
+                    });
+                }
+                if let Some(fc) = part.get("functionCall") {
+                    // Function calls arrive whole; emit start/delta/end together.
+                    self.saw_tool_call = true;
+                    let id = uuid::Uuid::new_v4().to_string();
+                    out.push(StreamEvent::ToolCallStart {
+                        id: id.clone(),
+                        name: fc["name"].as_str().unwrap_or("").to_string(),
+                    });
+                    out.push(StreamEvent::ToolCallDelta {
+                        id: id.clone(),
+                        json_chunk: fc["args"].to_string(),
+                    });
+                    out.push(StreamEvent::ToolCallEnd { id });
+                }
+            }
+        }
+
+        if let Some(reason) = candidate["finishReason"].as_str() {
+            self.finish_reason = Some(reason.to_string());
+        }
+        if data["usageMetadata"].is_object() {
+            self.usage = parse_usage(&data["usageMetadata"]);
+        }
+        out
+    }
+
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        if self.finish_reason.is_none() {
+            return vec![sse::truncated_stream_error("google")];
+        }
+
+        let mut out = Vec::new();
+        if self.text_open {
+            out.push(StreamEvent::ContentEnd);
+        }
+        let finish_reason = if self.saw_tool_call {
+            FinishReason::ToolUse
+        } else {
+            parse_finish_reason(self.finish_reason.as_deref())
+        };
+        out.push(StreamEvent::MessageEnd {
+            usage: self.usage.clone(),
+            finish_reason,
+        });
+        out
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Message conversion helpers
 // ---------------------------------------------------------------------------
@@ -358,8 +472,28 @@ impl ProviderAdapter for GeminiAdapter {
         Ok(response)
     }
 
-    fn stream(&self, _request: &Request) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>> {
-        Box::pin(tokio_stream::empty::<StreamEvent>())
+    fn stream(&self, request: &Request) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>> {
+        let body = self.build_request_body(request);
+        let model = if request.model.is_empty() {
+            &self.default_model
+        } else {
+            &request.model
+        };
+
+        let url = format!(
+            "{}/models/{}:streamGenerateContent?alt=sse&key={}",
+            self.base_url, model, self.api_key
+        );
+
+        sse::spawn_stream(
+            self.client
+                .post(&url)
+                .header("content-type", "application/json")
+                .json(&body),
+            "google",
+            map_error,
+            StreamState::new(model.to_string()),
+        )
     }
 
     fn name(&self) -> &str {
@@ -375,7 +509,7 @@ impl ProviderAdapter for GeminiAdapter {
     }
 
     fn supports_streaming(&self) -> bool {
-        false
+        true
     }
 
     fn supports_reasoning(&self) -> bool {
@@ -581,7 +715,7 @@ mod tests {
         assert_eq!(provider.name(), "google");
         assert_eq!(provider.default_model(), "gemini-2.5-pro");
         assert!(provider.supports_tools());
-        assert!(!provider.supports_streaming());
+        assert!(provider.supports_streaming());
         assert!(provider.supports_reasoning());
         assert_eq!(provider.context_window_size(), 1_000_000);
     }
@@ -627,4 +761,74 @@ mod tests {
         );
         assert!(matches!(err, AttractorError::AuthError { .. }));
     }
+
+    // ---- streaming ----
+
+    const GEMINI_SSE: &str = "data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Let me think\",\"thought\":true}]}}]}\n\n\
+data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\"Hello\"}]}}]}\n\n\
+data: {\"candidates\":[{\"content\":{\"role\":\"model\",\"parts\":[{\"text\":\" world\"}]},\"finishReason\":\"STOP\"}],\"usageMetadata\":{\"promptTokenCount\":4,\"candidatesTokenCount\":2,\"totalTokenCount\":6}}\n\n";
+
+    #[tokio::test]
+    async fn stream_translates_sse_chunks() {
+        use tokio_stream::StreamExt;
+
+        let (base_url, request) =
+            crate::sse::mock_server(200, "text/event-stream", GEMINI_SSE).await;
+        let adapter = GeminiAdapter::new("test-key".into()).with_base_url(base_url);
+        let events: Vec<StreamEvent> = adapter.stream(&make_basic_request()).collect().await;
+
+        let raw_request = request.await.unwrap();
+        assert!(raw_request
+            .starts_with("POST /models/gemini-2.5-pro:streamGenerateContent?alt=sse&key=test-key"));
+
+        assert_eq!(events.len(), 7);
+        assert!(matches!(
+            &events[0],
+            StreamEvent::MessageStart { model, .. } if model == "gemini-2.5-pro"
+        ));
+        assert!(
+            matches!(&events[1], StreamEvent::ThinkingDelta { text } if text == "Let me think")
+        );
+        assert!(matches!(events[2], StreamEvent::ContentStart));
+        assert!(matches!(&events[3], StreamEvent::ContentDelta { text } if text == "Hello"));
+        assert!(matches!(&events[4], StreamEvent::ContentDelta { text } if text == " world"));
+        assert!(matches!(events[5], StreamEvent::ContentEnd));
+        match &events[6] {
+            StreamEvent::MessageEnd {
+                usage,
+                finish_reason,
+            } => {
+                assert_eq!(usage.total_tokens, 6);
+                assert_eq!(*finish_reason, FinishReason::EndTurn);
+            }
+            other => panic!("expected MessageEnd, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn stream_function_call_chunk_emits_tool_events() {
+        let mut state = StreamState::new("gemini-2.5-pro".into());
+        let events = state.on_event(&crate::sse::SseEvent {
+            event: None,
+            data: r#"{"candidates":[{"content":{"parts":[{"functionCall":{"name":"read_file","args":{"path":"a.rs"}}}]},"finishReason":"STOP"}]}"#.into(),
+        });
+        assert_eq!(events.len(), 4);
+        assert!(
+            matches!(&events[1], StreamEvent::ToolCallStart { name, .. } if name == "read_file")
+        );
+        assert!(matches!(
+            &events[2],
+            StreamEvent::ToolCallDelta { json_chunk, .. } if json_chunk == r#"{"path":"a.rs"}"#
+        ));
+        assert!(matches!(events[3], StreamEvent::ToolCallEnd { .. }));
+
+        let end = state.finish();
+        assert!(matches!(
+            end.as_slice(),
+            [StreamEvent::MessageEnd {
+                finish_reason: FinishReason::ToolUse,
+                ..
+            }]
+        ));
+    }
 }
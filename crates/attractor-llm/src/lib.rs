@@ -13,6 +13,8 @@ mod gemini;
 mod openai;
 #[cfg(feature = "providers")]
 mod provider;
+#[cfg(feature = "providers")]
+mod sse;
 mod types;
 
 #[cfg(feature = "providers")]
@@ -2,7 +2,9 @@ use async_trait::async_trait;
 use futures_core::Stream;
 use serde_json::json;
 use std::pin::Pin;
+use std::sync::Arc;
 
+use crate::sse::{self, SseEvent, SseHandler};
 use crate::{
     ContentPart, FinishReason, Message, ProviderAdapter, Request, Response, Role, StreamEvent,
     ToolCallResult, Usage,
@@ -99,23 +101,7 @@ impl OpenAiAdapter {
         let id = body["id"].as_str().unwrap_or("").to_string();
         let model = body["model"].as_str().unwrap_or("").to_string();
 
-        // Map status to finish reason
-        let finish_reason = match body["status"].as_str() {
-            Some("completed") => {
-                // Check if there are function_call items in output — that means ToolUse
-                let has_tool_calls = body["output"]
-                    .as_array()
-                    .map(|arr| arr.iter().any(|item| item["type"] == "function_call"))
-                    .unwrap_or(false);
-                if has_tool_calls {
-                    FinishReason::ToolUse
-                } else {
-                    FinishReason::EndTurn
-                }
-            }
-            Some("incomplete") => FinishReason::MaxTokens,
-            _ => FinishReason::EndTurn,
-        };
+        let finish_reason = parse_finish_reason(&body);
 
         // Extract text and tool calls from output array
         let mut text_parts: Vec<String> = Vec::new();
@@ -152,32 +138,155 @@ impl OpenAiAdapter {
             }
         }
 
-        // Parse usage
-        let usage_obj = &body["usage"];
-        let input_tokens = usage_obj["input_tokens"].as_u64().unwrap_or(0);
-        let output_tokens = usage_obj["output_tokens"].as_u64().unwrap_or(0);
-        let cached_tokens = usage_obj["input_tokens_details"]["cached_tokens"].as_u64();
-        let reasoning_tokens = usage_obj["output_tokens_details"]["reasoning_tokens"].as_u64();
-
-        let usage = Usage {
-            input_tokens,
-            output_tokens,
-            reasoning_tokens,
-            cache_read_tokens: cached_tokens,
-            cache_write_tokens: None,
-            total_tokens: input_tokens + output_tokens,
-        };
-
         Ok(Response {
             id,
             text: text_parts.join(""),
             tool_calls,
             reasoning: None,
-            usage,
+            usage: parse_usage(&body["usage"]),
             model,
             finish_reason,
         })
     }
+
+    fn responses_request(&self, body: &serde_json::Value) -> reqwest::RequestBuilder {
+        self.client
+            .post(format!("{}/v1/responses", self.base_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(body)
+    }
+}
+
+/// Map a response object's status to a finish reason.
+fn parse_finish_reason(body: &serde_json::Value) -> FinishReason {
+    match body["status"].as_str() {
+        Some("completed") => {
+            // Check if there are function_call items in output — that means ToolUse
+            let has_tool_calls = body["output"]
+                .as_array()
+                .map(|arr| arr.iter().any(|item| item["type"] == "function_call"))
+                .unwrap_or(false);
+            if has_tool_calls {
+                FinishReason::ToolUse
+            } else {
+                FinishReason::EndTurn
+            }
+        }
+        Some("incomplete") => FinishReason::MaxTokens,
+        _ => FinishReason::EndTurn,
+    }
+}
+
+fn parse_usage(usage_obj: &serde_json::Value) -> Usage {
+    let input_tokens = usage_obj["input_tokens"].as_u64().unwrap_or(0);
+    let output_tokens = usage_obj["output_tokens"].as_u64().unwrap_or(0);
+    let cached_tokens = usage_obj["input_tokens_details"]["cached_tokens"].as_u64();
+    let reasoning_tokens = usage_obj["output_tokens_details"]["reasoning_tokens"].as_u64();
+
+    Usage {
+        input_tokens,
+        output_tokens,
+        reasoning_tokens,
+        cache_read_tokens: cached_tokens,
+        cache_write_tokens: None,
+        total_tokens: input_tokens + output_tokens,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Streaming (Responses API SSE → StreamEvent)
+// ---------------------------------------------------------------------------
+
+#[derive(Default)]
+struct StreamState {
+    finished: bool,
+}
+
+impl SseHandler for StreamState {
+    fn on_event(&mut self, event: &SseEvent) -> Vec<StreamEvent> {
+        if event.data == "[DONE]" {
+            return Vec::new();
+        }
+        let data = match sse::parse_data("openai", event) {
+            Ok(data) => data,
+            Err(err) => return vec![err],
+        };
+
+        match data["type"].as_str().unwrap_or_default() {
+            "response.created" => vec![StreamEvent::MessageStart {
+                id: data["response"]["id"].as_str().unwrap_or("").to_string(),
+                model: data["response"]["model"].as_str().unwrap_or("").to_string(),
+            }],
+            "response.output_item.added" => {
+                let item = &data["item"];
+                match item["type"].as_str() {
+                    Some("message") => vec![StreamEvent::ContentStart],
+                    Some("function_call") => vec![StreamEvent::ToolCallStart {
+                        id: item["id"].as_str().unwrap_or("").to_string(),
+                        name: item["name"].as_str().unwrap_or("").to_string(),
+                    }],
+                    _ => Vec::new(),
+                }
+            }
+            "response.output_text.delta" => vec![StreamEvent::ContentDelta {
+                text: data["delta"].as_str().unwrap_or("").to_string(),
+            }],
+            "response.reasoning_summary_text.delta" => vec![StreamEvent::ThinkingDelta {
+                text: data["delta"].as_str().unwrap_or("").to_string(),
+            }],
+            "response.function_call_arguments.delta" => vec![StreamEvent::ToolCallDelta {
+                id: data["item_id"].as_str().unwrap_or("").to_string(),
+                json_chunk: data["delta"].as_str().unwrap_or("").to_string(),
+            }],
+            "response.output_item.done" => {
+                let item = &data["item"];
+                match item["type"].as_str() {
+                    Some("message") => vec![StreamEvent::ContentEnd],
+                    Some("function_call") => vec![StreamEvent::ToolCallEnd {
+                        id: item["id"].as_str().unwrap_or("").to_string(),
+                    }],
+                    _ => Vec::new(),
+                }
+            }
+            "response.completed" | "response.incomplete" => {
+                self.finished = true;
+                let response = &data["response"];
+                vec![StreamEvent::MessageEnd {
+                    usage: parse_usage(&response["usage"]),
+                    finish_reason: parse_finish_reason(response),
+                }]
+            }
+            "response.failed" | "error" => {
+                self.finished = true;
+                let error = if data["type"] == "error" {
+                    &data
+                } else {
+                    &data["response"]["error"]
+                };
+                vec![StreamEvent::Error(Arc::new(
+                    AttractorError::ProviderError {
+                        provider: "openai".into(),
+                        status: 0,
+                        message: error["message"]
+                            .as_str()
+                            .unwrap_or("response failed")
+                            .to_string(),
+                        retryable: error["code"].as_str() == Some("server_error"),
+                    },
+                ))]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        if self.finished {
+            Vec::new()
+        } else {
+            vec![sse::truncated_stream_error("openai")]
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -331,20 +440,14 @@ impl ProviderAdapter for OpenAiAdapter {
     async fn complete(&self, request: &Request) -> Result<Response, AttractorError> {
         let body = self.build_request_body(request);
 
-        let resp = self
-            .client
-            .post(format!("{}/v1/responses", self.base_url))
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&body)
-            .send()
-            .await
-            .map_err(|e| AttractorError::ProviderError {
+        let resp = self.responses_request(&body).send().await.map_err(|e| {
+            AttractorError::ProviderError {
                 provider: "openai".into(),
                 status: 0,
                 message: e.to_string(),
                 retryable: true,
-            })?;
+            }
+        })?;
 
         let status = resp.status();
         let response_body = resp
@@ -372,8 +475,16 @@ impl ProviderAdapter for OpenAiAdapter {
         self.parse_response(json)
     }
 
-    fn stream(&self, _request: &Request) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>> {
-        Box::pin(tokio_stream::empty::<StreamEvent>())
+    fn stream(&self, request: &Request) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + '_>> {
+        let mut body = self.build_request_body(request);
+        body["stream"] = json!(true);
+
+        sse::spawn_stream(
+            self.responses_request(&body),
+            "openai",
+            map_error,
+            StreamState::default(),
+        )
     }
 
     fn name(&self) -> &str {
@@ -389,7 +500,7 @@ impl ProviderAdapter for OpenAiAdapter {
     }
 
     fn supports_streaming(&self) -> bool {
-        false // Stub for now
+        true
     }
 
     fn supports_reasoning(&self) -> bool {
@@ -661,4 +772,94 @@ mod tests {
         assert!(body.get("tools").is_none() || body["tools"].is_null());
         assert!(body.get("reasoning").is_none() || body["reasoning"].is_null());
     }
+
+    // ---- streaming ----
+
+    const OPENAI_SSE: &str = "event: response.created\n\
+data: {\"type\":\"response.created\",\"response\":{\"id\":\"resp_1\",\"model\":\"gpt-4o\",\"status\":\"in_progress\"}}\n\n\
+event: response.output_item.added\n\
+data: {\"type\":\"response.output_item.added\",\"output_index\":0,\"item\":{\"type\":\"message\",\"id\":\"msg_1\"}}\n\n\
+event: response.output_text.delta\n\
+data: {\"type\":\"response.output_text.delta\",\"item_id\":\"msg_1\",\"delta\":\"Hi \"}\n\n\
+event: response.output_text.delta\n\
+data: {\"type\":\"response.output_text.delta\",\"item_id\":\"msg_1\",\"delta\":\"there\"}\n\n\
+event: response.output_item.done\n\
+data: {\"type\":\"response.output_item.done\",\"output_index\":0,\"item\":{\"type\":\"message\",\"id\":\"msg_1\"}}\n\n\
+event: response.output_item.added\n\
+data: {\"type\":\"response.output_item.added\",\"output_index\":1,\"item\":{\"type\":\"function_call\",\"id\":\"fc_1\",\"name\":\"shell\"}}\n\n\
+event: response.function_call_arguments.delta\n\
+data: {\"type\":\"response.function_call_arguments.delta\",\"item_id\":\"fc_1\",\"delta\":\"{\\\"cmd\\\":\\\"ls\\\"}\"}\n\n\
+event: response.output_item.done\n\
+data: {\"type\":\"response.output_item.done\",\"output_index\":1,\"item\":{\"type\":\"function_call\",\"id\":\"fc_1\",\"name\":\"shell\"}}\n\n\
+event: response.completed\n\
+data: {\"type\":\"response.completed\",\"response\":{\"id\":\"resp_1\",\"status\":\"completed\",\"output\":[{\"type\":\"message\"},{\"type\":\"function_call\"}],\"usage\":{\"input_tokens\":8,\"output_tokens\":5}}}\n\n";
+
+    #[tokio::test]
+    async fn stream_translates_sse_events() {
+        use tokio_stream::StreamExt;
+
+        let (base_url, request) =
+            crate::sse::mock_server(200, "text/event-stream", OPENAI_SSE).await;
+        let adapter = OpenAiAdapter::new("test-key".into()).with_base_url(base_url);
+        let events: Vec<StreamEvent> = adapter.stream(&make_basic_request()).collect().await;
+
+        let raw_request = request.await.unwrap();
+        assert!(raw_request.starts_with("POST /v1/responses"));
+        assert!(raw_request.contains("\"stream\":true"));
+
+        assert_eq!(events.len(), 9);
+        assert!(matches!(
+            &events[0],
+            StreamEvent::MessageStart { id, model } if id == "resp_1" && model == "gpt-4o"
+        ));
+        assert!(matches!(events[1], StreamEvent::ContentStart));
+        assert!(matches!(&events[2], StreamEvent::ContentDelta { text } if text == "Hi "));
+        assert!(matches!(&events[3], StreamEvent::ContentDelta { text } if text == "there"));
+        assert!(matches!(events[4], StreamEvent::ContentEnd));
+        assert!(matches!(
+            &events[5],
+            StreamEvent::ToolCallStart { id, name } if id == "fc_1" && name == "shell"
+        ));
+        assert!(matches!(
+            &events[6],
+            StreamEvent::ToolCallDelta { id, json_chunk } if id == "fc_1" && json_chunk == r#"{"cmd":"ls"}"#
+        ));
+        assert!(matches!(&events[7], StreamEvent::ToolCallEnd { id } if id == "fc_1"));
+        match &events[8] {
+            StreamEvent::MessageEnd {
+                usage,
+                finish_reason,
+            } => {
+                assert_eq!(usage.total_tokens, 13);
+                assert_eq!(*finish_reason, FinishReason::ToolUse);
+            }
+            other => panic!("expected MessageEnd, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn stream_maps_rate_limit_errors() {
+        use tokio_stream::StreamExt;
+
+        let (base_url, _request) = crate::sse::mock_server(
+            429,
+            "application/json",
+            r#"{"error":{"message":"slow down","retry_after":2}}"#,
+        )
+        .await;
+        let adapter = OpenAiAdapter::new("test-key".into()).with_base_url(base_url);
+        let events: Vec<StreamEvent> = adapter.stream(&make_basic_request()).collect().await;
+
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            StreamEvent::Error(err) => assert!(matches!(
+                err.as_ref(),
+                AttractorError::RateLimited {
+                    retry_after_ms: 2000,
+                    ..
+                }
+            )),
+            other => panic!("expected Error, got {other:?}"),
+        }
+    }
 }
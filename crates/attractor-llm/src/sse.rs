@@ -0,0 +1,270 @@
+//! Server-Sent Events plumbing shared by the provider adapters.
+//!
+//! [`SseParser`] splits a byte stream into [`SseEvent`]s, and [`spawn_stream`]
+//! drives a streaming HTTP request, handing each event to a provider-specific
+//! [`SseHandler`] that translates it into unified [`StreamEvent`]s.
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use attractor_types::AttractorError;
+use futures_core::Stream;
+
+use crate::StreamEvent;
+
+// ---------------------------------------------------------------------------
+// SseParser
+// ---------------------------------------------------------------------------
+
+/// A single event from a `text/event-stream` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SseEvent {
+    /// The `event:` field, if present.
+    pub event: Option<String>,
+    /// All `data:` lines joined with `\n`.
+    pub data: String,
+}
+
+/// Incremental `text/event-stream` parser.
+///
+/// Feed it arbitrary chunks with [`SseParser::push`]; complete events are
+/// returned as soon as their terminating blank line arrives.
+#[derive(Debug, Default)]
+pub(crate) struct SseParser {
+    buf: Vec<u8>,
+}
+
+impl SseParser {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buf
+            .extend(chunk.iter().copied().filter(|&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buf.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buf.drain(..end + 2).collect();
+            if let Some(event) = parse_block(&String::from_utf8_lossy(&block)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+fn parse_block(block: &str) -> Option<SseEvent> {
+    let mut event = None;
+    let mut data: Vec<&str> = Vec::new();
+    for line in block.lines() {
+        if line.is_empty() || line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "event" => event = Some(value.to_string()),
+            "data" => data.push(value),
+            _ => {}
+        }
+    }
+    if event.is_none() && data.is_empty() {
+        return None;
+    }
+    Some(SseEvent {
+        event,
+        data: data.join("\n"),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Streaming driver
+// ---------------------------------------------------------------------------
+
+/// Translates one provider's SSE events into unified stream events.
+pub(crate) trait SseHandler: Send + 'static {
+    /// Handle one SSE event.
+    fn on_event(&mut self, event: &SseEvent) -> Vec<StreamEvent>;
+
+    /// Called once the HTTP body ends.
+    fn finish(&mut self) -> Vec<StreamEvent> {
+        Vec::new()
+    }
+}
+
+/// Send `request` and stream its SSE body through `handler`.
+///
+/// Transport failures and non-2xx responses (mapped with `map_error`) are
+/// reported as a single [`StreamEvent::Error`]. The request runs on a spawned
+/// task that stops as soon as the returned stream is dropped.
+pub(crate) fn spawn_stream<H: SseHandler>(
+    request: reqwest::RequestBuilder,
+    provider: &'static str,
+    map_error: fn(reqwest::StatusCode, &str) -> AttractorError,
+    mut handler: H,
+) -> Pin<Box<dyn Stream<Item = StreamEvent> + Send + 'static>> {
+    let (tx, rx) = tokio::sync::mpsc::channel(64);
+
+    tokio::spawn(async move {
+        let send_error = |e: AttractorError| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(StreamEvent::Error(Arc::new(e))).await;
+            }
+        };
+        let transport_error = |e: reqwest::Error| AttractorError::ProviderError {
+            provider: provider.into(),
+            status: 0,
+            message: e.to_string(),
+            retryable: true,
+        };
+
+        let mut resp = match request.send().await {
+            Ok(resp) => resp,
+            Err(e) => return send_error(transport_error(e)).await,
+        };
+
+        let status = resp.status();
+        if !status.is_success() {
+            let body = resp.text().await.unwrap_or_default();
+            return send_error(map_error(status, &body)).await;
+        }
+
+        let mut parser = SseParser::default();
+        loop {
+            let chunk = match resp.chunk().await {
+                Ok(Some(chunk)) => chunk,
+                Ok(None) => break,
+                Err(e) => return send_error(transport_error(e)).await,
+            };
+            for event in parser.push(&chunk) {
+                for out in handler.on_event(&event) {
+                    if tx.send(out).await.is_err() {
+                        // Receiver dropped; stop reading.
+                        return;
+                    }
+                }
+            }
+        }
+        for out in handler.finish() {
+            if tx.send(out).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx))
+}
+
+/// Parse an SSE `data:` payload as JSON, turning failures into an error event.
+pub(crate) fn parse_data(
+    provider: &str,
+    event: &SseEvent,
+) -> Result<serde_json::Value, StreamEvent> {
+    serde_json::from_str(&event.data).map_err(|e| {
+        StreamEvent::Error(Arc::new(AttractorError::ProviderError {
+            provider: provider.into(),
+            status: 0,
+            message: format!("Failed to parse stream event JSON: {e}"),
+            retryable: false,
+        }))
+    })
+}
+
+/// Error event for a stream that ended before the provider finished the message.
+pub(crate) fn truncated_stream_error(provider: &str) -> StreamEvent {
+    StreamEvent::Error(Arc::new(AttractorError::ProviderError {
+        provider: provider.into(),
+        status: 0,
+        message: "Stream ended before the message completed".into(),
+        retryable: true,
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// Test support
+// ---------------------------------------------------------------------------
+
+/// Serve a single HTTP request on a local port, answering with `status` and
+/// `body`. Returns the base URL and a handle yielding the raw request text.
+#[cfg(test)]
+pub(crate) async fn mock_server(
+    status: u16,
+    content_type: &'static str,
+    body: &'static str,
+) -> (String, tokio::task::JoinHandle<String>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let handle = tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+
+        // Read headers, then as many body bytes as Content-Length announces.
+        let mut raw = Vec::new();
+        let mut buf = [0u8; 4096];
+        let header_end = loop {
+            let n = socket.read(&mut buf).await.unwrap();
+            raw.extend_from_slice(&buf[..n]);
+            if let Some(pos) = raw.windows(4).position(|w| w == b"\r\n\r\n") {
+                break pos + 4;
+            }
+        };
+        let headers = String::from_utf8_lossy(&raw[..header_end]).to_lowercase();
+        let content_length = headers
+            .lines()
+            .find_map(|l| l.strip_prefix("content-length:"))
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .unwrap_or(0);
+        while raw.len() < header_end + content_length {
+            let n = socket.read(&mut buf).await.unwrap();
+            raw.extend_from_slice(&buf[..n]);
+        }
+
+        let response = format!(
+            "HTTP/1.1 {status} STATUS\r\ncontent-type: {content_type}\r\nconnection: close\r\n\r\n{body}"
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        socket.shutdown().await.unwrap();
+
+        String::from_utf8_lossy(&raw).into_owned()
+    });
+
+    (format!("http://{addr}"), handle)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parser_splits_events_across_chunks() {
+        let mut parser = SseParser::default();
+        assert!(parser.push(b"event: ping\ndata: {\"a\"").is_empty());
+        let events = parser.push(b": 1}\n\nevent: done\r\ndata: x\r\n\r\n");
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    event: Some("ping".into()),
+                    data: "{\"a\": 1}".into(),
+                },
+                SseEvent {
+                    event: Some("done".into()),
+                    data: "x".into(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parser_joins_multiline_data_and_skips_comments() {
+        let mut parser = SseParser::default();
+        let events = parser.push(b": keep-alive\n\ndata: one\ndata: two\n\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, None);
+        assert_eq!(events[0].data, "one\ntwo");
+    }
+}
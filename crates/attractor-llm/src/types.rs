@@ -262,6 +262,95 @@ pub enum StreamEvent {
     Error(Arc<attractor_types::AttractorError>),
 }
 
+// ---------------------------------------------------------------------------
+// StreamAccumulator
+// ---------------------------------------------------------------------------
+
+/// Folds a sequence of [`StreamEvent`]s back into a complete [`Response`].
+#[derive(Debug, Default)]
+pub struct StreamAccumulator {
+    id: String,
+    model: String,
+    text: String,
+    reasoning: String,
+    /// Tool calls in start order: (id, name, accumulated argument JSON).
+    tool_calls: Vec<(String, String, String)>,
+    end: Option<(Usage, FinishReason)>,
+    error: Option<Arc<attractor_types::AttractorError>>,
+}
+
+impl StreamAccumulator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::MessageStart { id, model } => {
+                self.id = id.clone();
+                self.model = model.clone();
+            }
+            StreamEvent::ContentDelta { text } => self.text.push_str(text),
+            StreamEvent::ThinkingDelta { text } => self.reasoning.push_str(text),
+            StreamEvent::ToolCallStart { id, name } => {
+                self.tool_calls
+                    .push((id.clone(), name.clone(), String::new()));
+            }
+            StreamEvent::ToolCallDelta { id, json_chunk } => {
+                if let Some(call) = self.tool_calls.iter_mut().find(|c| &c.0 == id) {
+                    call.2.push_str(json_chunk);
+                }
+            }
+            StreamEvent::MessageEnd {
+                usage,
+                finish_reason,
+            } => self.end = Some((usage.clone(), *finish_reason)),
+            StreamEvent::Error(err) => {
+                self.error.get_or_insert_with(|| err.clone());
+            }
+            StreamEvent::ContentStart
+            | StreamEvent::ContentEnd
+            | StreamEvent::ToolCallEnd { .. } => {}
+        }
+    }
+
+    /// Build the final response. Fails if the stream carried an error or ended
+    /// without a `MessageEnd`.
+    pub fn finish(self) -> Result<Response, attractor_types::AttractorError> {
+        if let Some(err) = self.error {
+            return Err(Arc::try_unwrap(err)
+                .unwrap_or_else(|err| attractor_types::AttractorError::Other(err.to_string())));
+        }
+        let (usage, finish_reason) = self.end.ok_or_else(|| {
+            attractor_types::AttractorError::Other("Stream ended without a MessageEnd event".into())
+        })?;
+
+        let tool_calls = self
+            .tool_calls
+            .into_iter()
+            .map(|(id, name, args)| ToolCallResult {
+                id,
+                name,
+                arguments: if args.trim().is_empty() {
+                    serde_json::json!({})
+                } else {
+                    serde_json::from_str(&args).unwrap_or(serde_json::Value::String(args))
+                },
+            })
+            .collect();
+
+        Ok(Response {
+            id: self.id,
+            text: self.text,
+            tool_calls,
+            reasoning: (!self.reasoning.is_empty()).then_some(self.reasoning),
+            usage,
+            model: self.model,
+            finish_reason,
+        })
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -438,4 +527,69 @@ mod tests {
         assert_eq!(back.usage.total_tokens, 30);
         assert_eq!(back.finish_reason, FinishReason::EndTurn);
     }
+
+    #[test]
+    fn stream_accumulator_builds_response() {
+        let mut acc = StreamAccumulator::new();
+        for event in [
+            StreamEvent::MessageStart {
+                id: "msg_1".into(),
+                model: "m".into(),
+            },
+            StreamEvent::ThinkingDelta { text: "hmm".into() },
+            StreamEvent::ContentStart,
+            StreamEvent::ContentDelta { text: "Hel".into() },
+            StreamEvent::ContentDelta { text: "lo".into() },
+            StreamEvent::ContentEnd,
+            StreamEvent::ToolCallStart {
+                id: "t1".into(),
+                name: "read_file".into(),
+            },
+            StreamEvent::ToolCallDelta {
+                id: "t1".into(),
+                json_chunk: r#"{"path":"#.into(),
+            },
+            StreamEvent::ToolCallDelta {
+                id: "t1".into(),
+                json_chunk: r#""a.rs"}"#.into(),
+            },
+            StreamEvent::ToolCallEnd { id: "t1".into() },
+            StreamEvent::MessageEnd {
+                usage: Usage {
+                    total_tokens: 7,
+                    ..Usage::default()
+                },
+                finish_reason: FinishReason::ToolUse,
+            },
+        ] {
+            acc.push(&event);
+        }
+
+        let resp = acc.finish().unwrap();
+        assert_eq!(resp.id, "msg_1");
+        assert_eq!(resp.text, "Hello");
+        assert_eq!(resp.reasoning.as_deref(), Some("hmm"));
+        assert_eq!(resp.tool_calls.len(), 1);
+        assert_eq!(resp.tool_calls[0].arguments["path"], "a.rs");
+        assert_eq!(resp.usage.total_tokens, 7);
+        assert_eq!(resp.finish_reason, FinishReason::ToolUse);
+    }
+
+    #[test]
+    fn stream_accumulator_surfaces_errors_and_truncation() {
+        let mut acc = StreamAccumulator::new();
+        acc.push(&StreamEvent::Error(Arc::new(
+            attractor_types::AttractorError::AuthError {
+                provider: "p".into(),
+            },
+        )));
+        assert!(matches!(
+            acc.finish(),
+            Err(attractor_types::AttractorError::AuthError { .. })
+        ));
+
+        let mut acc = StreamAccumulator::new();
+        acc.push(&StreamEvent::ContentDelta { text: "x".into() });
+        assert!(acc.finish().is_err());
+    }
 }
@@ -0,0 +1,265 @@
+//! Artifact store for node outputs too large to carry in the context.
+//!
+//! Artifacts live under `<logs>/artifacts/` as content-addressed files:
+//! `<sha256>` holds the bytes and `<sha256>.json` the [`ArtifactRef`]
+//! describing them. The context carries the reference instead of the
+//! content:
+//!
+//! ```json
+//! {"artifact": "9f86d0…", "name": "plan.result", "node_id": "plan",
+//!  "size": 48213, "media_type": "text/plain", "path": "/…/artifacts/9f86d0…"}
+//! ```
+//!
+//! The engine sets `artifacts_dir` in the context for runs with a logs dir.
+//! When the graph sets `artifact_threshold`, it moves any string output
+//! longer than that many bytes into the store; by default outputs stay
+//! inline. Handlers can also write artifacts themselves via
+//! [`ArtifactStore::from_snapshot`], as the engine does for node diffs.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use attractor_types::{AttractorError, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::drift::sha256_hex;
+
+/// Context key holding the artifact store's directory.
+pub const ARTIFACTS_DIR_KEY: &str = "artifacts_dir";
+
+/// A stored artifact, as recorded in the context and in its metadata file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactRef {
+    /// SHA-256 of the content.
+    #[serde(rename = "artifact")]
+    pub hash: String,
+    /// What the artifact is, usually the context key it replaced.
+    pub name: String,
+    /// Node that produced it.
+    pub node_id: String,
+    /// Size in bytes.
+    pub size: u64,
+    pub media_type: String,
+    /// Absolute path of the content file.
+    pub path: PathBuf,
+}
+
+impl ArtifactRef {
+    /// Parse a context value, if it is an artifact reference.
+    pub fn from_value(value: &serde_json::Value) -> Option<Self> {
+        value.get("artifact")?;
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    pub fn to_value(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+
+    /// Read the content as UTF-8 (lossily).
+    pub async fn read_text(&self) -> Result<String> {
+        let bytes = tokio::fs::read(&self.path).await.map_err(|e| {
+            AttractorError::Other(format!(
+                "Failed to read artifact {} ({}): {}",
+                self.name,
+                self.path.display(),
+                e
+            ))
+        })?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// Content-addressed artifact files in one directory.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    /// The store of a run with logs in `logs_root`, i.e.
+    /// `<logs_root>/artifacts`. The path is made absolute so references stay
+    /// valid from any working directory.
+    pub fn for_logs(logs_root: &Path) -> Result<Self> {
+        let logs_root = std::path::absolute(logs_root)?;
+        Ok(Self {
+            root: logs_root.join("artifacts"),
+        })
+    }
+
+    /// The store named by `artifacts_dir` in a context snapshot, if any.
+    pub fn from_snapshot(snapshot: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        let dir = snapshot.get(ARTIFACTS_DIR_KEY)?.as_str()?;
+        Some(Self {
+            root: PathBuf::from(dir),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Store `content`, returning its reference. Identical content is only
+    /// written once.
+    pub async fn put(
+        &self,
+        name: &str,
+        node_id: &str,
+        media_type: &str,
+        content: &[u8],
+    ) -> Result<ArtifactRef> {
+        tokio::fs::create_dir_all(&self.root).await?;
+        let hash = sha256_hex(content);
+        let path = self.root.join(&hash);
+        if !path.exists() {
+            // Write then rename so readers never see a partial file
+            let partial = self.root.join(format!("{}.partial", hash));
+            tokio::fs::write(&partial, content).await?;
+            tokio::fs::rename(&partial, &path).await?;
+        }
+        let artifact = ArtifactRef {
+            hash: hash.clone(),
+            name: name.to_string(),
+            node_id: node_id.to_string(),
+            size: content.len() as u64,
+            media_type: media_type.to_string(),
+            path,
+        };
+        let meta = serde_json::to_string_pretty(&artifact)?;
+        tokio::fs::write(self.root.join(format!("{}.json", hash)), meta).await?;
+        Ok(artifact)
+    }
+
+    /// Every artifact in the store, sorted by name.
+    pub async fn list(&self) -> Result<Vec<ArtifactRef>> {
+        let mut artifacts = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(artifacts),
+            Err(e) => return Err(e.into()),
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let json = tokio::fs::read_to_string(&path).await?;
+                artifacts.push(serde_json::from_str::<ArtifactRef>(&json)?);
+            }
+        }
+        artifacts.sort_by(|a, b| a.name.cmp(&b.name).then(a.hash.cmp(&b.hash)));
+        Ok(artifacts)
+    }
+
+    /// Find an artifact by name (the most recent write wins) or by a unique
+    /// hash prefix.
+    pub async fn find(&self, name_or_hash: &str) -> Result<ArtifactRef> {
+        let artifacts = self.list().await?;
+        // Metadata is rewritten on every put, so its mtime is the last write
+        let mut latest = None;
+        for artifact in artifacts.iter().filter(|a| a.name == name_or_hash) {
+            let meta = self.root.join(format!("{}.json", artifact.hash));
+            let modified = tokio::fs::metadata(&meta).await?.modified()?;
+            if latest.as_ref().is_none_or(|(time, _)| modified > *time) {
+                latest = Some((modified, artifact));
+            }
+        }
+        if let Some((_, artifact)) = latest {
+            return Ok(artifact.clone());
+        }
+        let mut matches = artifacts
+            .into_iter()
+            .filter(|a| a.hash.starts_with(name_or_hash));
+        match (matches.next(), matches.next()) {
+            (Some(artifact), None) => Ok(artifact),
+            (Some(_), Some(_)) => Err(AttractorError::Other(format!(
+                "Artifact hash prefix '{}' is ambiguous",
+                name_or_hash
+            ))),
+            _ => Err(AttractorError::Other(format!(
+                "No artifact named '{}' in {}",
+                name_or_hash,
+                self.root.display()
+            ))),
+        }
+    }
+}
+
+/// Replace the artifact references in `snapshot` with their content. With
+/// `keys`, only those entries are resolved. Unreadable artifacts are left as
+/// references.
+pub async fn resolve_artifacts(
+    snapshot: &mut HashMap<String, serde_json::Value>,
+    keys: Option<&[String]>,
+) {
+    for (key, value) in snapshot.iter_mut() {
+        if keys.is_some_and(|keys| !keys.contains(key)) {
+            continue;
+        }
+        let Some(artifact) = ArtifactRef::from_value(value) else {
+            continue;
+        };
+        match artifact.read_text().await {
+            Ok(text) => *value = serde_json::Value::String(text),
+            Err(e) => tracing::warn!(key = %key, "{}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn put_deduplicates_and_finds_by_name_or_hash() {
+        let logs = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::for_logs(logs.path()).unwrap();
+
+        let a = store
+            .put("plan.result", "plan", "text/plain", b"the plan")
+            .await
+            .unwrap();
+        let b = store
+            .put("plan.result", "plan", "text/plain", b"the plan")
+            .await
+            .unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.size, 8);
+        assert!(a.path.is_absolute());
+        store
+            .put("build.result", "build", "text/plain", b"built")
+            .await
+            .unwrap();
+
+        let listed: Vec<String> = store
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(listed, vec!["build.result", "plan.result"]);
+        assert_eq!(store.find("plan.result").await.unwrap(), a);
+        assert_eq!(store.find(&a.hash[..10]).await.unwrap(), a);
+        assert!(store.find("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn references_round_trip_through_the_context() {
+        let logs = tempfile::tempdir().unwrap();
+        let store = ArtifactStore::for_logs(logs.path()).unwrap();
+        let artifact = store
+            .put("impl.result", "impl", "text/plain", b"long output")
+            .await
+            .unwrap();
+
+        let mut snapshot = HashMap::new();
+        snapshot.insert("impl.result".to_string(), artifact.to_value());
+        snapshot.insert("plain".to_string(), serde_json::json!("text"));
+        assert_eq!(
+            ArtifactRef::from_value(&snapshot["impl.result"]),
+            Some(artifact)
+        );
+        assert_eq!(ArtifactRef::from_value(&snapshot["plain"]), None);
+
+        resolve_artifacts(&mut snapshot, None).await;
+        assert_eq!(snapshot["impl.result"], "long output");
+    }
+}
@@ -0,0 +1,239 @@
+//! Per-node file-change tracking.
+//!
+//! The engine snapshots the working directory before and after a node runs
+//! and records what the node changed:
+//!   - `<node>.changed_files`: sorted paths, relative to the workdir
+//!   - `<node>.diff_path`: a unified diff under `<logs>/changes/` (git
+//!     workdirs only, and only for runs with a logs dir)
+//!
+//! In a git checkout a snapshot is a tree object written from a throwaway
+//! index, so untracked files count, ignored files do not, and the user's
+//! index is left alone. Other directories are walked and hashed, which finds
+//! changed files but cannot produce a diff.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use attractor_types::{AttractorError, Result};
+
+use crate::drift::sha256_hex;
+
+/// Directories never walked when hashing a non-git workdir.
+const SKIPPED_DIRS: &[&str] = &[".git", ".pas", "target", "node_modules"];
+
+/// Non-git workdirs with more files than this are not tracked.
+const MAX_HASHED_FILES: usize = 20_000;
+
+/// The state of a working directory at one point in time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkdirSnapshot {
+    /// Tree object holding the workdir's content (tracked and untracked).
+    Git { dir: PathBuf, tree: String },
+    /// Content hash of every file, keyed by path relative to the workdir.
+    Files {
+        dir: PathBuf,
+        hashes: BTreeMap<PathBuf, String>,
+    },
+}
+
+/// What changed between two snapshots.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkdirChanges {
+    /// Added, modified and deleted paths, relative to the workdir, sorted.
+    pub files: Vec<String>,
+    /// Unified diff, when the workdir is a git checkout.
+    pub diff: Option<String>,
+}
+
+impl WorkdirSnapshot {
+    /// Snapshot `dir`, using git when it is inside a work tree.
+    pub async fn take(dir: &Path) -> Result<Self> {
+        if git(dir, &["rev-parse", "--is-inside-work-tree"], None)
+            .await
+            .is_ok_and(|out| out == "true")
+        {
+            return Ok(Self::Git {
+                dir: dir.to_path_buf(),
+                tree: write_tree(dir).await?,
+            });
+        }
+        let root = dir.to_path_buf();
+        let hashes = tokio::task::spawn_blocking(move || hash_files(&root))
+            .await
+            .map_err(|e| AttractorError::Other(format!("File hashing task failed: {}", e)))??;
+        Ok(Self::Files {
+            dir: dir.to_path_buf(),
+            hashes,
+        })
+    }
+
+    /// Changes from `self` (before) to `after`.
+    pub async fn changes_to(&self, after: &WorkdirSnapshot) -> Result<WorkdirChanges> {
+        match (self, after) {
+            (Self::Git { dir, tree: before }, Self::Git { tree: after, .. }) => {
+                if before == after {
+                    return Ok(WorkdirChanges::default());
+                }
+                let names = git(
+                    dir,
+                    &["diff", "--relative", "--name-only", "-z", before, after],
+                    None,
+                )
+                .await?;
+                let mut files: Vec<String> = names
+                    .split('\0')
+                    .filter(|p| !p.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                files.sort();
+                let mut diff = git(dir, &["diff", "--relative", before, after], None).await?;
+                if !diff.is_empty() {
+                    // `git` output comes back trimmed; patches need the newline
+                    diff.push('\n');
+                }
+                Ok(WorkdirChanges {
+                    files,
+                    diff: Some(diff),
+                })
+            }
+            (Self::Files { hashes: before, .. }, Self::Files { hashes: after, .. }) => {
+                let mut files: Vec<String> = after
+                    .iter()
+                    .filter(|(path, hash)| before.get(*path) != Some(*hash))
+                    .map(|(path, _)| path)
+                    .chain(before.keys().filter(|path| !after.contains_key(*path)))
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+                files.sort();
+                Ok(WorkdirChanges { files, diff: None })
+            }
+            _ => Err(AttractorError::Other(
+                "Workdir snapshots are of different kinds".into(),
+            )),
+        }
+    }
+}
+
+/// Write the work tree's current content as a tree object without touching
+/// the real index: a copy of it is updated instead.
+async fn write_tree(dir: &Path) -> Result<String> {
+    let index = PathBuf::from(git(dir, &["rev-parse", "--git-path", "index"], None).await?);
+    let index = dir.join(index);
+    let scratch = std::env::temp_dir().join(format!("pas-index-{}", uuid::Uuid::new_v4().simple()));
+    // Starting from a copy keeps git's stat cache, so unchanged files are
+    // not re-hashed
+    if index.exists() {
+        tokio::fs::copy(&index, &scratch).await?;
+    }
+    let result = async {
+        git(dir, &["add", "--all", "--", "."], Some(&scratch)).await?;
+        git(dir, &["write-tree"], Some(&scratch)).await
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&scratch).await;
+    result
+}
+
+/// Run `git` in `dir`, optionally against another index file. Returns
+/// trimmed stdout, or stderr as the error.
+async fn git(dir: &Path, args: &[&str], index: Option<&Path>) -> Result<String> {
+    let mut cmd = tokio::process::Command::new("git");
+    cmd.args(["-c", "core.quotepath=off"])
+        .args(args)
+        .current_dir(dir);
+    if let Some(index) = index {
+        cmd.env("GIT_INDEX_FILE", index);
+    }
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| AttractorError::Other(format!("Failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(AttractorError::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Hash every regular file under `root`, skipping [`SKIPPED_DIRS`].
+fn hash_files(root: &Path) -> Result<BTreeMap<PathBuf, String>> {
+    let mut hashes = BTreeMap::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                if !SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()) {
+                    pending.push(path);
+                }
+            } else if file_type.is_file() {
+                if hashes.len() >= MAX_HASHED_FILES {
+                    return Err(AttractorError::Other(format!(
+                        "{} has more than {} files — not tracking changes",
+                        root.display(),
+                        MAX_HASHED_FILES
+                    )));
+                }
+                let relative = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+                hashes.insert(relative, sha256_hex(&std::fs::read(&path)?));
+            }
+        }
+    }
+    Ok(hashes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn git_snapshots_report_files_and_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        git(dir.path(), &["init", "--quiet"], None).await.unwrap();
+        std::fs::write(dir.path().join("keep.txt"), "same\n").unwrap();
+        std::fs::write(dir.path().join("edit.txt"), "old\n").unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "build/\n").unwrap();
+        git(dir.path(), &["add", "keep.txt"], None).await.unwrap();
+
+        let before = WorkdirSnapshot::take(dir.path()).await.unwrap();
+        std::fs::write(dir.path().join("edit.txt"), "new\n").unwrap();
+        std::fs::write(dir.path().join("added.txt"), "hi\n").unwrap();
+        std::fs::create_dir(dir.path().join("build")).unwrap();
+        std::fs::write(dir.path().join("build/out"), "ignored").unwrap();
+        let after = WorkdirSnapshot::take(dir.path()).await.unwrap();
+
+        let changes = before.changes_to(&after).await.unwrap();
+        assert_eq!(changes.files, vec!["added.txt", "edit.txt"]);
+        let diff = changes.diff.unwrap();
+        assert!(diff.contains("-old\n+new"), "got: {diff}");
+        // The real index only ever saw keep.txt
+        let staged = git(dir.path(), &["diff", "--cached", "--name-only"], None)
+            .await
+            .unwrap();
+        assert_eq!(staged, "keep.txt");
+    }
+
+    #[tokio::test]
+    async fn hash_walk_reports_changed_files_without_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        std::fs::write(dir.path().join("gone.txt"), "x").unwrap();
+        std::fs::create_dir(dir.path().join("target")).unwrap();
+
+        let before = WorkdirSnapshot::take(dir.path()).await.unwrap();
+        assert!(matches!(before, WorkdirSnapshot::Files { .. }));
+        std::fs::write(dir.path().join("a.txt"), "b").unwrap();
+        std::fs::remove_file(dir.path().join("gone.txt")).unwrap();
+        std::fs::write(dir.path().join("target/skip"), "x").unwrap();
+        let after = WorkdirSnapshot::take(dir.path()).await.unwrap();
+
+        let changes = before.changes_to(&after).await.unwrap();
+        assert_eq!(changes.files, vec!["a.txt", "gone.txt"]);
+        assert_eq!(changes.diff, None);
+    }
+}
@@ -4,6 +4,17 @@
 //! to disk.  On restart, [`load_checkpoint`] discovers the latest snapshot so
 //! the pipeline can resume from the last completed node instead of starting
 //! over.
+//!
+//! Every snapshot is also appended to a journal under `<logs_root>/checkpoints/`,
+//! one file per step. [`restore_checkpoint`] copies a journal entry back to
+//! `checkpoint.json`, rewinding the next resume to any node the run reached.
+//!
+//! Sub-pipeline nodes keep their child run's checkpoints nested under the
+//! parent's, in `<logs_root>/subpipelines/<node>/`, so a resumed parent
+//! resumes an interrupted child where it stopped.
+//!
+//! The sessions of conversation threads (see [`crate::threads`]) live in the
+//! context snapshot, so a resumed run continues them too.
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -20,6 +31,9 @@ pub struct PipelineCheckpoint {
     pub completed_nodes: Vec<String>,
     /// Outcome produced by each completed node, keyed by node ID.
     pub node_outcomes: HashMap<String, attractor_types::Outcome>,
+    /// Number of retry attempts spent on each node so far.
+    #[serde(default)]
+    pub node_retries: HashMap<String, usize>,
     /// Serialised snapshot of the pipeline [`Context`](attractor_types::Context).
     pub context_snapshot: HashMap<String, serde_json::Value>,
     /// RFC 3339 timestamp of when the checkpoint was created.
@@ -27,6 +41,10 @@ pub struct PipelineCheckpoint {
     /// Optional session ID for tracking execution sessions (e.g., for SSE streaming).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub session_id: Option<String>,
+    /// Hashes of the graph the checkpoint was taken from, for drift
+    /// detection on resume. Absent in checkpoints from older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<crate::drift::GraphFingerprint>,
 }
 
 impl PipelineCheckpoint {
@@ -41,9 +59,11 @@ impl PipelineCheckpoint {
             current_node_id,
             completed_nodes,
             node_outcomes,
+            node_retries: HashMap::new(),
             context_snapshot,
             timestamp: chrono::Utc::now().to_rfc3339(),
             session_id: None,
+            fingerprint: None,
         }
     }
 
@@ -59,9 +79,11 @@ impl PipelineCheckpoint {
             current_node_id,
             completed_nodes,
             node_outcomes,
+            node_retries: HashMap::new(),
             context_snapshot,
             timestamp: chrono::Utc::now().to_rfc3339(),
             session_id: Some(session_id),
+            fingerprint: None,
         }
     }
 }
@@ -107,6 +129,144 @@ pub async fn clear_checkpoint(logs_root: &Path) -> attractor_types::Result<()> {
     Ok(())
 }
 
+// ---------------------------------------------------------------------------
+// Journal
+// ---------------------------------------------------------------------------
+
+/// A checkpoint recorded in the journal.
+#[derive(Debug, Clone)]
+pub struct JournalEntry {
+    /// Position in the journal, starting at 1.
+    pub seq: usize,
+    pub path: PathBuf,
+    pub checkpoint: PipelineCheckpoint,
+}
+
+fn journal_dir(logs_root: &Path) -> PathBuf {
+    logs_root.join("checkpoints")
+}
+
+/// The sequence number of a journal file, from its `<seq>-<node>.json` name.
+fn journal_seq(path: &Path) -> Option<usize> {
+    let (seq, _) = path.file_name()?.to_str()?.split_once('-')?;
+    seq.parse().ok()
+}
+
+/// The highest sequence number in the journal directory `dir`, found from
+/// the file names alone.
+async fn last_journal_seq(dir: &Path) -> attractor_types::Result<Option<usize>> {
+    let mut last = None;
+    let mut read_dir = tokio::fs::read_dir(dir).await?;
+    while let Some(file) = read_dir.next_entry().await? {
+        last = last.max(journal_seq(&file.path()));
+    }
+    Ok(last)
+}
+
+/// Append a checkpoint to the journal as `checkpoints/<seq>-<node>.json`.
+pub async fn append_journal(
+    checkpoint: &PipelineCheckpoint,
+    logs_root: &Path,
+) -> attractor_types::Result<PathBuf> {
+    let dir = journal_dir(logs_root);
+    tokio::fs::create_dir_all(&dir).await?;
+    let seq = last_journal_seq(&dir).await?.map_or(1, |seq| seq + 1);
+    let node: String = checkpoint
+        .current_node_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let path = dir.join(format!("{:04}-{}.json", seq, node));
+    let json = serde_json::to_string_pretty(checkpoint)?;
+    tokio::fs::write(&path, json).await?;
+    Ok(path)
+}
+
+/// Load every journal entry, oldest first. Empty when there is no journal.
+pub async fn load_journal(logs_root: &Path) -> attractor_types::Result<Vec<JournalEntry>> {
+    let dir = journal_dir(logs_root);
+    if !tokio::fs::try_exists(&dir).await? {
+        return Ok(Vec::new());
+    }
+    let mut entries = Vec::new();
+    let mut read_dir = tokio::fs::read_dir(&dir).await?;
+    while let Some(file) = read_dir.next_entry().await? {
+        let path = file.path();
+        let Some(seq) = journal_seq(&path) else {
+            continue;
+        };
+        let json = tokio::fs::read_to_string(&path).await?;
+        let checkpoint = serde_json::from_str(&json)?;
+        entries.push(JournalEntry {
+            seq,
+            path,
+            checkpoint,
+        });
+    }
+    entries.sort_by_key(|entry| entry.seq);
+    Ok(entries)
+}
+
+/// Delete the journal. Called when a run starts from scratch.
+pub async fn clear_journal(logs_root: &Path) -> attractor_types::Result<()> {
+    let dir = journal_dir(logs_root);
+    if tokio::fs::try_exists(&dir).await? {
+        tokio::fs::remove_dir_all(&dir).await?;
+    }
+    Ok(())
+}
+
+/// Rewind to just before `node_id` ran: copy the latest journal entry
+/// positioned at that node to `checkpoint.json`, so the next resume restores
+/// its context, completed nodes and outcomes and executes `node_id` again.
+pub async fn restore_checkpoint(
+    logs_root: &Path,
+    node_id: &str,
+) -> attractor_types::Result<PipelineCheckpoint> {
+    let entry = load_journal(logs_root)
+        .await?
+        .into_iter()
+        .rev()
+        .find(|entry| entry.checkpoint.current_node_id == node_id)
+        .ok_or_else(|| {
+            attractor_types::AttractorError::Other(format!(
+                "No checkpoint before node '{}' in {} — the run never reached it",
+                node_id,
+                journal_dir(logs_root).display()
+            ))
+        })?;
+    save_checkpoint(&entry.checkpoint, logs_root).await?;
+    Ok(entry.checkpoint)
+}
+
+// ---------------------------------------------------------------------------
+// Nested runs
+// ---------------------------------------------------------------------------
+
+/// Context key holding the logs directory of the run, for nested runs.
+pub const LOGS_DIR_KEY: &str = "logs_dir";
+
+/// The logs directory of the child run of sub-pipeline node `node_id`.
+pub fn sub_pipeline_logs(logs_root: &Path, node_id: &str) -> PathBuf {
+    logs_root.join("subpipelines").join(node_id)
+}
+
+/// Delete the checkpoints of every nested child run. Called when a run
+/// starts from scratch, so its children do too.
+pub async fn clear_sub_pipelines(logs_root: &Path) -> attractor_types::Result<()> {
+    let dir = logs_root.join("subpipelines");
+    if tokio::fs::try_exists(&dir).await? {
+        tokio::fs::remove_dir_all(&dir).await?;
+    }
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -138,6 +298,7 @@ mod tests {
         assert_eq!(loaded.current_node_id, "node_b");
         assert_eq!(loaded.completed_nodes, vec!["node_a".to_string()]);
         assert_eq!(loaded.context_snapshot.get("key").unwrap(), "value");
+        assert!(loaded.node_retries.is_empty());
     }
 
     #[tokio::test]
@@ -161,6 +322,57 @@ mod tests {
         assert!(!dir.path().join("checkpoint.json").exists());
     }
 
+    #[tokio::test]
+    async fn journal_appends_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        for node in ["a", "b", "a"] {
+            let cp = PipelineCheckpoint::new(node.into(), vec![], HashMap::new(), HashMap::new());
+            append_journal(&cp, dir.path()).await.unwrap();
+        }
+
+        let journal = load_journal(dir.path()).await.unwrap();
+        let nodes: Vec<(usize, &str)> = journal
+            .iter()
+            .map(|e| (e.seq, e.checkpoint.current_node_id.as_str()))
+            .collect();
+        assert_eq!(nodes, vec![(1, "a"), (2, "b"), (3, "a")]);
+
+        clear_journal(dir.path()).await.unwrap();
+        assert!(load_journal(dir.path()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn journal_numbers_entries_from_file_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let journal = dir.path().join("checkpoints");
+        std::fs::create_dir_all(&journal).unwrap();
+        // Appending never reads the entries themselves
+        std::fs::write(journal.join("0009-build.json"), "not a checkpoint").unwrap();
+        let cp = PipelineCheckpoint::new("test".into(), vec![], HashMap::new(), HashMap::new());
+        let path = append_journal(&cp, dir.path()).await.unwrap();
+        assert!(path.ends_with("0010-test.json"), "{}", path.display());
+    }
+
+    #[tokio::test]
+    async fn restore_rewinds_to_latest_entry_for_node() {
+        let dir = tempfile::tempdir().unwrap();
+        for (node, pass) in [("b", 1), ("c", 1), ("b", 2), ("c", 2)] {
+            let mut ctx = HashMap::new();
+            ctx.insert("pass".into(), serde_json::json!(pass));
+            let cp = PipelineCheckpoint::new(node.into(), vec!["a".into()], HashMap::new(), ctx);
+            append_journal(&cp, dir.path()).await.unwrap();
+            save_checkpoint(&cp, dir.path()).await.unwrap();
+        }
+
+        restore_checkpoint(dir.path(), "b").await.unwrap();
+        let loaded = load_checkpoint(dir.path()).await.unwrap().unwrap();
+        assert_eq!(loaded.current_node_id, "b");
+        assert_eq!(loaded.context_snapshot["pass"], 2);
+
+        let err = restore_checkpoint(dir.path(), "z").await.unwrap_err();
+        assert!(err.to_string().contains("No checkpoint before node 'z'"));
+    }
+
     #[tokio::test]
     async fn serialization_preserves_all_fields() {
         let cp = sample_checkpoint();
@@ -216,5 +428,6 @@ mod tests {
 
         let restored: PipelineCheckpoint = serde_json::from_str(json).unwrap();
         assert_eq!(restored.session_id, None);
+        assert!(restored.fingerprint.is_none());
     }
 }
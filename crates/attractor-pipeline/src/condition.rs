@@ -2,19 +2,53 @@
 //!
 //! Grammar:
 //! ```text
-//! ConditionExpr  ::= Clause ( '&&' Clause )*
+//! ConditionExpr  ::= OrExpr
+//! OrExpr         ::= AndExpr ( '||' AndExpr )*
+//! AndExpr        ::= Unary ( '&&' Unary )*
+//! Unary          ::= '!' Unary | '(' OrExpr ')' | Clause
 //! Clause         ::= Key Operator Literal
 //! Key            ::= identifier ( '.' identifier )*
-//! Operator       ::= '=' | '!='
+//! Operator       ::= '=' | '!=' | '<' | '<=' | '>' | '>=' | 'contains' | 'matches'
 //! Literal        ::= QuotedString | BareWord | Integer | Boolean
 //! ```
+//!
+//! `=` and `!=` compare strings. `<`, `<=`, `>` and `>=` compare numerically and
+//! are false when the resolved value is not a number. `contains` is a substring
+//! test and `matches` a regular-expression search.
 
 use attractor_types::AttractorError;
 
-/// A parsed condition expression consisting of one or more clauses joined by `&&`.
+/// A parsed condition expression.
 #[derive(Debug, Clone, PartialEq)]
-pub struct ConditionExpr {
-    pub clauses: Vec<Clause>,
+pub enum ConditionExpr {
+    /// The empty condition, which always holds.
+    Always,
+    Clause(Clause),
+    Not(Box<ConditionExpr>),
+    And(Vec<ConditionExpr>),
+    Or(Vec<ConditionExpr>),
+}
+
+impl ConditionExpr {
+    /// All clauses in the expression, in source order.
+    pub fn clauses(&self) -> Vec<&Clause> {
+        let mut out = Vec::new();
+        self.collect_clauses(&mut out);
+        out
+    }
+
+    fn collect_clauses<'a>(&'a self, out: &mut Vec<&'a Clause>) {
+        match self {
+            ConditionExpr::Always => {}
+            ConditionExpr::Clause(c) => out.push(c),
+            ConditionExpr::Not(inner) => inner.collect_clauses(out),
+            ConditionExpr::And(items) | ConditionExpr::Or(items) => {
+                for item in items {
+                    item.collect_clauses(out);
+                }
+            }
+        }
+    }
 }
 
 /// A single comparison clause: `key op value`.
@@ -23,6 +57,18 @@ pub struct Clause {
     pub key: String,
     pub operator: Operator,
     pub value: String,
+    /// `value` compiled, for `matches` clauses.
+    pattern: Option<Pattern>,
+}
+
+/// A compiled `matches` regex, equal to another with the same source.
+#[derive(Debug, Clone)]
+struct Pattern(regex::Regex);
+
+impl PartialEq for Pattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
 }
 
 /// Comparison operator.
@@ -30,104 +76,229 @@ pub struct Clause {
 pub enum Operator {
     Eq,
     NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    Contains,
+    Matches,
+}
+
+impl Operator {
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq
+        )
+    }
 }
 
 /// Parse a condition string into a [`ConditionExpr`].
 ///
-/// An empty or whitespace-only input produces an expression with zero clauses,
-/// which [`evaluate_condition`] treats as always true.
+/// An empty or whitespace-only input produces [`ConditionExpr::Always`].
+/// Errors report the 1-based column where parsing failed.
 pub fn parse_condition(input: &str) -> Result<ConditionExpr, AttractorError> {
-    let trimmed = input.trim();
-    if trimmed.is_empty() {
-        return Ok(ConditionExpr {
-            clauses: Vec::new(),
-        });
+    if input.trim().is_empty() {
+        return Ok(ConditionExpr::Always);
     }
 
-    let mut clauses = Vec::new();
-    for part in trimmed.split("&&") {
-        let clause = parse_clause(part.trim())?;
-        clauses.push(clause);
+    let mut parser = Parser { input, pos: 0 };
+    let expr = parser.parse_or()?;
+    parser.skip_whitespace();
+    if let Some(c) = parser.peek() {
+        return Err(parser.error(&format!("unexpected '{c}'")));
     }
+    Ok(expr)
+}
 
-    Ok(ConditionExpr { clauses })
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
 }
 
-fn parse_clause(input: &str) -> Result<Clause, AttractorError> {
-    let input = input.trim();
-    if input.is_empty() {
-        return Err(make_error("empty clause"));
+impl<'a> Parser<'a> {
+    fn remaining(&self) -> &'a str {
+        &self.input[self.pos..]
     }
 
-    // Find the operator position. We need to distinguish `!=` from `=`.
-    let (key_end, operator, op_len) = find_operator(input)?;
+    fn peek(&self) -> Option<char> {
+        self.remaining().chars().next()
+    }
 
-    let key = input[..key_end].trim().to_string();
-    if key.is_empty() {
-        return Err(make_error("missing key before operator"));
+    fn skip_whitespace(&mut self) {
+        let rest = self.remaining();
+        self.pos += rest.len() - rest.trim_start().len();
     }
-    // Validate key: identifier segments separated by dots.
-    for seg in key.split('.') {
-        if seg.is_empty() || !seg.chars().all(|c| c.is_alphanumeric() || c == '_') {
-            return Err(make_error(&format!("invalid key segment: '{seg}'")));
-        }
+
+    fn error(&self, msg: &str) -> AttractorError {
+        self.error_at(self.pos, msg)
     }
 
-    let raw_value = input[key_end + op_len..].trim();
-    if raw_value.is_empty() {
-        return Err(make_error(&format!(
-            "missing value after operator in '{input}'"
-        )));
+    fn error_at(&self, pos: usize, msg: &str) -> AttractorError {
+        let column = self.input[..pos].chars().count() + 1;
+        make_error(&format!("{msg} at column {column}"))
     }
 
-    let value = strip_quotes(raw_value);
+    fn parse_or(&mut self) -> Result<ConditionExpr, AttractorError> {
+        let mut items = vec![self.parse_and()?];
+        loop {
+            self.skip_whitespace();
+            if !self.remaining().starts_with("||") {
+                break;
+            }
+            self.pos += 2;
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            ConditionExpr::Or(items)
+        })
+    }
 
-    Ok(Clause {
-        key,
-        operator,
-        value,
-    })
-}
+    fn parse_and(&mut self) -> Result<ConditionExpr, AttractorError> {
+        let mut items = vec![self.parse_unary()?];
+        loop {
+            self.skip_whitespace();
+            if !self.remaining().starts_with("&&") {
+                break;
+            }
+            self.pos += 2;
+            items.push(self.parse_unary()?);
+        }
+        Ok(if items.len() == 1 {
+            items.remove(0)
+        } else {
+            ConditionExpr::And(items)
+        })
+    }
 
-fn find_operator(input: &str) -> Result<(usize, Operator, usize), AttractorError> {
-    // Scan for `!=` or `=`, skipping quoted regions.
-    let bytes = input.as_bytes();
-    let mut i = 0;
-    while i < bytes.len() {
-        match bytes[i] {
-            b'"' | b'\'' => {
-                // Skip quoted string.
-                let quote = bytes[i];
-                i += 1;
-                while i < bytes.len() && bytes[i] != quote {
-                    i += 1;
-                }
-                i += 1; // skip closing quote
+    fn parse_unary(&mut self) -> Result<ConditionExpr, AttractorError> {
+        self.skip_whitespace();
+        match self.peek() {
+            None => Err(self.error("expected a clause")),
+            Some('!') => {
+                self.pos += 1;
+                Ok(ConditionExpr::Not(Box::new(self.parse_unary()?)))
             }
-            b'!' if i + 1 < bytes.len() && bytes[i + 1] == b'=' => {
-                return Ok((i, Operator::NotEq, 2));
+            Some('(') => {
+                let open = self.pos;
+                self.pos += 1;
+                let inner = self.parse_or()?;
+                self.skip_whitespace();
+                if self.peek() != Some(')') {
+                    return Err(self.error_at(open, "unclosed '('"));
+                }
+                self.pos += 1;
+                Ok(inner)
             }
-            b'=' => {
-                return Ok((i, Operator::Eq, 1));
+            Some(_) => self.parse_clause().map(ConditionExpr::Clause),
+        }
+    }
+
+    fn parse_clause(&mut self) -> Result<Clause, AttractorError> {
+        let key_start = self.pos;
+        let key_len = self
+            .remaining()
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+            .unwrap_or(self.remaining().len());
+        let key = &self.input[key_start..key_start + key_len];
+        if key.is_empty() {
+            return Err(self.error("missing key before operator"));
+        }
+        // Validate key: identifier segments separated by dots.
+        if key.split('.').any(|seg| seg.is_empty()) {
+            return Err(self.error_at(key_start, &format!("invalid key '{key}'")));
+        }
+        self.pos += key_len;
+
+        self.skip_whitespace();
+        let operator = self.parse_operator(key)?;
+
+        self.skip_whitespace();
+        let value_start = self.pos;
+        let value = self.parse_literal()?;
+
+        if operator.is_numeric() && value.trim().parse::<f64>().is_err() {
+            return Err(self.error_at(
+                value_start,
+                &format!("expected a number after '{key}', found '{value}'"),
+            ));
+        }
+        let pattern = match operator {
+            Operator::Matches => match regex::Regex::new(&value) {
+                Ok(re) => Some(Pattern(re)),
+                Err(e) => {
+                    return Err(self.error_at(value_start, &format!("invalid regex: {e}")));
+                }
+            },
+            _ => None,
+        };
+
+        Ok(Clause {
+            key: key.to_string(),
+            operator,
+            value,
+            pattern,
+        })
+    }
+
+    fn parse_operator(&mut self, key: &str) -> Result<Operator, AttractorError> {
+        const SYMBOLS: &[(&str, Operator)] = &[
+            ("!=", Operator::NotEq),
+            ("<=", Operator::LtEq),
+            (">=", Operator::GtEq),
+            ("<", Operator::Lt),
+            (">", Operator::Gt),
+            ("=", Operator::Eq),
+        ];
+        const KEYWORDS: &[(&str, Operator)] = &[
+            ("contains", Operator::Contains),
+            ("matches", Operator::Matches),
+        ];
+
+        let rest = self.remaining();
+        for (sym, op) in SYMBOLS {
+            if rest.starts_with(sym) {
+                self.pos += sym.len();
+                return Ok(*op);
             }
-            _ => {
-                i += 1;
+        }
+        for (word, op) in KEYWORDS {
+            if let Some(after) = rest.strip_prefix(word) {
+                if after.starts_with(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+                    self.pos += word.len();
+                    return Ok(*op);
+                }
             }
         }
+        Err(self.error(&format!("expected an operator after '{key}'")))
     }
-    Err(make_error(&format!("no operator found in '{input}'")))
-}
 
-fn strip_quotes(s: &str) -> String {
-    if s.len() >= 2 {
-        let bytes = s.as_bytes();
-        if (bytes[0] == b'"' && bytes[bytes.len() - 1] == b'"')
-            || (bytes[0] == b'\'' && bytes[bytes.len() - 1] == b'\'')
-        {
-            return s[1..s.len() - 1].to_string();
+    fn parse_literal(&mut self) -> Result<String, AttractorError> {
+        let rest = self.remaining();
+        if let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') {
+            let Some(end) = rest[1..].find(quote) else {
+                return Err(self.error("unterminated string"));
+            };
+            let value = rest[1..1 + end].to_string();
+            self.pos += end + 2;
+            return Ok(value);
+        }
+
+        // A bare word runs up to the next `&&`, `||`, `)` or the end of input.
+        let end = ["&&", "||", ")"]
+            .iter()
+            .filter_map(|stop| rest.find(stop))
+            .min()
+            .unwrap_or(rest.len());
+        let value = rest[..end].trim_end();
+        if value.is_empty() {
+            return Err(self.error("missing value after operator"));
         }
+        self.pos += end;
+        Ok(value.to_string())
     }
-    s.to_string()
 }
 
 fn make_error(msg: &str) -> AttractorError {
@@ -139,20 +310,37 @@ fn make_error(msg: &str) -> AttractorError {
 /// The `resolve` function maps a key (e.g. `"outcome"`, `"context.env"`) to its
 /// string value. Keys not found should resolve to an empty string.
 ///
-/// An expression with zero clauses (from an empty input) evaluates to `true`.
+/// [`ConditionExpr::Always`] (from an empty input) evaluates to `true`.
 pub fn evaluate_condition(expr: &ConditionExpr, resolve: &dyn Fn(&str) -> String) -> bool {
-    // Empty expression → always true.
-    if expr.clauses.is_empty() {
-        return true;
+    match expr {
+        ConditionExpr::Always => true,
+        ConditionExpr::Clause(clause) => evaluate_clause(clause, resolve),
+        ConditionExpr::Not(inner) => !evaluate_condition(inner, resolve),
+        ConditionExpr::And(items) => items.iter().all(|e| evaluate_condition(e, resolve)),
+        ConditionExpr::Or(items) => items.iter().any(|e| evaluate_condition(e, resolve)),
     }
+}
 
-    expr.clauses.iter().all(|clause| {
-        let actual = resolve(&clause.key);
-        match clause.operator {
-            Operator::Eq => actual == clause.value,
-            Operator::NotEq => actual != clause.value,
-        }
-    })
+fn evaluate_clause(clause: &Clause, resolve: &dyn Fn(&str) -> String) -> bool {
+    let actual = resolve(&clause.key);
+    let numbers = || {
+        let a = actual.trim().parse::<f64>().ok()?;
+        let b = clause.value.trim().parse::<f64>().ok()?;
+        Some((a, b))
+    };
+    match clause.operator {
+        Operator::Eq => actual == clause.value,
+        Operator::NotEq => actual != clause.value,
+        Operator::Lt => numbers().is_some_and(|(a, b)| a < b),
+        Operator::LtEq => numbers().is_some_and(|(a, b)| a <= b),
+        Operator::Gt => numbers().is_some_and(|(a, b)| a > b),
+        Operator::GtEq => numbers().is_some_and(|(a, b)| a >= b),
+        Operator::Contains => actual.contains(&clause.value),
+        Operator::Matches => clause
+            .pattern
+            .as_ref()
+            .is_some_and(|Pattern(re)| re.is_match(&actual)),
+    }
 }
 
 #[cfg(test)]
@@ -172,10 +360,11 @@ mod tests {
     #[test]
     fn simple_equality() {
         let expr = parse_condition("outcome=success").unwrap();
-        assert_eq!(expr.clauses.len(), 1);
-        assert_eq!(expr.clauses[0].key, "outcome");
-        assert_eq!(expr.clauses[0].operator, Operator::Eq);
-        assert_eq!(expr.clauses[0].value, "success");
+        let clauses = expr.clauses();
+        assert_eq!(clauses.len(), 1);
+        assert_eq!(clauses[0].key, "outcome");
+        assert_eq!(clauses[0].operator, Operator::Eq);
+        assert_eq!(clauses[0].value, "success");
 
         assert!(evaluate_condition(&expr, &simple_resolve("success")));
         assert!(!evaluate_condition(&expr, &simple_resolve("fail")));
@@ -184,8 +373,8 @@ mod tests {
     #[test]
     fn not_equal() {
         let expr = parse_condition("outcome!=fail").unwrap();
-        assert_eq!(expr.clauses[0].operator, Operator::NotEq);
-        assert_eq!(expr.clauses[0].value, "fail");
+        assert_eq!(expr.clauses()[0].operator, Operator::NotEq);
+        assert_eq!(expr.clauses()[0].value, "fail");
 
         assert!(evaluate_condition(&expr, &simple_resolve("success")));
         assert!(!evaluate_condition(&expr, &simple_resolve("fail")));
@@ -194,9 +383,9 @@ mod tests {
     #[test]
     fn compound_condition() {
         let expr = parse_condition("outcome=success && context.tests_passed=true").unwrap();
-        assert_eq!(expr.clauses.len(), 2);
-        assert_eq!(expr.clauses[0].key, "outcome");
-        assert_eq!(expr.clauses[1].key, "context.tests_passed");
+        assert_eq!(expr.clauses().len(), 2);
+        assert_eq!(expr.clauses()[0].key, "outcome");
+        assert_eq!(expr.clauses()[1].key, "context.tests_passed");
 
         assert!(evaluate_condition(&expr, &simple_resolve("success")));
         assert!(!evaluate_condition(&expr, &simple_resolve("fail")));
@@ -205,7 +394,7 @@ mod tests {
     #[test]
     fn empty_condition_always_true() {
         let expr = parse_condition("").unwrap();
-        assert!(expr.clauses.is_empty());
+        assert!(expr.clauses().is_empty());
         assert!(evaluate_condition(&expr, &simple_resolve("anything")));
 
         let expr2 = parse_condition("   ").unwrap();
@@ -225,11 +414,11 @@ mod tests {
     #[test]
     fn quoted_string_values() {
         let expr = parse_condition(r#"outcome="success""#).unwrap();
-        assert_eq!(expr.clauses[0].value, "success");
+        assert_eq!(expr.clauses()[0].value, "success");
         assert!(evaluate_condition(&expr, &simple_resolve("success")));
 
         let expr2 = parse_condition("outcome='success'").unwrap();
-        assert_eq!(expr2.clauses[0].value, "success");
+        assert_eq!(expr2.clauses()[0].value, "success");
         assert!(evaluate_condition(&expr2, &simple_resolve("success")));
     }
 
@@ -246,7 +435,7 @@ mod tests {
     #[test]
     fn integer_values_as_strings() {
         let expr = parse_condition("context.count=42").unwrap();
-        assert_eq!(expr.clauses[0].value, "42");
+        assert_eq!(expr.clauses()[0].value, "42");
 
         let resolve = |key: &str| -> String {
             if key == "context.count" {
@@ -257,4 +446,125 @@ mod tests {
         };
         assert!(evaluate_condition(&expr, &resolve));
     }
+
+    fn resolve_map<'a>(pairs: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> String + 'a {
+        move |key: &str| {
+            pairs
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, v)| v.to_string())
+                .unwrap_or_default()
+        }
+    }
+
+    #[test]
+    fn or_and_precedence() {
+        // && binds tighter than ||
+        let expr = parse_condition("outcome=success || outcome=fail && attempts<3").unwrap();
+        assert!(matches!(expr, ConditionExpr::Or(ref items) if items.len() == 2));
+
+        assert!(evaluate_condition(
+            &expr,
+            &resolve_map(&[("outcome", "success")])
+        ));
+        assert!(evaluate_condition(
+            &expr,
+            &resolve_map(&[("outcome", "fail"), ("attempts", "2")])
+        ));
+        assert!(!evaluate_condition(
+            &expr,
+            &resolve_map(&[("outcome", "fail"), ("attempts", "3")])
+        ));
+    }
+
+    #[test]
+    fn parentheses_and_negation() {
+        let expr = parse_condition("!(outcome=success || outcome=partial_success)").unwrap();
+        assert!(evaluate_condition(
+            &expr,
+            &resolve_map(&[("outcome", "fail")])
+        ));
+        assert!(!evaluate_condition(
+            &expr,
+            &resolve_map(&[("outcome", "success")])
+        ));
+
+        let expr = parse_condition("(a=1 || b=1) && c=1").unwrap();
+        assert!(evaluate_condition(
+            &expr,
+            &resolve_map(&[("b", "1"), ("c", "1")])
+        ));
+        assert!(!evaluate_condition(&expr, &resolve_map(&[("b", "1")])));
+    }
+
+    #[test]
+    fn numeric_comparisons() {
+        let resolve = resolve_map(&[("tests.failed_count", "2"), ("node.cost_usd", "1.75")]);
+        for (cond, expected) in [
+            ("tests.failed_count < 3", true),
+            ("tests.failed_count <= 2", true),
+            ("tests.failed_count > 2", false),
+            ("tests.failed_count >= 2", true),
+            ("node.cost_usd > 1.5", true),
+            ("node.cost_usd < 1.5", false),
+        ] {
+            let expr = parse_condition(cond).unwrap();
+            assert_eq!(evaluate_condition(&expr, &resolve), expected, "{cond}");
+        }
+
+        // Non-numeric or missing values never satisfy a numeric comparison
+        let expr = parse_condition("missing < 3").unwrap();
+        assert!(!evaluate_condition(&expr, &resolve));
+    }
+
+    #[test]
+    fn contains_and_matches() {
+        let resolve = resolve_map(&[("review.result", "Looks good, APPROVED by lead")]);
+
+        let expr = parse_condition("review.result contains APPROVED").unwrap();
+        assert_eq!(expr.clauses()[0].operator, Operator::Contains);
+        assert!(evaluate_condition(&expr, &resolve));
+
+        let expr = parse_condition(r#"review.result matches "^Looks \w+""#).unwrap();
+        assert_eq!(expr.clauses()[0].operator, Operator::Matches);
+        assert!(evaluate_condition(&expr, &resolve));
+
+        let expr = parse_condition("review.result matches '(?i)rejected'").unwrap();
+        assert!(!evaluate_condition(&expr, &resolve));
+        // The regex is compiled when parsing, and clones share it
+        assert!(expr.clauses()[0].pattern.is_some());
+        assert_eq!(expr.clone(), expr);
+        assert_ne!(
+            parse_condition("review.result matches 'a'").unwrap(),
+            parse_condition("review.result matches 'b'").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_errors_report_column() {
+        let err = parse_condition("outcome=success && (a=1").unwrap_err();
+        assert!(
+            err.to_string().contains("unclosed '(' at column 20"),
+            "{err}"
+        );
+
+        let err = parse_condition("count < many").unwrap_err();
+        assert!(err.to_string().contains("at column 9"), "{err}");
+
+        let err = parse_condition("a=1 )").unwrap_err();
+        assert!(
+            err.to_string().contains("unexpected ')' at column 5"),
+            "{err}"
+        );
+
+        let err = parse_condition(r#"a matches "(""#).unwrap_err();
+        assert!(err.to_string().contains("invalid regex"), "{err}");
+
+        let err = parse_condition("outcome ~ x").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("expected an operator after 'outcome' at column 9"),
+            "{err}"
+        );
+    }
 }
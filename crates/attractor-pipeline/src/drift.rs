@@ -0,0 +1,248 @@
+//! Drift detection between a checkpoint and the graph being resumed.
+//!
+//! Checkpoints record a [`GraphFingerprint`] of the graph they were taken
+//! from. On resume the engine compares it with the current graph and applies
+//! a [`DriftPolicy`] to the resulting [`DriftReport`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::graph::{PipelineGraph, PipelineNode};
+
+/// SHA-256 of `data`, hex encoded.
+pub(crate) fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Content hashes of a graph: one for the whole graph and one per node.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GraphFingerprint {
+    /// Hash of the graph attributes, every node and every edge.
+    pub graph_hash: String,
+    /// Hash of each node's attributes, keyed by node ID.
+    pub node_hashes: HashMap<String, String>,
+}
+
+impl GraphFingerprint {
+    /// Fingerprint a graph after transforms, so stylesheet and `${var}`
+    /// changes count as drift too.
+    pub fn of(graph: &PipelineGraph) -> Self {
+        let node_hashes: HashMap<String, String> = graph
+            .all_nodes()
+            .map(|n| (n.id.clone(), node_hash(n)))
+            .collect();
+
+        let attrs: BTreeMap<_, _> = graph.attrs.iter().collect();
+        let nodes: BTreeMap<_, _> = node_hashes.iter().collect();
+        let edges: Vec<_> = graph
+            .all_edges()
+            .iter()
+            .map(|e| {
+                serde_json::json!([
+                    e.from,
+                    e.to,
+                    e.label,
+                    e.condition,
+                    e.weight,
+                    e.fidelity,
+                    e.thread_id,
+                    e.loop_restart
+                ])
+            })
+            .collect();
+        let canonical = serde_json::json!({
+            "name": graph.name,
+            "goal": graph.goal,
+            "attrs": attrs,
+            "nodes": nodes,
+            "edges": edges,
+        });
+
+        Self {
+            graph_hash: sha256_hex(canonical.to_string().as_bytes()),
+            node_hashes,
+        }
+    }
+
+    /// Compare a saved fingerprint (`self`) with the current one.
+    pub fn diff(&self, current: &GraphFingerprint) -> DriftReport {
+        let mut report = DriftReport {
+            graph_changed: self.graph_hash != current.graph_hash,
+            ..Default::default()
+        };
+        for (id, hash) in &current.node_hashes {
+            match self.node_hashes.get(id) {
+                None => report.added.push(id.clone()),
+                Some(saved) if saved != hash => report.changed.push(id.clone()),
+                Some(_) => {}
+            }
+        }
+        report.removed = self
+            .node_hashes
+            .keys()
+            .filter(|id| !current.node_hashes.contains_key(*id))
+            .cloned()
+            .collect();
+        report.changed.sort();
+        report.added.sort();
+        report.removed.sort();
+        report
+    }
+}
+
+/// Hash of the attributes that shape a node's behaviour. Raw DOT attributes
+/// are included alongside the fields transforms may rewrite.
+fn node_hash(node: &PipelineNode) -> String {
+    let raw: BTreeMap<_, _> = node.raw_attrs.iter().collect();
+    let canonical = serde_json::json!({
+        "raw": raw,
+        "shape": node.shape,
+        "prompt": node.prompt,
+        "llm_model": node.llm_model,
+        "llm_provider": node.llm_provider,
+        "reasoning_effort": node.reasoning_effort,
+    });
+    sha256_hex(canonical.to_string().as_bytes())
+}
+
+/// Differences between the graph a checkpoint was taken from and the current
+/// graph. Node lists are sorted by ID.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DriftReport {
+    /// Whether anything changed, including edges and graph attributes.
+    pub graph_changed: bool,
+    pub changed: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl DriftReport {
+    pub fn is_empty(&self) -> bool {
+        !self.graph_changed
+    }
+}
+
+impl fmt::Display for DriftReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        for (kind, ids) in [
+            ("changed", &self.changed),
+            ("added", &self.added),
+            ("removed", &self.removed),
+        ] {
+            if !ids.is_empty() {
+                parts.push(format!("{}: {}", kind, ids.join(", ")));
+            }
+        }
+        if parts.is_empty() && self.graph_changed {
+            parts.push("edges or graph attributes changed".into());
+        }
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+/// What to do when resuming a checkpoint whose graph has since changed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DriftPolicy {
+    /// Fail the run and report the drift.
+    #[default]
+    Refuse,
+    /// Resume anyway; completed nodes keep their old results.
+    Continue,
+    /// Rewind to just before the earliest completed node that changed, so it
+    /// and everything after it run again.
+    Invalidate,
+}
+
+impl FromStr for DriftPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "refuse" => Ok(Self::Refuse),
+            "continue" => Ok(Self::Continue),
+            "invalidate" => Ok(Self::Invalidate),
+            other => Err(format!(
+                "unknown drift policy '{}' (expected refuse, continue or invalidate)",
+                other
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(src: &str) -> PipelineGraph {
+        PipelineGraph::from_dot(attractor_dot::parse(src).unwrap()).unwrap()
+    }
+
+    const BASE: &str = r#"digraph G {
+        start [shape="Mdiamond"]
+        plan [shape="box", prompt="Plan it"]
+        build [shape="box", prompt="Build it"]
+        done [shape="Msquare"]
+        start -> plan -> build -> done
+    }"#;
+
+    #[test]
+    fn identical_graphs_have_no_drift() {
+        let a = GraphFingerprint::of(&graph(BASE));
+        let b = GraphFingerprint::of(&graph(BASE));
+        assert_eq!(a, b);
+        assert!(a.diff(&b).is_empty());
+    }
+
+    #[test]
+    fn reports_changed_added_and_removed_nodes() {
+        let saved = GraphFingerprint::of(&graph(BASE));
+        let current = GraphFingerprint::of(&graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                plan [shape="box", prompt="Plan it carefully"]
+                test [shape="box", prompt="Test it"]
+                done [shape="Msquare"]
+                start -> plan -> test -> done
+            }"#,
+        ));
+
+        let report = saved.diff(&current);
+        assert!(!report.is_empty());
+        assert_eq!(report.changed, vec!["plan"]);
+        assert_eq!(report.added, vec!["test"]);
+        assert_eq!(report.removed, vec!["build"]);
+        assert_eq!(
+            report.to_string(),
+            "changed: plan; added: test; removed: build"
+        );
+    }
+
+    #[test]
+    fn edge_changes_drift_the_graph_only() {
+        let saved = GraphFingerprint::of(&graph(BASE));
+        let current = GraphFingerprint::of(&graph(&BASE.replace(
+            "start -> plan -> build -> done",
+            "start -> plan -> build -> done\n plan -> done [label=\"skip\"]",
+        )));
+
+        let report = saved.diff(&current);
+        assert!(report.graph_changed);
+        assert!(report.changed.is_empty());
+        assert_eq!(report.to_string(), "edges or graph attributes changed");
+    }
+
+    #[test]
+    fn parses_policies() {
+        assert_eq!("Invalidate".parse(), Ok(DriftPolicy::Invalidate));
+        assert_eq!("refuse".parse(), Ok(DriftPolicy::Refuse));
+        assert!("ignore".parse::<DriftPolicy>().is_err());
+    }
+}
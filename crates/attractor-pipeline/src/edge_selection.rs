@@ -6,7 +6,7 @@
 
 use std::sync::OnceLock;
 
-use crate::condition::{evaluate_condition, parse_condition};
+use crate::condition::evaluate_condition;
 use crate::graph::{PipelineEdge, PipelineGraph};
 
 /// Select the next edge to follow after a node completes.
@@ -26,12 +26,8 @@ pub fn select_edge<'a>(
     let condition_edges: Vec<_> = edges
         .iter()
         .filter(|e| {
-            if let Some(ref cond) = e.condition {
-                if let Ok(expr) = parse_condition(cond) {
-                    evaluate_condition(&expr, resolve)
-                } else {
-                    false
-                }
+            if let Some(ref expr) = e.condition_expr {
+                evaluate_condition(expr, resolve)
             } else {
                 false
             }
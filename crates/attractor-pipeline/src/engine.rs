@@ -2,17 +2,41 @@
 //!
 //! Implements the 5-phase lifecycle: parse, validate, initialize, execute, finalize.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
 
 use attractor_types::{AttractorError, Context, Outcome, Result, StageStatus};
-
-use crate::checkpoint::{clear_checkpoint, load_checkpoint, save_checkpoint, PipelineCheckpoint};
+use futures::stream::{FuturesUnordered, StreamExt};
+
+use crate::artifacts::{resolve_artifacts, ArtifactStore, ARTIFACTS_DIR_KEY};
+use crate::changes::WorkdirSnapshot;
+use crate::checkpoint::{
+    append_journal, clear_checkpoint, clear_journal, clear_sub_pipelines, load_checkpoint,
+    load_journal, save_checkpoint, sub_pipeline_logs, PipelineCheckpoint, LOGS_DIR_KEY,
+};
+use crate::condition::{evaluate_condition, ConditionExpr};
+use crate::drift::{DriftPolicy, GraphFingerprint};
 use crate::edge_selection::select_edge;
+use crate::events::{EventEmitter, PipelineEvent};
+use crate::fidelity::EDGE_FIDELITY_KEY;
 use crate::goal_gate::enforce_goal_gates;
-use crate::graph::PipelineGraph;
-use crate::handler::{default_registry, HandlerRegistry};
+use crate::graph::{PipelineEdge, PipelineGraph, PipelineNode};
+use crate::handler::{default_registry, DynHandler, HandlerRegistry};
+use crate::handlers::manager::{ManagerConfig, MANAGER_LOOP_TYPE};
+use crate::handlers::parallel::{
+    BranchResult, JoinPolicy, PARALLEL_CANCELLED_KEY, PARALLEL_RESULTS_KEY,
+};
+use crate::handlers::subpipeline::{SubPipelineConfig, SUB_PIPELINE_TYPE};
+use crate::retry::{execute_with_retry_notify, RetryPolicy};
+use crate::threads::EDGE_THREAD_KEY;
+use crate::transforms::{context_references, expand_node_context};
 use crate::validation::validate_or_raise;
+use crate::worktree::RunWorktree;
 
 // ---------------------------------------------------------------------------
 // Public types
@@ -20,7 +44,10 @@ use crate::validation::validate_or_raise;
 
 /// The core pipeline executor. Owns a handler registry and drives graph traversal.
 pub struct PipelineExecutor {
-    registry: HandlerRegistry,
+    registry: Arc<HandlerRegistry>,
+    events: Option<EventEmitter>,
+    drift_policy: DriftPolicy,
+    worktree: bool,
 }
 
 /// Configuration for a pipeline run.
@@ -33,6 +60,11 @@ pub struct PipelineConfig {
 pub struct PipelineResult {
     pub completed_nodes: Vec<String>,
     pub node_outcomes: HashMap<String, Outcome>,
+    /// Retry attempts spent on each node (nodes that never retried are absent).
+    pub node_retries: HashMap<String, usize>,
+    /// Cost of every node execution in the run, repeats included. Nodes a
+    /// resumed run restored from its checkpoint count once each.
+    pub total_cost: f64,
     pub final_context: HashMap<String, serde_json::Value>,
 }
 
@@ -62,6 +94,232 @@ fn status_to_string(status: StageStatus) -> String {
     }
 }
 
+/// Resolve a condition key against the latest outcome and a context snapshot.
+fn condition_value(
+    key: &str,
+    outcome: &Outcome,
+    snapshot: &HashMap<String, serde_json::Value>,
+) -> String {
+    match key {
+        "outcome" => status_to_string(outcome.status),
+        "preferred_label" => outcome.preferred_label.clone().unwrap_or_default(),
+        _ => snapshot
+            .get(key)
+            .map(|v| match v {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Bool(b) => b.to_string(),
+                serde_json::Value::Number(n) => n.to_string(),
+                _ => v.to_string(),
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// A context snapshot with the artifact references at `keys` replaced by
+/// their content, for conditions and `${ctx.key}` expansion. Only the keys
+/// an expression reads are loaded, so a step never reads every artifact.
+async fn resolved_snapshot(
+    context: &Context,
+    keys: &[String],
+) -> HashMap<String, serde_json::Value> {
+    let mut snapshot = context.snapshot().await;
+    if !keys.is_empty() {
+        resolve_artifacts(&mut snapshot, Some(keys)).await;
+    }
+    snapshot
+}
+
+/// Context keys read by the conditions on `node_id`'s outgoing edges.
+fn condition_keys(node_id: &str, graph: &PipelineGraph) -> Vec<String> {
+    graph
+        .outgoing_edges(node_id)
+        .iter()
+        .filter_map(|edge| edge.condition_expr.as_ref())
+        .flat_map(clause_keys)
+        .collect()
+}
+
+fn clause_keys(expr: &ConditionExpr) -> Vec<String> {
+    expr.clauses().into_iter().map(|c| c.key.clone()).collect()
+}
+
+/// Move string outputs longer than the graph's `artifact_threshold` into the
+/// run's artifact store, leaving references in their place. A no-op unless
+/// the graph sets a threshold and the run has a store; failures only warn.
+async fn store_large_outputs(
+    node_id: &str,
+    outcome: &mut Outcome,
+    context: &Context,
+    graph: &PipelineGraph,
+) {
+    let threshold = graph
+        .attrs
+        .get("artifact_threshold")
+        .map(attr_to_json)
+        .and_then(|v| v.as_u64().or_else(|| v.as_str()?.parse().ok()))
+        .unwrap_or(0) as usize;
+    if threshold == 0 {
+        return;
+    }
+    let Some(store) = ArtifactStore::from_snapshot(&context.snapshot().await) else {
+        return;
+    };
+    for (key, value) in outcome.context_updates.iter_mut() {
+        let serde_json::Value::String(text) = value else {
+            continue;
+        };
+        if text.len() <= threshold {
+            continue;
+        }
+        match store.put(key, node_id, "text/plain", text.as_bytes()).await {
+            Ok(artifact) => {
+                tracing::info!(key = %key, size = artifact.size, "Stored output as artifact");
+                *value = artifact.to_value();
+            }
+            Err(e) => tracing::warn!(key = %key, "Failed to store artifact: {}", e),
+        }
+    }
+}
+
+/// The `<node>.cost_usd` value reported by a node's outcome, if any.
+fn node_cost(node_id: &str, outcome: &Outcome) -> Option<f64> {
+    outcome
+        .context_updates
+        .get(&format!("{}.cost_usd", node_id))
+        .and_then(|v| v.as_f64())
+}
+
+/// Apply an outcome's context updates plus the `outcome`/`preferred_label` keys.
+async fn apply_outcome(context: &Context, outcome: &Outcome) {
+    context.apply_updates(outcome.context_updates.clone()).await;
+    context
+        .set(
+            "outcome",
+            serde_json::Value::String(status_to_string(outcome.status)),
+        )
+        .await;
+    if let Some(ref label) = outcome.preferred_label {
+        context
+            .set("preferred_label", serde_json::Value::String(label.clone()))
+            .await;
+    }
+}
+
+fn budget_exceeded(spent: f64, max: f64) -> AttractorError {
+    AttractorError::Other(format!(
+        "Pipeline exceeded budget (${:.2} > ${:.2}). Use --max-budget-usd to increase.",
+        spent, max
+    ))
+}
+
+/// The run's budget as seen by concurrent parallel branches, which draw it
+/// down as their nodes finish so that every branch stops once it is spent.
+struct SharedBudget {
+    max: f64,
+    spent: std::sync::Mutex<f64>,
+}
+
+impl SharedBudget {
+    fn new(max: f64, spent: f64) -> Self {
+        Self {
+            max,
+            spent: std::sync::Mutex::new(spent),
+        }
+    }
+
+    fn spend(&self, cost: f64) {
+        *self.spent.lock().unwrap_or_else(|e| e.into_inner()) += cost;
+    }
+
+    fn check(&self) -> Result<()> {
+        let spent = *self.spent.lock().unwrap_or_else(|e| e.into_inner());
+        if spent > self.max {
+            tracing::error!(cost = spent, max = self.max, "Budget exceeded");
+            return Err(budget_exceeded(spent, self.max));
+        }
+        Ok(())
+    }
+}
+
+/// Keys in `current` that are new or changed relative to `base`.
+fn context_delta(
+    base: &HashMap<String, serde_json::Value>,
+    current: HashMap<String, serde_json::Value>,
+) -> HashMap<String, serde_json::Value> {
+    current
+        .into_iter()
+        .filter(|(k, v)| base.get(k) != Some(v))
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Parallel fan-out
+// ---------------------------------------------------------------------------
+
+/// A branch run to completion, with the bookkeeping the caller must absorb.
+struct BranchRun {
+    result: BranchResult,
+    node_outcomes: HashMap<String, Outcome>,
+    node_retries: HashMap<String, usize>,
+    cost: f64,
+}
+
+/// The joined result of a fan-out: where to continue, and what each branch did.
+struct FanOut<'g> {
+    join_node: &'g PipelineNode,
+    branches: Vec<BranchRun>,
+    /// Branches dropped before finishing because the join policy was met.
+    cancelled: Vec<String>,
+}
+
+impl<'g> FanOut<'g> {
+    /// Total number of node executions across all branches.
+    fn step_count(&self) -> u64 {
+        self.branches
+            .iter()
+            .map(|b| b.result.completed_nodes.len() as u64)
+            .sum()
+    }
+
+    fn total_cost(&self) -> f64 {
+        self.branches.iter().map(|b| b.cost).sum()
+    }
+
+    /// Record branch nodes/outcomes and publish the branch results to `context`.
+    async fn absorb(
+        self,
+        completed_nodes: &mut Vec<String>,
+        node_outcomes: &mut HashMap<String, Outcome>,
+        node_retries: &mut HashMap<String, usize>,
+        context: &Context,
+    ) -> &'g PipelineNode {
+        let mut results = Vec::with_capacity(self.branches.len());
+        for branch in self.branches {
+            completed_nodes.extend(branch.result.completed_nodes.iter().cloned());
+            node_outcomes.extend(branch.node_outcomes);
+            for (id, n) in branch.node_retries {
+                *node_retries.entry(id).or_default() += n;
+            }
+            results.push(branch.result);
+        }
+        context
+            .set(
+                PARALLEL_RESULTS_KEY,
+                serde_json::to_value(&results).unwrap_or_default(),
+            )
+            .await;
+        context
+            .set(PARALLEL_CANCELLED_KEY, serde_json::json!(self.cancelled))
+            .await;
+        self.join_node
+    }
+}
+
+type BranchFuture<'a> = Pin<Box<dyn Future<Output = Result<BranchRun>> + Send + 'a>>;
+
+// Boxed so a manager node's child run can recurse back into the engine.
+type OutcomeFuture<'a> = Pin<Box<dyn Future<Output = Result<Outcome>> + Send + 'a>>;
+
 // ---------------------------------------------------------------------------
 // PipelineExecutor
 // ---------------------------------------------------------------------------
@@ -69,14 +327,866 @@ fn status_to_string(status: StageStatus) -> String {
 impl PipelineExecutor {
     /// Create an executor with the given handler registry.
     pub fn new(registry: HandlerRegistry) -> Self {
-        Self { registry }
+        Self {
+            registry: Arc::new(registry),
+            events: None,
+            drift_policy: DriftPolicy::default(),
+            worktree: false,
+        }
     }
 
     /// Create an executor pre-loaded with the default built-in handlers.
     pub fn with_default_registry() -> Self {
-        Self {
-            registry: default_registry(),
+        Self::new(default_registry())
+    }
+
+    /// Publish [`PipelineEvent`]s for every run through `emitter`.
+    pub fn with_event_emitter(mut self, emitter: EventEmitter) -> Self {
+        self.events = Some(emitter);
+        self
+    }
+
+    /// How to resume a checkpoint taken from a since-changed graph. Defaults
+    /// to [`DriftPolicy::Refuse`].
+    pub fn with_drift_policy(mut self, policy: DriftPolicy) -> Self {
+        self.drift_policy = policy;
+        self
+    }
+
+    /// Run each fresh pipeline in its own git worktree and branch, committing
+    /// after every successful node. See [`crate::worktree`].
+    pub fn with_worktree(mut self, enabled: bool) -> Self {
+        self.worktree = enabled;
+        self
+    }
+
+    /// Subscribe to the executor's events, or `None` if no emitter is set.
+    ///
+    /// Subscribe before starting a run to observe it from the beginning.
+    pub fn subscribe(&self) -> Option<tokio::sync::broadcast::Receiver<PipelineEvent>> {
+        self.events.as_ref().map(EventEmitter::subscribe)
+    }
+
+    /// The executor for the child run of manager loop or sub-pipeline node
+    /// `node`: the same handlers and policies, with its events wrapped in
+    /// [`PipelineEvent::Child`] so they are not taken for the parent's.
+    fn child_executor(&self, node: &PipelineNode) -> PipelineExecutor {
+        PipelineExecutor {
+            registry: self.registry.clone(),
+            events: self.events.as_ref().map(|events| events.scoped(&node.id)),
+            drift_policy: self.drift_policy,
+            worktree: self.worktree,
+        }
+    }
+
+    fn emit(&self, event: PipelineEvent) {
+        if let Some(events) = &self.events {
+            events.emit(event);
+        }
+    }
+
+    /// Report the context keys an outcome wrote.
+    fn emit_context_updated(&self, node_id: &str, outcome: &Outcome) {
+        if self.events.is_none() || outcome.context_updates.is_empty() {
+            return;
+        }
+        let mut keys: Vec<String> = outcome.context_updates.keys().cloned().collect();
+        keys.sort();
+        self.emit(PipelineEvent::ContextUpdated {
+            node_id: node_id.to_string(),
+            keys,
+        });
+    }
+
+    fn emit_edge_selected(&self, edge: &PipelineEdge) {
+        self.emit(PipelineEvent::EdgeSelected {
+            from_node: edge.from.clone(),
+            to_node: edge.to.clone(),
+            edge_label: edge.label.clone(),
+        });
+    }
+
+    /// Save a checkpoint positioned at `current_node_id`, append it to the
+    /// journal and announce it.
+    #[allow(clippy::too_many_arguments)]
+    async fn save_progress(
+        &self,
+        logs: &Path,
+        fingerprint: &GraphFingerprint,
+        current_node_id: &str,
+        completed_nodes: &[String],
+        node_outcomes: &HashMap<String, Outcome>,
+        node_retries: &HashMap<String, usize>,
+        context: &Context,
+    ) -> Result<()> {
+        let mut cp = PipelineCheckpoint::new(
+            current_node_id.to_string(),
+            completed_nodes.to_vec(),
+            node_outcomes.clone(),
+            context.snapshot().await,
+        );
+        cp.node_retries = node_retries.clone();
+        cp.fingerprint = Some(fingerprint.clone());
+        save_checkpoint(&cp, logs).await?;
+        append_journal(&cp, logs).await?;
+        self.emit(PipelineEvent::CheckpointSaved {
+            node_id: current_node_id.to_string(),
+        });
+        Ok(())
+    }
+
+    /// Compare a checkpoint's graph fingerprint with the current graph and
+    /// apply the drift policy. Returns the checkpoint to resume from, or
+    /// `None` to start over.
+    ///
+    /// Only drift in completed nodes is subject to the policy: nodes that
+    /// have not run yet pick up their new definition either way.
+    async fn reconcile_drift(
+        &self,
+        cp: PipelineCheckpoint,
+        fingerprint: &GraphFingerprint,
+        logs: &Path,
+    ) -> Result<Option<PipelineCheckpoint>> {
+        // Checkpoints from older versions carry no fingerprint
+        let Some(saved) = &cp.fingerprint else {
+            return Ok(Some(cp));
+        };
+        let report = saved.diff(fingerprint);
+        if report.is_empty() {
+            return Ok(Some(cp));
+        }
+        let stale_completed = report
+            .changed
+            .iter()
+            .chain(&report.removed)
+            .any(|id| cp.completed_nodes.contains(id));
+        if !stale_completed {
+            tracing::info!(drift = %report, "Pipeline changed since checkpoint, no completed node affected");
+            return Ok(Some(cp));
+        }
+
+        match self.drift_policy {
+            DriftPolicy::Refuse => Err(AttractorError::Other(format!(
+                "Pipeline changed since the checkpoint was saved ({report}). \
+                 Use --on-drift continue|invalidate, --from <node> or --fresh."
+            ))),
+            DriftPolicy::Continue => {
+                tracing::warn!(drift = %report, "Pipeline changed since checkpoint, resuming anyway");
+                Ok(Some(cp))
+            }
+            DriftPolicy::Invalidate => {
+                // Removed nodes cannot re-run; only changed ones are invalidated
+                let Some(first_changed) = cp
+                    .completed_nodes
+                    .iter()
+                    .find(|id| report.changed.contains(id))
+                    .cloned()
+                else {
+                    tracing::warn!(drift = %report, "Completed nodes were removed from the pipeline, resuming");
+                    return Ok(Some(cp));
+                };
+                let rewind = load_journal(logs)
+                    .await?
+                    .into_iter()
+                    .rev()
+                    .find(|entry| entry.checkpoint.current_node_id == first_changed);
+                match rewind {
+                    Some(entry) => {
+                        tracing::warn!(
+                            drift = %report,
+                            node = %first_changed,
+                            "Pipeline changed since checkpoint, re-running from first changed node"
+                        );
+                        Ok(Some(entry.checkpoint))
+                    }
+                    None => {
+                        tracing::warn!(
+                            drift = %report,
+                            node = %first_changed,
+                            "Pipeline changed since checkpoint and no journaled state before the changed node, starting over"
+                        );
+                        Ok(None)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Find the run's worktree. A resumed run keeps the one recorded in its
+    /// context, reset to the commit the checkpoint saw; a fresh run in
+    /// worktree mode creates one off the `workdir` repository.
+    async fn prepare_worktree(
+        &self,
+        graph: &PipelineGraph,
+        context: &Context,
+        resumed: bool,
+    ) -> Result<Option<RunWorktree>> {
+        if let Some(worktree) = RunWorktree::from_context(&context.snapshot().await) {
+            if resumed {
+                if let Some(serde_json::Value::String(head)) = context.get("worktree.head").await {
+                    worktree.reset_to(&head).await?;
+                }
+            }
+            return Ok(Some(worktree));
+        }
+        if !self.worktree {
+            return Ok(None);
+        }
+        let dir = match context.get("workdir").await {
+            Some(serde_json::Value::String(dir)) => PathBuf::from(dir),
+            _ => std::env::current_dir()?,
+        };
+        let worktree = RunWorktree::create(&dir, &graph.name).await?;
+        let workdir = worktree.workdir_for(&dir).await?;
+        worktree.record(context, &workdir).await;
+        Ok(Some(worktree))
+    }
+
+    /// Snapshot the workdir before a node that may change files, unless the
+    /// graph sets `track_changes=false`. Failures only warn.
+    async fn snapshot_workdir(
+        &self,
+        handler_type: &str,
+        graph: &PipelineGraph,
+        context: &Context,
+    ) -> Option<WorkdirSnapshot> {
+        const NO_FILE_CHANGES: &[&str] = &[
+            "start",
+            "exit",
+            "conditional",
+            "parallel",
+            "parallel.fan_in",
+            "wait.human",
+        ];
+        let disabled = graph
+            .attrs
+            .get("track_changes")
+            .map(attr_to_json)
+            .is_some_and(|v| v == false || v == "false");
+        if disabled || NO_FILE_CHANGES.contains(&handler_type) {
+            return None;
+        }
+        let dir = match context.get("workdir").await {
+            Some(serde_json::Value::String(dir)) => PathBuf::from(dir),
+            _ => std::env::current_dir().ok()?,
+        };
+        match WorkdirSnapshot::take(&dir).await {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                tracing::warn!(dir = %dir.display(), "Not tracking file changes: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Record what a node changed since `before` as `<node>.changed_files`,
+    /// storing the diff as the `<node>.diff` artifact when there is one.
+    /// Failures only warn.
+    async fn record_changes(
+        &self,
+        before: &WorkdirSnapshot,
+        node: &PipelineNode,
+        context: &Context,
+    ) {
+        let recorded = async {
+            let dir = match before {
+                WorkdirSnapshot::Git { dir, .. } | WorkdirSnapshot::Files { dir, .. } => dir,
+            };
+            let changes = before
+                .changes_to(&WorkdirSnapshot::take(dir).await?)
+                .await?;
+            if !changes.files.is_empty() {
+                tracing::info!(node = %node.id, files = changes.files.len(), "Node changed files");
+            }
+            context
+                .set(
+                    &format!("{}.changed_files", node.id),
+                    serde_json::json!(changes.files),
+                )
+                .await;
+            let (Some(diff), Some(store)) = (
+                changes.diff,
+                ArtifactStore::from_snapshot(&context.snapshot().await),
+            ) else {
+                return Ok(());
+            };
+            if diff.is_empty() {
+                return Ok(());
+            }
+            let key = format!("{}.diff", node.id);
+            let artifact = store
+                .put(&key, &node.id, "text/x-diff", diff.as_bytes())
+                .await?;
+            context
+                .set(
+                    &format!("{}.diff_path", node.id),
+                    serde_json::Value::String(artifact.path.to_string_lossy().into_owned()),
+                )
+                .await;
+            context.set(&key, artifact.to_value()).await;
+            Ok::<_, AttractorError>(())
+        };
+        if let Err(e) = recorded.await {
+            tracing::warn!(node = %node.id, "Failed to record file changes: {}", e);
+        }
+    }
+
+    /// Commit a successful node's changes to the run's worktree branch.
+    async fn commit_node(
+        &self,
+        worktree: Option<&RunWorktree>,
+        node: &PipelineNode,
+        status: StageStatus,
+        context: &Context,
+    ) -> Result<()> {
+        let Some(worktree) = worktree else {
+            return Ok(());
+        };
+        if !matches!(status, StageStatus::Success | StageStatus::PartialSuccess) {
+            return Ok(());
+        }
+        if let Some(commit) = worktree.commit_node(&node.id, &node.label).await? {
+            tracing::info!(node = %node.id, commit = %commit, "Committed node changes");
+            context
+                .set(
+                    &format!("{}.commit", node.id),
+                    serde_json::Value::String(commit.clone()),
+                )
+                .await;
+            context
+                .set("worktree.head", serde_json::Value::String(commit))
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Look up the handler for a node, erroring if none is registered.
+    fn resolve_handler(&self, node: &PipelineNode) -> Result<(String, &DynHandler)> {
+        let handler_type = self.registry.resolve_type(node);
+        let handler =
+            self.registry
+                .get(&handler_type)
+                .ok_or_else(|| AttractorError::HandlerError {
+                    handler: handler_type.clone(),
+                    node: node.id.clone(),
+                    message: format!("No handler registered for type '{}'", handler_type),
+                })?;
+        Ok((handler_type, handler))
+    }
+
+    /// Execute `node` under its retry policy (`max_retries`, `retry_backoff`,
+    /// `retry_delay`), emitting stage events along the way. `${ctx.key}`
+    /// references in the node are expanded from `context` first. Returns the
+    /// final outcome and the number of retries it took.
+    async fn execute_node(
+        &self,
+        handler_type: &str,
+        handler: &DynHandler,
+        node: &PipelineNode,
+        context: &Context,
+        graph: &PipelineGraph,
+    ) -> Result<(Outcome, usize)> {
+        let references = node
+            .prompt
+            .as_deref()
+            .map(context_references)
+            .unwrap_or_default();
+        let expanded = if references.is_empty() {
+            std::borrow::Cow::Borrowed(node)
+        } else {
+            expand_node_context(node, &resolved_snapshot(context, &references).await)
+        };
+        let node = expanded.as_ref();
+        let policy = RetryPolicy::for_node(node, graph)?;
+        self.emit(PipelineEvent::StageStarted {
+            node_id: node.id.clone(),
+            handler_type: handler_type.to_string(),
+        });
+        let started = Instant::now();
+        let retries = AtomicUsize::new(0);
+        let result = execute_with_retry_notify(
+            || self.dispatch(handler_type, handler, node, context, graph),
+            policy.max_retries,
+            &policy.backoff,
+            &node.id,
+            |attempt, delay, reason| {
+                retries.store(attempt, Ordering::Relaxed);
+                self.emit(PipelineEvent::StageRetrying {
+                    node_id: node.id.clone(),
+                    attempt,
+                    delay_ms: delay.as_millis() as u64,
+                    reason: reason.to_string(),
+                });
+            },
+        )
+        .await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+
+        match result {
+            Ok(mut outcome) => {
+                store_large_outputs(&node.id, &mut outcome, context, graph).await;
+                self.emit(PipelineEvent::StageCompleted {
+                    node_id: node.id.clone(),
+                    status: status_to_string(outcome.status),
+                    duration_ms,
+                    cost_usd: node_cost(&node.id, &outcome),
+                    turns: outcome
+                        .context_updates
+                        .get(&format!("{}.turns", node.id))
+                        .and_then(|v| v.as_u64()),
+                    notes: outcome.notes.clone(),
+                });
+                Ok((outcome, retries.into_inner()))
+            }
+            Err(e) => {
+                self.emit(PipelineEvent::StageFailed {
+                    node_id: node.id.clone(),
+                    error: e.to_string(),
+                    duration_ms,
+                });
+                Err(e)
+            }
+        }
+    }
+
+    /// Run `handler` on `node`. Manager loop and sub-pipeline nodes then run
+    /// their child pipeline, whose result becomes the node's outcome.
+    fn dispatch<'a>(
+        &'a self,
+        handler_type: &'a str,
+        handler: &'a DynHandler,
+        node: &'a PipelineNode,
+        context: &'a Context,
+        graph: &'a PipelineGraph,
+    ) -> OutcomeFuture<'a> {
+        Box::pin(async move {
+            let outcome = handler.execute(node, context, graph).await?;
+            if outcome.status != StageStatus::Success {
+                return Ok(outcome);
+            }
+            match handler_type {
+                MANAGER_LOOP_TYPE => {
+                    let config = ManagerConfig::from_node(node, graph, &context.snapshot().await)?;
+                    self.supervise(node, config, context).await
+                }
+                SUB_PIPELINE_TYPE => {
+                    let config =
+                        SubPipelineConfig::from_node(node, graph, &context.snapshot().await)?;
+                    self.call_sub_pipeline(node, config, context).await
+                }
+                _ => Ok(outcome),
+            }
+        })
+    }
+
+    /// Run a manager node's child pipeline until its stop condition holds or
+    /// `max_iterations` runs have happened. Each iteration starts from the
+    /// parent context plus whatever earlier iterations produced.
+    ///
+    /// The outcome carries the child's last status and preferred label (or
+    /// `fail` if the stop condition never held), the child's context changes,
+    /// `<node>.iterations`, `<node>.stopped`, and the total child cost as
+    /// `<node>.cost_usd`. A child run that errors fails the node.
+    async fn supervise(
+        &self,
+        node: &PipelineNode,
+        config: ManagerConfig,
+        context: &Context,
+    ) -> Result<Outcome> {
+        let child = self.child_executor(node);
+        let base = context.snapshot().await;
+        let scratch = context.clone_isolated().await;
+        let iteration_key = format!("{}.iteration", node.id);
+        let mut cost = 0.0;
+        let mut iterations = 0;
+        let mut stopped = false;
+        let mut last = Outcome::success("");
+        let mut failure = None;
+
+        while iterations < config.max_iterations {
+            iterations += 1;
+            scratch
+                .set(&iteration_key, serde_json::json!(iterations))
+                .await;
+            tracing::info!(
+                node = %node.id,
+                child = %config.child.name,
+                iteration = iterations,
+                "Manager loop iteration"
+            );
+            let child_ctx = scratch.clone_isolated().await;
+            let run = match child.execute_graph(&config.child, child_ctx, None).await {
+                Ok(run) => run,
+                Err(e) => {
+                    failure = Some(format!("Iteration {} failed: {}", iterations, e));
+                    break;
+                }
+            };
+            cost += run.total_cost;
+
+            let mut snapshot = run.final_context;
+            last.status = snapshot
+                .get("outcome")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or(StageStatus::Success);
+            last.preferred_label = snapshot
+                .get("preferred_label")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            scratch
+                .apply_updates(context_delta(&scratch.snapshot().await, snapshot.clone()))
+                .await;
+
+            if let Some(condition) = &config.stop_condition {
+                resolve_artifacts(&mut snapshot, Some(&clause_keys(condition))).await;
+                let resolve = |key: &str| condition_value(key, &last, &snapshot);
+                if evaluate_condition(condition, &resolve) {
+                    stopped = true;
+                    break;
+                }
+            }
+        }
+
+        // Child bookkeeping stays behind: graph attributes, routing keys, and
+        // per-node costs, which are reported as one total instead
+        let child_attrs = &config.child.attrs;
+        let mut outcome = last;
+        outcome.context_updates = context_delta(&base, scratch.snapshot().await)
+            .into_iter()
+            .filter(|(key, _)| {
+                !child_attrs.contains_key(key)
+                    && !matches!(key.as_str(), "outcome" | "preferred_label")
+                    && *key != iteration_key
+                    && !key.ends_with(".cost_usd")
+            })
+            .collect();
+        let mut set = |key: &str, value: serde_json::Value| {
+            outcome
+                .context_updates
+                .insert(format!("{}.{}", node.id, key), value);
+        };
+        set("iterations", serde_json::json!(iterations));
+        set("stopped", serde_json::Value::Bool(stopped));
+        set("cost_usd", serde_json::json!(cost));
+
+        if let Some(reason) = failure {
+            outcome.status = StageStatus::Fail;
+            outcome.failure_reason = Some(reason);
+        } else if config.stop_condition.is_some() && !stopped {
+            outcome.status = StageStatus::Fail;
+            outcome.failure_reason = Some(format!(
+                "Stop condition not met after {} iteration(s)",
+                iterations
+            ));
+        }
+        outcome.notes = format!(
+            "Ran '{}' {} time(s){}",
+            config.child.name,
+            iterations,
+            if stopped { "; stop condition met" } else { "" }
+        );
+        Ok(outcome)
+    }
+
+    /// Run a sub-pipeline node's child pipeline with its mapped inputs. In a
+    /// run with a logs dir the child checkpoints under it, so an interrupted
+    /// child resumes when the parent does.
+    ///
+    /// The outcome carries the child's last status and preferred label, the
+    /// mapped outputs, and the child's total cost as `<node>.cost_usd`.
+    async fn call_sub_pipeline(
+        &self,
+        node: &PipelineNode,
+        config: SubPipelineConfig,
+        context: &Context,
+    ) -> Result<Outcome> {
+        let snapshot = context.snapshot().await;
+        let child_ctx = Context::new();
+        child_ctx
+            .apply_updates(config.child_context(&snapshot))
+            .await;
+        let logs = snapshot
+            .get(LOGS_DIR_KEY)
+            .and_then(|v| v.as_str())
+            .map(|dir| sub_pipeline_logs(Path::new(dir), &node.id));
+        tracing::info!(node = %node.id, child = %config.child.name, "Running sub-pipeline");
+
+        let run = self
+            .child_executor(node)
+            .execute_graph(&config.child, child_ctx, logs.as_deref())
+            .await
+            .map_err(|e| AttractorError::HandlerError {
+                handler: SUB_PIPELINE_TYPE.into(),
+                node: node.id.clone(),
+                message: format!("Sub-pipeline '{}' failed: {}", config.child.name, e),
+            })?;
+        let cost = run.total_cost;
+
+        let final_context = run.final_context;
+        let mut outcome = Outcome::success(format!(
+            "Sub-pipeline '{}' ran {} node(s)",
+            config.child.name,
+            run.completed_nodes.len()
+        ));
+        outcome.status = final_context
+            .get("outcome")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or(StageStatus::Success);
+        outcome.preferred_label = final_context
+            .get("preferred_label")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        if outcome.status == StageStatus::Fail {
+            outcome.failure_reason = Some(format!(
+                "Sub-pipeline '{}' finished with a failure",
+                config.child.name
+            ));
+        }
+        outcome.context_updates = config.parent_updates(&final_context);
+        outcome
+            .context_updates
+            .insert(format!("{}.cost_usd", node.id), serde_json::json!(cost));
+        Ok(outcome)
+    }
+
+    /// Find the fan-in node that joins the branches of `fork`: the nearest
+    /// node reachable from the fork that resolves to `parallel.fan_in`.
+    fn find_fan_in<'g>(
+        &self,
+        fork: &PipelineNode,
+        graph: &'g PipelineGraph,
+    ) -> Option<&'g PipelineNode> {
+        let mut visited = HashSet::new();
+        let mut queue: VecDeque<&str> = graph
+            .outgoing_edges(&fork.id)
+            .iter()
+            .map(|e| e.to.as_str())
+            .collect();
+        while let Some(id) = queue.pop_front() {
+            if !visited.insert(id) {
+                continue;
+            }
+            let Some(node) = graph.node(id) else {
+                continue;
+            };
+            if self.registry.resolve_type(node) == "parallel.fan_in" {
+                return Some(node);
+            }
+            queue.extend(graph.outgoing_edges(id).iter().map(|e| e.to.as_str()));
+        }
+        None
+    }
+
+    /// Fork every outgoing branch of `fork` with an isolated copy of `context`,
+    /// run them concurrently, and join them at the matching fan-in node.
+    ///
+    /// A branch whose node fails to run stops there as a failed branch, for
+    /// the fan-in's join policy to weigh. Exceeding the step limit or
+    /// `budget` aborts the whole fan-out.
+    async fn run_fan_out<'g>(
+        &self,
+        fork: &PipelineNode,
+        context: &Context,
+        graph: &'g PipelineGraph,
+        max_steps: u64,
+        budget: &SharedBudget,
+    ) -> Result<FanOut<'g>> {
+        let join_node = self.find_fan_in(fork, graph).ok_or_else(|| {
+            AttractorError::Other(format!(
+                "Parallel node '{}' has no reachable fan-in (tripleoctagon) node",
+                fork.id
+            ))
+        })?;
+        let base = context.snapshot().await;
+
+        let edges = graph.outgoing_edges(&fork.id);
+        tracing::info!(
+            node = %fork.id,
+            branches = edges.len(),
+            join = %join_node.id,
+            "Forking parallel branches"
+        );
+
+        let policy = JoinPolicy::from_node(join_node)?;
+        let mut pending = FuturesUnordered::new();
+        for edge in edges {
+            let branch_ctx = context.clone_isolated().await;
+            pending.push(self.run_branch(
+                &edge.to,
+                &join_node.id,
+                branch_ctx,
+                graph,
+                &base,
+                max_steps,
+                budget,
+            ));
+        }
+
+        // Collect branches in completion order; stop early once the join
+        // policy is satisfied. Dropping `pending` cancels unfinished branches.
+        let mut branches = Vec::with_capacity(edges.len());
+        let mut succeeded = 0;
+        while let Some(run) = pending.next().await {
+            let run = run?;
+            if matches!(
+                run.result.status,
+                StageStatus::Success | StageStatus::PartialSuccess
+            ) {
+                succeeded += 1;
+            }
+            branches.push(run);
+            if policy.early_exit_after().is_some_and(|n| succeeded >= n) {
+                break;
+            }
         }
+        drop(pending);
+
+        let cancelled: Vec<String> = edges
+            .iter()
+            .map(|e| e.to.clone())
+            .filter(|id| !branches.iter().any(|b| &b.result.branch_id == id))
+            .collect();
+        if !cancelled.is_empty() {
+            tracing::info!(
+                node = %fork.id,
+                policy = ?policy,
+                cancelled = ?cancelled,
+                "Join policy satisfied; cancelled remaining branches"
+            );
+        }
+
+        Ok(FanOut {
+            join_node,
+            branches,
+            cancelled,
+        })
+    }
+
+    /// Traverse a single branch from `start_id` until it reaches `join_id`,
+    /// an exit node, a node with no selectable outgoing edge, or a node that
+    /// fails to run.
+    #[allow(clippy::too_many_arguments)]
+    fn run_branch<'a>(
+        &'a self,
+        start_id: &'a str,
+        join_id: &'a str,
+        context: Context,
+        graph: &'a PipelineGraph,
+        base: &'a HashMap<String, serde_json::Value>,
+        max_steps: u64,
+        budget: &'a SharedBudget,
+    ) -> BranchFuture<'a> {
+        Box::pin(async move {
+            let mut current_node = graph.node(start_id).ok_or_else(|| {
+                AttractorError::Other(format!("Branch start '{}' not found", start_id))
+            })?;
+            let mut completed_nodes: Vec<String> = Vec::new();
+            let mut node_outcomes: HashMap<String, Outcome> = HashMap::new();
+            let mut node_retries: HashMap<String, usize> = HashMap::new();
+            let mut status = StageStatus::Success;
+            let mut error = None;
+            let mut cost = 0.0;
+            let mut step_count: u64 = 0;
+
+            while current_node.id != join_id && current_node.shape != "Msquare" {
+                step_count += 1;
+                if step_count >= max_steps {
+                    return Err(AttractorError::Other(format!(
+                        "Parallel branch '{start_id}' exceeded maximum step count ({max_steps})"
+                    )));
+                }
+                budget.check()?;
+
+                let (handler_type, handler) = self.resolve_handler(current_node)?;
+                let before = self.snapshot_workdir(&handler_type, graph, &context).await;
+                let (outcome, retries) = match self
+                    .execute_node(&handler_type, handler, current_node, &context, graph)
+                    .await
+                {
+                    Ok(done) => done,
+                    Err(e) => {
+                        tracing::warn!(
+                            branch = %start_id,
+                            node = %current_node.id,
+                            "Parallel branch failed: {}",
+                            e
+                        );
+                        completed_nodes.push(current_node.id.clone());
+                        node_outcomes.insert(current_node.id.clone(), Outcome::fail(e.to_string()));
+                        status = StageStatus::Fail;
+                        error = Some(format!("Node '{}' failed: {}", current_node.id, e));
+                        break;
+                    }
+                };
+                if retries > 0 {
+                    *node_retries.entry(current_node.id.clone()).or_default() += retries;
+                }
+
+                completed_nodes.push(current_node.id.clone());
+                node_outcomes.insert(current_node.id.clone(), outcome.clone());
+                let node_cost = node_cost(&current_node.id, &outcome).unwrap_or(0.0);
+                cost += node_cost;
+                budget.spend(node_cost);
+                status = outcome.status;
+                apply_outcome(&context, &outcome).await;
+                self.emit_context_updated(&current_node.id, &outcome);
+                // Branches share the workdir, so this also picks up whatever
+                // concurrent branches changed while the node ran
+                if let Some(before) = &before {
+                    self.record_changes(before, current_node, &context).await;
+                }
+
+                if handler_type == "parallel" {
+                    let fan_out = self
+                        .run_fan_out(current_node, &context, graph, max_steps, budget)
+                        .await?;
+                    cost += fan_out.total_cost();
+                    current_node = fan_out
+                        .absorb(
+                            &mut completed_nodes,
+                            &mut node_outcomes,
+                            &mut node_retries,
+                            &context,
+                        )
+                        .await;
+                    continue;
+                }
+
+                let snapshot =
+                    resolved_snapshot(&context, &condition_keys(&current_node.id, graph)).await;
+                let resolve = |key: &str| condition_value(key, &outcome, &snapshot);
+                match select_edge(&current_node.id, &outcome, &resolve, graph) {
+                    Some(edge) => {
+                        self.emit_edge_selected(edge);
+                        current_node = graph.node(&edge.to).ok_or_else(|| {
+                            AttractorError::Other(format!("Edge target '{}' not found", edge.to))
+                        })?;
+                    }
+                    None => break,
+                }
+            }
+
+            tracing::info!(
+                branch = %start_id,
+                status = %status_to_string(status),
+                nodes = completed_nodes.len(),
+                "Parallel branch finished"
+            );
+
+            let context_updates = context_delta(base, context.snapshot().await);
+            Ok(BranchRun {
+                result: BranchResult {
+                    branch_id: start_id.to_string(),
+                    status,
+                    completed_nodes,
+                    context_updates,
+                    error,
+                },
+                node_outcomes,
+                node_retries,
+                cost,
+            })
+        })
     }
 
     /// Run the full 5-phase pipeline lifecycle on the given graph.
@@ -97,7 +1207,8 @@ impl PipelineExecutor {
     ///
     /// If `logs_root` points to a directory containing `checkpoint.json`,
     /// execution resumes from the last saved node. A checkpoint is saved
-    /// after every node completion and cleared on successful finish.
+    /// after every node completion and cleared on successful finish; each one
+    /// is also kept in the journal for [`crate::restore_checkpoint`].
     pub async fn run_with_checkpoint(
         &self,
         graph: &PipelineGraph,
@@ -114,6 +1225,32 @@ impl PipelineExecutor {
         graph: &PipelineGraph,
         context: Context,
         logs_root: Option<&Path>,
+    ) -> Result<PipelineResult> {
+        self.emit(PipelineEvent::PipelineStarted {
+            pipeline_name: graph.name.clone(),
+            node_count: graph.all_nodes().count(),
+        });
+        let started = Instant::now();
+        let result = self.execute_graph(graph, context, logs_root).await;
+        match &result {
+            Ok(run) => self.emit(PipelineEvent::PipelineCompleted {
+                pipeline_name: graph.name.clone(),
+                completed_nodes: run.completed_nodes.clone(),
+                duration_ms: started.elapsed().as_millis() as u64,
+            }),
+            Err(e) => self.emit(PipelineEvent::PipelineFailed {
+                pipeline_name: graph.name.clone(),
+                error: e.to_string(),
+            }),
+        }
+        result
+    }
+
+    async fn execute_graph(
+        &self,
+        graph: &PipelineGraph,
+        context: Context,
+        logs_root: Option<&Path>,
     ) -> Result<PipelineResult> {
         // Phase 2: Validate
         validate_or_raise(graph)?;
@@ -124,15 +1261,23 @@ impl PipelineExecutor {
         }
         let mut completed_nodes: Vec<String> = Vec::new();
         let mut node_outcomes: HashMap<String, Outcome> = HashMap::new();
+        let mut node_retries: HashMap<String, usize> = HashMap::new();
 
         // Phase 4: Execute — check for checkpoint to resume from
         let start = graph
             .start_node()
             .ok_or_else(|| AttractorError::ValidationError("No start node found".into()))?;
         let mut current_node = start;
+        let fingerprint = GraphFingerprint::of(graph);
+        let mut resumed = false;
+        let mut restored_cost = 0.0;
 
         if let Some(logs) = logs_root {
-            if let Some(cp) = load_checkpoint(logs).await? {
+            let resume = match load_checkpoint(logs).await? {
+                Some(cp) => self.reconcile_drift(cp, &fingerprint, logs).await?,
+                None => None,
+            };
+            if let Some(cp) = resume {
                 tracing::info!(
                     node = %cp.current_node_id,
                     completed = cp.completed_nodes.len(),
@@ -143,6 +1288,11 @@ impl PipelineExecutor {
                 // Restore completed state
                 completed_nodes = cp.completed_nodes;
                 node_outcomes = cp.node_outcomes;
+                node_retries = cp.node_retries;
+                restored_cost = node_outcomes
+                    .iter()
+                    .filter_map(|(id, outcome)| node_cost(id, outcome))
+                    .sum();
                 // Jump to the node that was about to execute
                 current_node = graph.node(&cp.current_node_id).ok_or_else(|| {
                     AttractorError::Other(format!(
@@ -150,21 +1300,60 @@ impl PipelineExecutor {
                         cp.current_node_id
                     ))
                 })?;
+                resumed = true;
             }
         }
 
-        // Safety limits from context (set by CLI flags)
-        let max_budget: f64 = context
-            .get("max_budget_usd")
-            .await
-            .and_then(|v| v.as_f64())
-            .unwrap_or(200.0);
-        let max_steps: u64 = context
-            .get("max_steps")
-            .await
-            .and_then(|v| v.as_u64())
-            .unwrap_or(200);
-        let mut total_cost: f64 = 0.0;
+        let worktree = self.prepare_worktree(graph, &context, resumed).await?;
+        if let Some(logs) = logs_root {
+            let logs_dir = std::path::absolute(logs)?;
+            context
+                .set(
+                    LOGS_DIR_KEY,
+                    serde_json::Value::String(logs_dir.to_string_lossy().into_owned()),
+                )
+                .await;
+            // Child runs share their parent's store
+            if context.get(ARTIFACTS_DIR_KEY).await.is_none() {
+                let store = ArtifactStore::for_logs(logs)?;
+                context
+                    .set(
+                        ARTIFACTS_DIR_KEY,
+                        serde_json::Value::String(store.root().to_string_lossy().into_owned()),
+                    )
+                    .await;
+            }
+        }
+
+        if let Some(logs) = logs_root {
+            if !resumed {
+                // Fresh run: start a new journal with the state before the start
+                // node, and drop any checkpoints left by child runs
+                clear_journal(logs).await?;
+                clear_sub_pipelines(logs).await?;
+                let mut cp = PipelineCheckpoint::new(
+                    start.id.clone(),
+                    Vec::new(),
+                    HashMap::new(),
+                    context.snapshot().await,
+                );
+                cp.fingerprint = Some(fingerprint.clone());
+                append_journal(&cp, logs).await?;
+            }
+        }
+
+        // Safety limits from context (set by CLI flags)
+        let max_budget: f64 = context
+            .get("max_budget_usd")
+            .await
+            .and_then(|v| v.as_f64())
+            .unwrap_or(200.0);
+        let max_steps: u64 = context
+            .get("max_steps")
+            .await
+            .and_then(|v| v.as_u64())
+            .unwrap_or(200);
+        let mut total_cost: f64 = 0.0;
         let mut step_count: u64 = 0;
 
         loop {
@@ -178,108 +1367,149 @@ impl PipelineExecutor {
             }
             if total_cost > max_budget {
                 tracing::error!(cost = total_cost, max = max_budget, "Budget exceeded");
-                return Err(AttractorError::Other(format!(
-                    "Pipeline exceeded budget (${:.2} > ${:.2}). Use --max-budget-usd to increase.",
-                    total_cost, max_budget
-                )));
+                return Err(budget_exceeded(total_cost, max_budget));
             }
 
             // Terminal check (exit node)
             if current_node.shape == "Msquare" {
                 // Check goal gates
+                if self.events.is_some() {
+                    for (node_id, outcome) in &node_outcomes {
+                        if graph.node(node_id).is_some_and(|n| n.goal_gate) {
+                            self.emit(PipelineEvent::GoalGateChecked {
+                                node_id: node_id.clone(),
+                                satisfied: matches!(
+                                    outcome.status,
+                                    StageStatus::Success | StageStatus::PartialSuccess
+                                ),
+                            });
+                        }
+                    }
+                    for cluster in graph.clusters().iter().filter(|c| c.goal_gate) {
+                        let visited: Vec<&Outcome> = cluster
+                            .nodes
+                            .iter()
+                            .filter_map(|id| node_outcomes.get(id))
+                            .collect();
+                        if !visited.is_empty() {
+                            self.emit(PipelineEvent::GoalGateChecked {
+                                node_id: cluster.name.clone(),
+                                satisfied: visited.iter().all(|o| {
+                                    matches!(
+                                        o.status,
+                                        StageStatus::Success | StageStatus::PartialSuccess
+                                    )
+                                }),
+                            });
+                        }
+                    }
+                }
                 let gate_result = enforce_goal_gates(graph, &node_outcomes)?;
                 if !gate_result.all_satisfied {
                     if let Some(ref target) = gate_result.retry_target {
-                        current_node = graph.node(target).ok_or_else(|| {
+                        current_node = graph.resolve_target(target).ok_or_else(|| {
                             AttractorError::Other(format!("Retry target '{}' not found", target))
                         })?;
+                        context.set(EDGE_THREAD_KEY, serde_json::Value::Null).await;
+                        context
+                            .set(EDGE_FIDELITY_KEY, serde_json::Value::Null)
+                            .await;
                         continue;
                     }
                 }
 
                 // Execute the exit handler
-                let handler_type = self.registry.resolve_type(current_node);
-                let handler = self.registry.get(&handler_type).ok_or_else(|| {
-                    AttractorError::HandlerError {
-                        handler: handler_type.clone(),
-                        node: current_node.id.clone(),
-                        message: format!("No handler registered for type '{}'", handler_type),
-                    }
-                })?;
-                let outcome = handler.execute(current_node, &context, graph).await?;
+                let (handler_type, handler) = self.resolve_handler(current_node)?;
+                let (outcome, _) = self
+                    .execute_node(&handler_type, handler, current_node, &context, graph)
+                    .await?;
                 completed_nodes.push(current_node.id.clone());
                 node_outcomes.insert(current_node.id.clone(), outcome);
                 break;
             }
 
-            // Execute handler
-            let handler_type = self.registry.resolve_type(current_node);
-            let handler =
-                self.registry
-                    .get(&handler_type)
-                    .ok_or_else(|| AttractorError::HandlerError {
-                        handler: handler_type.clone(),
-                        node: current_node.id.clone(),
-                        message: format!("No handler registered for type '{}'", handler_type),
-                    })?;
-            let outcome = handler.execute(current_node, &context, graph).await?;
+            // Execute handler, retrying per the node's retry policy
+            let (handler_type, handler) = self.resolve_handler(current_node)?;
+            let before = self.snapshot_workdir(&handler_type, graph, &context).await;
+            let (outcome, retries) = self
+                .execute_node(&handler_type, handler, current_node, &context, graph)
+                .await?;
+            if retries > 0 {
+                *node_retries.entry(current_node.id.clone()).or_default() += retries;
+                tracing::info!(node = %current_node.id, retries, "Node retried");
+            }
 
             // Record
             completed_nodes.push(current_node.id.clone());
             node_outcomes.insert(current_node.id.clone(), outcome.clone());
 
             // Track cost from this node
-            if let Some(cost) = outcome
-                .context_updates
-                .get(&format!("{}.cost_usd", current_node.id))
-            {
-                if let Some(c) = cost.as_f64() {
-                    total_cost += c;
-                    tracing::info!(
-                        node = %current_node.id,
-                        node_cost = c,
-                        total_cost = total_cost,
-                        budget_remaining = max_budget - total_cost,
-                        "Cost update"
-                    );
-                }
+            if let Some(c) = node_cost(&current_node.id, &outcome) {
+                total_cost += c;
+                tracing::info!(
+                    node = %current_node.id,
+                    node_cost = c,
+                    total_cost = total_cost,
+                    budget_remaining = max_budget - total_cost,
+                    "Cost update"
+                );
             }
 
             // Apply context updates
-            context.apply_updates(outcome.context_updates.clone()).await;
-            context
-                .set(
-                    "outcome",
-                    serde_json::Value::String(status_to_string(outcome.status)),
-                )
-                .await;
-            if let Some(ref label) = outcome.preferred_label {
-                context
-                    .set("preferred_label", serde_json::Value::String(label.clone()))
+            apply_outcome(&context, &outcome).await;
+            self.emit_context_updated(&current_node.id, &outcome);
+            if let Some(before) = &before {
+                self.record_changes(before, current_node, &context).await;
+            }
+
+            // Parallel fan-out: run every branch concurrently, then continue
+            // from the fan-in node that joins them.
+            if handler_type == "parallel" {
+                let fork = current_node;
+                let budget = SharedBudget::new(max_budget, total_cost);
+                let fan_out = self
+                    .run_fan_out(current_node, &context, graph, max_steps, &budget)
+                    .await?;
+                step_count += fan_out.step_count();
+                total_cost += fan_out.total_cost();
+                current_node = fan_out
+                    .absorb(
+                        &mut completed_nodes,
+                        &mut node_outcomes,
+                        &mut node_retries,
+                        &context,
+                    )
                     .await;
+                // One commit for the fork covers the changes of all branches
+                self.commit_node(worktree.as_ref(), fork, outcome.status, &context)
+                    .await?;
+
+                if let Some(logs) = logs_root {
+                    self.save_progress(
+                        logs,
+                        &fingerprint,
+                        &current_node.id,
+                        &completed_nodes,
+                        &node_outcomes,
+                        &node_retries,
+                        &context,
+                    )
+                    .await?;
+                }
+                continue;
             }
+            self.commit_node(worktree.as_ref(), current_node, outcome.status, &context)
+                .await?;
 
             // Select next edge — resolve condition keys from outcome and context
-            let ctx_snapshot = context.snapshot().await;
-            let resolve = |key: &str| -> String {
-                match key {
-                    "outcome" => status_to_string(outcome.status),
-                    "preferred_label" => outcome.preferred_label.clone().unwrap_or_default(),
-                    _ => ctx_snapshot
-                        .get(key)
-                        .map(|v| match v {
-                            serde_json::Value::String(s) => s.clone(),
-                            serde_json::Value::Bool(b) => b.to_string(),
-                            serde_json::Value::Number(n) => n.to_string(),
-                            _ => v.to_string(),
-                        })
-                        .unwrap_or_default(),
-                }
-            };
+            let ctx_snapshot =
+                resolved_snapshot(&context, &condition_keys(&current_node.id, graph)).await;
+            let resolve = |key: &str| condition_value(key, &outcome, &ctx_snapshot);
             let next_edge = select_edge(&current_node.id, &outcome, &resolve, graph);
 
             match next_edge {
                 Some(edge) => {
+                    self.emit_edge_selected(edge);
                     // Handle loop_restart
                     if edge.loop_restart {
                         completed_nodes.clear();
@@ -289,16 +1519,35 @@ impl PipelineExecutor {
                     current_node = graph.node(&next_id).ok_or_else(|| {
                         AttractorError::Other(format!("Edge target '{}' not found", next_id))
                     })?;
+                    context
+                        .set(
+                            EDGE_THREAD_KEY,
+                            edge.thread_id
+                                .clone()
+                                .map_or(serde_json::Value::Null, serde_json::Value::String),
+                        )
+                        .await;
+                    context
+                        .set(
+                            EDGE_FIDELITY_KEY,
+                            edge.fidelity
+                                .clone()
+                                .map_or(serde_json::Value::Null, serde_json::Value::String),
+                        )
+                        .await;
 
                     // Save checkpoint: the *next* node to execute
                     if let Some(logs) = logs_root {
-                        let cp = PipelineCheckpoint::new(
-                            current_node.id.clone(),
-                            completed_nodes.clone(),
-                            node_outcomes.clone(),
-                            context.snapshot().await,
-                        );
-                        save_checkpoint(&cp, logs).await?;
+                        self.save_progress(
+                            logs,
+                            &fingerprint,
+                            &current_node.id,
+                            &completed_nodes,
+                            &node_outcomes,
+                            &node_retries,
+                            &context,
+                        )
+                        .await?;
                     }
                 }
                 None => {
@@ -323,6 +1572,8 @@ impl PipelineExecutor {
         Ok(PipelineResult {
             completed_nodes,
             node_outcomes,
+            node_retries,
+            total_cost: restored_cost + total_cost,
             final_context,
         })
     }
@@ -711,6 +1962,236 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn manager_loop_runs_child_until_stop_condition() {
+        struct CountingHandler;
+
+        #[async_trait]
+        impl NodeHandler for CountingHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &crate::graph::PipelineNode,
+                ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                let count = ctx.get("count").await.and_then(|v| v.as_u64()).unwrap_or(0);
+                let mut outcome = Outcome::success("counted");
+                outcome
+                    .context_updates
+                    .insert("count".into(), serde_json::json!(count + 1));
+                outcome
+                    .context_updates
+                    .insert(format!("{}.cost_usd", node.id), serde_json::json!(0.5));
+                Ok(outcome)
+            }
+        }
+
+        let run = |stop: &str, max: u32| {
+            let graph = parse_graph(&format!(
+                r#"digraph G {{
+                    start [shape="Mdiamond"]
+                    mgr [shape="house", subgraph="cluster_tasks",
+                         stop_condition="{stop}", max_iterations={max}]
+                    gave_up [shape="box"]
+                    done [shape="Msquare"]
+                    start -> mgr
+                    mgr -> done [condition="outcome=success"]
+                    mgr -> gave_up [condition="outcome=fail"]
+                    gave_up -> done
+                    subgraph cluster_tasks {{
+                        task_start [shape="Mdiamond"]
+                        next_task [shape="box"]
+                        task_done [shape="Msquare"]
+                        task_start -> next_task -> task_done
+                    }}
+                }}"#
+            ));
+            async move {
+                let mut registry = test_registry();
+                registry.register(CountingHandler);
+                registry.register(crate::handlers::ManagerLoopHandler);
+                PipelineExecutor::new(registry).run(&graph).await.unwrap()
+            }
+        };
+
+        let result = run("count=3", 5).await;
+        let ctx = &result.final_context;
+        assert_eq!(result.node_outcomes["mgr"].status, StageStatus::Success);
+        assert_eq!(ctx["count"], 3);
+        assert_eq!(ctx["mgr.iterations"], 3);
+        assert_eq!(ctx["mgr.stopped"], true);
+        assert_eq!(ctx["mgr.cost_usd"], 1.5);
+        assert!(!ctx.contains_key("next_task.cost_usd"));
+        assert!(!result.completed_nodes.contains(&"next_task".to_string()));
+
+        let result = run("count=99", 2).await;
+        let mgr = &result.node_outcomes["mgr"];
+        assert_eq!(mgr.status, StageStatus::Fail);
+        assert!(mgr
+            .failure_reason
+            .as_deref()
+            .unwrap()
+            .contains("2 iteration"));
+        assert_eq!(mgr.context_updates["count"], 2);
+        assert!(result.completed_nodes.contains(&"gave_up".to_string()));
+    }
+
+    #[tokio::test]
+    async fn manager_loop_counts_every_child_execution_cost() {
+        /// Bumps `count` and reports a cost of 0.25 per execution.
+        struct LoopingHandler;
+
+        #[async_trait]
+        impl NodeHandler for LoopingHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &crate::graph::PipelineNode,
+                ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                let count = ctx.get("count").await.and_then(|v| v.as_u64()).unwrap_or(0);
+                let mut outcome = Outcome::success("looped");
+                outcome
+                    .context_updates
+                    .insert("count".into(), serde_json::json!(count + 1));
+                outcome
+                    .context_updates
+                    .insert(format!("{}.cost_usd", node.id), serde_json::json!(0.25));
+                Ok(outcome)
+            }
+        }
+
+        // Each child run executes `work` twice before it reaches the exit
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                mgr [shape="house", subgraph="cluster_tasks", max_iterations=2]
+                done [shape="Msquare"]
+                start -> mgr -> done
+                subgraph cluster_tasks {
+                    task_start [shape="Mdiamond"]
+                    work [shape="box"]
+                    task_done [shape="Msquare"]
+                    task_start -> work
+                    work -> work [condition="count=1"]
+                    work -> work [condition="count=3"]
+                    work -> task_done
+                }
+            }"#,
+        );
+        let mut registry = test_registry();
+        registry.register(LoopingHandler);
+        registry.register(crate::handlers::ManagerLoopHandler);
+        let result = PipelineExecutor::new(registry).run(&graph).await.unwrap();
+
+        let ctx = &result.final_context;
+        assert_eq!(ctx["count"], 4);
+        assert_eq!(ctx["mgr.iterations"], 2);
+        assert_eq!(ctx["mgr.cost_usd"], 1.0);
+        assert_eq!(result.total_cost, 1.0);
+    }
+
+    #[tokio::test]
+    async fn sub_pipeline_maps_context_and_resumes_nested_checkpoint() {
+        use std::sync::{Arc, Mutex};
+
+        /// Records every node it runs; `flaky` errors the first time.
+        struct FlakyHandler(Arc<Mutex<Vec<String>>>);
+
+        #[async_trait]
+        impl NodeHandler for FlakyHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &crate::graph::PipelineNode,
+                ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                let first = {
+                    let mut runs = self.0.lock().unwrap();
+                    runs.push(node.id.clone());
+                    runs.iter().filter(|id| **id == node.id).count() == 1
+                };
+                if node.id == "flaky" && first {
+                    return Err(AttractorError::Other("interrupted".into()));
+                }
+                let task = match ctx.get("task").await {
+                    Some(serde_json::Value::String(task)) => format!(" ({})", task),
+                    _ => String::new(),
+                };
+                let mut outcome = Outcome::success("ok");
+                outcome.context_updates.insert(
+                    format!("{}.result", node.id),
+                    serde_json::json!(format!("{} done{}", node.id, task)),
+                );
+                Ok(outcome)
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("child.dot"),
+            r#"digraph child {
+                start [shape="Mdiamond"]
+                first [shape="box"]
+                flaky [shape="box"]
+                done [shape="Msquare"]
+                start -> first -> flaky -> done
+            }"#,
+        )
+        .unwrap();
+        let parent = dir.path().join("parent.dot");
+        std::fs::write(
+            &parent,
+            r#"digraph parent {
+                start [shape="Mdiamond"]
+                prep [shape="box"]
+                sub [node_type="subpipeline", sub_pipeline="child.dot",
+                     inputs="task=prep.result", outputs="summary=flaky.result"]
+                done [shape="Msquare"]
+                start -> prep -> sub -> done
+            }"#,
+        )
+        .unwrap();
+        let graph = PipelineGraph::load(&parent, &HashMap::new()).unwrap();
+        let logs = dir.path().join("logs");
+        let runs = Arc::new(Mutex::new(Vec::new()));
+        let executor = || {
+            let mut registry = test_registry();
+            registry.register(FlakyHandler(runs.clone()));
+            registry.register(crate::handlers::SubPipelineHandler);
+            PipelineExecutor::new(registry)
+        };
+
+        let err = executor()
+            .run_with_checkpoint(&graph, Context::new(), &logs)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("interrupted"), "got: {err}");
+        assert!(logs.join("subpipelines/sub/checkpoint.json").exists());
+
+        let result = executor()
+            .run_with_checkpoint(&graph, Context::new(), &logs)
+            .await
+            .unwrap();
+        // Neither level re-ran what had already finished
+        assert_eq!(
+            *runs.lock().unwrap(),
+            vec!["prep", "first", "flaky", "flaky"]
+        );
+        assert_eq!(result.final_context["summary"], "flaky done (prep done)");
+        assert!(!result.final_context.contains_key("first.result"));
+        assert!(!logs.join("subpipelines/sub/checkpoint.json").exists());
+    }
+
     // Test 8: PipelineExecutor::new and with_default_registry
     #[test]
     fn executor_constructors() {
@@ -815,4 +2296,862 @@ mod tests {
             "Expected budget error, got: {err}"
         );
     }
+
+    // Test 11: Parallel fan-out runs branches concurrently and joins at fan-in
+    #[tokio::test]
+    async fn parallel_fan_out_runs_branches_concurrently() {
+        use crate::graph::PipelineNode;
+        use crate::handlers::{FanInHandler, ParallelHandler};
+        use std::sync::Arc;
+
+        /// Handler that blocks until every branch has reached it, so the test
+        /// only completes if the branches are actually running concurrently.
+        struct BarrierHandler {
+            barrier: Arc<tokio::sync::Barrier>,
+        }
+
+        #[async_trait]
+        impl NodeHandler for BarrierHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &PipelineNode,
+                _ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                self.barrier.wait().await;
+                let mut outcome = Outcome::success("branch done");
+                outcome.context_updates.insert(
+                    format!("{}.result", node.id),
+                    serde_json::Value::String(format!("{} output", node.id)),
+                );
+                Ok(outcome)
+            }
+        }
+
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                fork [shape="component"]
+                lint [shape="box", prompt="lint"]
+                test [shape="box", prompt="test"]
+                merge [shape="tripleoctagon"]
+                done [shape="Msquare"]
+                start -> fork
+                fork -> lint
+                fork -> test
+                lint -> merge
+                test -> merge
+                merge -> done
+            }"#,
+        );
+
+        let mut registry = HandlerRegistry::new();
+        registry.register(StartHandler);
+        registry.register(ExitHandler);
+        registry.register(ParallelHandler);
+        registry.register(FanInHandler);
+        registry.register(BarrierHandler {
+            barrier: Arc::new(tokio::sync::Barrier::new(2)),
+        });
+
+        let executor = PipelineExecutor::new(registry);
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), executor.run(&graph))
+            .await
+            .expect("branches should run concurrently")
+            .unwrap();
+
+        for id in ["fork", "lint", "test", "merge", "done"] {
+            assert!(
+                result.completed_nodes.contains(&id.to_string()),
+                "Expected {id} in completed nodes, got: {:?}",
+                result.completed_nodes
+            );
+        }
+        assert_eq!(result.node_outcomes["merge"].status, StageStatus::Success);
+
+        // Each branch ran with an isolated context and reported only its own keys.
+        let results: Vec<BranchResult> =
+            serde_json::from_value(result.final_context[PARALLEL_RESULTS_KEY].clone()).unwrap();
+        assert_eq!(results.len(), 2);
+        let lint = results.iter().find(|r| r.branch_id == "lint").unwrap();
+        assert!(lint.context_updates.contains_key("lint.result"));
+        assert!(!lint.context_updates.contains_key("test.result"));
+
+        // The fan-in merged both branch contexts back into the main context.
+        assert_eq!(result.final_context["lint.result"], "lint output");
+        assert_eq!(result.final_context["test.result"], "test output");
+    }
+
+    #[tokio::test]
+    async fn parallel_branches_track_node_changes() {
+        use crate::handlers::{FanInHandler, ParallelHandler};
+
+        /// Writes `<node>.txt` into the workdir when the node is `write`.
+        struct WritingHandler;
+
+        #[async_trait]
+        impl NodeHandler for WritingHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &crate::graph::PipelineNode,
+                ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                if node.id == "write" {
+                    let workdir = ctx.get("workdir").await.unwrap();
+                    let path = Path::new(workdir.as_str().unwrap()).join("write.txt");
+                    std::fs::write(path, "written").unwrap();
+                }
+                Ok(Outcome::success("done"))
+            }
+        }
+
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                fork [shape="component"]
+                write [shape="box"]
+                read [shape="box"]
+                merge [shape="tripleoctagon"]
+                done [shape="Msquare"]
+                start -> fork
+                fork -> write
+                fork -> read
+                write -> merge
+                read -> merge
+                merge -> done
+            }"#,
+        );
+        let mut registry = test_registry();
+        registry.register(ParallelHandler);
+        registry.register(FanInHandler);
+        registry.register(WritingHandler);
+        let workdir = tempfile::tempdir().unwrap();
+        let context = Context::new();
+        context
+            .set(
+                "workdir",
+                serde_json::Value::String(workdir.path().to_string_lossy().into_owned()),
+            )
+            .await;
+
+        let result = PipelineExecutor::new(registry)
+            .run_with_context(&graph, context)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.final_context["write.changed_files"],
+            serde_json::json!(["write.txt"])
+        );
+        let results: Vec<BranchResult> =
+            serde_json::from_value(result.final_context[PARALLEL_RESULTS_KEY].clone()).unwrap();
+        let write = results.iter().find(|r| r.branch_id == "write").unwrap();
+        assert!(write.context_updates.contains_key("write.changed_files"));
+    }
+
+    // Test 12: first_success join policy stops waiting for slower branches
+    #[tokio::test]
+    async fn parallel_first_success_cancels_remaining_branches() {
+        use crate::graph::PipelineNode;
+        use crate::handlers::{FanInHandler, ParallelHandler};
+
+        /// Handler where the `slow` node never completes.
+        struct RaceHandler;
+
+        #[async_trait]
+        impl NodeHandler for RaceHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &PipelineNode,
+                _ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                if node.id == "slow" {
+                    std::future::pending::<()>().await;
+                }
+                Ok(Outcome::success("fast done"))
+            }
+        }
+
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                fork [shape="component"]
+                fast [shape="box", prompt="fast"]
+                slow [shape="box", prompt="slow"]
+                merge [shape="tripleoctagon", join_policy="first_success"]
+                done [shape="Msquare"]
+                start -> fork
+                fork -> fast
+                fork -> slow
+                fast -> merge
+                slow -> merge
+                merge -> done
+            }"#,
+        );
+
+        let mut registry = HandlerRegistry::new();
+        registry.register(StartHandler);
+        registry.register(ExitHandler);
+        registry.register(ParallelHandler);
+        registry.register(FanInHandler);
+        registry.register(RaceHandler);
+
+        let executor = PipelineExecutor::new(registry);
+        let result = tokio::time::timeout(std::time::Duration::from_secs(5), executor.run(&graph))
+            .await
+            .expect("first_success should not wait for the slow branch")
+            .unwrap();
+
+        assert!(result.completed_nodes.contains(&"fast".to_string()));
+        assert!(!result.completed_nodes.contains(&"slow".to_string()));
+        assert_eq!(result.node_outcomes["merge"].status, StageStatus::Success);
+        assert_eq!(
+            result.final_context[PARALLEL_CANCELLED_KEY],
+            serde_json::json!(["slow"])
+        );
+    }
+
+    #[tokio::test]
+    async fn parallel_branch_errors_are_failed_branches() {
+        use crate::graph::PipelineNode;
+        use crate::handlers::{FanInHandler, ParallelHandler};
+
+        /// `broken` errors at once; every other node succeeds after a pause
+        /// and costs $0.60.
+        struct BranchHandler;
+
+        #[async_trait]
+        impl NodeHandler for BranchHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &PipelineNode,
+                _ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                if node.id == "broken" {
+                    return Err(AttractorError::Other("crashed".into()));
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                let mut outcome = Outcome::success("done");
+                outcome
+                    .context_updates
+                    .insert(format!("{}.cost_usd", node.id), serde_json::json!(0.6));
+                Ok(outcome)
+            }
+        }
+
+        let graph = |policy: &str| {
+            parse_graph(&format!(
+                r#"digraph G {{
+                    start [shape="Mdiamond"]
+                    fork [shape="component"]
+                    broken [shape="box"]
+                    ok [shape="box"]
+                    ok_again [shape="box"]
+                    merge [shape="tripleoctagon", join_policy="{policy}"]
+                    done [shape="Msquare"]
+                    start -> fork
+                    fork -> broken
+                    fork -> ok
+                    broken -> merge
+                    ok -> ok_again -> merge
+                    merge -> done
+                }}"#
+            ))
+        };
+        let executor = || {
+            let mut registry = HandlerRegistry::new();
+            registry.register(StartHandler);
+            registry.register(ExitHandler);
+            registry.register(ParallelHandler);
+            registry.register(FanInHandler);
+            registry.register(BranchHandler);
+            PipelineExecutor::new(registry)
+        };
+
+        for (policy, merged) in [
+            ("first_success", StageStatus::Success),
+            ("wait_all", StageStatus::PartialSuccess),
+        ] {
+            let result = executor().run(&graph(policy)).await.unwrap();
+            assert_eq!(result.node_outcomes["merge"].status, merged, "{policy}");
+            assert_eq!(result.node_outcomes["broken"].status, StageStatus::Fail);
+            let results: Vec<BranchResult> =
+                serde_json::from_value(result.final_context[PARALLEL_RESULTS_KEY].clone()).unwrap();
+            let broken = results.iter().find(|r| r.branch_id == "broken").unwrap();
+            assert_eq!(broken.status, StageStatus::Fail);
+            assert_eq!(
+                broken.error.as_deref(),
+                Some("Node 'broken' failed: crashed")
+            );
+        }
+
+        // The budget runs out inside the branch, before ok_again
+        let context = Context::new();
+        context.set("max_budget_usd", serde_json::json!(0.5)).await;
+        let err = executor()
+            .run_with_context(&graph("wait_all"), context)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeded budget"), "got: {err}");
+    }
+
+    // Test 13: Nodes are retried per max_retries and retries are counted
+    #[tokio::test]
+    async fn node_retries_on_retryable_error() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Handler that times out on its first call and succeeds afterwards.
+        struct FlakyHandler {
+            calls: AtomicUsize,
+        }
+
+        #[async_trait]
+        impl NodeHandler for FlakyHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                _node: &crate::graph::PipelineNode,
+                _ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                if self.calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    return Err(AttractorError::CommandTimeout { timeout_ms: 10 });
+                }
+                Ok(Outcome::success("recovered"))
+            }
+        }
+
+        let graph = parse_graph(
+            r#"digraph G {
+                retry_backoff = "none"
+                start [shape="Mdiamond"]
+                flaky [shape="box", prompt="work", max_retries=2]
+                done [shape="Msquare"]
+                start -> flaky -> done
+            }"#,
+        );
+
+        let mut registry = HandlerRegistry::new();
+        registry.register(StartHandler);
+        registry.register(ExitHandler);
+        registry.register(FlakyHandler {
+            calls: AtomicUsize::new(0),
+        });
+
+        let executor = PipelineExecutor::new(registry).with_event_emitter(EventEmitter::new(64));
+        let mut rx = executor.subscribe().unwrap();
+        let result = executor.run(&graph).await.unwrap();
+        assert_eq!(result.node_outcomes["flaky"].status, StageStatus::Success);
+        assert_eq!(result.node_retries.get("flaky"), Some(&1));
+        let mut retried = false;
+        while let Ok(event) = rx.try_recv() {
+            if let PipelineEvent::StageRetrying {
+                node_id, attempt, ..
+            } = event
+            {
+                assert_eq!((node_id.as_str(), attempt), ("flaky", 1));
+                retried = true;
+            }
+        }
+        assert!(retried, "expected a StageRetrying event");
+        assert!(!result.node_retries.contains_key("start"));
+
+        // Without max_retries the timeout aborts the run.
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                flaky [shape="box", prompt="work"]
+                done [shape="Msquare"]
+                start -> flaky -> done
+            }"#,
+        );
+        let mut registry = HandlerRegistry::new();
+        registry.register(StartHandler);
+        registry.register(ExitHandler);
+        registry.register(FlakyHandler {
+            calls: AtomicUsize::new(0),
+        });
+        let result = PipelineExecutor::new(registry).run(&graph).await;
+        assert!(matches!(result, Err(AttractorError::CommandTimeout { .. })));
+    }
+
+    // Test 14: A parallel node without a reachable fan-in is an error
+    #[tokio::test]
+    async fn parallel_without_fan_in_returns_error() {
+        use crate::handlers::ParallelHandler;
+
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                fork [shape="component"]
+                a [shape="box", prompt="a"]
+                b [shape="box", prompt="b"]
+                done [shape="Msquare"]
+                start -> fork
+                fork -> a
+                fork -> b
+                a -> done
+                b -> done
+            }"#,
+        );
+
+        let mut registry = test_registry();
+        registry.register(ParallelHandler);
+        let executor = PipelineExecutor::new(registry);
+
+        let err = executor.run(&graph).await.unwrap_err().to_string();
+        assert!(err.contains("no reachable fan-in"), "got: {err}");
+    }
+
+    #[tokio::test]
+    async fn journal_allows_rerun_from_middle_node() {
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                plan [shape="box", prompt="Plan"]
+                build [shape="box", prompt="Build"]
+                done [shape="Msquare"]
+                start -> plan -> build -> done
+            }"#,
+        );
+        let executor = test_executor();
+        let logs = tempfile::tempdir().unwrap();
+        executor
+            .run_with_checkpoint(&graph, Context::new(), logs.path())
+            .await
+            .unwrap();
+
+        let journal = crate::checkpoint::load_journal(logs.path()).await.unwrap();
+        let nodes: Vec<&str> = journal
+            .iter()
+            .map(|e| e.checkpoint.current_node_id.as_str())
+            .collect();
+        assert_eq!(nodes, vec!["start", "plan", "build", "done"]);
+
+        let cp = crate::checkpoint::restore_checkpoint(logs.path(), "build")
+            .await
+            .unwrap();
+        assert_eq!(cp.completed_nodes, vec!["start", "plan"]);
+        assert!(cp.context_snapshot.contains_key("plan.result"));
+
+        let result = executor
+            .run_with_checkpoint(&graph, Context::new(), logs.path())
+            .await
+            .unwrap();
+        assert_eq!(
+            result.completed_nodes,
+            vec!["start", "plan", "build", "done"]
+        );
+        // The rerun appends to the journal rather than starting a new one
+        let journal = crate::checkpoint::load_journal(logs.path()).await.unwrap();
+        assert_eq!(journal.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn worktree_mode_commits_and_tracks_node_changes() {
+        /// Writes `<node>.txt` into the workdir.
+        struct WritingHandler;
+
+        #[async_trait]
+        impl NodeHandler for WritingHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &crate::graph::PipelineNode,
+                ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                let workdir = ctx.get("workdir").await.unwrap();
+                let path = Path::new(workdir.as_str().unwrap()).join(format!("{}.txt", node.id));
+                std::fs::write(path, &node.id).unwrap();
+                Ok(Outcome::success("wrote file"))
+            }
+        }
+
+        let repo = tempfile::tempdir().unwrap();
+        for args in [
+            &["init", "--quiet"][..],
+            &[
+                "-c",
+                "user.name=t",
+                "-c",
+                "user.email=t@t",
+                "commit",
+                "--quiet",
+                "--allow-empty",
+                "-m",
+                "init",
+            ],
+        ] {
+            let status = std::process::Command::new("git")
+                .args(args)
+                .current_dir(repo.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                plan [shape="box", label="Plan it", prompt="Plan"]
+                build [shape="box", prompt="Build"]
+                done [shape="Msquare"]
+                start -> plan -> build -> done
+            }"#,
+        );
+        let mut registry = test_registry();
+        registry.register(WritingHandler);
+        let executor = PipelineExecutor::new(registry).with_worktree(true);
+        // Run from a subdirectory: handlers stay in the same one in the worktree
+        let app = repo.path().join("app");
+        std::fs::create_dir(&app).unwrap();
+        let context = || {
+            let ctx = Context::new();
+            let dir = app.to_string_lossy().into_owned();
+            async move {
+                ctx.set("workdir", serde_json::Value::String(dir)).await;
+                ctx
+            }
+        };
+        let logs = tempfile::tempdir().unwrap();
+
+        let result = executor
+            .run_with_checkpoint(&graph, context().await, logs.path())
+            .await
+            .unwrap();
+        let worktree = RunWorktree::from_context(&result.final_context).unwrap();
+        assert_eq!(
+            result.final_context["workdir"],
+            worktree.path.join("app").to_string_lossy().as_ref()
+        );
+        assert!(worktree.path.join("app/build.txt").exists());
+        assert!(!app.join("build.txt").exists());
+        let log = worktree.log().await.unwrap();
+        assert_eq!(log.len(), 2);
+        assert!(log[0].ends_with("plan: Plan it"));
+        assert!(result.final_context.contains_key("build.commit"));
+        assert_eq!(
+            result.final_context["build.changed_files"],
+            serde_json::json!(["build.txt"])
+        );
+        let diff = crate::artifacts::ArtifactRef::from_value(&result.final_context["build.diff"])
+            .expect("diff stored as an artifact");
+        assert_eq!(diff.name, "build.diff");
+        assert_eq!(
+            result.final_context["build.diff_path"],
+            diff.path.to_string_lossy().as_ref()
+        );
+        assert!(diff.read_text().await.unwrap().contains("+build"));
+
+        // Rewinding resets the tree before the re-run commits build again
+        crate::checkpoint::restore_checkpoint(logs.path(), "build")
+            .await
+            .unwrap();
+        std::fs::write(worktree.path.join("stray.txt"), "x").unwrap();
+        executor
+            .run_with_checkpoint(&graph, context().await, logs.path())
+            .await
+            .unwrap();
+        assert!(!worktree.path.join("stray.txt").exists());
+        assert_eq!(worktree.log().await.unwrap().len(), 2);
+
+        worktree
+            .finish(crate::worktree::WorktreeAction::Discard)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn large_outputs_are_stored_as_artifacts() {
+        let graph = parse_graph(
+            r#"digraph G {
+                artifact_threshold=5
+                start [shape="Mdiamond"]
+                plan [shape="box", prompt="Plan"]
+                done [shape="Msquare"]
+                start -> plan
+                plan -> done [condition="plan.result contains mock"]
+            }"#,
+        );
+        let logs = tempfile::tempdir().unwrap();
+        let result = test_executor()
+            .run_with_checkpoint(&graph, Context::new(), logs.path())
+            .await
+            .unwrap();
+
+        // "mock result" is over the threshold; the condition still saw the text
+        let artifact =
+            crate::artifacts::ArtifactRef::from_value(&result.final_context["plan.result"])
+                .expect("plan.result stored as an artifact");
+        assert_eq!(artifact.node_id, "plan");
+        assert_eq!(artifact.read_text().await.unwrap(), "mock result");
+        assert!(artifact
+            .path
+            .starts_with(std::path::absolute(logs.path()).unwrap()));
+        assert!(result.completed_nodes.contains(&"done".to_string()));
+
+        // Without a threshold outputs stay inline
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                plan [shape="box", prompt="Plan"]
+                done [shape="Msquare"]
+                start -> plan -> done
+            }"#,
+        );
+        let logs = tempfile::tempdir().unwrap();
+        let result = test_executor()
+            .run_with_checkpoint(&graph, Context::new(), logs.path())
+            .await
+            .unwrap();
+        assert_eq!(result.final_context["plan.result"], "mock result");
+    }
+
+    #[tokio::test]
+    async fn resume_after_graph_edit_applies_drift_policy() {
+        let v1 = r#"digraph G {
+                start [shape="Mdiamond"]
+                plan [shape="box", prompt="Plan"]
+                build [shape="box", prompt="Build"]
+                done [shape="Msquare"]
+                start -> plan -> build -> done
+            }"#;
+        let v2 = v1.replace(r#"prompt="Plan""#, r#"prompt="Plan carefully""#);
+        let logs = tempfile::tempdir().unwrap();
+        test_executor()
+            .run_with_checkpoint(&parse_graph(v1), Context::new(), logs.path())
+            .await
+            .unwrap();
+
+        // Leave a checkpoint just before the exit, then edit a completed node
+        crate::checkpoint::restore_checkpoint(logs.path(), "done")
+            .await
+            .unwrap();
+        let err = test_executor()
+            .run_with_checkpoint(&parse_graph(&v2), Context::new(), logs.path())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("changed: plan"), "got: {err}");
+
+        let executor = test_executor()
+            .with_drift_policy(DriftPolicy::Invalidate)
+            .with_event_emitter(EventEmitter::new(64));
+        let mut rx = executor.subscribe().unwrap();
+        executor
+            .run_with_checkpoint(&parse_graph(&v2), Context::new(), logs.path())
+            .await
+            .unwrap();
+        let mut started = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            if let PipelineEvent::StageStarted { node_id, .. } = event {
+                started.push(node_id);
+            }
+        }
+        assert_eq!(started, vec!["plan", "build", "done"]);
+
+        // Rewinding to before the edited node resumes even under Refuse
+        crate::checkpoint::restore_checkpoint(logs.path(), "plan")
+            .await
+            .unwrap();
+        let v3 = v2.replace(r#"prompt="Build""#, r#"prompt="Build it""#);
+        test_executor()
+            .run_with_checkpoint(&parse_graph(&v3), Context::new(), logs.path())
+            .await
+            .unwrap();
+    }
+
+    // Test 15: Executor publishes lifecycle events to subscribers
+    #[tokio::test]
+    async fn executor_emits_pipeline_events() {
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                process [shape="box", label="Process", prompt="Do work", goal_gate=true]
+                done [shape="Msquare"]
+                start -> process -> done
+            }"#,
+        );
+        let executor = test_executor().with_event_emitter(EventEmitter::new(64));
+        let mut rx = executor.subscribe().expect("emitter configured");
+
+        let logs = tempfile::tempdir().unwrap();
+        executor
+            .run_with_checkpoint(&graph, Context::new(), logs.path())
+            .await
+            .unwrap();
+
+        let mut events = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            events.push(event);
+        }
+
+        assert!(matches!(
+            events.first(),
+            Some(PipelineEvent::PipelineStarted { node_count: 3, .. })
+        ));
+        assert!(matches!(
+            events.last(),
+            Some(PipelineEvent::PipelineCompleted { completed_nodes, .. }) if completed_nodes.len() == 3
+        ));
+        let started: Vec<&str> = events
+            .iter()
+            .filter_map(|e| match e {
+                PipelineEvent::StageStarted { node_id, .. } => Some(node_id.as_str()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(started, vec!["start", "process", "done"]);
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PipelineEvent::StageCompleted { node_id, status, .. } if node_id == "process" && status == "success"
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PipelineEvent::EdgeSelected { from_node, to_node, .. } if from_node == "process" && to_node == "done"
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PipelineEvent::ContextUpdated { node_id, keys } if node_id == "process" && keys.contains(&"process.result".to_string())
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PipelineEvent::CheckpointSaved { node_id } if node_id == "done"
+        )));
+        assert!(events.iter().any(|e| matches!(
+            e,
+            PipelineEvent::GoalGateChecked { node_id, satisfied: true } if node_id == "process"
+        )));
+    }
+
+    // Test 16: Executor without an emitter has nothing to subscribe to
+    #[test]
+    fn executor_without_emitter_has_no_subscription() {
+        assert!(test_executor().subscribe().is_none());
+    }
+
+    // Test 17: ${ctx.key} references are expanded from the live context
+    #[tokio::test]
+    async fn ctx_variables_expand_at_execution_time() {
+        struct PromptEchoHandler;
+
+        #[async_trait]
+        impl NodeHandler for PromptEchoHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &crate::graph::PipelineNode,
+                _ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                let mut outcome = Outcome::success("echo");
+                outcome.context_updates.insert(
+                    format!("{}.result", node.id),
+                    serde_json::json!(node.prompt.clone().unwrap_or_default()),
+                );
+                Ok(outcome)
+            }
+        }
+
+        let graph = parse_graph(
+            r#"digraph G {
+                project = "my-app"
+                start [shape="Mdiamond"]
+                plan [shape="box", prompt="Plan ${ctx.project}"]
+                build [shape="box", prompt="Build from: ${ctx.plan.result}"]
+                done [shape="Msquare"]
+                start -> plan -> build -> done
+            }"#,
+        );
+        let mut reg = HandlerRegistry::new();
+        reg.register(StartHandler);
+        reg.register(ExitHandler);
+        reg.register(PromptEchoHandler);
+        let result = PipelineExecutor::new(reg).run(&graph).await.unwrap();
+
+        assert_eq!(
+            result.final_context["build.result"],
+            serde_json::json!("Build from: Plan my-app")
+        );
+    }
+
+    #[tokio::test]
+    async fn edge_thread_ids_carry_a_thread_into_the_next_node() {
+        struct ThreadHandler;
+
+        #[async_trait]
+        impl NodeHandler for ThreadHandler {
+            fn handler_type(&self) -> &str {
+                "codergen"
+            }
+            async fn execute(
+                &self,
+                node: &crate::graph::PipelineNode,
+                ctx: &Context,
+                _graph: &PipelineGraph,
+            ) -> Result<Outcome> {
+                let mut outcome = Outcome::success("threaded");
+                if let Some(thread) =
+                    crate::threads::Thread::for_node(node, &ctx.snapshot().await).await
+                {
+                    let session = thread.session.clone().unwrap_or_else(|| "s-1".into());
+                    outcome
+                        .context_updates
+                        .insert(format!("{}.session", node.id), serde_json::json!(session));
+                    outcome.context_updates.extend(thread.updates(
+                        &node.id,
+                        "mock",
+                        Some(session),
+                        None,
+                    ));
+                }
+                Ok(outcome)
+            }
+        }
+
+        let graph = parse_graph(
+            r#"digraph G {
+                start [shape="Mdiamond"]
+                implement [thread_id="impl"]
+                done [shape="Msquare"]
+                start -> implement -> test
+                test -> fix [thread_id="impl"]
+                fix -> done
+            }"#,
+        );
+        let mut reg = HandlerRegistry::new();
+        reg.register(StartHandler);
+        reg.register(ExitHandler);
+        reg.register(ThreadHandler);
+        let result = PipelineExecutor::new(reg).run(&graph).await.unwrap();
+
+        let ctx = &result.final_context;
+        assert!(!ctx.contains_key("test.session"));
+        assert_eq!(ctx["fix.session"], "s-1");
+        assert_eq!(
+            ctx["thread.impl.nodes"],
+            serde_json::json!(["implement", "fix"])
+        );
+        assert_eq!(ctx[EDGE_THREAD_KEY], serde_json::Value::Null);
+    }
 }
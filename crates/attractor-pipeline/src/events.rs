@@ -30,14 +30,27 @@ pub enum PipelineEvent {
         node_id: String,
         status: String,
         duration_ms: u64,
+        /// Cost reported by the node (`<node>.cost_usd`), if any.
+        #[serde(default)]
+        cost_usd: Option<f64>,
+        /// Agent turns reported by the node (`<node>.turns`), if any.
+        #[serde(default)]
+        turns: Option<u64>,
+        #[serde(default)]
+        notes: String,
     },
     StageFailed {
         node_id: String,
         error: String,
+        duration_ms: u64,
     },
     StageRetrying {
         node_id: String,
+        /// 1-based retry number.
         attempt: usize,
+        /// Backoff delay before the retry starts.
+        delay_ms: u64,
+        reason: String,
     },
     EdgeSelected {
         from_node: String,
@@ -55,25 +68,61 @@ pub enum PipelineEvent {
         node_id: String,
         keys: Vec<String>,
     },
+    /// Reply text an agent node's LLM streamed, as it arrives.
+    TextDelta {
+        node_id: String,
+        text: String,
+    },
+    /// An event of the child run of manager loop or sub-pipeline node
+    /// `node_id`. The node's own `StageCompleted` reports the child run's
+    /// total cost, so consumers summing costs skip these.
+    Child {
+        node_id: String,
+        event: Box<PipelineEvent>,
+    },
 }
 
 /// Event emitter wrapping a broadcast sender.
 #[derive(Clone)]
 pub struct EventEmitter {
     sender: tokio::sync::broadcast::Sender<PipelineEvent>,
+    /// Nodes whose child run this emitter reports, outermost first.
+    scope: Vec<String>,
 }
 
 impl EventEmitter {
     /// Create a new emitter with the given channel capacity.
     pub fn new(capacity: usize) -> Self {
         let (sender, _) = tokio::sync::broadcast::channel(capacity);
-        Self { sender }
+        Self {
+            sender,
+            scope: Vec::new(),
+        }
+    }
+
+    /// An emitter on the same channel for the child run of `node_id`: every
+    /// event it sends arrives wrapped in [`PipelineEvent::Child`].
+    pub fn scoped(&self, node_id: &str) -> Self {
+        let mut scope = self.scope.clone();
+        scope.push(node_id.to_string());
+        Self {
+            sender: self.sender.clone(),
+            scope,
+        }
     }
 
     /// Emit an event to all current subscribers.
     ///
     /// If there are no active receivers the event is silently dropped.
     pub fn emit(&self, event: PipelineEvent) {
+        let event = self
+            .scope
+            .iter()
+            .rev()
+            .fold(event, |event, node_id| PipelineEvent::Child {
+                node_id: node_id.clone(),
+                event: Box::new(event),
+            });
         let _ = self.sender.send(event);
     }
 
@@ -145,12 +194,38 @@ mod tests {
         });
     }
 
+    #[tokio::test]
+    async fn scoped_emitters_wrap_child_events() {
+        let emitter = EventEmitter::new(16);
+        let mut rx = emitter.subscribe();
+
+        emitter
+            .scoped("mgr")
+            .scoped("sub")
+            .emit(PipelineEvent::CheckpointSaved {
+                node_id: "n1".into(),
+            });
+
+        let PipelineEvent::Child { node_id, event } = rx.recv().await.unwrap() else {
+            panic!("expected a child event");
+        };
+        assert_eq!(node_id, "mgr");
+        let PipelineEvent::Child { node_id, event } = *event else {
+            panic!("expected a nested child event");
+        };
+        assert_eq!(node_id, "sub");
+        assert!(matches!(*event, PipelineEvent::CheckpointSaved { .. }));
+    }
+
     #[test]
     fn event_serialization_round_trip() {
         let event = PipelineEvent::StageCompleted {
             node_id: "node_42".into(),
             status: "ok".into(),
             duration_ms: 123,
+            cost_usd: Some(0.5),
+            turns: Some(3),
+            notes: String::new(),
         };
 
         let json = serde_json::to_string(&event).unwrap();
@@ -161,10 +236,13 @@ mod tests {
                 node_id,
                 status,
                 duration_ms,
+                cost_usd,
+                ..
             } => {
                 assert_eq!(node_id, "node_42");
                 assert_eq!(status, "ok");
                 assert_eq!(duration_ms, 123);
+                assert_eq!(cost_usd, Some(0.5));
             }
             other => panic!("unexpected variant after round-trip: {:?}", other),
         }
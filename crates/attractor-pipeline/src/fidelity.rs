@@ -0,0 +1,399 @@
+//! Context fidelity: how much of the run so far reaches a node's LLM.
+//!
+//! A node's mode is the `fidelity` of the edge the run arrived by (recorded
+//! by the engine as [`EDGE_FIDELITY_KEY`]), else its own `fidelity` (which a
+//! cluster can set for all its members), else the graph's
+//! `default_fidelity`, else `full`. It shapes the prior node results in the
+//! prompt:
+//!   - `full`: every value verbatim
+//!   - `truncate_chars:N`: each value cut to its last N characters
+//!   - `truncate:N`: every value verbatim; the mode keeps the last N turns of
+//!     an agent conversation instead
+//!   - `compact`: tool outputs (`.output`, `.stdout`, `.stderr`) are
+//!     dropped and every other value is cut to its last paragraph, where an
+//!     LLM states what it decided
+//!   - `summary:low|medium|high`: each value longer than the level's word
+//!     budget is replaced by an LLM-written summary. Summaries are cached in
+//!     the context as `summary.<key>.<level>`, so every result is summarized
+//!     once and reused by later nodes until it changes
+//!
+//! Agent nodes apply the same mode to the conversation they continue (see
+//! [`attractor_agent::fidelity`]).
+
+use std::collections::HashMap;
+use std::future::Future;
+
+use attractor_agent::{FidelityMode, SummaryDetail};
+use attractor_dot::AttributeValue;
+use attractor_types::Result;
+use serde_json::Value;
+
+use crate::artifacts::ArtifactRef;
+use crate::drift::sha256_hex;
+use crate::graph::{PipelineGraph, PipelineNode};
+
+/// Context key holding the `fidelity` of the edge the run last followed.
+pub const EDGE_FIDELITY_KEY: &str = "edge.fidelity";
+
+/// Longest value `compact` keeps, in characters.
+const COMPACT_LIMIT: usize = 600;
+
+/// Key suffixes of tool output, which `compact` drops.
+const TOOL_OUTPUT_SUFFIXES: &[&str] = &[".output", ".stdout", ".stderr"];
+
+/// The fidelity mode `node` runs with.
+pub fn node_fidelity(
+    node: &PipelineNode,
+    graph: &PipelineGraph,
+    snapshot: &HashMap<String, Value>,
+) -> FidelityMode {
+    snapshot
+        .get(EDGE_FIDELITY_KEY)
+        .and_then(Value::as_str)
+        .or(node.fidelity.as_deref())
+        .or_else(|| match graph.attrs.get("default_fidelity") {
+            Some(AttributeValue::String(s)) => Some(s.as_str()),
+            _ => None,
+        })
+        .filter(|s| !s.trim().is_empty())
+        .map(FidelityMode::parse)
+        .unwrap_or_default()
+}
+
+/// Prompt lines for the prior context `entries` under `mode`, leaving out
+/// what the mode drops. Under `summary` fidelity a value's entry in
+/// `summaries` replaces it.
+pub fn prior_context_lines(
+    entries: &[(String, Option<&Value>)],
+    mode: &FidelityMode,
+    summaries: &HashMap<String, String>,
+) -> Vec<String> {
+    entries
+        .iter()
+        .filter_map(|(key, value)| {
+            if let (FidelityMode::Summary { .. }, Some(summary)) = (mode, summaries.get(key)) {
+                return Some(format!("- {}: {}", key, summary));
+            }
+            if *mode == FidelityMode::Compact
+                && TOOL_OUTPUT_SUFFIXES.iter().any(|s| key.ends_with(s))
+            {
+                return None;
+            }
+            let text = match value {
+                None => return Some(format!("- {}: (not set)", key)),
+                Some(value) => match ArtifactRef::from_value(value) {
+                    Some(artifact) => {
+                        return Some(format!(
+                            "- {}: ({} bytes, stored at {})",
+                            key,
+                            artifact.size,
+                            artifact.path.display()
+                        ))
+                    }
+                    None => match value {
+                        Value::String(s) => s.clone(),
+                        other => other.to_string(),
+                    },
+                },
+            };
+            let text = match mode {
+                FidelityMode::Full
+                | FidelityMode::Summary { .. }
+                | FidelityMode::Truncate { .. } => text,
+                FidelityMode::TruncateChars { keep_last } => tail(&text, *keep_last),
+                FidelityMode::Compact => conclusion(&text),
+            };
+            Some(format!("- {}: {}", key, text))
+        })
+        .collect()
+}
+
+/// The last `limit` characters of `text`, noting how many were cut.
+fn tail(text: &str, limit: usize) -> String {
+    let total = text.chars().count();
+    if total <= limit {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(total - limit).collect();
+    format!("({} earlier characters) …{}", total - limit, kept)
+}
+
+/// The last paragraph of `text`, at most [`COMPACT_LIMIT`] characters.
+fn conclusion(text: &str) -> String {
+    let paragraphs: Vec<&str> = text
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    let Some(last) = paragraphs.last() else {
+        return String::new();
+    };
+    let last = tail(last, COMPACT_LIMIT);
+    if paragraphs.len() > 1 {
+        format!("… {}", last)
+    } else {
+        last
+    }
+}
+
+/// The summary of the context value `text` at `key` that a `summary`
+/// fidelity shows: the text itself when it is already within the detail's
+/// word budget, else the summary cached in `snapshot` while `text` is
+/// unchanged. `None` means an LLM has to write one (see [`summary_request`]).
+pub fn cached_summary(
+    snapshot: &HashMap<String, Value>,
+    key: &str,
+    text: &str,
+    detail: SummaryDetail,
+) -> Option<String> {
+    if text.split_whitespace().count() <= detail.word_budget() {
+        return Some(text.to_string());
+    }
+    let cached = snapshot.get(&summary_key(key, detail))?;
+    if cached.get("hash")?.as_str()? != sha256_hex(text.as_bytes()) {
+        return None;
+    }
+    Some(cached.get("text")?.as_str()?.to_string())
+}
+
+/// The request asking an LLM to summarize the context value `text` at `key`.
+pub fn summary_request(key: &str, text: &str, detail: SummaryDetail) -> String {
+    attractor_agent::fidelity::summary_prompt(
+        &format!("the `{}` output of an earlier pipeline step", key),
+        text,
+        detail,
+    )
+}
+
+/// The context update caching `summary` as the summary of `text` at `key`.
+pub fn summary_update(
+    key: &str,
+    text: &str,
+    detail: SummaryDetail,
+    summary: &str,
+) -> (String, Value) {
+    (
+        summary_key(key, detail),
+        serde_json::json!({"hash": sha256_hex(text.as_bytes()), "text": summary}),
+    )
+}
+
+/// The summaries a node shows under `summary` fidelity, and what writing
+/// them took.
+#[derive(Debug)]
+pub struct PriorSummaries<T> {
+    /// The summary of each prior result, by context key.
+    pub summaries: HashMap<String, String>,
+    /// Context updates caching the summaries written for this node.
+    pub updates: HashMap<String, Value>,
+    /// What `write` reported for each summary it wrote (cost, usage).
+    pub spent: Vec<T>,
+}
+
+/// Summarize the prior results `inputs` (context key and text) at `detail`,
+/// reusing the summaries cached in `snapshot`. Every other summary is
+/// written by `write`, which sends a [`summary_request`] to the node's LLM
+/// and returns the reply with what the call cost.
+pub async fn summarize_prior_results<T, F, Fut>(
+    inputs: Vec<(String, String)>,
+    snapshot: &HashMap<String, Value>,
+    detail: SummaryDetail,
+    mut write: F,
+) -> Result<PriorSummaries<T>>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<(String, T)>>,
+{
+    let mut prior = PriorSummaries {
+        summaries: HashMap::new(),
+        updates: HashMap::new(),
+        spent: Vec::new(),
+    };
+    for (key, text) in inputs {
+        let summary = match cached_summary(snapshot, &key, &text, detail) {
+            Some(summary) => summary,
+            None => {
+                let (summary, spent) = write(summary_request(&key, &text, detail)).await?;
+                let (cache_key, cached) = summary_update(&key, &text, detail, &summary);
+                prior.updates.insert(cache_key, cached);
+                prior.spent.push(spent);
+                summary
+            }
+        };
+        prior.summaries.insert(key, summary);
+    }
+    Ok(prior)
+}
+
+fn summary_key(key: &str, detail: SummaryDetail) -> String {
+    format!("summary.{}.{}", key, detail.as_str())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tests::make_node;
+    use serde_json::json;
+
+    fn graph(dot: &str) -> PipelineGraph {
+        PipelineGraph::from_dot(attractor_dot::parse(dot).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn fidelity_comes_from_the_edge_then_the_node_then_the_graph() {
+        let mut node = make_node("fix", "box", Some("Fix it"), HashMap::new());
+        let plain = graph("digraph G { A }");
+        let mut snapshot = HashMap::new();
+        assert_eq!(node_fidelity(&node, &plain, &snapshot), FidelityMode::Full);
+
+        let defaulted = graph(r#"digraph G { default_fidelity="compact" A }"#);
+        assert_eq!(
+            node_fidelity(&node, &defaulted, &snapshot),
+            FidelityMode::Compact
+        );
+
+        node.fidelity = Some("truncate_chars:100".into());
+        assert_eq!(
+            node_fidelity(&node, &defaulted, &snapshot),
+            FidelityMode::TruncateChars { keep_last: 100 }
+        );
+
+        snapshot.insert(EDGE_FIDELITY_KEY.to_string(), json!("summary:high"));
+        assert_eq!(
+            node_fidelity(&node, &defaulted, &snapshot),
+            FidelityMode::Summary {
+                detail: SummaryDetail::High
+            }
+        );
+    }
+
+    #[test]
+    fn modes_shape_prior_context_lines() {
+        let plan = json!("Read the code.\n\nWe will add a cache to the parser.");
+        let test = json!("running 40 tests\nok");
+        let entries = vec![
+            ("plan.result".to_string(), Some(&plan)),
+            ("test.stdout".to_string(), Some(&test)),
+            ("review.result".to_string(), None),
+        ];
+
+        let full = prior_context_lines(&entries, &FidelityMode::Full, &HashMap::new());
+        assert_eq!(
+            full[0],
+            "- plan.result: Read the code.\n\nWe will add a cache to the parser."
+        );
+        assert_eq!(full.len(), 3);
+
+        let truncated = prior_context_lines(
+            &entries,
+            &FidelityMode::TruncateChars { keep_last: 11 },
+            &HashMap::new(),
+        );
+        assert_eq!(
+            truncated[0],
+            "- plan.result: (39 earlier characters) …the parser."
+        );
+        assert_eq!(
+            truncated[1],
+            "- test.stdout: (8 earlier characters) …40 tests\nok"
+        );
+        // Turn truncation is for agent conversations; prompt values stay whole
+        assert_eq!(
+            prior_context_lines(
+                &entries,
+                &FidelityMode::Truncate { keep_last: 1 },
+                &HashMap::new()
+            ),
+            full
+        );
+
+        let compact = prior_context_lines(&entries, &FidelityMode::Compact, &HashMap::new());
+        assert_eq!(
+            compact,
+            vec![
+                "- plan.result: … We will add a cache to the parser.",
+                "- review.result: (not set)"
+            ]
+        );
+    }
+
+    #[test]
+    fn summaries_are_cached_per_value_and_detail() {
+        let long = "word ".repeat(500);
+        let mut snapshot = HashMap::new();
+        // Short values need no summary
+        assert_eq!(
+            cached_summary(&snapshot, "plan.result", "the plan", SummaryDetail::Low).as_deref(),
+            Some("the plan")
+        );
+        assert_eq!(
+            cached_summary(&snapshot, "plan.result", &long, SummaryDetail::Low),
+            None
+        );
+
+        let (key, value) = summary_update("plan.result", &long, SummaryDetail::Low, "Words.");
+        assert_eq!(key, "summary.plan.result.low");
+        snapshot.insert(key, value);
+        assert_eq!(
+            cached_summary(&snapshot, "plan.result", &long, SummaryDetail::Low).as_deref(),
+            Some("Words.")
+        );
+        // A changed value or another level needs a new summary
+        let changed = format!("{long} more");
+        assert_eq!(
+            cached_summary(&snapshot, "plan.result", &changed, SummaryDetail::Low),
+            None
+        );
+        assert_eq!(
+            cached_summary(&snapshot, "plan.result", &long, SummaryDetail::Medium),
+            None
+        );
+
+        let entries = vec![("plan.result".to_string(), None)];
+        let summaries = HashMap::from([("plan.result".to_string(), "Words.".to_string())]);
+        let mode = FidelityMode::Summary {
+            detail: SummaryDetail::Low,
+        };
+        assert_eq!(
+            prior_context_lines(&entries, &mode, &summaries),
+            vec!["- plan.result: Words."]
+        );
+    }
+
+    #[tokio::test]
+    async fn prior_results_are_summarized_once() {
+        let long = "word ".repeat(500);
+        let inputs = || {
+            vec![
+                ("plan.result".to_string(), long.clone()),
+                ("test.output".to_string(), "ok".to_string()),
+            ]
+        };
+        let write = |request: String| async move {
+            assert!(request.contains("`plan.result`"));
+            Ok(("Words.".to_string(), 0.25))
+        };
+
+        let mut snapshot = HashMap::new();
+        let prior = summarize_prior_results(inputs(), &snapshot, SummaryDetail::Low, write)
+            .await
+            .unwrap();
+        assert_eq!(prior.summaries["plan.result"], "Words.");
+        assert_eq!(prior.summaries["test.output"], "ok");
+        assert_eq!(prior.spent, vec![0.25]);
+        assert_eq!(prior.updates.len(), 1);
+
+        // Once cached, nothing is written again
+        snapshot.extend(prior.updates);
+        let prior = summarize_prior_results(inputs(), &snapshot, SummaryDetail::Low, write)
+            .await
+            .unwrap();
+        assert_eq!(prior.summaries["plan.result"], "Words.");
+        assert!(prior.spent.is_empty());
+        assert!(prior.updates.is_empty());
+    }
+}
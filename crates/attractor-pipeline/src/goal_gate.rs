@@ -15,6 +15,10 @@ pub struct GoalGateResult {
 
 /// Check whether all visited goal gate nodes have succeeded.
 /// Only checks nodes that appear in `node_outcomes` (visited nodes).
+///
+/// A cluster with `goal_gate=true` gates all of its members: it is
+/// unsatisfied if any visited member failed, and retries from the cluster's
+/// `retry_target` or else its entry node.
 pub fn check_goal_gates(
     graph: &PipelineGraph,
     node_outcomes: &HashMap<String, Outcome>,
@@ -36,6 +40,30 @@ pub fn check_goal_gates(
             }
         }
     }
+    for cluster in graph.clusters().iter().filter(|c| c.goal_gate) {
+        let failed = cluster.nodes.iter().find(|id| {
+            node_outcomes.get(*id).is_some_and(|outcome| {
+                !matches!(
+                    outcome.status,
+                    StageStatus::Success | StageStatus::PartialSuccess
+                )
+            })
+        });
+        if let Some(node_id) = failed {
+            let retry = cluster
+                .retry_target
+                .as_deref()
+                .or(cluster.fallback_retry_target.as_deref())
+                .and_then(|target| graph.resolve_target(target))
+                .or_else(|| graph.cluster_entry(cluster))
+                .map(|node| node.id.clone());
+            return GoalGateResult {
+                all_satisfied: false,
+                failed_node_id: Some(node_id.clone()),
+                retry_target: retry,
+            };
+        }
+    }
     GoalGateResult {
         all_satisfied: true,
         failed_node_id: None,
@@ -48,8 +76,11 @@ pub fn check_goal_gates(
 /// 2. Node `fallback_retry_target`
 /// 3. Graph `retry_target` attribute
 /// 4. Graph `fallback_retry_target` attribute
+///
+/// A target naming a cluster resolves to the cluster's entry node.
 fn resolve_retry_target(node: &PipelineNode, graph: &PipelineGraph) -> Option<String> {
-    node.retry_target
+    let target = node
+        .retry_target
         .clone()
         .or_else(|| node.fallback_retry_target.clone())
         .or_else(|| {
@@ -66,7 +97,11 @@ fn resolve_retry_target(node: &PipelineNode, graph: &PipelineGraph) -> Option<St
                     AttributeValue::String(s) => Some(s.clone()),
                     _ => None,
                 })
-        })
+        })?;
+    match graph.resolve_target(&target) {
+        Some(node) => Some(node.id.clone()),
+        None => Some(target),
+    }
 }
 
 /// Enforce goal gates: if unsatisfied and no retry target, return error.
@@ -273,4 +308,34 @@ mod tests {
         let result = check_goal_gates(&pg, &outcomes);
         assert!(result.all_satisfied);
     }
+
+    #[test]
+    fn cluster_goal_gate_retries_from_cluster_entry() {
+        let pg = parse_and_build(
+            r#"digraph G {
+            start -> write
+            subgraph cluster_impl {
+                goal_gate = true
+                write -> test
+            }
+            test -> review -> done
+            review [goal_gate=true, retry_target="cluster_impl"]
+        }"#,
+        );
+
+        let mut outcomes = HashMap::new();
+        outcomes.insert("write".into(), make_outcome(StageStatus::Success));
+        outcomes.insert("test".into(), make_outcome(StageStatus::Fail));
+        let result = check_goal_gates(&pg, &outcomes);
+        assert!(!result.all_satisfied);
+        assert_eq!(result.failed_node_id.as_deref(), Some("test"));
+        assert_eq!(result.retry_target.as_deref(), Some("write"));
+
+        // A node's retry_target may name a cluster too
+        outcomes.insert("test".into(), make_outcome(StageStatus::Success));
+        outcomes.insert("review".into(), make_outcome(StageStatus::Fail));
+        let result = check_goal_gates(&pg, &outcomes);
+        assert_eq!(result.failed_node_id.as_deref(), Some("review"));
+        assert_eq!(result.retry_target.as_deref(), Some("write"));
+    }
 }
@@ -1,18 +1,72 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
-use attractor_dot::{AttributeValue, DotGraph, EdgeDef, NodeDef};
+use attractor_dot::{AttributeValue, DotGraph, EdgeDef, NodeDef, SubgraphDef};
+use attractor_types::AttractorError;
+
+use crate::condition::{parse_condition, ConditionExpr};
+use crate::transforms::apply_transforms_with_overrides;
 
 #[derive(Debug, Clone)]
 pub struct PipelineGraph {
     pub name: String,
     pub goal: String,
     pub attrs: HashMap<String, AttributeValue>,
+    /// The .dot file the graph was loaded from, if any. Relative
+    /// `sub_pipeline` paths are resolved against its directory.
+    pub source: Option<PathBuf>,
     nodes: HashMap<String, PipelineNode>,
     edges: Vec<PipelineEdge>,
     /// Maps node_id to a range (start, count) into the sorted `edges` vec.
     /// Edges are sorted by `from` so each node's outgoing edges are contiguous.
     adjacency: HashMap<String, (usize, usize)>,
+    /// Clusters named by a node's `subgraph` attribute, kept out of this
+    /// graph and built as pipelines of their own.
+    subpipelines: HashMap<String, PipelineGraph>,
+    /// Named subgraphs whose nodes are part of this graph, in file order.
+    clusters: Vec<PipelineCluster>,
+}
+
+/// Cluster attributes that apply to every member node, unless the node sets
+/// them itself.
+pub const SCOPED_ATTRS: &[&str] = &[
+    "retry_target",
+    "fallback_retry_target",
+    "timeout",
+    "max_budget_usd",
+    "fidelity",
+    "thread_id",
+];
+
+/// A named subgraph kept as a scope over its member nodes.
+#[derive(Debug, Clone)]
+pub struct PipelineCluster {
+    pub name: String,
+    pub label: Option<String>,
+    /// The whole cluster must succeed before the pipeline may exit.
+    pub goal_gate: bool,
+    pub retry_target: Option<String>,
+    pub fallback_retry_target: Option<String>,
+    /// The cluster's own attributes (`key=value` statements in its body).
+    pub attrs: HashMap<String, AttributeValue>,
+    /// Member node IDs, sorted.
+    pub nodes: Vec<String>,
+}
+
+impl PipelineCluster {
+    pub fn contains(&self, node_id: &str) -> bool {
+        self.nodes
+            .binary_search_by(|n| n.as_str().cmp(node_id))
+            .is_ok()
+    }
+
+    /// The cluster's [`SCOPED_ATTRS`] that it sets.
+    pub fn scoped_attrs(&self) -> impl Iterator<Item = (&str, &AttributeValue)> {
+        SCOPED_ATTRS
+            .iter()
+            .filter_map(|key| Some((*key, self.attrs.get(*key)?)))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -23,6 +77,8 @@ pub struct PipelineNode {
     pub node_type: Option<String>,
     pub prompt: Option<String>,
     pub max_retries: usize,
+    pub retry_backoff: Option<String>,
+    pub retry_delay: Option<Duration>,
     pub goal_gate: bool,
     pub retry_target: Option<String>,
     pub fallback_retry_target: Option<String>,
@@ -44,6 +100,9 @@ pub struct PipelineEdge {
     pub to: String,
     pub label: Option<String>,
     pub condition: Option<String>,
+    /// `condition` parsed once when the graph is built; `None` when it is
+    /// absent or invalid (validation reports invalid conditions).
+    pub condition_expr: Option<ConditionExpr>,
     pub weight: i32,
     pub fidelity: Option<String>,
     pub thread_id: Option<String>,
@@ -99,11 +158,15 @@ fn node_def_to_pipeline_node(
 
     let shape = get_string_attr(&attrs, "shape").unwrap_or_else(|| "box".to_string());
     let label = get_string_attr(&attrs, "label").unwrap_or_else(|| id.to_string());
-    let node_type = get_string_attr(&attrs, "type");
+    // `node_type` is the documented spelling; `type` is accepted as an alias.
+    let node_type =
+        get_string_attr(&attrs, "node_type").or_else(|| get_string_attr(&attrs, "type"));
     let prompt = get_string_attr(&attrs, "prompt");
     let max_retries = get_int_attr(&attrs, "max_retries")
         .map(|v| v as usize)
         .unwrap_or(0);
+    let retry_backoff = get_string_attr(&attrs, "retry_backoff");
+    let retry_delay = get_duration_attr(&attrs, "retry_delay");
     let goal_gate = get_bool_attr(&attrs, "goal_gate").unwrap_or(false);
     let retry_target = get_string_attr(&attrs, "retry_target");
     let fallback_retry_target = get_string_attr(&attrs, "fallback_retry_target");
@@ -126,6 +189,8 @@ fn node_def_to_pipeline_node(
         node_type,
         prompt,
         max_retries,
+        retry_backoff,
+        retry_delay,
         goal_gate,
         retry_target,
         fallback_retry_target,
@@ -149,11 +214,13 @@ fn edge_def_to_pipeline_edge(
     let mut attrs = edge_defaults.clone();
     attrs.extend(edge_def.attrs.iter().map(|(k, v)| (k.clone(), v.clone())));
 
+    let condition = get_string_attr(&attrs, "condition");
     PipelineEdge {
         from: edge_def.from.clone(),
         to: edge_def.to.clone(),
         label: get_string_attr(&attrs, "label"),
-        condition: get_string_attr(&attrs, "condition"),
+        condition_expr: condition.as_deref().and_then(|c| parse_condition(c).ok()),
+        condition,
         weight: get_int_attr(&attrs, "weight")
             .map(|v| v as i32)
             .unwrap_or(0),
@@ -163,28 +230,134 @@ fn edge_def_to_pipeline_edge(
     }
 }
 
+/// The scoped attributes `sg` sets that its members would otherwise get from
+/// the graph's `node [...]` block. The parser bakes default blocks into each
+/// node's attributes, so these have to be told apart from values the members
+/// (or the cluster's own `node [...]` block) set.
+fn inherited_scope(
+    sg: &SubgraphDef,
+    graph_defaults: &HashMap<String, AttributeValue>,
+) -> Vec<&'static str> {
+    SCOPED_ATTRS
+        .iter()
+        .copied()
+        .filter(|key| sg.attrs.contains_key(*key))
+        .filter(|key| {
+            graph_defaults
+                .get(*key)
+                .is_some_and(|v| sg.node_defaults.get(*key) == Some(v))
+        })
+        .collect()
+}
+
+/// Build an inline child pipeline from a cluster. It inherits the parent's
+/// graph attributes and defaults, with the cluster's own layered on top.
+fn inline_pipeline(parent: &DotGraph, sg: &SubgraphDef) -> attractor_types::Result<PipelineGraph> {
+    let mut attrs = parent.attrs.clone();
+    attrs.extend(sg.attrs.iter().map(|(k, v)| (k.clone(), v.clone())));
+    let mut node_defaults = parent.node_defaults.clone();
+    node_defaults.extend(sg.node_defaults.iter().map(|(k, v)| (k.clone(), v.clone())));
+    let mut edge_defaults = parent.edge_defaults.clone();
+    edge_defaults.extend(sg.edge_defaults.iter().map(|(k, v)| (k.clone(), v.clone())));
+
+    PipelineGraph::from_dot(DotGraph {
+        name: sg.name.clone().unwrap_or_default(),
+        attrs,
+        nodes: sg.nodes.clone(),
+        edges: sg.edges.clone(),
+        subgraphs: Vec::new(),
+        node_defaults,
+        edge_defaults,
+    })
+}
+
 impl PipelineGraph {
+    /// Read, parse and transform a .dot file, with `overrides` replacing
+    /// graph attributes of the same name (see
+    /// [`apply_transforms_with_overrides`]).
+    pub fn load(path: &Path, overrides: &HashMap<String, String>) -> attractor_types::Result<Self> {
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            AttractorError::Other(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        let mut graph = Self::from_dot(attractor_dot::parse(&source)?)?;
+        graph.set_source(path);
+        apply_transforms_with_overrides(&mut graph, overrides)?;
+        Ok(graph)
+    }
+
+    fn set_source(&mut self, path: &Path) {
+        self.source = Some(path.to_path_buf());
+        for child in self.subpipelines.values_mut() {
+            child.set_source(path);
+        }
+    }
+
     pub fn from_dot(graph: DotGraph) -> attractor_types::Result<Self> {
         let mut nodes = HashMap::new();
         let mut all_edges = Vec::new();
 
+        // Clusters a node runs as its child pipeline are not part of this graph
+        let inline: HashSet<String> = graph
+            .nodes
+            .values()
+            .chain(graph.subgraphs.iter().flat_map(|sg| sg.nodes.values()))
+            .filter_map(|n| get_string_attr(&n.attrs, "subgraph"))
+            .collect();
+        let mut subpipelines = HashMap::new();
+        for sg in &graph.subgraphs {
+            if let Some(name) = sg.name.as_ref().filter(|name| inline.contains(*name)) {
+                subpipelines.insert(name.clone(), inline_pipeline(&graph, sg)?);
+            }
+        }
+        let is_inline = |sg: &SubgraphDef| sg.name.as_ref().is_some_and(|n| inline.contains(n));
+        let mut clusters = Vec::new();
+
         // Collect top-level nodes with graph-level defaults
         for (id, node_def) in &graph.nodes {
             let pn = node_def_to_pipeline_node(id, node_def, &graph.node_defaults, None);
             nodes.insert(id.clone(), pn);
         }
 
-        // Collect subgraph nodes (with subgraph-level defaults layered on top)
-        for sg in &graph.subgraphs {
+        // Collect subgraph nodes (with the subgraph's scoped attributes and
+        // defaults layered on top)
+        for sg in graph.subgraphs.iter().filter(|sg| !is_inline(sg)) {
+            let inherited = inherited_scope(sg, &graph.node_defaults);
+            let mut sg_defaults = sg.node_defaults.clone();
+            for key in SCOPED_ATTRS {
+                if let Some(value) = sg.attrs.get(*key) {
+                    if inherited.contains(key) || !sg_defaults.contains_key(*key) {
+                        sg_defaults.insert(key.to_string(), value.clone());
+                    }
+                }
+            }
             for (id, node_def) in &sg.nodes {
+                let mut node_def = node_def.clone();
+                for key in &inherited {
+                    if node_def.attrs.get(*key) == graph.node_defaults.get(*key) {
+                        node_def.attrs.remove(*key);
+                    }
+                }
                 let pn = node_def_to_pipeline_node(
                     id,
-                    node_def,
+                    &node_def,
                     &graph.node_defaults,
-                    Some(&sg.node_defaults),
+                    Some(&sg_defaults),
                 );
                 nodes.insert(id.clone(), pn);
             }
+            if let Some(name) = &sg.name {
+                let mut members: Vec<String> = sg.nodes.keys().cloned().collect();
+                members.sort();
+                clusters.push(PipelineCluster {
+                    name: name.clone(),
+                    label: get_string_attr(&sg.attrs, "label"),
+                    goal_gate: get_bool_attr(&sg.attrs, "goal_gate").unwrap_or(false),
+                    retry_target: get_string_attr(&sg.attrs, "retry_target"),
+                    fallback_retry_target: get_string_attr(&sg.attrs, "fallback_retry_target"),
+                    attrs: sg.attrs.clone(),
+                    nodes: members,
+                });
+            }
         }
 
         // Collect top-level edges
@@ -193,7 +366,7 @@ impl PipelineGraph {
         }
 
         // Collect subgraph edges
-        for sg in &graph.subgraphs {
+        for sg in graph.subgraphs.iter().filter(|sg| !is_inline(sg)) {
             let mut sg_edge_defaults = graph.edge_defaults.clone();
             sg_edge_defaults.extend(sg.edge_defaults.iter().map(|(k, v)| (k.clone(), v.clone())));
             for edge_def in &sg.edges {
@@ -222,9 +395,12 @@ impl PipelineGraph {
             name: graph.name,
             goal,
             attrs: graph.attrs,
+            source: None,
             nodes,
             edges: all_edges,
             adjacency,
+            subpipelines,
+            clusters,
         })
     }
 
@@ -263,6 +439,52 @@ impl PipelineGraph {
     pub fn all_edges(&self) -> &[PipelineEdge] {
         &self.edges
     }
+
+    /// The inline child pipeline built from the cluster called `name`.
+    pub fn subpipeline(&self, name: &str) -> Option<&PipelineGraph> {
+        self.subpipelines.get(name)
+    }
+
+    pub fn subpipelines_mut(&mut self) -> impl Iterator<Item = &mut PipelineGraph> {
+        self.subpipelines.values_mut()
+    }
+
+    pub fn clusters(&self) -> &[PipelineCluster] {
+        &self.clusters
+    }
+
+    pub fn cluster(&self, name: &str) -> Option<&PipelineCluster> {
+        self.clusters.iter().find(|c| c.name == name)
+    }
+
+    /// The cluster containing `node_id`. A node declared in several clusters
+    /// belongs to the last, as its attributes do.
+    pub fn cluster_of(&self, node_id: &str) -> Option<&PipelineCluster> {
+        self.clusters.iter().rev().find(|c| c.contains(node_id))
+    }
+
+    /// Where a run enters `cluster`: the first member (by ID) with no
+    /// incoming edge from another member.
+    pub fn cluster_entry(&self, cluster: &PipelineCluster) -> Option<&PipelineNode> {
+        let entered_internally = |id: &str| {
+            self.edges
+                .iter()
+                .any(|e| e.to == id && e.from != id && cluster.contains(&e.from))
+        };
+        cluster
+            .nodes
+            .iter()
+            .find(|id| !entered_internally(id))
+            .or(cluster.nodes.first())
+            .and_then(|id| self.node(id))
+    }
+
+    /// Resolve a jump target such as a `retry_target`: a node ID, or a
+    /// cluster name standing for the cluster's entry node.
+    pub fn resolve_target(&self, target: &str) -> Option<&PipelineNode> {
+        self.node(target)
+            .or_else(|| self.cluster_entry(self.cluster(target)?))
+    }
 }
 
 #[cfg(test)]
@@ -364,12 +586,14 @@ mod tests {
     fn typed_attribute_extraction() {
         let pg = parse_and_build(
             r#"digraph G {
-            step [max_retries=3, goal_gate=true, timeout=30s, allow_partial=false]
+            step [max_retries=3, retry_backoff="fixed", retry_delay=2s, goal_gate=true, timeout=30s, allow_partial=false]
         }"#,
         );
 
         let node = pg.node("step").unwrap();
         assert_eq!(node.max_retries, 3);
+        assert_eq!(node.retry_backoff.as_deref(), Some("fixed"));
+        assert_eq!(node.retry_delay, Some(Duration::from_secs(2)));
         assert!(node.goal_gate);
         assert_eq!(node.timeout, Some(Duration::from_secs(30)));
         assert!(!node.allow_partial);
@@ -401,6 +625,88 @@ mod tests {
         assert_eq!(pg.all_edges().len(), 3);
     }
 
+    #[test]
+    fn clusters_named_by_subgraph_attr_become_child_pipelines() {
+        let pg = parse_and_build(
+            r#"digraph G {
+            goal = "Ship it"
+            node [llm_model="m1"]
+            start [shape="Mdiamond"]
+            mgr [shape="house", subgraph="cluster_loop"]
+            done [shape="Msquare"]
+            start -> mgr -> done
+            subgraph cluster_loop {
+                node [shape="box"]
+                loop_start [shape="Mdiamond"]
+                work
+                loop_done [shape="Msquare"]
+                loop_start -> work -> loop_done
+            }
+        }"#,
+        );
+
+        assert!(pg.node("work").is_none());
+        assert_eq!(pg.all_edges().len(), 2);
+        let child = pg.subpipeline("cluster_loop").unwrap();
+        assert_eq!(child.name, "cluster_loop");
+        assert_eq!(child.goal, "Ship it");
+        assert_eq!(child.start_node().unwrap().id, "loop_start");
+        let work = child.node("work").unwrap();
+        assert_eq!(work.shape, "box");
+        assert_eq!(work.llm_model.as_deref(), Some("m1"));
+    }
+
+    #[test]
+    fn clusters_scope_attributes_over_their_members() {
+        let pg = parse_and_build(
+            r#"digraph G {
+            node [timeout="300s"]
+            start [shape="Mdiamond"]
+            done [shape="Msquare"]
+            start -> write
+            subgraph cluster_impl {
+                label = "Implementation"
+                goal_gate = true
+                retry_target = "write"
+                timeout = "900s"
+                fidelity = "compact"
+                thread_id = "impl"
+                write -> test
+                test [timeout="60s"]
+            }
+            test -> done
+        }"#,
+        );
+
+        let cluster = pg.cluster("cluster_impl").unwrap();
+        assert_eq!(cluster.label.as_deref(), Some("Implementation"));
+        assert!(cluster.goal_gate);
+        assert_eq!(cluster.nodes, vec!["test", "write"]);
+        assert_eq!(pg.cluster_of("test").unwrap().name, "cluster_impl");
+        assert!(pg.cluster_of("start").is_none());
+
+        let write = pg.node("write").unwrap();
+        assert_eq!(write.timeout, Some(Duration::from_secs(900)));
+        assert_eq!(write.fidelity.as_deref(), Some("compact"));
+        assert_eq!(write.thread_id.as_deref(), Some("impl"));
+        assert_eq!(write.retry_target.as_deref(), Some("write"));
+        // The goal gate belongs to the cluster, not to each member
+        assert!(!write.goal_gate);
+        // Members keep attributes they set themselves
+        assert_eq!(
+            pg.node("test").unwrap().timeout,
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            pg.node("start").unwrap().timeout,
+            Some(Duration::from_secs(300))
+        );
+
+        assert_eq!(pg.cluster_entry(cluster).unwrap().id, "write");
+        assert_eq!(pg.resolve_target("cluster_impl").unwrap().id, "write");
+        assert!(pg.resolve_target("cluster_nope").is_none());
+    }
+
     #[test]
     fn goal_extracted_from_graph_attrs() {
         let pg = parse_and_build(
@@ -428,6 +734,27 @@ mod tests {
         assert!(edges[0].loop_restart);
     }
 
+    #[test]
+    fn edge_conditions_are_parsed_once() {
+        let pg = parse_and_build(
+            r#"digraph G {
+            A -> B [condition="review.result matches '^LGTM'"]
+            A -> C [condition="outcome matches '('"]
+            A -> D
+        }"#,
+        );
+
+        let edges = pg.outgoing_edges("A");
+        assert_eq!(
+            edges[0].condition_expr,
+            Some(parse_condition("review.result matches '^LGTM'").unwrap())
+        );
+        // Invalid and missing conditions have no expression
+        assert!(edges[1].condition.is_some());
+        assert!(edges[1].condition_expr.is_none());
+        assert!(edges[2].condition_expr.is_none());
+    }
+
     #[test]
     fn default_shape_is_box() {
         let pg = parse_and_build(
@@ -438,4 +765,17 @@ mod tests {
 
         assert_eq!(pg.node("plain_node").unwrap().shape, "box");
     }
+
+    #[test]
+    fn node_type_accepts_both_spellings() {
+        let pg = parse_and_build(
+            r#"digraph G {
+            a [node_type="agent"]
+            b [type="tool"]
+        }"#,
+        );
+
+        assert_eq!(pg.node("a").unwrap().node_type.as_deref(), Some("agent"));
+        assert_eq!(pg.node("b").unwrap().node_type.as_deref(), Some("tool"));
+    }
 }
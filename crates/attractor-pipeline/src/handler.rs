@@ -192,9 +192,11 @@ pub fn default_registry() -> HandlerRegistry {
     reg.register(ConditionalHandler);
     reg.register(crate::handlers::ToolHandler);
     reg.register(crate::handlers::CodergenHandler);
+    reg.register(crate::handlers::AgentHandler::from_env());
     reg.register(crate::handlers::ParallelHandler);
     reg.register(crate::handlers::FanInHandler);
     reg.register(crate::handlers::ManagerLoopHandler);
+    reg.register(crate::handlers::SubPipelineHandler);
     reg
 }
 
@@ -230,6 +232,8 @@ mod tests {
             node_type: node_type.map(String::from),
             prompt: None,
             max_retries: 0,
+            retry_backoff: None,
+            retry_delay: None,
             goal_gate: false,
             retry_target: None,
             fallback_retry_target: None,
@@ -333,6 +337,7 @@ mod tests {
         assert!(reg.has("conditional"));
         assert!(reg.has("tool"));
         assert!(reg.has("codergen"));
+        assert!(reg.has("agent"));
         assert!(reg.has("parallel"));
         assert!(reg.has("parallel.fan_in"));
         assert!(reg.has("stack.manager_loop"));
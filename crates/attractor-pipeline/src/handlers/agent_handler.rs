@@ -0,0 +1,633 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use attractor_agent::{AgentSession, FidelityMode, SessionConfig, Turn};
+use attractor_dot::AttributeValue;
+use attractor_llm::{LlmClient, Message, Request, Usage};
+use attractor_tools::ToolProfile;
+use attractor_types::{AttractorError, Context, Outcome, Result, StageStatus};
+
+use crate::events::{EventEmitter, PipelineEvent};
+use crate::fidelity::{node_fidelity, summarize_prior_results};
+use crate::graph::{PipelineGraph, PipelineNode};
+use crate::handler::NodeHandler;
+use crate::handlers::codergen_handler::{
+    build_full_prompt, preferred_label_for, prompt_snapshot, resolve_model, summary_inputs,
+};
+use crate::handlers::sandbox::NodeEnvironment;
+use crate::threads::Thread;
+
+/// Builds the `LlmClient` for each agent node run.
+pub type LlmClientFactory = Arc<dyn Fn() -> Result<LlmClient> + Send + Sync>;
+
+// ---------------------------------------------------------------------------
+// AgentHandler — in-process agent loop (node_type="agent")
+//
+// Runs the node's prompt through an `AgentSession` talking to the provider
+// APIs directly, instead of shelling out to a vendor CLI like
+// `CodergenHandler`. Requires provider API keys rather than installed CLIs.
+//
+// Supported node attributes:
+//   - prompt (required): The task prompt given to the agent
+//   - llm_provider: "anthropic"/"claude", "openai"/"codex", "google"/"gemini"
+//     (default: resolved from the model)
+//   - llm_model: Override the model (falls back to the graph `model` attribute)
+//   - tool_profile: "anthropic", "openai", "gemini" or "none"
+//     (default: the provider's profile)
+//   - max_tool_rounds: Maximum tool-use rounds for the prompt (default: 200)
+//   - system_prompt: Override the agent's system prompt
+//   - timeout: Duration before the agent run is abandoned (default: 10m)
+//   - sandbox: "none", "bubblewrap" or "overlay" (see handlers::sandbox)
+//   - thread_id: Continue the conversation of earlier agent nodes in the
+//     same thread (see crate::threads)
+//   - fidelity: How much prior context the prompt and a continued
+//     conversation carry (see crate::fidelity)
+//
+// The pipeline context key "workdir" sets the tools' working directory.
+// ---------------------------------------------------------------------------
+
+pub struct AgentHandler {
+    client_factory: LlmClientFactory,
+    events: Option<EventEmitter>,
+}
+
+impl AgentHandler {
+    /// Create a handler that builds clients with `factory`.
+    pub fn new(factory: LlmClientFactory) -> Self {
+        Self {
+            client_factory: factory,
+            events: None,
+        }
+    }
+
+    /// Create a handler that registers every provider with an API key in the environment.
+    pub fn from_env() -> Self {
+        Self::new(Arc::new(LlmClient::from_env))
+    }
+
+    /// Stream the agent's replies as [`PipelineEvent::TextDelta`]s on `emitter`.
+    pub fn with_event_emitter(mut self, emitter: EventEmitter) -> Self {
+        self.events = Some(emitter);
+        self
+    }
+}
+
+impl Default for AgentHandler {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// The provider agent nodes record their threads under.
+const THREAD_PROVIDER: &str = "agent";
+
+/// Map an `llm_provider` attribute onto the registered `LlmClient` provider name.
+fn normalize_provider(name: &str) -> String {
+    match name.to_ascii_lowercase().as_str() {
+        "claude" | "anthropic" => "anthropic".into(),
+        "codex" | "openai" => "openai".into(),
+        "gemini" | "google" => "google".into(),
+        other => other.to_string(),
+    }
+}
+
+fn tool_profile(name: &str, node_id: &str) -> Result<Option<ToolProfile>> {
+    match name.to_ascii_lowercase().as_str() {
+        "anthropic" | "claude" => Ok(Some(ToolProfile::anthropic())),
+        "openai" | "codex" => Ok(Some(ToolProfile::openai())),
+        "gemini" | "google" => Ok(Some(ToolProfile::gemini())),
+        "none" => Ok(None),
+        other => Err(AttractorError::HandlerError {
+            handler: "agent".into(),
+            node: node_id.into(),
+            message: format!("Unknown tool_profile '{}'", other),
+        }),
+    }
+}
+
+fn string_attr<'a>(node: &'a PipelineNode, key: &str) -> Option<&'a str> {
+    match node.raw_attrs.get(key) {
+        Some(AttributeValue::String(s)) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl NodeHandler for AgentHandler {
+    fn handler_type(&self) -> &str {
+        "agent"
+    }
+
+    async fn execute(
+        &self,
+        node: &PipelineNode,
+        context: &Context,
+        graph: &PipelineGraph,
+    ) -> Result<Outcome> {
+        let defaults = SessionConfig::default();
+        let model = resolve_model(node, graph)
+            .map(String::from)
+            .unwrap_or(defaults.model.clone());
+
+        tracing::info!(node = %node.id, label = %node.label, model = %model, "Executing agent handler");
+
+        // Check if dry_run is set in context
+        let dry_run = context
+            .get("dry_run")
+            .await
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        if dry_run {
+            tracing::info!(node = %node.id, "Dry run — skipping agent session");
+            let prompt = node.prompt.as_deref().unwrap_or("No prompt specified");
+            let mut updates = HashMap::new();
+            updates.insert(
+                format!("{}.result", node.id),
+                serde_json::Value::String(format!("Dry run — prompt not sent: {}", prompt)),
+            );
+            updates.insert(
+                format!("{}.completed", node.id),
+                serde_json::Value::Bool(true),
+            );
+            updates.insert(
+                format!("{}.dry_run", node.id),
+                serde_json::Value::Bool(true),
+            );
+            return Ok(Outcome {
+                status: StageStatus::Success,
+                preferred_label: None,
+                suggested_next_ids: vec![],
+                context_updates: updates,
+                notes: format!("Dry run — agent not invoked for: {}", node.label),
+                failure_reason: None,
+            });
+        }
+
+        let client = (self.client_factory)()?;
+
+        // Provider: explicit attribute, else whatever the catalog says about the model
+        let provider = node
+            .llm_provider
+            .as_deref()
+            .map(normalize_provider)
+            .or_else(|| {
+                client
+                    .model_catalog()
+                    .provider_for_model(&model)
+                    .map(String::from)
+            });
+
+        let profile = match string_attr(node, "tool_profile") {
+            Some(name) => tool_profile(name, &node.id)?,
+            None => tool_profile(provider.as_deref().unwrap_or("anthropic"), &node.id)
+                .unwrap_or_else(|_| Some(ToolProfile::anthropic())),
+        };
+        let registry = profile.map(|p| p.build_registry()).unwrap_or_default();
+
+        let max_tool_rounds = match node.raw_attrs.get("max_tool_rounds") {
+            Some(AttributeValue::Integer(n)) if *n > 0 => *n as usize,
+            Some(AttributeValue::String(s)) => s.parse().unwrap_or(defaults.max_tool_rounds),
+            _ => defaults.max_tool_rounds,
+        };
+
+        let snapshot = prompt_snapshot(node, context).await;
+        let env = NodeEnvironment::for_node(node, graph, context, "agent").await?;
+        let fidelity = node_fidelity(node, graph, &snapshot);
+
+        let config = SessionConfig {
+            model: model.clone(),
+            provider: provider.clone(),
+            system_prompt: string_attr(node, "system_prompt")
+                .map(String::from)
+                .unwrap_or(defaults.system_prompt.clone()),
+            max_tool_rounds,
+            fidelity,
+            ..defaults
+        };
+
+        // Carry on the thread's conversation, if it has one
+        let thread = Thread::for_node(node, &snapshot).await;
+        let history: Option<Vec<Turn>> = thread
+            .as_ref()
+            .and_then(|t| t.history_for(THREAD_PROVIDER))
+            .and_then(|json| match serde_json::from_str(json) {
+                Ok(history) => Some(history),
+                Err(e) => {
+                    tracing::warn!(node = %node.id, "Ignoring unreadable thread history: {}", e);
+                    None
+                }
+            });
+        let resumed = thread.as_ref().filter(|_| history.is_some());
+
+        // Under summary fidelity, condense long prior results first
+        let mut summaries = HashMap::new();
+        let mut summary_usage = Usage::default();
+        let mut summary_updates = HashMap::new();
+        if let FidelityMode::Summary { detail } = fidelity {
+            let inputs = summary_inputs(node, &snapshot, resumed).await;
+            let (client, config) = (&client, &config);
+            let prior = summarize_prior_results(inputs, &snapshot, detail, |request| async move {
+                let response = client
+                    .complete(&Request {
+                        model: config.model.clone(),
+                        messages: vec![Message::user(request)],
+                        tools: vec![],
+                        tool_choice: None,
+                        max_tokens: None,
+                        temperature: None,
+                        stop_sequences: vec![],
+                        reasoning_effort: None,
+                        provider: config.provider.clone(),
+                        provider_options: None,
+                    })
+                    .await?;
+                Ok((response.text, response.usage))
+            })
+            .await?;
+            for usage in &prior.spent {
+                summary_usage.input_tokens += usage.input_tokens;
+                summary_usage.output_tokens += usage.output_tokens;
+                summary_usage.total_tokens += usage.total_tokens;
+            }
+            summaries = prior.summaries;
+            summary_updates = prior.updates;
+        }
+
+        let full_prompt = build_full_prompt(node, graph, &snapshot, resumed, &summaries);
+        let mut session = AgentSession::new(client, registry, env.boxed(), config);
+        if let Some(history) = history {
+            session = session.with_history(history);
+        }
+        if let Some(ref events) = self.events {
+            let (events, node_id) = (events.clone(), node.id.clone());
+            session = session.with_text_delta_handler(Arc::new(move |text| {
+                events.emit(PipelineEvent::TextDelta {
+                    node_id: node_id.clone(),
+                    text: text.to_string(),
+                })
+            }));
+        }
+
+        let timeout_dur = node.timeout.unwrap_or(std::time::Duration::from_secs(600));
+        let text = tokio::time::timeout(timeout_dur, session.process_input(&full_prompt))
+            .await
+            .map_err(|_| AttractorError::CommandTimeout {
+                timeout_ms: timeout_dur.as_millis() as u64,
+            })??;
+
+        let mut usage = session.usage().clone();
+        usage.input_tokens += summary_usage.input_tokens;
+        usage.output_tokens += summary_usage.output_tokens;
+        usage.total_tokens += summary_usage.total_tokens;
+        let cost = attractor_llm::ModelCatalog::new().estimate_cost(&model, &usage);
+
+        tracing::info!(
+            node = %node.id,
+            input_tokens = usage.input_tokens,
+            output_tokens = usage.output_tokens,
+            cost_usd = ?cost,
+            "Agent session completed"
+        );
+
+        let preferred_label = preferred_label_for(node, graph, &text);
+
+        let mut updates = HashMap::new();
+        updates.insert(
+            format!("{}.completed", node.id),
+            serde_json::Value::Bool(true),
+        );
+        updates.insert(
+            format!("{}.result", node.id),
+            serde_json::Value::String(text.clone()),
+        );
+        updates.insert(
+            format!("{}.model", node.id),
+            serde_json::Value::String(model),
+        );
+        if let Some(ref p) = provider {
+            updates.insert(
+                format!("{}.provider", node.id),
+                serde_json::Value::String(p.clone()),
+            );
+        }
+        updates.insert(
+            format!("{}.input_tokens", node.id),
+            serde_json::json!(usage.input_tokens),
+        );
+        updates.insert(
+            format!("{}.output_tokens", node.id),
+            serde_json::json!(usage.output_tokens),
+        );
+        updates.insert(
+            format!("{}.total_tokens", node.id),
+            serde_json::json!(usage.total_tokens),
+        );
+        if let Some(cost) = cost {
+            updates.insert(format!("{}.cost_usd", node.id), serde_json::json!(cost));
+        }
+        if let Some(ref lbl) = preferred_label {
+            updates.insert(
+                format!("{}.label", node.id),
+                serde_json::Value::String(lbl.clone()),
+            );
+        }
+        updates.extend(summary_updates);
+        if let Some(ref thread) = thread {
+            let history = serde_json::to_string(session.history())?;
+            updates.extend(thread.updates(&node.id, THREAD_PROVIDER, None, Some(history)));
+        }
+        env.finish(node, true, &mut updates).await?;
+
+        Ok(Outcome {
+            status: StageStatus::Success,
+            preferred_label,
+            suggested_next_ids: vec![],
+            context_updates: updates,
+            notes: text,
+            failure_reason: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tests::{make_minimal_graph, make_node};
+    use attractor_llm::{FinishReason, ProviderAdapter, Response, StreamEvent};
+    use std::pin::Pin;
+    use std::sync::Mutex;
+
+    /// Replies with fixed text and records the requests it receives.
+    struct FixedProvider {
+        text: String,
+        requests: Arc<Mutex<Vec<Request>>>,
+    }
+
+    #[async_trait]
+    impl ProviderAdapter for FixedProvider {
+        async fn complete(&self, request: &Request) -> Result<Response> {
+            self.requests.lock().unwrap().push(request.clone());
+            Ok(Response {
+                id: "resp-1".into(),
+                text: self.text.clone(),
+                tool_calls: vec![],
+                reasoning: None,
+                usage: Usage {
+                    input_tokens: 1_000,
+                    output_tokens: 200,
+                    total_tokens: 1_200,
+                    ..Usage::default()
+                },
+                model: request.model.clone(),
+                finish_reason: FinishReason::EndTurn,
+            })
+        }
+        fn stream(
+            &self,
+            request: &Request,
+        ) -> Pin<Box<dyn futures::Stream<Item = StreamEvent> + Send + '_>> {
+            self.requests.lock().unwrap().push(request.clone());
+            let mut events = vec![StreamEvent::MessageStart {
+                id: "resp-1".into(),
+                model: request.model.clone(),
+            }];
+            events.extend(
+                self.text
+                    .split_inclusive(' ')
+                    .map(|word| StreamEvent::ContentDelta { text: word.into() }),
+            );
+            events.push(StreamEvent::MessageEnd {
+                usage: Usage {
+                    input_tokens: 1_000,
+                    output_tokens: 200,
+                    total_tokens: 1_200,
+                    ..Usage::default()
+                },
+                finish_reason: FinishReason::EndTurn,
+            });
+            Box::pin(futures::stream::iter(events))
+        }
+        fn name(&self) -> &str {
+            "anthropic"
+        }
+        fn default_model(&self) -> &str {
+            "mock-model"
+        }
+        fn supports_tools(&self) -> bool {
+            true
+        }
+        fn supports_streaming(&self) -> bool {
+            false
+        }
+        fn supports_reasoning(&self) -> bool {
+            false
+        }
+        fn context_window_size(&self) -> usize {
+            200_000
+        }
+    }
+
+    fn fixed_handler(text: &str) -> (AgentHandler, Arc<Mutex<Vec<Request>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let text = text.to_string();
+        let recorded = requests.clone();
+        let handler = AgentHandler::new(Arc::new(move || {
+            let mut client = LlmClient::new();
+            client.register_provider(FixedProvider {
+                text: text.clone(),
+                requests: recorded.clone(),
+            });
+            Ok(client)
+        }));
+        (handler, requests)
+    }
+
+    #[tokio::test]
+    async fn agent_reports_result_tokens_and_cost() {
+        let (handler, requests) = fixed_handler("All done");
+        let mut node = make_node("impl", "box", Some("Write the code"), HashMap::new());
+        node.llm_model = Some("claude-sonnet-4-5-20250929".into());
+        node.llm_provider = Some("claude".into());
+        let ctx = Context::default();
+        let graph = make_minimal_graph();
+
+        let outcome = handler.execute(&node, &ctx, &graph).await.unwrap();
+
+        assert_eq!(outcome.status, StageStatus::Success);
+        let updates = &outcome.context_updates;
+        assert_eq!(updates["impl.result"], serde_json::json!("All done"));
+        assert_eq!(updates["impl.provider"], serde_json::json!("anthropic"));
+        assert_eq!(updates["impl.input_tokens"], serde_json::json!(1_000));
+        assert_eq!(updates["impl.output_tokens"], serde_json::json!(200));
+        let cost = updates["impl.cost_usd"].as_f64().unwrap();
+        assert!((cost - 0.006).abs() < 1e-9);
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].model, "claude-sonnet-4-5-20250929");
+        assert_eq!(requests[0].provider.as_deref(), Some("anthropic"));
+        assert!(!requests[0].tools.is_empty());
+    }
+
+    #[tokio::test]
+    async fn agent_streams_replies_as_text_delta_events() {
+        let (handler, _) = fixed_handler("All done here");
+        let emitter = EventEmitter::new(16);
+        let mut rx = emitter.subscribe();
+        let handler = handler.with_event_emitter(emitter);
+        let node = make_node("impl", "box", Some("Write the code"), HashMap::new());
+
+        let outcome = handler
+            .execute(&node, &Context::default(), &make_minimal_graph())
+            .await
+            .unwrap();
+        assert_eq!(
+            outcome.context_updates["impl.result"],
+            serde_json::json!("All done here")
+        );
+
+        let mut streamed = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            let PipelineEvent::TextDelta { node_id, text } = event else {
+                panic!("unexpected event: {:?}", event);
+            };
+            assert_eq!(node_id, "impl");
+            streamed.push(text);
+        }
+        assert_eq!(streamed, vec!["All ", "done ", "here"]);
+    }
+
+    #[tokio::test]
+    async fn nodes_in_a_thread_continue_one_conversation() {
+        let (handler, requests) = fixed_handler("Done");
+        let ctx = Context::default();
+        let graph = make_minimal_graph();
+        let mut implement = make_node("implement", "box", Some("Write it"), HashMap::new());
+        implement.thread_id = Some("impl".into());
+        let mut fix = make_node("fix", "box", Some("Fix it"), HashMap::new());
+        fix.thread_id = Some("impl".into());
+
+        let outcome = handler.execute(&implement, &ctx, &graph).await.unwrap();
+        assert_eq!(
+            outcome.context_updates["thread.impl.nodes"],
+            serde_json::json!(["implement"])
+        );
+        ctx.apply_updates(outcome.context_updates).await;
+        let outcome = handler.execute(&fix, &ctx, &graph).await.unwrap();
+        assert_eq!(
+            outcome.context_updates["thread.impl.nodes"],
+            serde_json::json!(["implement", "fix"])
+        );
+
+        let requests = requests.lock().unwrap();
+        let texts: Vec<&str> = requests[1]
+            .messages
+            .iter()
+            .flat_map(|m| &m.content)
+            .filter_map(|part| match part {
+                attractor_llm::ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect();
+        // The earlier exchange is replayed; its result is not repeated
+        assert!(texts.iter().any(|t| t.contains("Write it")));
+        assert!(texts.contains(&"Done"));
+        assert!(!texts.iter().any(|t| t.contains("implement.result")));
+    }
+
+    #[tokio::test]
+    async fn summary_fidelity_summarizes_each_prior_result_once() {
+        let (handler, requests) = fixed_handler("Planned a parser cache");
+        let ctx = Context::default();
+        ctx.set("plan.result", serde_json::json!("plan ".repeat(200)))
+            .await;
+        ctx.set("lint.result", serde_json::json!("clean")).await;
+        let graph = make_minimal_graph();
+        let mut fix = make_node("fix", "box", Some("Fix it"), HashMap::new());
+        fix.fidelity = Some("summary:low".into());
+
+        let outcome = handler.execute(&fix, &ctx, &graph).await.unwrap();
+        let updates = &outcome.context_updates;
+        assert_eq!(
+            updates["summary.plan.result.low"]["text"],
+            "Planned a parser cache"
+        );
+        // Summary and task calls both count
+        assert_eq!(updates["fix.input_tokens"], serde_json::json!(2_000));
+
+        // A later node reuses the summary
+        ctx.apply_updates(outcome.context_updates).await;
+        let mut review = make_node("review", "box", Some("Review it"), HashMap::new());
+        review.fidelity = Some("summary:low".into());
+        handler.execute(&review, &ctx, &graph).await.unwrap();
+
+        let requests = requests.lock().unwrap();
+        let first_text = |request: &Request, index: usize| match &request.messages[index].content[0]
+        {
+            attractor_llm::ContentPart::Text { text } => text.clone(),
+            _ => String::new(),
+        };
+        assert_eq!(requests.len(), 3);
+        let summary_request = first_text(&requests[0], 0);
+        assert!(summary_request.contains("at most 150 words"));
+        assert!(summary_request.contains("`plan.result`"));
+        for task in [&requests[1], &requests[2]] {
+            // After the system prompt
+            let prompt = first_text(task, 1);
+            assert!(prompt.contains("- plan.result: Planned a parser cache\n"));
+            assert!(prompt.contains("- lint.result: clean\n"));
+            assert!(!prompt.contains("plan plan"));
+        }
+    }
+
+    #[tokio::test]
+    async fn agent_honors_tool_profile_none() {
+        let (handler, requests) = fixed_handler("ok");
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "tool_profile".to_string(),
+            AttributeValue::String("none".into()),
+        );
+        let node = make_node("plain", "box", Some("Say ok"), attrs);
+        let ctx = Context::default();
+        let graph = make_minimal_graph();
+
+        handler.execute(&node, &ctx, &graph).await.unwrap();
+
+        assert!(requests.lock().unwrap()[0].tools.is_empty());
+    }
+
+    #[tokio::test]
+    async fn agent_rejects_unknown_tool_profile() {
+        let (handler, _) = fixed_handler("ok");
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "tool_profile".to_string(),
+            AttributeValue::String("bogus".into()),
+        );
+        let node = make_node("n", "box", Some("x"), attrs);
+        let ctx = Context::default();
+        let graph = make_minimal_graph();
+
+        let err = handler.execute(&node, &ctx, &graph).await.unwrap_err();
+        assert!(err.to_string().contains("bogus"));
+    }
+
+    #[tokio::test]
+    async fn agent_dry_run_skips_client() {
+        let handler = AgentHandler::new(Arc::new(|| {
+            Err(AttractorError::Other("client should not be built".into()))
+        }));
+        let node = make_node("n", "box", Some("x"), HashMap::new());
+        let ctx = Context::default();
+        ctx.set("dry_run", serde_json::json!(true)).await;
+        let graph = make_minimal_graph();
+
+        let outcome = handler.execute(&node, &ctx, &graph).await.unwrap();
+        assert_eq!(
+            outcome.context_updates["n.dry_run"],
+            serde_json::json!(true)
+        );
+    }
+}
@@ -1,12 +1,17 @@
 use std::collections::HashMap;
 
 use async_trait::async_trait;
+use attractor_agent::FidelityMode;
 use attractor_dot::AttributeValue;
 use attractor_types::{AttractorError, Context, Outcome, Result, StageStatus};
 use serde::Deserialize;
 
+use crate::artifacts::resolve_artifacts;
+use crate::fidelity::{node_fidelity, prior_context_lines, summarize_prior_results};
 use crate::graph::{PipelineGraph, PipelineNode};
 use crate::handler::NodeHandler;
+use crate::handlers::structured_output::{flatten_fields, OutputSchema};
+use crate::threads::Thread;
 
 // ---------------------------------------------------------------------------
 // LlmCliProvider — which CLI tool to invoke for an LLM node
@@ -80,6 +85,8 @@ struct ClaudeOutput {
     total_cost_usd: f64,
     #[serde(default)]
     num_turns: u32,
+    #[serde(default)]
+    session_id: Option<String>,
 }
 
 /// Codex JSONL event (tagged enum for streaming deserializer).
@@ -87,6 +94,11 @@ struct ClaudeOutput {
 #[derive(Deserialize)]
 #[serde(tag = "type")]
 enum CodexEvent {
+    #[serde(rename = "thread.started")]
+    ThreadStarted {
+        #[serde(default)]
+        thread_id: Option<String>,
+    },
     #[serde(rename = "item.completed")]
     ItemCompleted { item: CodexItem },
     #[serde(rename = "turn.completed")]
@@ -100,7 +112,7 @@ enum CodexEvent {
     #[serde(rename = "error")]
     Error { message: String },
     #[serde(other)]
-    Other, // Absorbs thread.started, turn.started, item.started, item.updated
+    Other, // Absorbs turn.started, item.started, item.updated
 }
 
 #[derive(Deserialize)]
@@ -130,7 +142,6 @@ struct CodexError {
 #[derive(Deserialize)]
 struct GeminiOutput {
     #[serde(default)]
-    #[allow(dead_code)]
     session_id: Option<String>,
     #[serde(default)]
     response: Option<String>,
@@ -156,6 +167,8 @@ struct NormalizedCliResult {
     is_error: bool,
     cost_usd: Option<f64>,
     turns: Option<u32>,
+    /// Session the CLI can resume, when it reports one.
+    session_id: Option<String>,
     #[allow(dead_code)]
     raw_output: String,
 }
@@ -172,6 +185,10 @@ struct CliRunConfig<'a> {
     node: &'a PipelineNode,
     #[allow(dead_code)]
     graph: &'a PipelineGraph,
+    /// Keep the CLI session so a later node in the thread can resume it.
+    keep_session: bool,
+    /// Session to resume instead of starting a new one.
+    resume: Option<&'a str>,
 }
 
 fn build_cli_command(cfg: &CliRunConfig<'_>) -> tokio::process::Command {
@@ -182,33 +199,50 @@ fn build_cli_command(cfg: &CliRunConfig<'_>) -> tokio::process::Command {
                 .arg(cfg.prompt)
                 .arg("--output-format")
                 .arg("json")
-                .arg("--no-session-persistence")
                 .arg("--dangerously-skip-permissions")
                 .arg("--strict-mcp-config")
                 .arg("--disable-slash-commands");
+            if !cfg.keep_session {
+                cmd.arg("--no-session-persistence");
+            }
+            if let Some(session) = cfg.resume {
+                cmd.arg("--resume").arg(session);
+            }
             if let Some(model) = cfg.model {
                 cmd.arg("--model").arg(model);
             }
             if let Some(AttributeValue::String(tools)) = cfg.node.raw_attrs.get("allowed_tools") {
                 cmd.arg("--allowedTools").arg(tools);
             }
-            if let Some(AttributeValue::String(budget)) = cfg.node.raw_attrs.get("max_budget_usd") {
-                cmd.arg("--max-budget-usd").arg(budget);
+            match cfg.node.raw_attrs.get("max_budget_usd") {
+                Some(AttributeValue::String(budget)) => {
+                    cmd.arg("--max-budget-usd").arg(budget);
+                }
+                Some(AttributeValue::Float(budget)) => {
+                    cmd.arg("--max-budget-usd").arg(budget.to_string());
+                }
+                Some(AttributeValue::Integer(budget)) => {
+                    cmd.arg("--max-budget-usd").arg(budget.to_string());
+                }
+                _ => {}
             }
             cmd
         }
         LlmCliProvider::Codex => {
             let mut cmd = tokio::process::Command::new("codex");
-            cmd.arg("--json")
-                .arg("--yolo")
-                .arg("--skip-git-repo-check")
-                .arg("--ephemeral");
+            cmd.arg("--json").arg("--yolo").arg("--skip-git-repo-check");
+            if !cfg.keep_session {
+                cmd.arg("--ephemeral");
+            }
             if let Some(model) = cfg.model {
                 cmd.arg("--model").arg(model);
             }
             if let Some(dir) = cfg.workdir {
                 cmd.arg("--cd").arg(dir);
             }
+            if let Some(session) = cfg.resume {
+                cmd.arg("resume").arg(session);
+            }
             // Prompt is POSITIONAL (last arg) — NOT -p (that's --profile in Codex)
             cmd.arg(cfg.prompt);
             cmd
@@ -222,6 +256,9 @@ fn build_cli_command(cfg: &CliRunConfig<'_>) -> tokio::process::Command {
             if let Some(model) = cfg.model {
                 cmd.arg("--model").arg(model);
             }
+            if let Some(session) = cfg.resume {
+                cmd.arg("--resume").arg(session);
+            }
             // Prompt is POSITIONAL (preferred) — -p/--prompt is deprecated
             cmd.arg(cfg.prompt);
             // Gemini has NO --cwd flag — working dir set via cmd.current_dir() only
@@ -282,17 +319,20 @@ fn parse_claude_output(stdout: &str, node_id: &str) -> Result<NormalizedCliResul
         is_error: parsed.is_error || parsed.subtype == "error",
         cost_usd: Some(parsed.total_cost_usd),
         turns: Some(parsed.num_turns),
+        session_id: parsed.session_id,
         raw_output: stdout.to_string(),
     })
 }
 
 fn parse_codex_output(stdout: &str, node_id: &str) -> Result<NormalizedCliResult> {
     let mut last_message: Option<String> = None;
+    let mut session_id: Option<String> = None;
     let mut is_error = false;
     let mut error_message: Option<String> = None;
 
     for event in serde_json::Deserializer::from_str(stdout).into_iter::<CodexEvent>() {
         match event {
+            Ok(CodexEvent::ThreadStarted { thread_id }) => session_id = thread_id,
             Ok(CodexEvent::ItemCompleted { item }) => {
                 if item.item_type == "agent_message" {
                     if let Some(text) = item.text {
@@ -324,6 +364,7 @@ fn parse_codex_output(stdout: &str, node_id: &str) -> Result<NormalizedCliResult
         is_error,
         cost_usd: None,
         turns: None,
+        session_id,
         raw_output: stdout.to_string(),
     })
 }
@@ -345,6 +386,7 @@ fn parse_gemini_output(stdout: &str, node_id: &str) -> Result<NormalizedCliResul
             is_error: true,
             cost_usd: None,
             turns: None,
+            session_id: parsed.session_id,
             raw_output: stdout.to_string(),
         });
     }
@@ -354,6 +396,7 @@ fn parse_gemini_output(stdout: &str, node_id: &str) -> Result<NormalizedCliResul
         is_error: false,
         cost_usd: None,
         turns: None,
+        session_id: parsed.session_id,
         raw_output: stdout.to_string(),
     })
 }
@@ -372,6 +415,10 @@ fn parse_gemini_output(stdout: &str, node_id: &str) -> Result<NormalizedCliResul
 //   - allowed_tools: Comma-separated tool list (Claude only)
 //   - max_budget_usd: Spending cap for this node (Claude only)
 //   - timeout: Duration before the CLI invocation is killed (default: 10m)
+//   - thread_id: Resume the CLI session of earlier nodes in the same thread
+//     (see crate::threads)
+//   - fidelity: How much prior context the prompt carries; `summary:*` has
+//     the CLI summarize long prior results first (see crate::fidelity)
 //
 // The pipeline context key "workdir" controls the working directory.
 // ---------------------------------------------------------------------------
@@ -443,54 +490,13 @@ impl NodeHandler for CodergenHandler {
             });
         }
 
-        // Build the full prompt with pipeline context
-        let goal = &graph.goal;
-        let mut full_prompt = String::new();
-
-        if !goal.is_empty() {
-            full_prompt.push_str(&format!("Pipeline goal: {}\n\n", goal));
-        }
-
-        // Inject relevant context from prior nodes
-        let snapshot = context.snapshot().await;
-        let context_keys: Vec<_> = snapshot
-            .iter()
-            .filter(|(k, _)| k.ends_with(".result") || k.ends_with(".output"))
-            .collect();
-        if !context_keys.is_empty() {
-            full_prompt.push_str("Context from prior pipeline steps:\n");
-            for (k, v) in &context_keys {
-                if let serde_json::Value::String(s) = v {
-                    full_prompt.push_str(&format!("- {}: {}\n", k, s));
-                } else {
-                    full_prompt.push_str(&format!("- {}: {}\n", k, v));
-                }
-            }
-            full_prompt.push('\n');
-        }
-
-        full_prompt.push_str(&format!("Task ({}): {}", label, prompt));
-
-        // If this is a conditional node, instruct the LLM to output a label
-        if node.shape == "diamond" || node.node_type.as_deref() == Some("conditional") {
-            let edges = graph.outgoing_edges(&node.id);
-            let labels: Vec<_> = edges.iter().filter_map(|e| e.label.as_deref()).collect();
-            if !labels.is_empty() {
-                full_prompt.push_str(&format!(
-                    "\n\nYou MUST end your response with exactly one of these labels on its own line: {}",
-                    labels.join(", ")
-                ));
-            }
-        }
-
-        // Resolve model: node attribute, then graph-level fallback
-        let model = node
-            .llm_model
-            .as_deref()
-            .or_else(|| match graph.attrs.get("model") {
-                Some(AttributeValue::String(m)) => Some(m.as_str()),
-                _ => None,
-            });
+        let snapshot = prompt_snapshot(node, context).await;
+        let thread = Thread::for_node(node, &snapshot).await;
+        let resume = thread
+            .as_ref()
+            .and_then(|t| t.session_for(provider.binary_name()));
+        let resumed = thread.as_ref().filter(|_| resume.is_some());
+        let model = resolve_model(node, graph);
 
         // Resolve working directory from context
         let workdir = snapshot
@@ -498,84 +504,60 @@ impl NodeHandler for CodergenHandler {
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
 
-        // Build the CLI command via the provider-specific builder
-        let mut cmd = build_cli_command(&CliRunConfig {
-            provider,
-            prompt: &full_prompt,
-            model,
-            workdir: workdir.as_deref(),
-            node,
-            graph,
-        });
-
-        // Spawn the CLI process — detect missing binary
-        let child = cmd.spawn().map_err(|e| {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                AttractorError::CliNotFound {
-                    binary: provider.binary_name().to_string(),
-                }
-            } else {
-                AttractorError::HandlerError {
-                    handler: "codergen".into(),
-                    node: node.id.clone(),
-                    message: format!("Failed to spawn {}: {}", provider.display_name(), e),
-                }
-            }
-        })?;
-
-        // Apply timeout (default 10 minutes, configurable via node.timeout).
-        // IMPORTANT: We capture the PID before wait_with_output() consumes the
-        // Child. On timeout, we kill the process tree — tokio::time::timeout
-        // only drops the future, it does NOT kill the child process.
-        let child_pid = child.id();
+        // Default 10 minutes, configurable via node.timeout
         let timeout_dur = node.timeout.unwrap_or(std::time::Duration::from_secs(600));
-        let output = match tokio::time::timeout(timeout_dur, child.wait_with_output()).await {
-            Ok(result) => result.map_err(|e| AttractorError::HandlerError {
-                handler: "codergen".into(),
-                node: node.id.clone(),
-                message: format!("{} execution failed: {}", provider.display_name(), e),
-            })?,
-            Err(_elapsed) => {
-                // Timeout fired — kill the child process and its descendants
-                if let Some(pid) = child_pid {
-                    tracing::warn!(
-                        node = %node.id,
-                        pid = pid,
-                        timeout_secs = timeout_dur.as_secs(),
-                        "Killing timed-out {} process",
-                        provider.display_name()
-                    );
-                    // SIGKILL the child process — its MCP server children will
-                    // get SIGHUP when their parent exits.
-                    #[cfg(unix)]
-                    unsafe {
-                        libc::kill(pid as i32, libc::SIGKILL);
-                    }
-                }
-                return Err(AttractorError::CommandTimeout {
-                    timeout_ms: timeout_dur.as_millis() as u64,
-                });
-            }
-        };
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Under summary fidelity, the CLI first condenses long prior results
+        let mut summaries = HashMap::new();
+        let mut summary_cost = None;
+        let mut summary_updates = HashMap::new();
+        if let FidelityMode::Summary { detail } = node_fidelity(node, graph, &snapshot) {
+            let inputs = summary_inputs(node, &snapshot, resumed).await;
+            let workdir = workdir.as_deref();
+            let prior = summarize_prior_results(inputs, &snapshot, detail, |request| async move {
+                let result = run_cli(
+                    &CliRunConfig {
+                        provider,
+                        prompt: &request,
+                        model,
+                        workdir,
+                        node,
+                        graph,
+                        keep_session: false,
+                        resume: None,
+                    },
+                    timeout_dur,
+                )
+                .await?;
+                Ok((result.text, result.cost_usd))
+            })
+            .await?;
+            summaries = prior.summaries;
+            summary_updates = prior.updates;
+            summary_cost = prior.spent.into_iter().flatten().reduce(|a, b| a + b);
+        }
+        let mut full_prompt = build_full_prompt(node, graph, &snapshot, resumed, &summaries);
 
-        if !output.status.success() {
-            return Err(AttractorError::HandlerError {
-                handler: "codergen".into(),
-                node: node.id.clone(),
-                message: format!(
-                    "{} exited with {}: {}",
-                    provider.display_name(),
-                    output.status,
-                    stderr.trim()
-                ),
-            });
+        // Ask for structured JSON when the node declares an output_schema
+        let output_schema = OutputSchema::from_node(node, workdir.as_deref())?;
+        if let Some(ref schema) = output_schema {
+            full_prompt.push_str(&schema.prompt_instructions());
         }
 
-        // Parse output via the provider-specific parser
-        let cli_result = parse_cli_output(provider, &stdout, &stderr, &node.id)?;
+        let cli_result = run_cli(
+            &CliRunConfig {
+                provider,
+                prompt: &full_prompt,
+                model,
+                workdir: workdir.as_deref(),
+                node,
+                graph,
+                keep_session: thread.is_some(),
+                resume,
+            },
+            timeout_dur,
+        )
+        .await?;
 
         tracing::info!(
             node = %node.id,
@@ -587,21 +569,33 @@ impl NodeHandler for CodergenHandler {
         );
 
         // Determine status
-        let status = if cli_result.is_error {
+        let mut status = if cli_result.is_error {
             StageStatus::Fail
         } else {
             StageStatus::Success
         };
 
+        // Extract and validate structured output; invalid JSON is retried
+        let mut structured = None;
+        let mut schema_error = None;
+        if let (Some(schema), StageStatus::Success) = (&output_schema, status) {
+            match schema.extract(&cli_result.text) {
+                Ok(value) => structured = Some(value),
+                Err(e) => {
+                    tracing::warn!(node = %node.id, error = %e, "Structured output invalid");
+                    // The updates of a retried outcome are dropped, so the
+                    // error goes straight into the context for the retry's prompt
+                    context
+                        .set(schema_error_key(&node.id), serde_json::json!(e))
+                        .await;
+                    status = StageStatus::Retry;
+                    schema_error = Some(format!("Structured output invalid: {}", e));
+                }
+            }
+        }
+
         // Extract preferred_label from the response for conditional routing
-        let preferred_label =
-            if node.shape == "diamond" || node.node_type.as_deref() == Some("conditional") {
-                let edges = graph.outgoing_edges(&node.id);
-                let labels: Vec<String> = edges.iter().filter_map(|e| e.label.clone()).collect();
-                extract_label(&cli_result.text, &labels)
-            } else {
-                None
-            };
+        let preferred_label = preferred_label_for(node, graph, &cli_result.text);
 
         // Build context updates
         let mut updates = HashMap::new();
@@ -617,7 +611,12 @@ impl NodeHandler for CodergenHandler {
             format!("{}.provider", node.id),
             serde_json::Value::String(provider.display_name().into()),
         );
-        if let Some(cost) = cli_result.cost_usd {
+        if let Some(cost) = cli_result
+            .cost_usd
+            .into_iter()
+            .chain(summary_cost)
+            .reduce(|a, b| a + b)
+        {
             updates.insert(format!("{}.cost_usd", node.id), serde_json::json!(cost));
         }
         if let Some(turns) = cli_result.turns {
@@ -629,6 +628,28 @@ impl NodeHandler for CodergenHandler {
                 serde_json::Value::String(lbl.clone()),
             );
         }
+        if let Some(ref value) = structured {
+            updates.extend(flatten_fields(&node.id, value));
+            updates.insert(schema_error_key(&node.id), serde_json::Value::Null);
+        }
+        updates.extend(summary_updates);
+        if let Some(ref thread) = thread {
+            if cli_result.session_id.is_none() {
+                tracing::warn!(node = %node.id, thread = %thread.id, "No session id to resume the thread with");
+            }
+            updates.extend(thread.updates(
+                &node.id,
+                provider.binary_name(),
+                cli_result.session_id.clone(),
+                None,
+            ));
+        }
+
+        let failure_reason = match status {
+            StageStatus::Fail => Some(format!("{} returned an error", provider.display_name())),
+            StageStatus::Retry => schema_error,
+            _ => None,
+        };
 
         Ok(Outcome {
             status,
@@ -636,13 +657,293 @@ impl NodeHandler for CodergenHandler {
             suggested_next_ids: vec![],
             context_updates: updates,
             notes: cli_result.text,
-            failure_reason: if status == StageStatus::Fail {
-                Some(format!("{} returned an error", provider.display_name()))
-            } else {
-                None
-            },
+            failure_reason,
+        })
+    }
+}
+
+/// Spawn the CLI described by `cfg` and parse its output, killing it once
+/// `timeout_dur` has passed.
+async fn run_cli(
+    cfg: &CliRunConfig<'_>,
+    timeout_dur: std::time::Duration,
+) -> Result<NormalizedCliResult> {
+    let (provider, node) = (cfg.provider, cfg.node);
+    let mut cmd = build_cli_command(cfg);
+
+    // Spawn the CLI process — detect missing binary
+    let child = cmd.spawn().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            AttractorError::CliNotFound {
+                binary: provider.binary_name().to_string(),
+            }
+        } else {
+            AttractorError::HandlerError {
+                handler: "codergen".into(),
+                node: node.id.clone(),
+                message: format!("Failed to spawn {}: {}", provider.display_name(), e),
+            }
+        }
+    })?;
+
+    // IMPORTANT: We capture the PID before wait_with_output() consumes the
+    // Child. On timeout, we kill the process tree — tokio::time::timeout
+    // only drops the future, it does NOT kill the child process.
+    let child_pid = child.id();
+    let output = match tokio::time::timeout(timeout_dur, child.wait_with_output()).await {
+        Ok(result) => result.map_err(|e| AttractorError::HandlerError {
+            handler: "codergen".into(),
+            node: node.id.clone(),
+            message: format!("{} execution failed: {}", provider.display_name(), e),
+        })?,
+        Err(_elapsed) => {
+            // Timeout fired — kill the child process and its descendants
+            if let Some(pid) = child_pid {
+                tracing::warn!(
+                    node = %node.id,
+                    pid = pid,
+                    timeout_secs = timeout_dur.as_secs(),
+                    "Killing timed-out {} process",
+                    provider.display_name()
+                );
+                // SIGKILL the child process — its MCP server children will
+                // get SIGHUP when their parent exits.
+                #[cfg(unix)]
+                unsafe {
+                    libc::kill(pid as i32, libc::SIGKILL);
+                }
+            }
+            return Err(AttractorError::CommandTimeout {
+                timeout_ms: timeout_dur.as_millis() as u64,
+            });
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if !output.status.success() {
+        return Err(AttractorError::HandlerError {
+            handler: "codergen".into(),
+            node: node.id.clone(),
+            message: format!(
+                "{} exited with {}: {}",
+                provider.display_name(),
+                output.status,
+                stderr.trim()
+            ),
+        });
+    }
+
+    // Parse output via the provider-specific parser
+    parse_cli_output(provider, &stdout, &stderr, &node.id)
+}
+
+fn is_conditional(node: &PipelineNode) -> bool {
+    node.shape == "diamond" || node.node_type.as_deref() == Some("conditional")
+}
+
+/// The keys listed in a node's `inputs` attribute, e.g.
+/// `inputs="plan.result,build.diff"`.
+fn input_keys(node: &PipelineNode) -> Option<Vec<String>> {
+    match node.raw_attrs.get("inputs") {
+        Some(AttributeValue::String(s)) => Some(
+            s.split(',')
+                .map(str::trim)
+                .filter(|k| !k.is_empty())
+                .map(String::from)
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+/// The context snapshot to build a node's prompt from, with the artifacts
+/// named in its `inputs` loaded.
+pub(crate) async fn prompt_snapshot(
+    node: &PipelineNode,
+    context: &Context,
+) -> HashMap<String, serde_json::Value> {
+    let mut snapshot = context.snapshot().await;
+    if let Some(keys) = input_keys(node) {
+        resolve_artifacts(&mut snapshot, Some(&keys)).await;
+    }
+    snapshot
+}
+
+/// The prior context of a node's prompt: its `inputs` keys in order, or
+/// else every `.result` / `.output` value by key, minus those of the nodes
+/// already in a `resumed` thread.
+fn prior_entries<'a>(
+    node: &PipelineNode,
+    snapshot: &'a HashMap<String, serde_json::Value>,
+    resumed: Option<&Thread>,
+) -> Vec<(String, Option<&'a serde_json::Value>)> {
+    if let Some(keys) = input_keys(node) {
+        return keys
+            .into_iter()
+            .map(|k| {
+                let value = snapshot.get(&k);
+                (k, value)
+            })
+            .collect();
+    }
+    let in_thread = |key: &str| {
+        resumed.is_some_and(|thread| {
+            thread
+                .nodes
+                .iter()
+                .any(|id| key == format!("{}.result", id) || key == format!("{}.output", id))
+        })
+    };
+    let mut entries: Vec<_> = snapshot
+        .iter()
+        .filter(|(k, _)| k.ends_with(".result") || k.ends_with(".output"))
+        .filter(|(k, _)| !in_thread(k))
+        .map(|(k, v)| (k.clone(), Some(v)))
+        .collect();
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// The text of a node's prior context values, artifacts loaded: what
+/// `summary` fidelity summarizes.
+pub(crate) async fn summary_inputs(
+    node: &PipelineNode,
+    snapshot: &HashMap<String, serde_json::Value>,
+    resumed: Option<&Thread>,
+) -> Vec<(String, String)> {
+    let mut values: HashMap<String, serde_json::Value> = prior_entries(node, snapshot, resumed)
+        .into_iter()
+        .filter_map(|(k, v)| Some((k, v?.clone())))
+        .collect();
+    resolve_artifacts(&mut values, None).await;
+    let mut inputs: Vec<(String, String)> = values
+        .into_iter()
+        .map(|(k, v)| match v {
+            serde_json::Value::String(s) => (k, s),
+            other => (k, other.to_string()),
+        })
+        .collect();
+    inputs.sort();
+    inputs
+}
+
+/// Context key holding why the node's last response failed its
+/// `output_schema`, until a response passes.
+fn schema_error_key(node_id: &str) -> String {
+    format!("{}.schema_error", node_id)
+}
+
+/// Assemble the prompt sent to the LLM: pipeline goal, context from prior
+/// nodes, human gate feedback, why the node's last response failed its
+/// `output_schema`, the node's task, and — for conditional nodes — the
+/// instruction to end with one of the outgoing edge labels.
+///
+/// The prior context is the node's `inputs` keys in order when it sets them,
+/// and otherwise every `.result` / `.output` value, with artifacts listed by
+/// path rather than inlined. The node's fidelity shapes it (see
+/// [`crate::fidelity`]); under `summary` fidelity values with an entry in
+/// `summaries` show that instead.
+///
+/// When the node continues a `resumed` thread the conversation already holds
+/// the goal and its earlier nodes' results, so those are left out.
+pub(crate) fn build_full_prompt(
+    node: &PipelineNode,
+    graph: &PipelineGraph,
+    snapshot: &HashMap<String, serde_json::Value>,
+    resumed: Option<&Thread>,
+    summaries: &HashMap<String, String>,
+) -> String {
+    let prompt = node.prompt.as_deref().unwrap_or("No prompt specified");
+    let goal = &graph.goal;
+    let mut full_prompt = String::new();
+
+    if !goal.is_empty() && resumed.is_none() {
+        full_prompt.push_str(&format!("Pipeline goal: {}\n\n", goal));
+    }
+
+    // Inject relevant context from prior nodes, at the node's fidelity
+    let entries = prior_entries(node, snapshot, resumed);
+    let fidelity = node_fidelity(node, graph, snapshot);
+    let lines = prior_context_lines(&entries, &fidelity, summaries);
+    if !lines.is_empty() {
+        full_prompt.push_str("Context from prior pipeline steps:\n");
+        for line in lines {
+            full_prompt.push_str(&line);
+            full_prompt.push('\n');
+        }
+        full_prompt.push('\n');
+    }
+
+    // Feedback typed at human gates (`<gate>.feedback`)
+    let mut feedback: Vec<_> = snapshot
+        .iter()
+        .filter_map(|(k, v)| Some((k.strip_suffix(".feedback")?, v.as_str()?)))
+        .filter(|(_, text)| !text.trim().is_empty())
+        .collect();
+    if !feedback.is_empty() {
+        feedback.sort();
+        full_prompt.push_str("Reviewer feedback:\n");
+        for (gate, text) in feedback {
+            full_prompt.push_str(&format!("- {}: {}\n", gate, text));
+        }
+        full_prompt.push('\n');
+    }
+
+    // A retry after a response that failed the node's output_schema
+    if let Some(error) = snapshot
+        .get(&schema_error_key(&node.id))
+        .and_then(|v| v.as_str())
+    {
+        full_prompt.push_str(&format!(
+            "Your previous response was rejected: its JSON did not match the output schema ({}). Fix this in your new response.\n\n",
+            error
+        ));
+    }
+
+    full_prompt.push_str(&format!("Task ({}): {}", node.label, prompt));
+
+    // If this is a conditional node, instruct the LLM to output a label
+    if is_conditional(node) {
+        let edges = graph.outgoing_edges(&node.id);
+        let labels: Vec<_> = edges.iter().filter_map(|e| e.label.as_deref()).collect();
+        if !labels.is_empty() {
+            full_prompt.push_str(&format!(
+                "\n\nYou MUST end your response with exactly one of these labels on its own line: {}",
+                labels.join(", ")
+            ));
+        }
+    }
+
+    full_prompt
+}
+
+/// Resolve the model for a node: node attribute, then graph-level fallback.
+pub(crate) fn resolve_model<'a>(
+    node: &'a PipelineNode,
+    graph: &'a PipelineGraph,
+) -> Option<&'a str> {
+    node.llm_model
+        .as_deref()
+        .or_else(|| match graph.attrs.get("model") {
+            Some(AttributeValue::String(m)) => Some(m.as_str()),
+            _ => None,
         })
+}
+
+/// For conditional nodes, pick the outgoing edge label named in `response`.
+pub(crate) fn preferred_label_for(
+    node: &PipelineNode,
+    graph: &PipelineGraph,
+    response: &str,
+) -> Option<String> {
+    if !is_conditional(node) {
+        return None;
     }
+    let edges = graph.outgoing_edges(&node.id);
+    let labels: Vec<String> = edges.iter().filter_map(|e| e.label.clone()).collect();
+    extract_label(response, &labels)
 }
 
 /// Scan the Claude response for one of the expected edge labels.
@@ -753,6 +1054,26 @@ mod tests {
         assert_eq!(result.turns, Some(3));
     }
 
+    #[test]
+    fn parsers_capture_session_ids() {
+        let claude = r#"{"result":"ok","session_id":"c-123","total_cost_usd":0.01,"num_turns":1}"#;
+        let result = parse_claude_output(claude, "n").unwrap();
+        assert_eq!(result.session_id.as_deref(), Some("c-123"));
+
+        let codex = concat!(
+            r#"{"type":"thread.started","thread_id":"x-456"}"#,
+            "\n",
+            r#"{"type":"item.completed","item":{"type":"agent_message","text":"ok"}}"#,
+        );
+        let result = parse_codex_output(codex, "n").unwrap();
+        assert_eq!(result.session_id.as_deref(), Some("x-456"));
+        assert_eq!(result.text, "ok");
+
+        let gemini = r#"{"session_id":"g-789","response":"ok"}"#;
+        let result = parse_gemini_output(gemini, "n").unwrap();
+        assert_eq!(result.session_id.as_deref(), Some("g-789"));
+    }
+
     #[test]
     fn parse_claude_output_error() {
         let json = r#"{"result":"Something failed","is_error":true,"subtype":"error","total_cost_usd":0.01,"num_turns":1}"#;
@@ -855,6 +1176,8 @@ mod tests {
             workdir: None,
             node: &node,
             graph: &graph,
+            keep_session: false,
+            resume: None,
         };
         let cmd = build_cli_command(&cfg);
         let args: Vec<_> = cmd
@@ -880,6 +1203,8 @@ mod tests {
             workdir: Some("/tmp"),
             node: &node,
             graph: &graph,
+            keep_session: false,
+            resume: None,
         };
         let cmd = build_cli_command(&cfg);
         let args: Vec<_> = cmd
@@ -906,6 +1231,8 @@ mod tests {
             workdir: None,
             node: &node,
             graph: &graph,
+            keep_session: false,
+            resume: None,
         };
         let cmd = build_cli_command(&cfg);
         let args: Vec<_> = cmd
@@ -919,6 +1246,42 @@ mod tests {
         assert!(args.contains(&"gemini-2.5-pro"));
     }
 
+    #[test]
+    fn build_cli_command_resumes_thread_sessions() {
+        let node = make_node("n", "box", Some("do work"), HashMap::new());
+        let graph = make_minimal_graph();
+        let args = |provider, keep_session, resume| {
+            let cmd = build_cli_command(&CliRunConfig {
+                provider,
+                prompt: "test prompt",
+                model: None,
+                workdir: None,
+                node: &node,
+                graph: &graph,
+                keep_session,
+                resume,
+            });
+            cmd.as_std()
+                .get_args()
+                .map(|a| a.to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        assert!(args(LlmCliProvider::Claude, false, None).contains("--no-session-persistence"));
+        let claude = args(LlmCliProvider::Claude, true, Some("c-1"));
+        assert!(!claude.contains("--no-session-persistence"));
+        assert!(claude.contains("--resume c-1"));
+
+        assert!(args(LlmCliProvider::Codex, false, None).contains("--ephemeral"));
+        let codex = args(LlmCliProvider::Codex, true, Some("x-1"));
+        assert!(!codex.contains("--ephemeral"));
+        assert!(codex.ends_with("resume x-1 test prompt"));
+
+        let gemini = args(LlmCliProvider::Gemini, true, Some("g-1"));
+        assert!(gemini.contains("--resume g-1"));
+    }
+
     // --- CodergenHandler dry-run with provider ---
 
     #[tokio::test]
@@ -966,4 +1329,129 @@ mod tests {
         let response = "This player is interesting but I need more data.";
         assert_eq!(extract_label(response, &labels), None);
     }
+
+    #[test]
+    fn full_prompt_includes_reviewer_feedback() {
+        let node = make_node("fixup", "box", Some("Apply the review"), HashMap::new());
+        let graph = make_minimal_graph();
+        let mut snapshot = HashMap::new();
+        snapshot.insert(
+            "review.feedback".to_string(),
+            serde_json::json!("the migration is missing an index"),
+        );
+        snapshot.insert("signoff.feedback".to_string(), serde_json::json!(""));
+
+        let prompt = build_full_prompt(&node, &graph, &snapshot, None, &HashMap::new());
+        assert!(
+            prompt.contains("Reviewer feedback:\n- review: the migration is missing an index\n")
+        );
+        assert!(!prompt.contains("signoff"));
+        assert!(prompt.find("Reviewer feedback").unwrap() < prompt.find("Task (fixup)").unwrap());
+    }
+
+    #[test]
+    fn full_prompt_includes_the_last_schema_error() {
+        let node = make_node("review", "box", Some("Review the diff"), HashMap::new());
+        let graph = make_minimal_graph();
+        let mut snapshot = HashMap::new();
+        let prompt = build_full_prompt(&node, &graph, &snapshot, None, &HashMap::new());
+        assert!(!prompt.contains("rejected"));
+
+        snapshot.insert(
+            "review.schema_error".to_string(),
+            serde_json::json!("severity: expected one of [\"low\",\"high\"]"),
+        );
+        let prompt = build_full_prompt(&node, &graph, &snapshot, None, &HashMap::new());
+        assert!(prompt.contains(
+            "did not match the output schema (severity: expected one of [\"low\",\"high\"])"
+        ));
+        assert!(prompt.find("rejected").unwrap() < prompt.find("Task (review)").unwrap());
+
+        // Another node's error and a cleared error stay out
+        let other = make_node("fix", "box", Some("Fix it"), HashMap::new());
+        let prompt = build_full_prompt(&other, &graph, &snapshot, None, &HashMap::new());
+        assert!(!prompt.contains("rejected"));
+        snapshot.insert("review.schema_error".to_string(), serde_json::Value::Null);
+        let prompt = build_full_prompt(&node, &graph, &snapshot, None, &HashMap::new());
+        assert!(!prompt.contains("rejected"));
+    }
+
+    #[tokio::test]
+    async fn full_prompt_honours_inputs_and_lists_artifacts() {
+        let logs = tempfile::tempdir().unwrap();
+        let store = crate::artifacts::ArtifactStore::for_logs(logs.path()).unwrap();
+        let diff = store
+            .put("build.diff", "build", "text/x-diff", b"+fn main() {}")
+            .await
+            .unwrap();
+        let ctx = Context::default();
+        ctx.set("plan.result", serde_json::json!("three steps"))
+            .await;
+        ctx.set("build.diff", diff.to_value()).await;
+        ctx.set("build.result", diff.to_value()).await;
+        let graph = make_minimal_graph();
+
+        // Without inputs, artifacts are referenced, not inlined
+        let node = make_node("review", "box", Some("Review"), HashMap::new());
+        let prompt = build_full_prompt(
+            &node,
+            &graph,
+            &prompt_snapshot(&node, &ctx).await,
+            None,
+            &HashMap::new(),
+        );
+        assert!(prompt.contains("- plan.result: three steps\n"));
+        assert!(prompt.contains(&format!(
+            "- build.result: (13 bytes, stored at {})",
+            diff.path.display()
+        )));
+        assert!(!prompt.contains("build.diff"));
+
+        // With inputs, only the listed keys appear, artifacts loaded
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "inputs".to_string(),
+            AttributeValue::String("build.diff, missing.result".into()),
+        );
+        let node = make_node("review", "box", Some("Review"), attrs);
+        let prompt = build_full_prompt(
+            &node,
+            &graph,
+            &prompt_snapshot(&node, &ctx).await,
+            None,
+            &HashMap::new(),
+        );
+        assert!(prompt.contains("- build.diff: +fn main() {}\n- missing.result: (not set)\n"));
+        assert!(!prompt.contains("plan.result"));
+    }
+
+    #[test]
+    fn full_prompt_applies_the_node_fidelity() {
+        let graph = make_minimal_graph();
+        let mut snapshot = HashMap::new();
+        snapshot.insert(
+            "plan.result".to_string(),
+            serde_json::json!("Looked around.\n\nAdd a parser cache."),
+        );
+        snapshot.insert("test.output".to_string(), serde_json::json!("40 passed"));
+        let mut node = make_node("fix", "box", Some("Fix it"), HashMap::new());
+
+        let prompt = build_full_prompt(&node, &graph, &snapshot, None, &HashMap::new());
+        assert!(prompt.contains(
+            "- plan.result: Looked around.\n\nAdd a parser cache.\n- test.output: 40 passed\n"
+        ));
+
+        node.fidelity = Some("compact".into());
+        let prompt = build_full_prompt(&node, &graph, &snapshot, None, &HashMap::new());
+        assert!(prompt.contains("- plan.result: … Add a parser cache.\n\n"));
+        assert!(!prompt.contains("test.output"));
+
+        // The edge the run arrived by overrides the node
+        snapshot.insert(
+            crate::fidelity::EDGE_FIDELITY_KEY.to_string(),
+            serde_json::json!("truncate_chars:6"),
+        );
+        let prompt = build_full_prompt(&node, &graph, &snapshot, None, &HashMap::new());
+        assert!(prompt.contains("- plan.result: (29 earlier characters) …cache.\n"));
+    }
 }
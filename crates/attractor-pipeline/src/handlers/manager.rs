@@ -1,47 +1,114 @@
 //! Manager loop handler for "house" shaped nodes.
 //!
-//! Supervises a sub-pipeline or sub-section of the graph.
+//! A manager node supervises a child pipeline, running it again and again
+//! until a stop condition holds. The child is either an inline cluster
+//! (`subgraph="cluster_name"`, which is then left out of the parent graph)
+//! or another .dot file (`sub_pipeline="path.dot"`).
+//!
+//! Supported node attributes:
+//!   - subgraph / sub_pipeline: the child pipeline (exactly one is required)
+//!   - stop_condition: checked against the child's final context after each
+//!     iteration, using the edge condition syntax
+//!   - max_iterations: upper bound on iterations (default 10)
+
+use std::collections::HashMap;
 
 use async_trait::async_trait;
-use attractor_types::{Context, Outcome, Result, StageStatus};
+use attractor_dot::AttributeValue;
+use attractor_types::{AttractorError, Context, Outcome, Result};
 
+use crate::condition::{parse_condition, ConditionExpr};
 use crate::graph::{PipelineGraph, PipelineNode};
 use crate::handler::NodeHandler;
+use crate::handlers::subpipeline::load_child;
+
+/// The handler type of manager loop nodes.
+pub const MANAGER_LOOP_TYPE: &str = "stack.manager_loop";
+
+/// Iterations allowed when a node sets no `max_iterations`.
+pub const DEFAULT_MAX_ITERATIONS: usize = 10;
+
+/// A manager node's child pipeline and loop settings.
+#[derive(Debug, Clone)]
+pub struct ManagerConfig {
+    pub child: PipelineGraph,
+    pub stop_condition: Option<ConditionExpr>,
+    pub max_iterations: usize,
+}
+
+impl ManagerConfig {
+    /// Read the loop settings of `node` and load its child pipeline (see
+    /// [`load_child`]).
+    pub fn from_node(
+        node: &PipelineNode,
+        graph: &PipelineGraph,
+        snapshot: &HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        let error = |message: String| AttractorError::HandlerError {
+            handler: MANAGER_LOOP_TYPE.into(),
+            node: node.id.clone(),
+            message,
+        };
+        let attr = |key: &str| match node.raw_attrs.get(key) {
+            Some(AttributeValue::String(s)) if !s.is_empty() => Some(s.as_str()),
+            _ => None,
+        };
+
+        let child = load_child(node, graph, snapshot, MANAGER_LOOP_TYPE)?;
+
+        let stop_condition = attr("stop_condition")
+            .map(parse_condition)
+            .transpose()
+            .map_err(|e| error(format!("Invalid stop_condition: {}", e)))?;
+
+        let max_iterations = match node.raw_attrs.get("max_iterations") {
+            None => DEFAULT_MAX_ITERATIONS,
+            Some(AttributeValue::Integer(n)) if *n > 0 => *n as usize,
+            Some(AttributeValue::String(s)) => s
+                .parse()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| error(format!("Invalid max_iterations '{}'", s)))?,
+            Some(other) => return Err(error(format!("Invalid max_iterations {:?}", other))),
+        };
+
+        Ok(Self {
+            child,
+            stop_condition,
+            max_iterations,
+        })
+    }
+}
 
 /// Handler for "stack.manager_loop" type nodes (shape="house").
-/// Supervises execution and can coordinate sub-tasks.
+///
+/// The handler only checks the node's configuration; the execution engine
+/// runs the child pipeline loop and reports its result as the node's outcome.
 pub struct ManagerLoopHandler;
 
 #[async_trait]
 impl NodeHandler for ManagerLoopHandler {
     fn handler_type(&self) -> &str {
-        "stack.manager_loop"
+        MANAGER_LOOP_TYPE
     }
 
     async fn execute(
         &self,
         node: &PipelineNode,
-        _context: &Context,
-        _graph: &PipelineGraph,
+        context: &Context,
+        graph: &PipelineGraph,
     ) -> Result<Outcome> {
-        let prompt = node.prompt.as_deref().unwrap_or("Supervise sub-tasks");
-        tracing::info!(node = %node.id, "Manager loop executing: {}", prompt);
-
-        Ok(Outcome {
-            status: StageStatus::Success,
-            preferred_label: None,
-            suggested_next_ids: vec![],
-            context_updates: {
-                let mut updates = std::collections::HashMap::new();
-                updates.insert(
-                    format!("{}.managed", node.id),
-                    serde_json::Value::Bool(true),
-                );
-                updates
-            },
-            notes: format!("Manager completed: {}", prompt),
-            failure_reason: None,
-        })
+        let config = ManagerConfig::from_node(node, graph, &context.snapshot().await)?;
+        tracing::info!(
+            node = %node.id,
+            child = %config.child.name,
+            max_iterations = config.max_iterations,
+            "Manager loop"
+        );
+        Ok(Outcome::success(format!(
+            "Supervising '{}' for up to {} iteration(s)",
+            config.child.name, config.max_iterations
+        )))
     }
 }
 
@@ -52,69 +119,108 @@ impl NodeHandler for ManagerLoopHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
-
-    fn make_node(id: &str, shape: &str, prompt: Option<&str>) -> PipelineNode {
-        PipelineNode {
-            id: id.to_string(),
-            label: id.to_string(),
-            shape: shape.to_string(),
-            node_type: None,
-            prompt: prompt.map(String::from),
-            max_retries: 0,
-            goal_gate: false,
-            retry_target: None,
-            fallback_retry_target: None,
-            fidelity: None,
-            thread_id: None,
-            classes: Vec::new(),
-            timeout: None,
-            llm_model: None,
-            llm_provider: None,
-            reasoning_effort: None,
-            auto_status: true,
-            allow_partial: false,
-            raw_attrs: HashMap::new(),
-        }
+    use crate::handlers::tests::make_node;
+    use attractor_types::StageStatus;
+
+    fn parse(dot: &str) -> PipelineGraph {
+        PipelineGraph::from_dot(attractor_dot::parse(dot).unwrap()).unwrap()
     }
 
-    fn make_minimal_graph() -> PipelineGraph {
-        let dot = r#"digraph G { A -> B }"#;
-        let parsed = attractor_dot::parse(dot).unwrap();
-        PipelineGraph::from_dot(parsed).unwrap()
+    fn manager(attrs: &[(&str, AttributeValue)]) -> PipelineNode {
+        let attrs = attrs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        make_node("mgr", "house", None, attrs)
     }
 
-    #[tokio::test]
-    async fn manager_handler_returns_success() {
-        let handler = ManagerLoopHandler;
-        let node = make_node("mgr", "house", Some("Coordinate workers"));
-        let ctx = Context::default();
-        let graph = make_minimal_graph();
+    fn string(s: &str) -> AttributeValue {
+        AttributeValue::String(s.to_string())
+    }
 
-        let outcome = handler.execute(&node, &ctx, &graph).await.unwrap();
-        assert_eq!(outcome.status, StageStatus::Success);
-        assert!(outcome.notes.contains("Coordinate workers"));
-        assert_eq!(
-            outcome.context_updates.get("mgr.managed"),
-            Some(&serde_json::Value::Bool(true))
+    const CHILD: &str = r#"digraph child {
+        start [shape="Mdiamond"]
+        work
+        done [shape="Msquare"]
+        start -> work -> done
+    }"#;
+
+    #[test]
+    fn config_reads_inline_cluster_and_loop_settings() {
+        let graph = parse(
+            r#"digraph G {
+            start [shape="Mdiamond"]
+            mgr [shape="house", subgraph="cluster_loop"]
+            done [shape="Msquare"]
+            start -> mgr -> done
+            subgraph cluster_loop {
+                s [shape="Mdiamond"]
+                e [shape="Msquare"]
+                s -> e
+            }
+        }"#,
         );
+        let node = manager(&[
+            ("subgraph", string("cluster_loop")),
+            ("stop_condition", string("tasks.remaining=0")),
+            ("max_iterations", AttributeValue::Integer(3)),
+        ]);
+        let config = ManagerConfig::from_node(&node, &graph, &HashMap::new()).unwrap();
+        assert_eq!(config.child.name, "cluster_loop");
+        assert_eq!(config.max_iterations, 3);
+        assert!(config.stop_condition.is_some());
+
+        let missing = manager(&[("subgraph", string("cluster_nope"))]);
+        assert!(ManagerConfig::from_node(&missing, &graph, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn config_loads_sub_pipeline_relative_to_parent_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("child.dot"), CHILD).unwrap();
+        let mut graph = parse("digraph G { A -> B }");
+        graph.source = Some(dir.path().join("parent.dot"));
+
+        let node = manager(&[("sub_pipeline", string("child.dot"))]);
+        let config = ManagerConfig::from_node(&node, &graph, &HashMap::new()).unwrap();
+        assert_eq!(config.child.name, "child");
+        assert_eq!(config.max_iterations, DEFAULT_MAX_ITERATIONS);
+        assert!(config.stop_condition.is_none());
     }
 
     #[tokio::test]
-    async fn manager_handler_default_prompt() {
+    async fn handler_rejects_nodes_without_a_child() {
         let handler = ManagerLoopHandler;
-        let node = make_node("mgr", "house", None);
-        let ctx = Context::default();
-        let graph = make_minimal_graph();
+        let graph = parse("digraph G { A -> B }");
+        let err = handler
+            .execute(&manager(&[]), &Context::default(), &graph)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("sub_pipeline"));
 
-        let outcome = handler.execute(&node, &ctx, &graph).await.unwrap();
-        assert_eq!(outcome.status, StageStatus::Success);
-        assert!(outcome.notes.contains("Supervise sub-tasks"));
+        let bad = manager(&[
+            ("sub_pipeline", string("/nonexistent/child.dot")),
+            ("subgraph", string("cluster_x")),
+        ]);
+        assert!(handler
+            .execute(&bad, &Context::default(), &graph)
+            .await
+            .is_err());
     }
 
-    #[test]
-    fn manager_handler_type() {
-        let handler = ManagerLoopHandler;
-        assert_eq!(handler.handler_type(), "stack.manager_loop");
+    #[tokio::test]
+    async fn handler_passes_through_valid_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("child.dot");
+        std::fs::write(&path, CHILD).unwrap();
+        let node = manager(&[("sub_pipeline", string(&path.to_string_lossy()))]);
+
+        let outcome = ManagerLoopHandler
+            .execute(&node, &Context::default(), &parse("digraph G { A -> B }"))
+            .await
+            .unwrap();
+        assert_eq!(outcome.status, StageStatus::Success);
+        assert!(outcome.notes.contains("'child'"));
+        assert_eq!(ManagerLoopHandler.handler_type(), "stack.manager_loop");
     }
 }
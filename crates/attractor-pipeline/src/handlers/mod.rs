@@ -1,14 +1,21 @@
 //! Additional node handlers beyond the basic start/exit/conditional.
 
+pub mod agent_handler;
 pub mod codergen_handler;
 pub mod manager;
 pub mod parallel;
+pub(crate) mod sandbox;
+pub mod structured_output;
+pub mod subpipeline;
 pub mod tool_handler;
 pub mod wait_human;
 
+pub use agent_handler::{AgentHandler, LlmClientFactory};
 pub use codergen_handler::CodergenHandler;
 pub use manager::ManagerLoopHandler;
 pub use parallel::{FanInHandler, ParallelHandler};
+pub use structured_output::OutputSchema;
+pub use subpipeline::SubPipelineHandler;
 pub use tool_handler::ToolHandler;
 
 // ---------------------------------------------------------------------------
@@ -36,6 +43,8 @@ pub(crate) mod tests {
             node_type: None,
             prompt: prompt.map(String::from),
             max_retries: 0,
+            retry_backoff: None,
+            retry_delay: None,
             goal_gate: false,
             retry_target: None,
             fallback_retry_target: None,
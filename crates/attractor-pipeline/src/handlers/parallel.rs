@@ -1,12 +1,251 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
-use attractor_types::{Context, Outcome, Result, StageStatus};
+use attractor_dot::AttributeValue;
+use attractor_types::{AttractorError, Context, Outcome, Result, StageStatus};
+use serde::{Deserialize, Serialize};
 
 use crate::graph::{PipelineGraph, PipelineNode};
 use crate::handler::NodeHandler;
 
+/// Context key under which the engine stores the [`BranchResult`]s of the
+/// most recent fan-out before executing the matching fan-in node.
+pub const PARALLEL_RESULTS_KEY: &str = "parallel.results";
+
+/// Context key holding the IDs of branches cancelled because the fan-in's
+/// join policy was satisfied before they finished.
+pub const PARALLEL_CANCELLED_KEY: &str = "parallel.cancelled";
+
+/// The result of running one parallel branch to its fan-in node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchResult {
+    /// ID of the first node in the branch (the fan-out edge target).
+    pub branch_id: String,
+    /// Final status of the branch (the status of its last executed node).
+    pub status: StageStatus,
+    /// Nodes executed by the branch, in order.
+    pub completed_nodes: Vec<String>,
+    /// Context keys the branch added or changed relative to the fork point.
+    pub context_updates: HashMap<String, serde_json::Value>,
+    /// Why the branch stopped early, if one of its nodes failed to run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// How a fan-in node merges the branch contexts back into the main context.
+///
+/// Selected with the `merge_strategy` attribute on the fan-in node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Apply branch updates in completion order; later branches overwrite
+    /// earlier ones (`last_writer_wins`, the default).
+    LastWriterWins,
+    /// Prefix every key with the branch ID: `<branch_id>.<key>` (`namespaced`).
+    Namespaced,
+    /// Keys written by more than one branch become a JSON array of the
+    /// branch values in completion order (`append`).
+    Append,
+    /// Fail the fan-in if two branches write different values to the same
+    /// key (`fail_on_conflict`).
+    FailOnConflict,
+}
+
+impl std::str::FromStr for MergeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "last_writer_wins" => Ok(Self::LastWriterWins),
+            "namespaced" => Ok(Self::Namespaced),
+            "append" => Ok(Self::Append),
+            "fail_on_conflict" => Ok(Self::FailOnConflict),
+            other => Err(format!(
+                "unknown merge_strategy '{other}'; expected one of: \
+                 last_writer_wins, namespaced, append, fail_on_conflict"
+            )),
+        }
+    }
+}
+
+/// When the engine may stop waiting for parallel branches.
+///
+/// Selected with the `join_policy` attribute on the fan-in node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinPolicy {
+    /// Wait for every branch (`wait_all`, the default).
+    WaitAll,
+    /// Continue as soon as one branch succeeds (`first_success`).
+    FirstSuccess,
+    /// Continue as soon as N branches succeed (`quorum:N`).
+    Quorum(usize),
+}
+
+impl std::str::FromStr for JoinPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().split_once(':') {
+            Some(("quorum", n)) => match n.trim().parse::<usize>() {
+                Ok(n) if n > 0 => Ok(Self::Quorum(n)),
+                _ => Err(format!("invalid quorum size '{}' in join_policy", n.trim())),
+            },
+            None if s.trim() == "wait_all" => Ok(Self::WaitAll),
+            None if s.trim() == "first_success" => Ok(Self::FirstSuccess),
+            _ => Err(format!(
+                "unknown join_policy '{}'; expected one of: wait_all, first_success, quorum:N",
+                s.trim()
+            )),
+        }
+    }
+}
+
+impl JoinPolicy {
+    /// Number of successful branches that lets the engine stop waiting early,
+    /// or `None` when every branch must finish.
+    pub fn early_exit_after(&self) -> Option<usize> {
+        match self {
+            Self::WaitAll => None,
+            Self::FirstSuccess => Some(1),
+            Self::Quorum(n) => Some(*n),
+        }
+    }
+
+    /// Fan-in status given how many of the joined branches succeeded.
+    fn status(&self, succeeded: usize, total: usize) -> StageStatus {
+        match self {
+            Self::WaitAll if total == 0 || succeeded == total => StageStatus::Success,
+            Self::WaitAll if succeeded > 0 => StageStatus::PartialSuccess,
+            Self::WaitAll => StageStatus::Fail,
+            Self::FirstSuccess | Self::Quorum(_) => {
+                let needed = self.early_exit_after().unwrap_or(total);
+                if succeeded >= needed {
+                    StageStatus::Success
+                } else {
+                    StageStatus::Fail
+                }
+            }
+        }
+    }
+}
+
+/// Parse a string attribute of a fan-in node with `FromStr`, falling back to
+/// `default` when the attribute is absent.
+fn parse_attr<T: std::str::FromStr<Err = String>>(
+    node: &PipelineNode,
+    key: &str,
+    default: T,
+) -> Result<T> {
+    match node.raw_attrs.get(key) {
+        Some(AttributeValue::String(s)) => s.parse().map_err(|e| AttractorError::HandlerError {
+            handler: "parallel.fan_in".into(),
+            node: node.id.clone(),
+            message: e,
+        }),
+        _ => Ok(default),
+    }
+}
+
+impl JoinPolicy {
+    /// Read the `join_policy` attribute of a fan-in node.
+    pub fn from_node(node: &PipelineNode) -> Result<Self> {
+        parse_attr(node, "join_policy", Self::WaitAll)
+    }
+}
+
+impl MergeStrategy {
+    /// Read the `merge_strategy` attribute of a fan-in node.
+    pub fn from_node(node: &PipelineNode) -> Result<Self> {
+        parse_attr(node, "merge_strategy", Self::LastWriterWins)
+    }
+}
+
+/// Context keys managed by the engine itself, never merged from branches.
+const ENGINE_KEYS: &[&str] = &[
+    "outcome",
+    "preferred_label",
+    PARALLEL_RESULTS_KEY,
+    PARALLEL_CANCELLED_KEY,
+];
+
+/// Merge branch context updates according to `strategy`.
+///
+/// Returns the merged updates, or the sorted list of conflicting keys when
+/// `strategy` is [`MergeStrategy::FailOnConflict`] and branches disagree.
+pub fn merge_branches(
+    strategy: MergeStrategy,
+    results: &[BranchResult],
+) -> std::result::Result<HashMap<String, serde_json::Value>, Vec<String>> {
+    let entries = results.iter().flat_map(|r| {
+        r.context_updates
+            .iter()
+            .filter(|(k, _)| !ENGINE_KEYS.contains(&k.as_str()))
+            .map(move |(k, v)| (r, k, v))
+    });
+
+    let mut merged: HashMap<String, serde_json::Value> = HashMap::new();
+    match strategy {
+        MergeStrategy::LastWriterWins => {
+            for (_, k, v) in entries {
+                merged.insert(k.clone(), v.clone());
+            }
+        }
+        MergeStrategy::Namespaced => {
+            for (r, k, v) in entries {
+                merged.insert(format!("{}.{}", r.branch_id, k), v.clone());
+            }
+        }
+        MergeStrategy::Append => {
+            let mut collected: HashMap<String, Vec<serde_json::Value>> = HashMap::new();
+            for (_, k, v) in entries {
+                collected.entry(k.clone()).or_default().push(v.clone());
+            }
+            for (k, mut values) in collected {
+                let value = if values.len() == 1 {
+                    values.remove(0)
+                } else {
+                    serde_json::Value::Array(values)
+                };
+                merged.insert(k, value);
+            }
+        }
+        MergeStrategy::FailOnConflict => {
+            let mut conflicts = Vec::new();
+            for (_, k, v) in entries {
+                match merged.get(k) {
+                    Some(existing) if existing != v => {
+                        if !conflicts.contains(k) {
+                            conflicts.push(k.clone());
+                        }
+                    }
+                    _ => {
+                        merged.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+            if !conflicts.is_empty() {
+                conflicts.sort();
+                return Err(conflicts);
+            }
+        }
+    }
+    Ok(merged)
+}
+
+/// Read the branch results stored by the engine for the current fan-in.
+pub async fn branch_results(context: &Context) -> Vec<BranchResult> {
+    context
+        .get(PARALLEL_RESULTS_KEY)
+        .await
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
 /// Handler for "parallel" type nodes (shape="component").
 /// Represents a fan-out point where multiple branches can execute.
-/// In the current implementation, branches are identified by outgoing edges.
+///
+/// Branches are identified by outgoing edges. The handler itself only reports
+/// the branch targets; the execution engine forks each branch concurrently
+/// with an isolated context and joins them at the matching fan-in node.
 pub struct ParallelHandler;
 
 #[async_trait]
@@ -34,12 +273,11 @@ impl NodeHandler for ParallelHandler {
 
         // The parallel handler itself just passes through.
         // The execution engine is responsible for actually forking execution.
-        // For now, suggest the first branch and let the engine handle routing.
         Ok(Outcome {
             status: StageStatus::Success,
             preferred_label: None,
             suggested_next_ids: branch_targets,
-            context_updates: std::collections::HashMap::new(),
+            context_updates: HashMap::new(),
             notes: format!("Fan-out to {} branches", branch_count),
             failure_reason: None,
         })
@@ -48,6 +286,15 @@ impl NodeHandler for ParallelHandler {
 
 /// Handler for "parallel.fan_in" type nodes (shape="tripleoctagon").
 /// Collects results from parallel branches.
+///
+/// Supported node attributes:
+///   - merge_strategy: last_writer_wins (default), namespaced, append, fail_on_conflict
+///   - join_policy: wait_all (default), first_success, quorum:N
+///
+/// The merged branch context is returned as context updates. Under
+/// `wait_all` the status is `Success` when every branch succeeded,
+/// `PartialSuccess` when only some did, and `Fail` when none did; the other
+/// policies succeed once enough branches succeeded.
 pub struct FanInHandler;
 
 #[async_trait]
@@ -59,22 +306,71 @@ impl NodeHandler for FanInHandler {
     async fn execute(
         &self,
         node: &PipelineNode,
-        _context: &Context,
+        context: &Context,
         _graph: &PipelineGraph,
     ) -> Result<Outcome> {
-        tracing::info!(node = %node.id, "Fan-in merge point");
+        let strategy = MergeStrategy::from_node(node)?;
+        let policy = JoinPolicy::from_node(node)?;
+        let results = branch_results(context).await;
+        let succeeded = results.iter().filter(|r| is_success(r.status)).count();
+
+        tracing::info!(
+            node = %node.id,
+            branches = results.len(),
+            succeeded = succeeded,
+            strategy = ?strategy,
+            policy = ?policy,
+            "Fan-in merge point"
+        );
+
+        let mut updates = match merge_branches(strategy, &results) {
+            Ok(updates) => updates,
+            Err(conflicts) => {
+                return Ok(Outcome::fail(format!(
+                    "Parallel branches wrote conflicting values for: {}",
+                    conflicts.join(", ")
+                )));
+            }
+        };
+        updates.insert(
+            format!("{}.branches_succeeded", node.id),
+            serde_json::json!(succeeded),
+        );
+        updates.insert(
+            format!("{}.branches_total", node.id),
+            serde_json::json!(results.len()),
+        );
+
+        let status = policy.status(succeeded, results.len());
 
         Ok(Outcome {
-            status: StageStatus::Success,
+            status,
             preferred_label: None,
             suggested_next_ids: vec![],
-            context_updates: std::collections::HashMap::new(),
-            notes: "Fan-in merge completed".to_string(),
-            failure_reason: None,
+            context_updates: updates,
+            notes: format!(
+                "Fan-in merge completed: {}/{} branches succeeded",
+                succeeded,
+                results.len()
+            ),
+            failure_reason: if status == StageStatus::Fail {
+                Some(format!(
+                    "Join policy {:?} not satisfied: {}/{} branches succeeded",
+                    policy,
+                    succeeded,
+                    results.len()
+                ))
+            } else {
+                None
+            },
         })
     }
 }
 
+fn is_success(status: StageStatus) -> bool {
+    matches!(status, StageStatus::Success | StageStatus::PartialSuccess)
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -82,7 +378,6 @@ impl NodeHandler for FanInHandler {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
 
     fn make_node(id: &str, shape: &str) -> PipelineNode {
         PipelineNode {
@@ -92,6 +387,8 @@ mod tests {
             node_type: None,
             prompt: None,
             max_retries: 0,
+            retry_backoff: None,
+            retry_delay: None,
             goal_gate: false,
             retry_target: None,
             fallback_retry_target: None,
@@ -143,6 +440,176 @@ mod tests {
         let outcome = handler.execute(&node, &ctx, &graph).await.unwrap();
         assert_eq!(outcome.status, StageStatus::Success);
         assert!(outcome.suggested_next_ids.is_empty());
-        assert_eq!(outcome.notes, "Fan-in merge completed");
+        assert!(outcome.notes.starts_with("Fan-in merge completed"));
+    }
+
+    fn branch(id: &str, status: StageStatus) -> BranchResult {
+        BranchResult {
+            branch_id: id.into(),
+            status,
+            completed_nodes: vec![id.into()],
+            context_updates: HashMap::new(),
+            error: None,
+        }
+    }
+
+    fn branch_with(id: &str, updates: &[(&str, serde_json::Value)]) -> BranchResult {
+        let mut b = branch(id, StageStatus::Success);
+        b.context_updates = updates
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect();
+        b
+    }
+
+    async fn fan_in_with(results: Vec<BranchResult>) -> Outcome {
+        fan_in_node_with(make_node("merge", "tripleoctagon"), results).await
+    }
+
+    async fn fan_in_node_with(node: PipelineNode, results: Vec<BranchResult>) -> Outcome {
+        let dot = r#"digraph G { A -> B }"#;
+        let parsed = attractor_dot::parse(dot).unwrap();
+        let graph = PipelineGraph::from_dot(parsed).unwrap();
+        let ctx = Context::default();
+        ctx.set(PARALLEL_RESULTS_KEY, serde_json::to_value(results).unwrap())
+            .await;
+        FanInHandler.execute(&node, &ctx, &graph).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn fan_in_partial_success_when_some_branches_fail() {
+        let outcome = fan_in_with(vec![
+            branch("a", StageStatus::Success),
+            branch("b", StageStatus::Fail),
+        ])
+        .await;
+        assert_eq!(outcome.status, StageStatus::PartialSuccess);
+        assert!(outcome.notes.contains("1/2"));
+    }
+
+    #[tokio::test]
+    async fn fan_in_fails_when_all_branches_fail() {
+        let outcome = fan_in_with(vec![
+            branch("a", StageStatus::Fail),
+            branch("b", StageStatus::Fail),
+        ])
+        .await;
+        assert_eq!(outcome.status, StageStatus::Fail);
+        assert!(outcome.failure_reason.is_some());
+    }
+
+    #[test]
+    fn merge_strategy_and_join_policy_parse() {
+        assert_eq!("namespaced".parse(), Ok(MergeStrategy::Namespaced));
+        assert_eq!("append".parse(), Ok(MergeStrategy::Append));
+        assert!("union".parse::<MergeStrategy>().is_err());
+
+        assert_eq!("wait_all".parse(), Ok(JoinPolicy::WaitAll));
+        assert_eq!("first_success".parse(), Ok(JoinPolicy::FirstSuccess));
+        assert_eq!("quorum:2".parse(), Ok(JoinPolicy::Quorum(2)));
+        assert!("quorum:0".parse::<JoinPolicy>().is_err());
+        assert!("quorum".parse::<JoinPolicy>().is_err());
+    }
+
+    #[test]
+    fn merge_last_writer_wins() {
+        let results = vec![
+            branch_with("a", &[("shared", "a".into()), ("a.result", "ra".into())]),
+            branch_with(
+                "b",
+                &[("shared", "b".into()), ("outcome", "success".into())],
+            ),
+        ];
+        let merged = merge_branches(MergeStrategy::LastWriterWins, &results).unwrap();
+        assert_eq!(merged["shared"], "b");
+        assert_eq!(merged["a.result"], "ra");
+        assert!(!merged.contains_key("outcome"));
+    }
+
+    #[test]
+    fn merge_namespaced_prefixes_branch_id() {
+        let results = vec![
+            branch_with("a", &[("shared", "a".into())]),
+            branch_with("b", &[("shared", "b".into())]),
+        ];
+        let merged = merge_branches(MergeStrategy::Namespaced, &results).unwrap();
+        assert_eq!(merged["a.shared"], "a");
+        assert_eq!(merged["b.shared"], "b");
+        assert!(!merged.contains_key("shared"));
+    }
+
+    #[test]
+    fn merge_append_collects_collisions() {
+        let results = vec![
+            branch_with("a", &[("shared", "a".into()), ("only_a", 1.into())]),
+            branch_with("b", &[("shared", "b".into())]),
+        ];
+        let merged = merge_branches(MergeStrategy::Append, &results).unwrap();
+        assert_eq!(merged["shared"], serde_json::json!(["a", "b"]));
+        assert_eq!(merged["only_a"], 1);
+    }
+
+    #[test]
+    fn merge_fail_on_conflict_reports_keys() {
+        let same = vec![
+            branch_with("a", &[("shared", "x".into())]),
+            branch_with("b", &[("shared", "x".into())]),
+        ];
+        assert!(merge_branches(MergeStrategy::FailOnConflict, &same).is_ok());
+
+        let differ = vec![
+            branch_with("a", &[("shared", "a".into())]),
+            branch_with("b", &[("shared", "b".into())]),
+        ];
+        assert_eq!(
+            merge_branches(MergeStrategy::FailOnConflict, &differ),
+            Err(vec!["shared".to_string()])
+        );
+    }
+
+    #[tokio::test]
+    async fn fan_in_applies_merge_strategy_attribute() {
+        let mut node = make_node("merge", "tripleoctagon");
+        node.raw_attrs.insert(
+            "merge_strategy".into(),
+            AttributeValue::String("fail_on_conflict".into()),
+        );
+        let outcome = fan_in_node_with(
+            node,
+            vec![
+                branch_with("a", &[("shared", "a".into())]),
+                branch_with("b", &[("shared", "b".into())]),
+            ],
+        )
+        .await;
+        assert_eq!(outcome.status, StageStatus::Fail);
+        assert!(outcome.failure_reason.unwrap().contains("shared"));
+    }
+
+    #[tokio::test]
+    async fn fan_in_quorum_policy_sets_status() {
+        let mut node = make_node("merge", "tripleoctagon");
+        node.raw_attrs.insert(
+            "join_policy".into(),
+            AttributeValue::String("quorum:2".into()),
+        );
+        let results = vec![
+            branch("a", StageStatus::Success),
+            branch("b", StageStatus::Fail),
+            branch("c", StageStatus::Success),
+        ];
+        let outcome = fan_in_node_with(node.clone(), results).await;
+        assert_eq!(outcome.status, StageStatus::Success);
+        assert_eq!(outcome.context_updates["merge.branches_succeeded"], 2);
+
+        let outcome = fan_in_node_with(
+            node,
+            vec![
+                branch("a", StageStatus::Success),
+                branch("b", StageStatus::Fail),
+            ],
+        )
+        .await;
+        assert_eq!(outcome.status, StageStatus::Fail);
     }
 }
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use attractor_dot::AttributeValue;
+use attractor_tools::{
+    ExecutionEnvironment, LocalExecutionEnvironment, OverlayExecutionEnvironment, SandboxConfig,
+    SandboxExecutionEnvironment,
+};
+use attractor_types::{AttractorError, Context, Result};
+
+use crate::graph::{PipelineGraph, PipelineNode};
+
+// ---------------------------------------------------------------------------
+// NodeEnvironment — where agent and tool nodes run their commands
+//
+// Selected by the node's `sandbox` attribute, falling back to the graph's:
+//   - "none" (default): directly on the host, in the workdir
+//   - "bubblewrap" / "bwrap": read-only root, writable workdir, no network
+//     unless sandbox_network=true (Linux, needs `bwrap`)
+//   - "overlay": on a scratch copy of the workdir. Changes are recorded as
+//     <node>.changes / <node>.overlay_diff and copied back only when
+//     overlay_apply=true and the node succeeds; otherwise the copy is kept
+//     at <node>.overlay_dir for review.
+// ---------------------------------------------------------------------------
+
+pub(crate) enum NodeEnvironment {
+    Local(LocalExecutionEnvironment),
+    Bubblewrap(SandboxExecutionEnvironment),
+    Overlay(OverlayExecutionEnvironment),
+}
+
+impl NodeEnvironment {
+    /// Build the environment a node asks for, rooted at the context's
+    /// `workdir` (or the current directory).
+    pub(crate) async fn for_node(
+        node: &PipelineNode,
+        graph: &PipelineGraph,
+        context: &Context,
+        handler: &str,
+    ) -> Result<Self> {
+        let err = |message: String| AttractorError::HandlerError {
+            handler: handler.into(),
+            node: node.id.clone(),
+            message,
+        };
+        let workdir = match context.get("workdir").await {
+            Some(serde_json::Value::String(dir)) => PathBuf::from(dir),
+            _ => std::env::current_dir()
+                .map_err(|e| err(format!("Failed to resolve working directory: {}", e)))?,
+        };
+
+        let mode = string_attr(&node.raw_attrs, "sandbox")
+            .or_else(|| string_attr(&graph.attrs, "sandbox"))
+            .unwrap_or("none");
+        match mode {
+            "none" => Ok(Self::Local(LocalExecutionEnvironment::new(workdir))),
+            "bubblewrap" | "bwrap" => {
+                let config = SandboxConfig {
+                    allow_network: bool_attr(node, "sandbox_network"),
+                    ..Default::default()
+                };
+                SandboxExecutionEnvironment::new(workdir, config)
+                    .map(Self::Bubblewrap)
+                    .map_err(|e| err(e.to_string()))
+            }
+            "overlay" => OverlayExecutionEnvironment::new(workdir)
+                .await
+                .map(Self::Overlay)
+                .map_err(|e| err(format!("Failed to create overlay: {}", e))),
+            other => Err(err(format!(
+                "Unknown sandbox '{}' (expected none, bubblewrap or overlay)",
+                other
+            ))),
+        }
+    }
+
+    pub(crate) fn is_local(&self) -> bool {
+        matches!(self, Self::Local(_))
+    }
+
+    pub(crate) fn as_env(&self) -> &dyn ExecutionEnvironment {
+        match self {
+            Self::Local(env) => env,
+            Self::Bubblewrap(env) => env,
+            Self::Overlay(env) => env,
+        }
+    }
+
+    /// An owned handle to the same environment, for an agent session.
+    pub(crate) fn boxed(&self) -> Box<dyn ExecutionEnvironment> {
+        match self {
+            Self::Local(env) => Box::new(env.clone()),
+            Self::Bubblewrap(env) => Box::new(env.clone()),
+            Self::Overlay(env) => Box::new(env.clone()),
+        }
+    }
+
+    /// Record an overlay's changes in `updates`, applying them to the workdir
+    /// when the node succeeded and has `overlay_apply=true`.
+    pub(crate) async fn finish(
+        self,
+        node: &PipelineNode,
+        succeeded: bool,
+        updates: &mut HashMap<String, serde_json::Value>,
+    ) -> Result<()> {
+        let Self::Overlay(overlay) = self else {
+            return Ok(());
+        };
+        let changes = overlay.changes().await?;
+        let listed: Vec<String> = changes
+            .iter()
+            .map(|c| format!("{} {}", c.kind.code(), c.path.display()))
+            .collect();
+        updates.insert(format!("{}.changes", node.id), serde_json::json!(listed));
+        updates.insert(
+            // `<node>.diff` is the engine's record of changes to the workdir
+            format!("{}.overlay_diff", node.id),
+            serde_json::Value::String(overlay.diff().await?),
+        );
+
+        let apply = succeeded && bool_attr(node, "overlay_apply");
+        if apply {
+            overlay.apply().await?;
+            tracing::info!(node = %node.id, changes = changes.len(), "Applied overlay changes");
+            overlay.discard().await?;
+        } else {
+            tracing::info!(
+                node = %node.id,
+                changes = changes.len(),
+                dir = %overlay.scratch().display(),
+                "Overlay changes kept for review"
+            );
+            updates.insert(
+                format!("{}.overlay_dir", node.id),
+                serde_json::Value::String(overlay.scratch().to_string_lossy().into_owned()),
+            );
+        }
+        updates.insert(
+            format!("{}.overlay_applied", node.id),
+            serde_json::Value::Bool(apply),
+        );
+        Ok(())
+    }
+}
+
+fn string_attr<'a>(attrs: &'a HashMap<String, AttributeValue>, key: &str) -> Option<&'a str> {
+    match attrs.get(key) {
+        Some(AttributeValue::String(s)) => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+fn bool_attr(node: &PipelineNode, key: &str) -> bool {
+    match node.raw_attrs.get(key) {
+        Some(AttributeValue::Boolean(b)) => *b,
+        Some(AttributeValue::String(s)) => s == "true",
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tests::{make_minimal_graph, make_node};
+
+    fn node_with(attrs: &[(&str, &str)]) -> PipelineNode {
+        let attrs = attrs
+            .iter()
+            .map(|(k, v)| (k.to_string(), AttributeValue::String(v.to_string())))
+            .collect();
+        make_node("n", "parallelogram", None, attrs)
+    }
+
+    #[tokio::test]
+    async fn defaults_to_local_and_rejects_unknown_modes() {
+        let ctx = Context::default();
+        let graph = make_minimal_graph();
+        let env = NodeEnvironment::for_node(&node_with(&[]), &graph, &ctx, "tool")
+            .await
+            .unwrap();
+        assert!(env.is_local());
+
+        let err = NodeEnvironment::for_node(&node_with(&[("sandbox", "vm")]), &graph, &ctx, "tool")
+            .await
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("Unknown sandbox 'vm'"));
+    }
+
+    #[tokio::test]
+    async fn overlay_records_changes_and_applies_on_request() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        let ctx = Context::default();
+        ctx.set(
+            "workdir",
+            serde_json::Value::String(dir.path().to_string_lossy().into()),
+        )
+        .await;
+        let graph = make_minimal_graph();
+
+        for apply in ["false", "true"] {
+            let node = node_with(&[("sandbox", "overlay"), ("overlay_apply", apply)]);
+            let env = NodeEnvironment::for_node(&node, &graph, &ctx, "tool")
+                .await
+                .unwrap();
+            env.as_env()
+                .exec_command("echo two > a.txt", 5000, None, None)
+                .await
+                .unwrap();
+            let mut updates = HashMap::new();
+            env.finish(&node, true, &mut updates).await.unwrap();
+
+            assert_eq!(updates["n.changes"], serde_json::json!(["M a.txt"]));
+            assert!(updates["n.overlay_diff"].as_str().unwrap().contains("+two"));
+            assert_eq!(updates["n.overlay_applied"], apply == "true");
+            assert_eq!(updates.contains_key("n.overlay_dir"), apply == "false");
+            if let Some(serde_json::Value::String(scratch)) = updates.get("n.overlay_dir") {
+                std::fs::remove_dir_all(scratch).unwrap();
+            }
+        }
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "two\n"
+        );
+    }
+}
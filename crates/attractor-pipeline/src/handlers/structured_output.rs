@@ -0,0 +1,406 @@
+//! Structured JSON output for codergen nodes (`output_schema` attribute).
+//!
+//! The schema is given inline (`output_schema="{...}"`) or as a path to a JSON
+//! file, resolved against the `workdir` context key. The handler appends
+//! [`OutputSchema::prompt_instructions`] to the prompt, then calls
+//! [`OutputSchema::extract`] on the response and flattens the object into
+//! `<node>.<field>` context keys with [`flatten_fields`].
+//!
+//! Validation covers the commonly used JSON Schema keywords: `type`, `enum`,
+//! `const`, `required`, `properties`, `additionalProperties: false`, `items`,
+//! `minimum`, `maximum`, `minLength`, `maxLength`, `minItems`, `maxItems` and
+//! `pattern`. Other keywords are ignored.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use attractor_dot::AttributeValue;
+use attractor_types::{AttractorError, Result};
+use serde_json::Value;
+
+use crate::graph::PipelineNode;
+
+/// A JSON Schema the node's response must satisfy.
+#[derive(Debug, Clone)]
+pub struct OutputSchema {
+    schema: Value,
+}
+
+impl OutputSchema {
+    pub fn new(schema: Value) -> Self {
+        Self { schema }
+    }
+
+    /// Load the node's `output_schema`, if it has one.
+    ///
+    /// A value starting with `{` is parsed as inline JSON; anything else is a
+    /// file path, relative to `workdir` when given.
+    pub fn from_node(node: &PipelineNode, workdir: Option<&str>) -> Result<Option<Self>> {
+        let Some(AttributeValue::String(raw)) = node.raw_attrs.get("output_schema") else {
+            return Ok(None);
+        };
+        let raw = raw.trim();
+        let schema_error = |message: String| AttractorError::HandlerError {
+            handler: "output_schema".into(),
+            node: node.id.clone(),
+            message,
+        };
+
+        let source = if raw.starts_with('{') {
+            raw.to_string()
+        } else {
+            let path = match workdir {
+                Some(dir) => Path::new(dir).join(raw),
+                None => Path::new(raw).to_path_buf(),
+            };
+            std::fs::read_to_string(&path).map_err(|e| {
+                schema_error(format!(
+                    "Failed to read output_schema {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?
+        };
+
+        let schema: Value = serde_json::from_str(&source)
+            .map_err(|e| schema_error(format!("output_schema is not valid JSON: {}", e)))?;
+        Ok(Some(Self::new(schema)))
+    }
+
+    /// Prompt suffix asking the model to answer with a matching JSON object.
+    pub fn prompt_instructions(&self) -> String {
+        let pretty = serde_json::to_string_pretty(&self.schema).unwrap_or_default();
+        format!(
+            "\n\nEnd your response with a single JSON object in a ```json code block \
+             that conforms to this JSON Schema:\n```json\n{}\n```",
+            pretty
+        )
+    }
+
+    /// Extract the JSON object from `response` and validate it.
+    ///
+    /// The error describes what was missing or invalid, suitable for a retry
+    /// failure reason.
+    pub fn extract(&self, response: &str) -> std::result::Result<Value, String> {
+        let value = extract_json(response).ok_or("no JSON object found in response")?;
+        let errors = validate(&self.schema, &value);
+        if errors.is_empty() {
+            Ok(value)
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+/// Find the JSON object in an LLM response.
+///
+/// Prefers the last ```json fenced block; otherwise takes the last top-level
+/// `{...}` in the text that parses as JSON.
+pub fn extract_json(text: &str) -> Option<Value> {
+    let fenced = text
+        .split("```json")
+        .skip(1)
+        .filter_map(|block| block.split("```").next())
+        .filter_map(|body| serde_json::from_str::<Value>(body.trim()).ok())
+        .filter(Value::is_object)
+        .last();
+    if fenced.is_some() {
+        return fenced;
+    }
+
+    let mut last = None;
+    let mut pos = 0;
+    while let Some(offset) = text[pos..].find('{') {
+        let start = pos + offset;
+        let mut stream = serde_json::Deserializer::from_str(&text[start..]).into_iter::<Value>();
+        match stream.next() {
+            Some(Ok(value)) if value.is_object() => {
+                pos = start + stream.byte_offset();
+                last = Some(value);
+            }
+            _ => pos = start + 1,
+        }
+    }
+    last
+}
+
+/// Validate `value` against `schema`, returning one message per violation.
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(schema, value, "$", &mut errors);
+    errors
+}
+
+fn validate_at(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        return;
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => vec![],
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| has_type(value, t)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            errors.push(format!(
+                "{}: {} is not one of {}",
+                path,
+                value,
+                Value::Array(options.clone())
+            ));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if expected != value {
+            errors.push(format!("{}: expected {}, got {}", path, expected, value));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(key) {
+                        errors.push(format!("{}: missing required field '{}'", path, key));
+                    }
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, field) in map {
+                let field_path = format!("{}.{}", path, key);
+                match properties.and_then(|p| p.get(key)) {
+                    Some(field_schema) => validate_at(field_schema, field, &field_path, errors),
+                    None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                        errors.push(format!("{}: unexpected field", field_path));
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_bound(
+                schema,
+                "minItems",
+                items.len() as f64,
+                path,
+                errors,
+                |n, b| n >= b,
+            );
+            check_bound(
+                schema,
+                "maxItems",
+                items.len() as f64,
+                path,
+                errors,
+                |n, b| n <= b,
+            );
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(item_schema, item, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as f64;
+            check_bound(schema, "minLength", len, path, errors, |n, b| n >= b);
+            check_bound(schema, "maxLength", len, path, errors, |n, b| n <= b);
+            if let Some(Value::String(pattern)) = schema.get("pattern") {
+                match regex::Regex::new(pattern) {
+                    Ok(re) if !re.is_match(s) => {
+                        errors.push(format!("{}: does not match pattern '{}'", path, pattern));
+                    }
+                    Ok(_) => {}
+                    Err(e) => {
+                        errors.push(format!("{}: invalid pattern '{}': {}", path, pattern, e))
+                    }
+                }
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            check_bound(schema, "minimum", n, path, errors, |n, b| n >= b);
+            check_bound(schema, "maximum", n, path, errors, |n, b| n <= b);
+        }
+        _ => {}
+    }
+}
+
+fn check_bound(
+    schema: &serde_json::Map<String, Value>,
+    keyword: &str,
+    actual: f64,
+    path: &str,
+    errors: &mut Vec<String>,
+    ok: impl Fn(f64, f64) -> bool,
+) {
+    if let Some(bound) = schema.get(keyword).and_then(Value::as_f64) {
+        if !ok(actual, bound) {
+            errors.push(format!(
+                "{}: {} violates {} {}",
+                path, actual, keyword, bound
+            ));
+        }
+    }
+}
+
+fn has_type(value: &Value, t: &str) -> bool {
+    match t {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Flatten a JSON object into `<node_id>.<field>` context keys.
+///
+/// Nested objects become dotted keys (`<node>.a.b`); arrays and scalars are
+/// stored as-is.
+pub fn flatten_fields(node_id: &str, value: &Value) -> HashMap<String, Value> {
+    let mut out = HashMap::new();
+    flatten_into(node_id, value, &mut out);
+    out
+}
+
+fn flatten_into(prefix: &str, value: &Value, out: &mut HashMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map {
+                flatten_into(&format!("{}.{}", prefix, key), field, out);
+            }
+        }
+        other => {
+            out.insert(prefix.to_string(), other.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tests::make_node;
+    use serde_json::json;
+
+    fn review_schema() -> OutputSchema {
+        OutputSchema::new(json!({
+            "type": "object",
+            "required": ["severity", "issues"],
+            "properties": {
+                "severity": { "type": "string", "enum": ["low", "medium", "high"] },
+                "issues": { "type": "integer", "minimum": 0 },
+                "details": {
+                    "type": "object",
+                    "properties": { "file": { "type": "string" } }
+                }
+            }
+        }))
+    }
+
+    #[test]
+    fn extract_prefers_last_fenced_block() {
+        let text = "Example: ```json\n{\"severity\": \"low\"}\n```\n\
+                    Final:\n```json\n{\"severity\": \"high\", \"issues\": 2}\n```";
+        assert_eq!(
+            extract_json(text),
+            Some(json!({"severity": "high", "issues": 2}))
+        );
+    }
+
+    #[test]
+    fn extract_falls_back_to_bare_object() {
+        let text =
+            "I checked {nothing here}. Result: {\"severity\": \"medium\", \"issues\": 1} done";
+        assert_eq!(
+            extract_json(text),
+            Some(json!({"severity": "medium", "issues": 1}))
+        );
+        assert_eq!(extract_json("no json at all"), None);
+    }
+
+    #[test]
+    fn extract_validates_against_schema() {
+        let schema = review_schema();
+        let ok = schema
+            .extract("```json\n{\"severity\": \"high\", \"issues\": 3}\n```")
+            .unwrap();
+        assert_eq!(ok["severity"], "high");
+
+        let err = schema
+            .extract("```json\n{\"severity\": \"urgent\", \"issues\": -1}\n```")
+            .unwrap_err();
+        assert!(err.contains("$.severity"), "{err}");
+        assert!(err.contains("minimum"), "{err}");
+
+        let err = schema.extract("{\"issues\": 1}").unwrap_err();
+        assert!(err.contains("missing required field 'severity'"), "{err}");
+
+        let err = schema.extract("plain text").unwrap_err();
+        assert!(err.contains("no JSON object"), "{err}");
+    }
+
+    #[test]
+    fn flatten_nested_fields() {
+        let flat = flatten_fields(
+            "review",
+            &json!({"severity": "high", "details": {"file": "main.rs"}, "tags": ["a"]}),
+        );
+        assert_eq!(flat["review.severity"], json!("high"));
+        assert_eq!(flat["review.details.file"], json!("main.rs"));
+        assert_eq!(flat["review.tags"], json!(["a"]));
+    }
+
+    #[test]
+    fn from_node_reads_inline_and_file_schemas() {
+        let node = make_node("n", "box", None, HashMap::new());
+        assert!(OutputSchema::from_node(&node, None).unwrap().is_none());
+
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "output_schema".to_string(),
+            AttributeValue::String(r#"{"type": "object"}"#.into()),
+        );
+        let node = make_node("n", "box", None, attrs);
+        assert!(OutputSchema::from_node(&node, None).unwrap().is_some());
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("schema.json"), r#"{"required": ["x"]}"#).unwrap();
+        let mut attrs = HashMap::new();
+        attrs.insert(
+            "output_schema".to_string(),
+            AttributeValue::String("schema.json".into()),
+        );
+        let node = make_node("n", "box", None, attrs);
+        let schema = OutputSchema::from_node(&node, dir.path().to_str())
+            .unwrap()
+            .unwrap();
+        assert!(schema.extract("{\"y\": 1}").is_err());
+    }
+}
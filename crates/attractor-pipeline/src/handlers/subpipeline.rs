@@ -0,0 +1,302 @@
+//! Sub-pipeline handler: one pipeline calling another.
+//!
+//! A `node_type="subpipeline"` node runs another .dot file as a child
+//! pipeline, like a function call. The child sees only the parent context
+//! keys listed in `inputs`, and only the keys listed in `outputs` come back.
+//!
+//! Supported node attributes:
+//!   - sub_pipeline: the .dot file to run, relative to the parent's file
+//!     (required; `subgraph="cluster_name"` runs an inline cluster instead)
+//!   - inputs: comma-separated keys copied into the child context, each
+//!     `key` or `child_key=parent_key` (default: the whole parent context)
+//!   - outputs: comma-separated keys copied back from the child's final
+//!     context, each `key` or `parent_key=child_key` (default: none)
+//!
+//! Run settings (`workdir`, `dry_run`, safety limits, the artifact store and
+//! the run's worktree) always reach the child. The child's checkpoints are
+//! nested under the parent's logs dir (see [`crate::checkpoint`]).
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use attractor_dot::AttributeValue;
+use attractor_types::{AttractorError, Context, Outcome, Result};
+
+use crate::artifacts::ARTIFACTS_DIR_KEY;
+use crate::graph::{PipelineGraph, PipelineNode};
+use crate::handler::NodeHandler;
+use crate::validation::validate_or_raise;
+
+/// The handler type of sub-pipeline nodes.
+pub const SUB_PIPELINE_TYPE: &str = "subpipeline";
+
+/// Context keys every child run inherits, whatever its `inputs`.
+const RUN_KEYS: &[&str] = &[
+    "workdir",
+    "dry_run",
+    "max_steps",
+    "max_budget_usd",
+    ARTIFACTS_DIR_KEY,
+];
+
+/// Load and validate the child pipeline of `node`: the inline cluster named
+/// by `subgraph`, or the .dot file named by `sub_pipeline`. Relative paths
+/// are resolved against the directory of the parent's .dot file, or else the
+/// `workdir` in `snapshot`.
+pub(crate) fn load_child(
+    node: &PipelineNode,
+    graph: &PipelineGraph,
+    snapshot: &HashMap<String, serde_json::Value>,
+    handler_type: &str,
+) -> Result<PipelineGraph> {
+    let error = |message: String| AttractorError::HandlerError {
+        handler: handler_type.into(),
+        node: node.id.clone(),
+        message,
+    };
+    let child = match (attr(node, "subgraph"), attr(node, "sub_pipeline")) {
+        (Some(name), None) => graph
+            .subpipeline(name)
+            .cloned()
+            .ok_or_else(|| error(format!("No cluster named '{}' in the graph", name)))?,
+        (None, Some(path)) => {
+            let path = resolve_path(Path::new(path), graph, snapshot);
+            PipelineGraph::load(&path, &HashMap::new())?
+        }
+        (Some(_), Some(_)) => {
+            return Err(error(
+                "Set either 'subgraph' or 'sub_pipeline', not both".into(),
+            ))
+        }
+        (None, None) => {
+            return Err(error(format!(
+                "{} node needs a 'subgraph' or 'sub_pipeline' attribute",
+                handler_type
+            )))
+        }
+    };
+    validate_or_raise(&child)
+        .map_err(|e| error(format!("Child pipeline '{}' is invalid: {}", child.name, e)))?;
+    Ok(child)
+}
+
+fn attr<'a>(node: &'a PipelineNode, key: &str) -> Option<&'a str> {
+    match node.raw_attrs.get(key) {
+        Some(AttributeValue::String(s)) if !s.trim().is_empty() => Some(s.as_str()),
+        _ => None,
+    }
+}
+
+fn resolve_path(
+    path: &Path,
+    graph: &PipelineGraph,
+    snapshot: &HashMap<String, serde_json::Value>,
+) -> PathBuf {
+    if path.is_absolute() {
+        return path.to_path_buf();
+    }
+    let base = graph
+        .source
+        .as_deref()
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .or_else(|| {
+            snapshot
+                .get("workdir")
+                .and_then(|v| v.as_str())
+                .map(PathBuf::from)
+        });
+    match base {
+        Some(base) => base.join(path),
+        None => path.to_path_buf(),
+    }
+}
+
+/// Parse a `key, target=source` list into `(target, source)` pairs.
+fn key_map(spec: &str) -> Vec<(String, String)> {
+    spec.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((target, source)) => (target.trim().to_string(), source.trim().to_string()),
+            None => (entry.to_string(), entry.to_string()),
+        })
+        .collect()
+}
+
+/// A sub-pipeline node's child pipeline and context mapping.
+#[derive(Debug, Clone)]
+pub struct SubPipelineConfig {
+    pub child: PipelineGraph,
+    /// `(child_key, parent_key)` pairs, or `None` to pass the whole context.
+    pub inputs: Option<Vec<(String, String)>>,
+    /// `(parent_key, child_key)` pairs.
+    pub outputs: Vec<(String, String)>,
+}
+
+impl SubPipelineConfig {
+    /// Read the mapping of `node` and load its child pipeline.
+    pub fn from_node(
+        node: &PipelineNode,
+        graph: &PipelineGraph,
+        snapshot: &HashMap<String, serde_json::Value>,
+    ) -> Result<Self> {
+        Ok(Self {
+            child: load_child(node, graph, snapshot, SUB_PIPELINE_TYPE)?,
+            inputs: attr(node, "inputs").map(key_map),
+            outputs: attr(node, "outputs").map(key_map).unwrap_or_default(),
+        })
+    }
+
+    /// The starting context of the child run, taken from the parent's.
+    pub fn child_context(
+        &self,
+        parent: &HashMap<String, serde_json::Value>,
+    ) -> HashMap<String, serde_json::Value> {
+        let Some(inputs) = &self.inputs else {
+            return parent.clone();
+        };
+        let mut values: HashMap<String, serde_json::Value> = parent
+            .iter()
+            .filter(|(key, _)| RUN_KEYS.contains(&key.as_str()) || key.starts_with("worktree."))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        for (target, source) in inputs {
+            if let Some(value) = parent.get(source) {
+                values.insert(target.clone(), value.clone());
+            }
+        }
+        values
+    }
+
+    /// The parent context updates from the child's final context: the
+    /// mapped `outputs`, plus the worktree tip if the child moved it.
+    pub fn parent_updates(
+        &self,
+        child: &HashMap<String, serde_json::Value>,
+    ) -> HashMap<String, serde_json::Value> {
+        let mut updates: HashMap<String, serde_json::Value> = self
+            .outputs
+            .iter()
+            .filter_map(|(target, source)| Some((target.clone(), child.get(source)?.clone())))
+            .collect();
+        if let Some(head) = child.get("worktree.head") {
+            updates.insert("worktree.head".into(), head.clone());
+        }
+        updates
+    }
+}
+
+/// Handler for "subpipeline" type nodes.
+///
+/// The handler only checks the node's configuration; the execution engine
+/// runs the child pipeline and reports its result as the node's outcome.
+pub struct SubPipelineHandler;
+
+#[async_trait]
+impl NodeHandler for SubPipelineHandler {
+    fn handler_type(&self) -> &str {
+        SUB_PIPELINE_TYPE
+    }
+
+    async fn execute(
+        &self,
+        node: &PipelineNode,
+        context: &Context,
+        graph: &PipelineGraph,
+    ) -> Result<Outcome> {
+        let config = SubPipelineConfig::from_node(node, graph, &context.snapshot().await)?;
+        tracing::info!(node = %node.id, child = %config.child.name, "Sub-pipeline");
+        Ok(Outcome::success(format!("Calling '{}'", config.child.name)))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tests::make_node;
+    use serde_json::json;
+
+    const CHILD: &str = r#"digraph commit {
+        start [shape="Mdiamond"]
+        done [shape="Msquare"]
+        start -> done
+    }"#;
+
+    fn sub_node(attrs: &[(&str, &str)]) -> PipelineNode {
+        let attrs = attrs
+            .iter()
+            .map(|(k, v)| (k.to_string(), AttributeValue::String(v.to_string())))
+            .collect();
+        make_node("sub", "box", None, attrs)
+    }
+
+    #[test]
+    fn inputs_and_outputs_map_context_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("commit.dot"), CHILD).unwrap();
+        let workdir = json!(dir.path().to_string_lossy());
+        let parent = HashMap::from([
+            ("workdir".to_string(), workdir.clone()),
+            ("worktree.head".to_string(), json!("abc")),
+            ("plan.result".to_string(), json!("the plan")),
+            ("secret".to_string(), json!("x")),
+        ]);
+        let graph =
+            PipelineGraph::from_dot(attractor_dot::parse("digraph G { A }").unwrap()).unwrap();
+
+        let node = sub_node(&[
+            ("sub_pipeline", "commit.dot"),
+            ("inputs", "plan=plan.result, missing"),
+            ("outputs", "commit.sha=sha"),
+        ]);
+        let config = SubPipelineConfig::from_node(&node, &graph, &parent).unwrap();
+        assert_eq!(config.child.name, "commit");
+
+        let child = config.child_context(&parent);
+        assert_eq!(child["plan"], "the plan");
+        assert_eq!(child["workdir"], workdir);
+        assert_eq!(child["worktree.head"], "abc");
+        assert!(!child.contains_key("secret"));
+        assert!(!child.contains_key("missing"));
+
+        let finished = HashMap::from([
+            ("sha".to_string(), json!("123")),
+            ("scratch".to_string(), json!(1)),
+            ("worktree.head".to_string(), json!("def")),
+        ]);
+        let updates = config.parent_updates(&finished);
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates["commit.sha"], "123");
+        assert_eq!(updates["worktree.head"], "def");
+
+        // Without `inputs` the child gets everything
+        let node = sub_node(&[("sub_pipeline", "commit.dot")]);
+        let config = SubPipelineConfig::from_node(&node, &graph, &parent).unwrap();
+        assert_eq!(config.child_context(&parent), parent);
+    }
+
+    #[tokio::test]
+    async fn handler_requires_a_loadable_child() {
+        let graph =
+            PipelineGraph::from_dot(attractor_dot::parse("digraph G { A }").unwrap()).unwrap();
+        let ctx = Context::default();
+        assert!(SubPipelineHandler
+            .execute(&sub_node(&[]), &ctx, &graph)
+            .await
+            .is_err());
+        assert!(SubPipelineHandler
+            .execute(
+                &sub_node(&[("sub_pipeline", "/nonexistent.dot")]),
+                &ctx,
+                &graph
+            )
+            .await
+            .is_err());
+    }
+}
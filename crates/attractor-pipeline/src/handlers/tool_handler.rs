@@ -6,6 +6,7 @@ use attractor_types::{AttractorError, Context, Outcome, Result, StageStatus};
 
 use crate::graph::{PipelineGraph, PipelineNode};
 use crate::handler::NodeHandler;
+use crate::handlers::sandbox::NodeEnvironment;
 
 // ---------------------------------------------------------------------------
 // ToolHandler — executes a shell command (parallelogram shape)
@@ -23,7 +24,7 @@ impl NodeHandler for ToolHandler {
         &self,
         node: &PipelineNode,
         context: &Context,
-        _graph: &PipelineGraph,
+        graph: &PipelineGraph,
     ) -> Result<Outcome> {
         let command = node
             .raw_attrs
@@ -74,40 +75,29 @@ impl NodeHandler for ToolHandler {
             });
         }
 
-        // Build the shell command
-        let mut cmd = tokio::process::Command::new("sh");
-        cmd.arg("-c").arg(&command);
-        cmd.stdout(std::process::Stdio::piped());
-        cmd.stderr(std::process::Stdio::piped());
-
-        // Set working directory from context
-        let snapshot = context.snapshot().await;
-        if let Some(serde_json::Value::String(dir)) = snapshot.get("workdir") {
-            cmd.current_dir(dir);
-        }
-
-        let child = cmd.spawn().map_err(|e| AttractorError::HandlerError {
-            handler: "tool".into(),
-            node: node.id.clone(),
-            message: format!("Failed to spawn command: {}", e),
-        })?;
-
         // Apply timeout if configured on the node, default 5 minutes
         let timeout_dur = node.timeout.unwrap_or(std::time::Duration::from_secs(300));
-        let output = tokio::time::timeout(timeout_dur, child.wait_with_output())
-            .await
-            .map_err(|_| AttractorError::CommandTimeout {
-                timeout_ms: timeout_dur.as_millis() as u64,
-            })?
-            .map_err(|e| AttractorError::HandlerError {
-                handler: "tool".into(),
-                node: node.id.clone(),
-                message: format!("Command execution failed: {}", e),
-            })?;
+        let env = NodeEnvironment::for_node(node, graph, context, "tool").await?;
 
-        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-        let exit_code = output.status.code().unwrap_or(-1);
+        let (stdout, stderr, exit_code) = if env.is_local() {
+            run_on_host(node, &command, context, timeout_dur).await?
+        } else {
+            let result = env
+                .as_env()
+                .exec_command(&command, timeout_dur.as_millis() as u64, None, None)
+                .await
+                .map_err(|e| AttractorError::HandlerError {
+                    handler: "tool".into(),
+                    node: node.id.clone(),
+                    message: format!("Command execution failed: {}", e),
+                })?;
+            if result.timed_out {
+                return Err(AttractorError::CommandTimeout {
+                    timeout_ms: timeout_dur.as_millis() as u64,
+                });
+            }
+            (result.stdout, result.stderr, result.exit_code)
+        };
 
         tracing::info!(
             node = %node.id,
@@ -117,7 +107,7 @@ impl NodeHandler for ToolHandler {
             "Tool command completed"
         );
 
-        let status = if output.status.success() {
+        let status = if exit_code == 0 {
             StageStatus::Success
         } else {
             StageStatus::Fail
@@ -147,6 +137,9 @@ impl NodeHandler for ToolHandler {
             );
         }
 
+        env.finish(node, status == StageStatus::Success, &mut updates)
+            .await?;
+
         // Combine stdout + stderr for notes, truncating if very long
         let combined = if stderr.is_empty() {
             stdout
@@ -181,6 +174,47 @@ impl NodeHandler for ToolHandler {
     }
 }
 
+/// Run `command` with `sh -c` directly on the host, in the context's workdir.
+async fn run_on_host(
+    node: &PipelineNode,
+    command: &str,
+    context: &Context,
+    timeout_dur: std::time::Duration,
+) -> Result<(String, String, i32)> {
+    let mut cmd = tokio::process::Command::new("sh");
+    cmd.arg("-c").arg(command);
+    cmd.stdout(std::process::Stdio::piped());
+    cmd.stderr(std::process::Stdio::piped());
+
+    // Set working directory from context
+    if let Some(serde_json::Value::String(dir)) = context.get("workdir").await {
+        cmd.current_dir(dir);
+    }
+
+    let child = cmd.spawn().map_err(|e| AttractorError::HandlerError {
+        handler: "tool".into(),
+        node: node.id.clone(),
+        message: format!("Failed to spawn command: {}", e),
+    })?;
+
+    let output = tokio::time::timeout(timeout_dur, child.wait_with_output())
+        .await
+        .map_err(|_| AttractorError::CommandTimeout {
+            timeout_ms: timeout_dur.as_millis() as u64,
+        })?
+        .map_err(|e| AttractorError::HandlerError {
+            handler: "tool".into(),
+            node: node.id.clone(),
+            message: format!("Command execution failed: {}", e),
+        })?;
+
+    Ok((
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        output.status.code().unwrap_or(-1),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,23 +1,36 @@
 //! WaitHumanHandler — pauses pipeline execution for human input.
+//!
+//! Questions with a node `timeout` resolve on their own: the answer falls back
+//! to the `default_choice` attribute, or — without one — no label is preferred
+//! and routing escalates via an edge conditioned on `<node>.timed_out`. A gate
+//! with neither fails rather than following an unconditional edge nobody chose.
+//!
+//! The answer is recorded as `<node>.choice` and any typed text as
+//! `<node>.feedback`, which codergen prompts include as "Reviewer feedback".
 
 use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 
-use attractor_types::{Context, Outcome, Result, StageStatus};
+use attractor_dot::AttributeValue;
+use attractor_types::{AttractorError, Context, Outcome, Result, StageStatus};
 
 use crate::graph::{PipelineGraph, PipelineNode};
 use crate::handler::NodeHandler;
-use crate::interviewer::{Interviewer, Question};
+use crate::interviewer::{Interviewer, Question, TimeoutInterviewer};
 
 pub struct WaitHumanHandler {
     interviewer: Arc<dyn Interviewer>,
 }
 
 impl WaitHumanHandler {
+    /// The interviewer is wrapped in a [`TimeoutInterviewer`] so node timeouts
+    /// apply whatever the interviewer does.
     pub fn new(interviewer: Arc<dyn Interviewer>) -> Self {
-        Self { interviewer }
+        Self {
+            interviewer: Arc::new(TimeoutInterviewer::new(interviewer)),
+        }
     }
 }
 
@@ -45,23 +58,70 @@ impl NodeHandler for WaitHumanHandler {
             } else {
                 choices
             },
-            default: None,
+            default: match node.raw_attrs.get("default_choice") {
+                Some(AttributeValue::String(choice)) => Some(choice.clone()),
+                _ => None,
+            },
             timeout: node.timeout,
         };
 
         let answer = self.interviewer.ask(&question).await?;
+        if answer.timed_out && answer.choice.is_empty() && !has_timeout_edge(node, graph) {
+            return Err(AttractorError::HandlerError {
+                handler: "wait.human".into(),
+                node: node.id.clone(),
+                message: format!(
+                    "Timed out waiting for a human, with no default_choice and no edge on {}.timed_out",
+                    node.id
+                ),
+            });
+        }
+
+        // Feedback is always written so a re-visited gate clears stale text
+        let mut context_updates = HashMap::new();
+        context_updates.insert(
+            format!("{}.timed_out", node.id),
+            serde_json::Value::Bool(answer.timed_out),
+        );
+        context_updates.insert(
+            format!("{}.choice", node.id),
+            serde_json::Value::String(answer.choice.clone()),
+        );
+        context_updates.insert(
+            format!("{}.feedback", node.id),
+            serde_json::Value::String(answer.custom_text.clone().unwrap_or_default()),
+        );
+
+        let notes = if !answer.timed_out {
+            "Human responded".to_string()
+        } else if answer.choice.is_empty() {
+            "Timed out waiting for a human; no default_choice".to_string()
+        } else {
+            format!("Timed out waiting for a human; using '{}'", answer.choice)
+        };
 
         Ok(Outcome {
             status: StageStatus::Success,
-            preferred_label: Some(answer.choice),
+            preferred_label: (!answer.choice.is_empty()).then_some(answer.choice),
             suggested_next_ids: vec![],
-            context_updates: HashMap::new(),
-            notes: "Human responded".into(),
+            context_updates,
+            notes,
             failure_reason: None,
         })
     }
 }
 
+/// Whether one of `node`'s outgoing edges is conditioned on
+/// `<node>.timed_out`, so a timed-out gate has somewhere to escalate to.
+pub(crate) fn has_timeout_edge(node: &PipelineNode, graph: &PipelineGraph) -> bool {
+    let key = format!("{}.timed_out", node.id);
+    graph
+        .outgoing_edges(&node.id)
+        .iter()
+        .filter_map(|e| e.condition_expr.as_ref())
+        .any(|expr| expr.clauses().iter().any(|c| c.key == key))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,6 +135,8 @@ mod tests {
             node_type: Some("wait.human".to_string()),
             prompt: prompt.map(String::from),
             max_retries: 0,
+            retry_backoff: None,
+            retry_delay: None,
             goal_gate: false,
             retry_target: None,
             fallback_retry_target: None,
@@ -112,6 +174,7 @@ mod tests {
         let answers = vec![Answer {
             choice: "Approve".into(),
             custom_text: None,
+            timed_out: false,
         }];
         let interviewer = Arc::new(RecordingInterviewer::new(answers));
         let handler = WaitHumanHandler::new(interviewer.clone());
@@ -138,6 +201,7 @@ mod tests {
         let answers = vec![Answer {
             choice: "Reject".into(),
             custom_text: Some("Not ready".into()),
+            timed_out: false,
         }];
         let interviewer = Arc::new(RecordingInterviewer::new(answers));
         let handler = WaitHumanHandler::new(interviewer);
@@ -149,6 +213,14 @@ mod tests {
         let outcome = handler.execute(&node, &ctx, &graph).await.unwrap();
 
         assert_eq!(outcome.preferred_label, Some("Reject".into()));
+        assert_eq!(
+            outcome.context_updates.get("gate.choice"),
+            Some(&serde_json::Value::String("Reject".into()))
+        );
+        assert_eq!(
+            outcome.context_updates.get("gate.feedback"),
+            Some(&serde_json::Value::String("Not ready".into()))
+        );
     }
 
     #[tokio::test]
@@ -156,6 +228,7 @@ mod tests {
         let answers = vec![Answer {
             choice: "Continue".into(),
             custom_text: None,
+            timed_out: false,
         }];
         let interviewer = Arc::new(RecordingInterviewer::new(answers));
         let handler = WaitHumanHandler::new(interviewer.clone());
@@ -183,6 +256,7 @@ mod tests {
         let answers = vec![Answer {
             choice: "OK".into(),
             custom_text: None,
+            timed_out: false,
         }];
         let interviewer = Arc::new(RecordingInterviewer::new(answers));
         let handler = WaitHumanHandler::new(interviewer.clone());
@@ -196,4 +270,80 @@ mod tests {
         let questions = interviewer.questions();
         assert_eq!(questions[0].prompt, "Confirm Deployment");
     }
+
+    /// Interviewer that never answers.
+    struct SilentInterviewer;
+
+    #[async_trait]
+    impl Interviewer for SilentInterviewer {
+        async fn ask(&self, _question: &Question) -> Result<crate::interviewer::Answer> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn timeout_uses_default_choice() {
+        let handler = WaitHumanHandler::new(Arc::new(SilentInterviewer));
+
+        let mut node = make_node("gate", "Gate", None);
+        node.timeout = Some(std::time::Duration::from_millis(10));
+        node.raw_attrs.insert(
+            "default_choice".into(),
+            AttributeValue::String("Approve".into()),
+        );
+        let graph = make_graph_with_labeled_edges("gate", &["Approve", "Reject"]);
+
+        let ctx = Context::default();
+        let outcome = handler.execute(&node, &ctx, &graph).await.unwrap();
+
+        assert_eq!(outcome.preferred_label, Some("Approve".into()));
+        assert_eq!(
+            outcome.context_updates.get("gate.timed_out"),
+            Some(&serde_json::Value::Bool(true))
+        );
+    }
+
+    #[tokio::test]
+    async fn timeout_without_default_escalates_through_timed_out_edge() {
+        let handler = WaitHumanHandler::new(Arc::new(SilentInterviewer));
+
+        let mut node = make_node("gate", "Gate", None);
+        node.timeout = Some(std::time::Duration::from_millis(10));
+        let graph = PipelineGraph::from_dot(
+            attractor_dot::parse(
+                r#"digraph G {
+                gate [shape="hexagon"]
+                gate -> ship [label="Approve"]
+                gate -> notify [condition="gate.timed_out=true"]
+            }"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let ctx = Context::default();
+        let outcome = handler.execute(&node, &ctx, &graph).await.unwrap();
+
+        assert_eq!(outcome.status, StageStatus::Success);
+        assert_eq!(outcome.preferred_label, None);
+        assert_eq!(
+            outcome.context_updates.get("gate.timed_out"),
+            Some(&serde_json::Value::Bool(true))
+        );
+    }
+
+    #[tokio::test]
+    async fn timeout_without_default_or_escalation_fails() {
+        let handler = WaitHumanHandler::new(Arc::new(SilentInterviewer));
+
+        let mut node = make_node("gate", "Gate", None);
+        node.timeout = Some(std::time::Duration::from_millis(10));
+        let graph = make_graph_with_labeled_edges("gate", &["Approve", "Reject"]);
+
+        let err = handler
+            .execute(&node, &Context::default(), &graph)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no default_choice"), "{err}");
+    }
 }
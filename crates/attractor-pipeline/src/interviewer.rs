@@ -1,5 +1,7 @@
 //! Interviewer trait and built-in implementations for human interaction.
 
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use attractor_types::Result;
 
@@ -15,6 +17,20 @@ pub struct Question {
 pub struct Answer {
     pub choice: String,
     pub custom_text: Option<String>,
+    /// True when nobody answered before the question's timeout and `choice`
+    /// is the question's default (empty if it has none).
+    pub timed_out: bool,
+}
+
+impl Answer {
+    /// The answer used when a question times out.
+    pub fn timed_out(question: &Question) -> Self {
+        Self {
+            choice: question.default.clone().unwrap_or_default(),
+            custom_text: None,
+            timed_out: true,
+        }
+    }
 }
 
 #[async_trait]
@@ -39,10 +55,54 @@ impl Interviewer for AutoApproveInterviewer {
         Ok(Answer {
             choice,
             custom_text: None,
+            timed_out: false,
         })
     }
 }
 
+// ---------------------------------------------------------------------------
+// Console input
+// ---------------------------------------------------------------------------
+
+/// Lines read from a blocking source by one long-lived thread.
+///
+/// Waiting for a line can be abandoned, as a [`TimeoutInterviewer`] does,
+/// without losing it: the line goes to the next reader instead of an orphaned
+/// blocking read, and the thread never holds up runtime shutdown.
+pub struct LineReader {
+    lines: tokio::sync::Mutex<tokio::sync::mpsc::UnboundedReceiver<String>>,
+}
+
+impl LineReader {
+    /// Start a thread reading `source` line by line.
+    pub fn spawn(source: impl std::io::BufRead + Send + 'static) -> Self {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            for line in source.lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            lines: tokio::sync::Mutex::new(rx),
+        }
+    }
+
+    /// The next line, without its newline, or `None` at end of input.
+    pub async fn next_line(&self) -> Option<String> {
+        self.lines.lock().await.recv().await
+    }
+}
+
+/// The process-wide reader of stdin. Everything that reads the terminal
+/// while a pipeline runs goes through it.
+pub fn stdin_lines() -> &'static LineReader {
+    static STDIN: std::sync::OnceLock<LineReader> = std::sync::OnceLock::new();
+    STDIN.get_or_init(|| LineReader::spawn(std::io::BufReader::new(std::io::stdin())))
+}
+
 // ---------------------------------------------------------------------------
 // ConsoleInterviewer
 // ---------------------------------------------------------------------------
@@ -56,22 +116,39 @@ impl Interviewer for ConsoleInterviewer {
         for (i, choice) in question.choices.iter().enumerate() {
             println!("  [{}] {}", i + 1, choice);
         }
-        let mut input = String::new();
-        std::io::stdin()
-            .read_line(&mut input)
-            .map_err(attractor_types::AttractorError::Io)?;
+        if let Some(timeout) = question.timeout {
+            match question.default {
+                Some(ref default) => {
+                    println!("(defaults to '{}' in {}s)", default, timeout.as_secs())
+                }
+                None => println!("(times out in {}s)", timeout.as_secs()),
+            }
+        }
+        // A TimeoutInterviewer may give up on us; the line then stays queued
+        let input = stdin_lines().next_line().await.unwrap_or_default();
         let trimmed = input.trim();
-        if let Ok(idx) = trimmed.parse::<usize>() {
-            if idx > 0 && idx <= question.choices.len() {
-                return Ok(Answer {
-                    choice: question.choices[idx - 1].clone(),
-                    custom_text: None,
-                });
+        let picked = match trimmed.parse::<usize>() {
+            Ok(idx) if idx > 0 && idx <= question.choices.len() => {
+                Some(question.choices[idx - 1].clone())
             }
+            _ => question
+                .choices
+                .iter()
+                .find(|c| c.eq_ignore_ascii_case(trimmed))
+                .cloned(),
+        };
+        if let Some(choice) = picked {
+            return Ok(Answer {
+                choice,
+                custom_text: None,
+                timed_out: false,
+            });
         }
+        // Anything else is free text, kept as feedback
         Ok(Answer {
             choice: trimmed.to_string(),
             custom_text: Some(trimmed.to_string()),
+            timed_out: false,
         })
     }
 }
@@ -112,11 +189,50 @@ impl Interviewer for RecordingInterviewer {
             .unwrap_or_else(|| Answer {
                 choice: question.choices.first().cloned().unwrap_or_default(),
                 custom_text: None,
+                timed_out: false,
             });
         Ok(answer)
     }
 }
 
+// ---------------------------------------------------------------------------
+// TimeoutInterviewer
+// ---------------------------------------------------------------------------
+
+/// Enforces `Question::timeout` around any interviewer.
+///
+/// If the inner interviewer has not answered when the timeout expires, its
+/// future is dropped and [`Answer::timed_out`] is returned instead.
+pub struct TimeoutInterviewer {
+    inner: Arc<dyn Interviewer>,
+}
+
+impl TimeoutInterviewer {
+    pub fn new(inner: Arc<dyn Interviewer>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl Interviewer for TimeoutInterviewer {
+    async fn ask(&self, question: &Question) -> Result<Answer> {
+        let Some(timeout) = question.timeout else {
+            return self.inner.ask(question).await;
+        };
+        match tokio::time::timeout(timeout, self.inner.ask(question)).await {
+            Ok(answer) => answer,
+            Err(_) => {
+                tracing::info!(
+                    prompt = %question.prompt,
+                    timeout_ms = timeout.as_millis() as u64,
+                    "Question timed out"
+                );
+                Ok(Answer::timed_out(question))
+            }
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -158,10 +274,12 @@ mod tests {
             Answer {
                 choice: "Yes".into(),
                 custom_text: None,
+                timed_out: false,
             },
             Answer {
                 choice: "No".into(),
                 custom_text: Some("custom".into()),
+                timed_out: false,
             },
         ];
         let interviewer = RecordingInterviewer::new(preset);
@@ -191,4 +309,89 @@ mod tests {
         assert_eq!(recorded[0].prompt, "First?");
         assert_eq!(recorded[1].prompt, "Second?");
     }
+
+    /// Interviewer that never answers, like a console nobody is watching.
+    struct SilentInterviewer;
+
+    #[async_trait]
+    impl Interviewer for SilentInterviewer {
+        async fn ask(&self, _question: &Question) -> Result<Answer> {
+            std::future::pending().await
+        }
+    }
+
+    #[tokio::test]
+    async fn timeout_returns_default_choice() {
+        let interviewer = TimeoutInterviewer::new(Arc::new(SilentInterviewer));
+        let question = Question {
+            prompt: "Deploy?".into(),
+            choices: vec!["Yes".into(), "No".into()],
+            default: Some("No".into()),
+            timeout: Some(std::time::Duration::from_millis(10)),
+        };
+        let answer = interviewer.ask(&question).await.unwrap();
+        assert!(answer.timed_out);
+        assert_eq!(answer.choice, "No");
+    }
+
+    #[tokio::test]
+    async fn timeout_passes_through_prompt_answers() {
+        let inner = Arc::new(RecordingInterviewer::new(vec![Answer {
+            choice: "Yes".into(),
+            custom_text: None,
+            timed_out: false,
+        }]));
+        let interviewer = TimeoutInterviewer::new(inner);
+        let question = Question {
+            prompt: "Deploy?".into(),
+            choices: vec!["Yes".into(), "No".into()],
+            default: None,
+            timeout: Some(std::time::Duration::from_secs(5)),
+        };
+        let answer = interviewer.ask(&question).await.unwrap();
+        assert!(!answer.timed_out);
+        assert_eq!(answer.choice, "Yes");
+    }
+
+    /// A blocking source that yields whatever the test sends it.
+    struct ChannelSource {
+        rx: std::sync::mpsc::Receiver<&'static str>,
+        pending: std::collections::VecDeque<u8>,
+    }
+
+    impl std::io::Read for ChannelSource {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            if self.pending.is_empty() {
+                match self.rx.recv() {
+                    Ok(text) => self.pending.extend(text.as_bytes()),
+                    Err(_) => return Ok(0),
+                }
+            }
+            let n = buf.len().min(self.pending.len());
+            for (slot, byte) in buf.iter_mut().zip(self.pending.drain(..n)) {
+                *slot = byte;
+            }
+            Ok(n)
+        }
+    }
+
+    #[tokio::test]
+    async fn abandoned_line_reads_do_not_lose_input() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let reader = LineReader::spawn(std::io::BufReader::new(ChannelSource {
+            rx,
+            pending: Default::default(),
+        }));
+
+        // A timed-out question gives up waiting...
+        let waited =
+            tokio::time::timeout(std::time::Duration::from_millis(10), reader.next_line()).await;
+        assert!(waited.is_err());
+
+        // ...and the next line goes to whoever asks next
+        tx.send("merge\n").unwrap();
+        assert_eq!(reader.next_line().await.as_deref(), Some("merge"));
+        drop(tx);
+        assert_eq!(reader.next_line().await, None);
+    }
 }
@@ -2,42 +2,67 @@
 //!
 //! This crate implements the core Attractor pipeline runner: DOT graph traversal,
 //! handler dispatch, edge selection, goal gate enforcement, checkpoint/resume,
-//! and the 11 built-in lint rules.
+//! git worktree isolation, and the 11 built-in lint rules.
 
+pub mod artifacts;
+pub mod changes;
 pub mod checkpoint;
 pub mod condition;
+pub mod drift;
 pub mod edge_selection;
 pub mod engine;
 pub mod events;
+pub mod fidelity;
 pub mod goal_gate;
 pub mod graph;
 pub mod handler;
 pub mod handlers;
 pub mod interviewer;
 pub mod retry;
+#[cfg(feature = "run-store")]
+pub mod run_store;
 pub mod stylesheet;
+pub mod threads;
 pub mod transforms;
 pub mod validation;
+pub mod worktree;
 
-pub use checkpoint::{clear_checkpoint, load_checkpoint, save_checkpoint, PipelineCheckpoint};
+pub use artifacts::{resolve_artifacts, ArtifactRef, ArtifactStore};
+pub use changes::{WorkdirChanges, WorkdirSnapshot};
+pub use checkpoint::{
+    append_journal, clear_checkpoint, clear_journal, clear_sub_pipelines, load_checkpoint,
+    load_journal, restore_checkpoint, save_checkpoint, sub_pipeline_logs, JournalEntry,
+    PipelineCheckpoint, LOGS_DIR_KEY,
+};
 pub use condition::{evaluate_condition, parse_condition, Clause, ConditionExpr, Operator};
+pub use drift::{DriftPolicy, DriftReport, GraphFingerprint};
 pub use edge_selection::select_edge;
 pub use engine::{PipelineConfig, PipelineExecutor, PipelineResult};
 pub use events::{EventEmitter, PipelineEvent};
+pub use fidelity::{node_fidelity, EDGE_FIDELITY_KEY};
 pub use goal_gate::{check_goal_gates, enforce_goal_gates, GoalGateResult};
-pub use graph::{PipelineEdge, PipelineGraph, PipelineNode};
+pub use graph::{PipelineCluster, PipelineEdge, PipelineGraph, PipelineNode, SCOPED_ATTRS};
 pub use handler::{
     default_registry, default_registry_with_interviewer, ConditionalHandler, DynHandler,
     ExitHandler, HandlerRegistry, NodeHandler, StartHandler,
 };
 pub use handlers::wait_human::WaitHumanHandler;
 pub use handlers::{
-    CodergenHandler, FanInHandler, ManagerLoopHandler, ParallelHandler, ToolHandler,
+    AgentHandler, CodergenHandler, FanInHandler, LlmClientFactory, ManagerLoopHandler,
+    OutputSchema, ParallelHandler, SubPipelineHandler, ToolHandler,
 };
 pub use interviewer::{
-    Answer, AutoApproveInterviewer, ConsoleInterviewer, Interviewer, Question, RecordingInterviewer,
+    stdin_lines, Answer, AutoApproveInterviewer, ConsoleInterviewer, Interviewer, LineReader,
+    Question, RecordingInterviewer, TimeoutInterviewer,
 };
-pub use retry::{execute_with_retry, BackoffPolicy};
+pub use retry::{execute_with_retry, execute_with_retry_notify, BackoffPolicy, RetryPolicy};
+#[cfg(feature = "run-store")]
+pub use run_store::{source_hash, NodeRecord, RunRecord, RunRecorder, RunStore};
 pub use stylesheet::{apply_stylesheet, parse_stylesheet, Declaration, Rule, Selector, Stylesheet};
-pub use transforms::{apply_transforms, expand_variables};
+pub use threads::{Thread, EDGE_THREAD_KEY};
+pub use transforms::{
+    apply_transforms, apply_transforms_with_overrides, context_references,
+    expand_context_variables, expand_node_context, expand_variables,
+};
 pub use validation::{validate, validate_or_raise, Diagnostic, LintRule, Severity};
+pub use worktree::{RunWorktree, WorktreeAction};
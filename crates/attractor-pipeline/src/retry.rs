@@ -2,6 +2,10 @@
 
 use std::time::Duration;
 
+use attractor_dot::AttributeValue;
+
+use crate::graph::{PipelineGraph, PipelineNode};
+
 /// Backoff policy controlling the delay between retry attempts.
 #[derive(Debug, Clone)]
 pub enum BackoffPolicy {
@@ -27,6 +31,84 @@ impl BackoffPolicy {
     }
 }
 
+impl BackoffPolicy {
+    /// Build a policy from a `retry_backoff` kind (`none`, `fixed`,
+    /// `exponential`) and an optional `retry_delay`.
+    ///
+    /// The delay is the fixed delay or the exponential base; when absent the
+    /// [`Default`] timings apply. An unset kind means exponential.
+    pub fn from_attrs(kind: Option<&str>, delay: Option<Duration>) -> Result<Self, String> {
+        let BackoffPolicy::Exponential { base, max } = BackoffPolicy::default() else {
+            unreachable!("default backoff is exponential");
+        };
+        match kind.map(str::trim) {
+            None | Some("exponential") => {
+                let base = delay.unwrap_or(base);
+                Ok(BackoffPolicy::Exponential {
+                    base,
+                    max: max.max(base),
+                })
+            }
+            Some("fixed") => Ok(BackoffPolicy::Fixed(delay.unwrap_or(base))),
+            Some("none") => Ok(BackoffPolicy::None),
+            Some(other) => Err(format!(
+                "unknown retry_backoff '{other}'; expected one of: none, fixed, exponential"
+            )),
+        }
+    }
+}
+
+/// Retry settings for a single node.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries allowed after the first attempt.
+    pub max_retries: usize,
+    /// Delay between attempts.
+    pub backoff: BackoffPolicy,
+}
+
+impl RetryPolicy {
+    /// Resolve the retry policy for `node`.
+    ///
+    /// Node attributes (`max_retries`, `retry_backoff`, `retry_delay`) take
+    /// precedence over graph attributes of the same name.
+    pub fn for_node(node: &PipelineNode, graph: &PipelineGraph) -> attractor_types::Result<Self> {
+        let max_retries = if node.raw_attrs.contains_key("max_retries") {
+            node.max_retries
+        } else {
+            match graph.attrs.get("max_retries") {
+                Some(AttributeValue::Integer(i)) => (*i).max(0) as usize,
+                _ => 0,
+            }
+        };
+
+        let graph_backoff = match graph.attrs.get("retry_backoff") {
+            Some(AttributeValue::String(s)) => Some(s.as_str()),
+            _ => None,
+        };
+        let graph_delay = match graph.attrs.get("retry_delay") {
+            Some(AttributeValue::Duration(d)) => Some(*d),
+            Some(AttributeValue::String(s)) => {
+                attractor_dot::duration_serde::parse_duration_str(s).ok()
+            }
+            _ => None,
+        };
+
+        let backoff = BackoffPolicy::from_attrs(
+            node.retry_backoff.as_deref().or(graph_backoff),
+            node.retry_delay.or(graph_delay),
+        )
+        .map_err(|e| {
+            attractor_types::AttractorError::ValidationError(format!("Node '{}': {e}", node.id))
+        })?;
+
+        Ok(Self {
+            max_retries,
+            backoff,
+        })
+    }
+}
+
 impl Default for BackoffPolicy {
     fn default() -> Self {
         BackoffPolicy::Exponential {
@@ -52,6 +134,24 @@ pub async fn execute_with_retry<F, Fut>(
 where
     F: Fn() -> Fut,
     Fut: std::future::Future<Output = attractor_types::Result<attractor_types::Outcome>>,
+{
+    execute_with_retry_notify(f, max_retries, policy, node_id, |_, _, _| {}).await
+}
+
+/// Like [`execute_with_retry`], but calls `on_retry(attempt, delay, reason)`
+/// before sleeping ahead of each retry. `attempt` is the 1-based retry number
+/// and `reason` describes why the previous attempt is being retried.
+pub async fn execute_with_retry_notify<F, Fut, R>(
+    f: F,
+    max_retries: usize,
+    policy: &BackoffPolicy,
+    node_id: &str,
+    on_retry: R,
+) -> attractor_types::Result<attractor_types::Outcome>
+where
+    F: Fn() -> Fut,
+    Fut: std::future::Future<Output = attractor_types::Result<attractor_types::Outcome>>,
+    R: Fn(usize, Duration, &str),
 {
     let mut last_err = None;
     for attempt in 0..=max_retries {
@@ -60,15 +160,21 @@ where
                 if outcome.status == attractor_types::StageStatus::Retry && attempt < max_retries {
                     let delay = policy.delay_for_attempt(attempt);
                     tracing::info!(node = %node_id, attempt, delay_ms = %delay.as_millis(), "Retrying");
+                    let reason = outcome
+                        .failure_reason
+                        .as_deref()
+                        .unwrap_or("handler requested retry");
+                    on_retry(attempt + 1, delay, reason);
                     tokio::time::sleep(delay).await;
                     continue;
                 }
                 return Ok(outcome);
             }
             Err(e) if e.is_retryable() && attempt < max_retries => {
-                last_err = Some(e);
                 let delay = policy.delay_for_attempt(attempt);
                 tracing::warn!(node = %node_id, attempt, delay_ms = %delay.as_millis(), "Retryable error, retrying");
+                on_retry(attempt + 1, delay, &e.to_string());
+                last_err = Some(e);
                 tokio::time::sleep(delay).await;
             }
             Err(e) => return Err(e),
@@ -295,4 +401,72 @@ mod tests {
         assert_eq!(outcome.status, StageStatus::Retry);
         assert_eq!(call_count.load(Ordering::SeqCst), 3);
     }
+
+    // 11. Backoff attributes build the matching policy
+    #[test]
+    fn backoff_from_attrs() {
+        assert!(matches!(
+            BackoffPolicy::from_attrs(Some("none"), None),
+            Ok(BackoffPolicy::None)
+        ));
+        let fixed = BackoffPolicy::from_attrs(Some("fixed"), Some(Duration::from_secs(2))).unwrap();
+        assert_eq!(fixed.delay_for_attempt(3), Duration::from_secs(2));
+        let exp = BackoffPolicy::from_attrs(None, Some(Duration::from_secs(1))).unwrap();
+        assert_eq!(exp.delay_for_attempt(1), Duration::from_secs(2));
+        assert!(BackoffPolicy::from_attrs(Some("linear"), None).is_err());
+    }
+
+    // 12. Node retry attributes override graph defaults
+    #[test]
+    fn retry_policy_node_overrides_graph() {
+        let dot = r#"digraph G {
+            max_retries = 2
+            retry_backoff = "fixed"
+            retry_delay = "3s"
+            a [prompt="a"]
+            b [prompt="b", max_retries=5, retry_backoff="none"]
+        }"#;
+        let graph = PipelineGraph::from_dot(attractor_dot::parse(dot).unwrap()).unwrap();
+
+        let a = RetryPolicy::for_node(graph.node("a").unwrap(), &graph).unwrap();
+        assert_eq!(a.max_retries, 2);
+        assert_eq!(a.backoff.delay_for_attempt(0), Duration::from_secs(3));
+
+        let b = RetryPolicy::for_node(graph.node("b").unwrap(), &graph).unwrap();
+        assert_eq!(b.max_retries, 5);
+        assert!(matches!(b.backoff, BackoffPolicy::None));
+    }
+
+    // 13. on_retry is notified before every retry with its attempt number
+    #[tokio::test]
+    async fn notify_reports_each_retry() {
+        let call_count = Arc::new(AtomicUsize::new(0));
+        let cc = call_count.clone();
+        let seen = std::sync::Mutex::new(Vec::new());
+
+        let result = execute_with_retry_notify(
+            move || {
+                let cc = cc.clone();
+                async move {
+                    if cc.fetch_add(1, Ordering::SeqCst) < 2 {
+                        Err(AttractorError::CommandTimeout { timeout_ms: 5 })
+                    } else {
+                        Ok(Outcome::success("ok"))
+                    }
+                }
+            },
+            3,
+            &BackoffPolicy::None,
+            "node_g",
+            |attempt, delay, reason| {
+                assert_eq!(delay, Duration::ZERO);
+                assert!(reason.contains("timed out"), "reason: {reason}");
+                seen.lock().unwrap().push(attempt);
+            },
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+    }
 }
@@ -0,0 +1,654 @@
+//! Persistent run history backed by SQLite (`run-store` feature).
+//!
+//! Every pipeline run gets a row in `runs` (pipeline, source hash, start/end,
+//! status, total cost) and one row in `run_nodes` per node execution
+//! (outcome, duration, cost, turns, result text). [`RunRecorder`] fills the
+//! tables from the executor's [`EventEmitter`], so recording needs no changes
+//! to the engine loop. Unlike `checkpoint.json`, records survive a successful
+//! run and can be compared across runs of the same pipeline.
+
+use std::path::{Path, PathBuf};
+
+use attractor_types::{AttractorError, Result};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+use crate::events::{EventEmitter, PipelineEvent};
+
+/// One pipeline run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: i64,
+    pub pipeline_name: String,
+    pub pipeline_path: String,
+    /// SHA-256 of the pipeline's DOT source, see [`source_hash`].
+    pub pipeline_hash: String,
+    /// `running`, `success` or `failed`. Runs killed mid-way stay `running`.
+    pub status: String,
+    /// RFC 3339 timestamps.
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub total_cost_usd: f64,
+    pub error: Option<String>,
+}
+
+/// One node execution within a run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeRecord {
+    pub node_id: String,
+    /// Outcome status (`success`, `fail`, ...), or `error` if the handler failed.
+    pub status: String,
+    pub duration_ms: u64,
+    pub cost_usd: Option<f64>,
+    pub turns: Option<u64>,
+    /// The node's notes (the LLM response for codergen nodes) or error message.
+    pub result: String,
+    pub finished_at: String,
+}
+
+type RunRow = (
+    i64,
+    String,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    f64,
+    Option<String>,
+);
+
+impl From<RunRow> for RunRecord {
+    fn from(row: RunRow) -> Self {
+        Self {
+            id: row.0,
+            pipeline_name: row.1,
+            pipeline_path: row.2,
+            pipeline_hash: row.3,
+            status: row.4,
+            started_at: row.5,
+            finished_at: row.6,
+            total_cost_usd: row.7,
+            error: row.8,
+        }
+    }
+}
+
+const RUN_COLUMNS: &str = "id, pipeline_name, pipeline_path, pipeline_hash, status, \
+                           started_at, finished_at, total_cost_usd, error";
+
+fn store_error(e: sqlx::Error) -> AttractorError {
+    AttractorError::Other(format!("Run store error: {}", e))
+}
+
+/// SHA-256 of a pipeline's DOT source, hex encoded.
+pub fn source_hash(source: &str) -> String {
+    crate::drift::sha256_hex(source.as_bytes())
+}
+
+/// Handle to the run history database.
+#[derive(Clone)]
+pub struct RunStore {
+    pool: SqlitePool,
+}
+
+impl RunStore {
+    /// `$PAS_RUNS_DB`, or `~/.pas/runs.db`.
+    pub fn default_path() -> PathBuf {
+        if let Ok(path) = std::env::var("PAS_RUNS_DB") {
+            return PathBuf::from(path);
+        }
+        let home = std::env::var("HOME").unwrap_or_else(|_| ".".into());
+        PathBuf::from(home).join(".pas").join("runs.db")
+    }
+
+    /// Open the database at [`RunStore::default_path`].
+    pub async fn open_default() -> Result<Self> {
+        Self::open(&Self::default_path()).await
+    }
+
+    /// Open (creating if needed) the database at `path`.
+    pub async fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Self::connect(&format!("sqlite:{}?mode=rwc", path.display()), 5).await
+    }
+
+    /// A private in-memory database, used by tests.
+    pub async fn in_memory() -> Result<Self> {
+        // Each connection to `sqlite::memory:` is a separate database
+        Self::connect("sqlite::memory:", 1).await
+    }
+
+    async fn connect(url: &str, max_connections: u32) -> Result<Self> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(max_connections)
+            .connect(url)
+            .await
+            .map_err(store_error)?;
+
+        // Create tables (idempotent)
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS runs (
+                id             INTEGER PRIMARY KEY AUTOINCREMENT,
+                pipeline_name  TEXT    NOT NULL,
+                pipeline_path  TEXT    NOT NULL,
+                pipeline_hash  TEXT    NOT NULL,
+                status         TEXT    NOT NULL DEFAULT 'running',
+                started_at     TEXT    NOT NULL,
+                finished_at    TEXT,
+                total_cost_usd REAL    NOT NULL DEFAULT 0,
+                error          TEXT
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .map_err(store_error)?;
+
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS run_nodes (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                run_id      INTEGER NOT NULL REFERENCES runs(id),
+                node_id     TEXT    NOT NULL,
+                status      TEXT    NOT NULL,
+                duration_ms INTEGER NOT NULL,
+                cost_usd    REAL,
+                turns       INTEGER,
+                result      TEXT    NOT NULL DEFAULT '',
+                finished_at TEXT    NOT NULL
+            )
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .map_err(store_error)?;
+
+        Ok(Self { pool })
+    }
+
+    /// Insert a `running` run and return its ID.
+    pub async fn start_run(
+        &self,
+        pipeline_name: &str,
+        pipeline_path: &str,
+        pipeline_hash: &str,
+    ) -> Result<i64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO runs (pipeline_name, pipeline_path, pipeline_hash, started_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(pipeline_name)
+        .bind(pipeline_path)
+        .bind(pipeline_hash)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .map_err(store_error)?;
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn record_node(&self, run_id: i64, node: &NodeRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO run_nodes
+                (run_id, node_id, status, duration_ms, cost_usd, turns, result, finished_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(run_id)
+        .bind(&node.node_id)
+        .bind(&node.status)
+        .bind(node.duration_ms as i64)
+        .bind(node.cost_usd)
+        .bind(node.turns.map(|t| t as i64))
+        .bind(&node.result)
+        .bind(&node.finished_at)
+        .execute(&self.pool)
+        .await
+        .map_err(store_error)?;
+        Ok(())
+    }
+
+    /// Mark a run finished: `success` when `error` is `None`, else `failed`.
+    /// The total cost is summed from the run's node records.
+    pub async fn finish_run(&self, run_id: i64, error: Option<&str>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE runs
+            SET status = ?,
+                finished_at = ?,
+                error = ?,
+                total_cost_usd = (
+                    SELECT COALESCE(SUM(cost_usd), 0) FROM run_nodes WHERE run_id = ?
+                )
+            WHERE id = ?
+            "#,
+        )
+        .bind(if error.is_some() { "failed" } else { "success" })
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(error)
+        .bind(run_id)
+        .bind(run_id)
+        .execute(&self.pool)
+        .await
+        .map_err(store_error)?;
+        Ok(())
+    }
+
+    /// Most recent runs first, optionally only those of one pipeline (matched
+    /// by name or path).
+    pub async fn list_runs(&self, pipeline: Option<&str>, limit: i64) -> Result<Vec<RunRecord>> {
+        let rows = match pipeline {
+            Some(p) => {
+                sqlx::query_as::<_, RunRow>(&format!(
+                    "SELECT {} FROM runs WHERE pipeline_name = ? OR pipeline_path = ? \
+                     ORDER BY id DESC LIMIT ?",
+                    RUN_COLUMNS
+                ))
+                .bind(p)
+                .bind(p)
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+            }
+            None => {
+                sqlx::query_as::<_, RunRow>(&format!(
+                    "SELECT {} FROM runs ORDER BY id DESC LIMIT ?",
+                    RUN_COLUMNS
+                ))
+                .bind(limit)
+                .fetch_all(&self.pool)
+                .await
+            }
+        }
+        .map_err(store_error)?;
+        Ok(rows.into_iter().map(RunRecord::from).collect())
+    }
+
+    pub async fn get_run(&self, run_id: i64) -> Result<Option<RunRecord>> {
+        let row =
+            sqlx::query_as::<_, RunRow>(&format!("SELECT {} FROM runs WHERE id = ?", RUN_COLUMNS))
+                .bind(run_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(store_error)?;
+        Ok(row.map(RunRecord::from))
+    }
+
+    /// Node executions of a run, in execution order.
+    pub async fn run_nodes(&self, run_id: i64) -> Result<Vec<NodeRecord>> {
+        let rows = sqlx::query_as::<
+            _,
+            (
+                String,
+                String,
+                i64,
+                Option<f64>,
+                Option<i64>,
+                String,
+                String,
+            ),
+        >(
+            r#"
+            SELECT node_id, status, duration_ms, cost_usd, turns, result, finished_at
+            FROM run_nodes
+            WHERE run_id = ?
+            ORDER BY id
+            "#,
+        )
+        .bind(run_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(store_error)?;
+
+        Ok(rows
+            .into_iter()
+            .map(
+                |(node_id, status, duration_ms, cost_usd, turns, result, finished_at)| NodeRecord {
+                    node_id,
+                    status,
+                    duration_ms: duration_ms as u64,
+                    cost_usd,
+                    turns: turns.map(|t| t as u64),
+                    result,
+                    finished_at,
+                },
+            )
+            .collect())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// RunRecorder
+// ---------------------------------------------------------------------------
+
+/// Records a run into a [`RunStore`] by listening to pipeline events.
+///
+/// Create it before starting the executor and call [`RunRecorder::finish`]
+/// with the run's result afterwards. Store failures are logged, never raised
+/// into the pipeline.
+pub struct RunRecorder {
+    store: RunStore,
+    run_id: i64,
+    stop: tokio::sync::oneshot::Sender<()>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl RunRecorder {
+    pub async fn start(
+        store: RunStore,
+        events: &EventEmitter,
+        pipeline_name: &str,
+        pipeline_path: &str,
+        source: &str,
+    ) -> Result<Self> {
+        let run_id = store
+            .start_run(pipeline_name, pipeline_path, &source_hash(source))
+            .await?;
+        let (stop, mut stop_rx) = tokio::sync::oneshot::channel();
+        let mut rx = events.subscribe();
+        let task_store = store.clone();
+
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    biased;
+                    event = rx.recv() => match event {
+                        Ok(event) => record_event(&task_store, run_id, event).await,
+                        Err(RecvError::Lagged(skipped)) => {
+                            tracing::warn!(run_id, skipped, "Run recorder lagged, node records lost");
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                    _ = &mut stop_rx => {
+                        // Every event was sent before finish(); drain what's queued
+                        loop {
+                            match rx.try_recv() {
+                                Ok(event) => record_event(&task_store, run_id, event).await,
+                                Err(TryRecvError::Lagged(_)) => continue,
+                                Err(_) => break,
+                            }
+                        }
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            store,
+            run_id,
+            stop,
+            task,
+        })
+    }
+
+    pub fn run_id(&self) -> i64 {
+        self.run_id
+    }
+
+    /// Flush pending node records and mark the run finished.
+    pub async fn finish<T>(self, result: &Result<T>) -> Result<i64> {
+        let _ = self.stop.send(());
+        if let Err(e) = self.task.await {
+            tracing::warn!(run_id = self.run_id, "Run recorder task failed: {}", e);
+        }
+        let error = result.as_ref().err().map(|e| e.to_string());
+        self.store.finish_run(self.run_id, error.as_deref()).await?;
+        Ok(self.run_id)
+    }
+}
+
+async fn record_event(store: &RunStore, run_id: i64, event: PipelineEvent) {
+    let record = match event {
+        PipelineEvent::StageCompleted {
+            node_id,
+            status,
+            duration_ms,
+            cost_usd,
+            turns,
+            notes,
+        } => NodeRecord {
+            node_id,
+            status,
+            duration_ms,
+            cost_usd,
+            turns,
+            result: notes,
+            finished_at: chrono::Utc::now().to_rfc3339(),
+        },
+        PipelineEvent::StageFailed {
+            node_id,
+            error,
+            duration_ms,
+        } => NodeRecord {
+            node_id,
+            status: "error".into(),
+            duration_ms,
+            cost_usd: None,
+            turns: None,
+            result: error,
+            finished_at: chrono::Utc::now().to_rfc3339(),
+        },
+        _ => return,
+    };
+    if let Err(e) = store.record_node(run_id, &record).await {
+        tracing::warn!(run_id, node = %record.node_id, "Failed to record node: {}", e);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::{PipelineGraph, PipelineNode};
+    use crate::handler::{default_registry, NodeHandler};
+    use crate::PipelineExecutor;
+    use async_trait::async_trait;
+    use attractor_types::{Context, Outcome};
+
+    /// Codergen stand-in that costs $0.50 per run.
+    struct PricedHandler;
+
+    #[async_trait]
+    impl NodeHandler for PricedHandler {
+        fn handler_type(&self) -> &str {
+            "codergen"
+        }
+        async fn execute(
+            &self,
+            node: &PipelineNode,
+            _ctx: &Context,
+            _graph: &PipelineGraph,
+        ) -> Result<Outcome> {
+            let mut outcome = Outcome::success("done");
+            outcome
+                .context_updates
+                .insert(format!("{}.cost_usd", node.id), serde_json::json!(0.5));
+            Ok(outcome)
+        }
+    }
+
+    /// Run `graph` with a recorder attached, returning its run record and
+    /// node records.
+    async fn record_run(graph: &PipelineGraph) -> (RunRecord, Vec<NodeRecord>) {
+        let store = RunStore::in_memory().await.unwrap();
+        let emitter = EventEmitter::default();
+        let recorder = RunRecorder::start(store.clone(), &emitter, &graph.name, "p.dot", "")
+            .await
+            .unwrap();
+        let mut registry = default_registry();
+        registry.register(PricedHandler);
+        let result = PipelineExecutor::new(registry)
+            .with_event_emitter(emitter)
+            .run(graph)
+            .await;
+        let run_id = recorder.finish(&result).await.unwrap();
+        result.unwrap();
+        (
+            store.get_run(run_id).await.unwrap().unwrap(),
+            store.run_nodes(run_id).await.unwrap(),
+        )
+    }
+
+    fn completed(node_id: &str, cost: Option<f64>) -> PipelineEvent {
+        PipelineEvent::StageCompleted {
+            node_id: node_id.into(),
+            status: "success".into(),
+            duration_ms: 10,
+            cost_usd: cost,
+            turns: Some(2),
+            notes: format!("{} done", node_id),
+        }
+    }
+
+    #[tokio::test]
+    async fn recorder_stores_runs_and_nodes() {
+        let store = RunStore::in_memory().await.unwrap();
+        let emitter = EventEmitter::default();
+
+        let recorder = RunRecorder::start(
+            store.clone(),
+            &emitter,
+            "review",
+            "pipelines/review.dot",
+            "digraph review {}",
+        )
+        .await
+        .unwrap();
+        emitter.emit(completed("plan", Some(0.25)));
+        emitter.emit(PipelineEvent::StageFailed {
+            node_id: "test".into(),
+            error: "boom".into(),
+            duration_ms: 5,
+        });
+        emitter.emit(completed("fix", Some(0.5)));
+        let run_id = recorder.finish::<()>(&Ok(())).await.unwrap();
+
+        let run = store.get_run(run_id).await.unwrap().unwrap();
+        assert_eq!(run.status, "success");
+        assert_eq!(run.pipeline_hash, source_hash("digraph review {}"));
+        assert!((run.total_cost_usd - 0.75).abs() < 1e-9);
+        assert!(run.finished_at.is_some());
+
+        let nodes = store.run_nodes(run_id).await.unwrap();
+        let ids: Vec<_> = nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(ids, vec!["plan", "test", "fix"]);
+        assert_eq!(nodes[0].turns, Some(2));
+        assert_eq!(nodes[0].result, "plan done");
+        assert_eq!(nodes[1].status, "error");
+        assert_eq!(nodes[1].result, "boom");
+    }
+
+    #[tokio::test]
+    async fn failed_runs_and_listing() {
+        let store = RunStore::in_memory().await.unwrap();
+        let a = store.start_run("a", "a.dot", "h1").await.unwrap();
+        let b = store.start_run("b", "b.dot", "h2").await.unwrap();
+        let a2 = store.start_run("a", "a.dot", "h3").await.unwrap();
+        store.finish_run(a, None).await.unwrap();
+        store.finish_run(b, Some("node x failed")).await.unwrap();
+
+        let all = store.list_runs(None, 10).await.unwrap();
+        assert_eq!(all.iter().map(|r| r.id).collect::<Vec<_>>(), vec![a2, b, a]);
+
+        let only_a = store.list_runs(Some("a.dot"), 10).await.unwrap();
+        assert_eq!(only_a.len(), 2);
+        assert_eq!(only_a[0].status, "running");
+
+        let failed = store.get_run(b).await.unwrap().unwrap();
+        assert_eq!(failed.status, "failed");
+        assert_eq!(failed.error.as_deref(), Some("node x failed"));
+        assert!(store.get_run(999).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn manager_child_runs_are_counted_once() {
+        let graph = PipelineGraph::from_dot(
+            attractor_dot::parse(
+                r#"digraph G {
+                    start [shape="Mdiamond"]
+                    plan [shape="box"]
+                    mgr [shape="house", subgraph="cluster_tasks", max_iterations=2]
+                    done [shape="Msquare"]
+                    start -> plan -> mgr -> done
+                    subgraph cluster_tasks {
+                        task_start [shape="Mdiamond"]
+                        task [shape="box"]
+                        task_done [shape="Msquare"]
+                        task_start -> task -> task_done
+                    }
+                }"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let (run, nodes) = record_run(&graph).await;
+        // plan, plus two iterations of task reported once by mgr
+        assert!(
+            (run.total_cost_usd - 1.5).abs() < 1e-9,
+            "{}",
+            run.total_cost_usd
+        );
+        let ids: Vec<_> = nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(ids, vec!["start", "plan", "mgr", "done"]);
+        assert_eq!(nodes[2].cost_usd, Some(1.0));
+    }
+
+    #[tokio::test]
+    async fn sub_pipeline_child_runs_are_counted_once() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("child.dot"),
+            r#"digraph child {
+                start [shape="Mdiamond"]
+                first [shape="box"]
+                second [shape="box"]
+                done [shape="Msquare"]
+                start -> first -> second -> done
+            }"#,
+        )
+        .unwrap();
+        let parent = dir.path().join("parent.dot");
+        std::fs::write(
+            &parent,
+            r#"digraph parent {
+                start [shape="Mdiamond"]
+                sub [node_type="subpipeline", sub_pipeline="child.dot"]
+                done [shape="Msquare"]
+                start -> sub -> done
+            }"#,
+        )
+        .unwrap();
+        let graph = PipelineGraph::load(&parent, &Default::default()).unwrap();
+
+        let (run, nodes) = record_run(&graph).await;
+        assert!(
+            (run.total_cost_usd - 1.0).abs() < 1e-9,
+            "{}",
+            run.total_cost_usd
+        );
+        let ids: Vec<_> = nodes.iter().map(|n| n.node_id.as_str()).collect();
+        assert_eq!(ids, vec!["start", "sub", "done"]);
+        assert_eq!(nodes[1].cost_usd, Some(1.0));
+    }
+
+    #[test]
+    fn source_hash_is_stable_hex() {
+        let h = source_hash("digraph G {}");
+        assert_eq!(h.len(), 64);
+        assert_eq!(h, source_hash("digraph G {}"));
+        assert_ne!(h, source_hash("digraph H {}"));
+    }
+}
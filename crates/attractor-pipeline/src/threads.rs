@@ -0,0 +1,181 @@
+//! Conversation threads shared across nodes.
+//!
+//! Nodes with the same `thread_id` continue one LLM conversation instead of
+//! each starting a fresh one, so a fixup node already knows what the
+//! implement node read, tried and decided. A node's thread is its own
+//! `thread_id` (which a cluster can set for all its members), or else the
+//! `thread_id` of the edge the run arrived by, which the engine records as
+//! [`EDGE_THREAD_KEY`].
+//!
+//! Threads are kept in the run context, so every checkpoint saves them and a
+//! resumed run picks the conversation up again:
+//!   - `thread.<id>.provider`: the provider that holds the conversation
+//!   - `thread.<id>.session`: the CLI session to resume (codergen nodes)
+//!   - `thread.<id>.history`: the agent conversation as JSON (agent nodes;
+//!     stored as an artifact once it outgrows the artifact threshold)
+//!   - `thread.<id>.nodes`: the nodes that have run in the thread, in order
+//!
+//! A node whose provider differs from the one holding its thread starts a
+//! new conversation, which then takes the thread over.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::artifacts::ArtifactRef;
+use crate::graph::PipelineNode;
+
+/// Context key holding the `thread_id` of the edge the run last followed.
+pub const EDGE_THREAD_KEY: &str = "edge.thread_id";
+
+/// A node's conversation thread and what earlier nodes left in it.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Thread {
+    pub id: String,
+    pub provider: Option<String>,
+    pub session: Option<String>,
+    /// The agent conversation, as JSON.
+    pub history: Option<String>,
+    pub nodes: Vec<String>,
+}
+
+impl Thread {
+    /// The thread `node` runs in, if any, as recorded in `snapshot`.
+    pub async fn for_node(node: &PipelineNode, snapshot: &HashMap<String, Value>) -> Option<Self> {
+        let id = node
+            .thread_id
+            .clone()
+            .or_else(|| Some(snapshot.get(EDGE_THREAD_KEY)?.as_str()?.to_string()))
+            .filter(|id| !id.is_empty())?;
+        let get = |field: &str| snapshot.get(&format!("thread.{}.{}", id, field));
+        let string = |field: &str| Some(get(field)?.as_str()?.to_string());
+        let history = match get("history") {
+            Some(Value::String(json)) => Some(json.clone()),
+            Some(value) => match ArtifactRef::from_value(value) {
+                Some(artifact) => match artifact.read_text().await {
+                    Ok(json) => Some(json),
+                    Err(e) => {
+                        tracing::warn!(thread = %id, "Thread history unreadable: {}", e);
+                        None
+                    }
+                },
+                None => None,
+            },
+            None => None,
+        };
+        let nodes = get("nodes")
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default();
+        Some(Self {
+            provider: string("provider"),
+            session: string("session"),
+            history,
+            nodes,
+            id,
+        })
+    }
+
+    /// Whether `provider` can carry on this thread's conversation.
+    pub fn continues(&self, provider: &str) -> bool {
+        self.provider.as_deref() == Some(provider)
+            && (self.session.is_some() || self.history.is_some())
+    }
+
+    /// The CLI session to resume with `provider`, if the thread has one.
+    pub fn session_for(&self, provider: &str) -> Option<&str> {
+        self.session
+            .as_deref()
+            .filter(|_| self.provider.as_deref() == Some(provider))
+    }
+
+    /// The agent conversation to continue with `provider`, if the thread has
+    /// one.
+    pub fn history_for(&self, provider: &str) -> Option<&str> {
+        self.history
+            .as_deref()
+            .filter(|_| self.provider.as_deref() == Some(provider))
+    }
+
+    /// Context updates recording that `node_id` ran in the thread, leaving
+    /// the conversation with `provider` as `session` or `history`.
+    pub fn updates(
+        &self,
+        node_id: &str,
+        provider: &str,
+        session: Option<String>,
+        history: Option<String>,
+    ) -> HashMap<String, Value> {
+        let key = |field: &str| format!("thread.{}.{}", self.id, field);
+        let mut nodes = if self.provider.as_deref() == Some(provider) {
+            self.nodes.clone()
+        } else {
+            Vec::new()
+        };
+        nodes.push(node_id.to_string());
+
+        let mut updates = HashMap::new();
+        updates.insert(key("provider"), Value::String(provider.to_string()));
+        updates.insert(key("session"), session.map_or(Value::Null, Value::String));
+        updates.insert(key("history"), history.map_or(Value::Null, Value::String));
+        updates.insert(key("nodes"), serde_json::json!(nodes));
+        updates
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::tests::make_node;
+    use serde_json::json;
+
+    fn node(thread_id: Option<&str>) -> PipelineNode {
+        let mut node = make_node("fix", "box", Some("Fix it"), HashMap::new());
+        node.thread_id = thread_id.map(String::from);
+        node
+    }
+
+    #[tokio::test]
+    async fn thread_comes_from_the_node_or_the_incoming_edge() {
+        let mut snapshot = HashMap::new();
+        assert_eq!(Thread::for_node(&node(None), &snapshot).await, None);
+
+        snapshot.insert(EDGE_THREAD_KEY.to_string(), json!("impl"));
+        let thread = Thread::for_node(&node(None), &snapshot).await.unwrap();
+        assert_eq!(thread.id, "impl");
+        assert!(!thread.continues("claude"));
+
+        let thread = Thread::for_node(&node(Some("review")), &snapshot)
+            .await
+            .unwrap();
+        assert_eq!(thread.id, "review");
+    }
+
+    #[tokio::test]
+    async fn updates_round_trip_through_the_context() {
+        let snapshot = HashMap::new();
+        let thread = Thread::for_node(&node(Some("impl")), &snapshot)
+            .await
+            .unwrap();
+        let mut snapshot = thread.updates("implement", "claude", Some("s-1".into()), None);
+
+        let thread = Thread::for_node(&node(Some("impl")), &snapshot)
+            .await
+            .unwrap();
+        assert!(thread.continues("claude"));
+        assert_eq!(thread.session_for("claude"), Some("s-1"));
+        assert_eq!(thread.session_for("codex"), None);
+        assert_eq!(thread.nodes, vec!["implement"]);
+
+        // Another provider takes the thread over with a new conversation
+        snapshot.extend(thread.updates("fix", "codex", Some("c-1".into()), None));
+        let thread = Thread::for_node(&node(Some("impl")), &snapshot)
+            .await
+            .unwrap();
+        assert_eq!(thread.session_for("codex"), Some("c-1"));
+        assert_eq!(thread.nodes, vec!["fix"]);
+    }
+}
@@ -2,20 +2,55 @@
 //!
 //! Processes the pipeline graph after parsing: applies stylesheets, expands
 //! variables in prompts, and performs other AST-level transformations.
+//!
+//! Two kinds of variable are supported. `${key}` is expanded once at load time
+//! from graph attributes (and any caller overrides). `${ctx.key}` is left in
+//! place and expanded just before a node runs, from the live [`Context`]
+//! snapshot — see [`expand_node_context`].
+//!
+//! [`Context`]: attractor_types::Context
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use attractor_dot::AttributeValue;
 
-use crate::graph::PipelineGraph;
+use crate::graph::{PipelineGraph, PipelineNode};
 use crate::stylesheet::{apply_stylesheet, parse_stylesheet};
 
 /// Apply all transforms to a pipeline graph in the correct order.
 pub fn apply_transforms(graph: &mut PipelineGraph) -> attractor_types::Result<()> {
+    apply_transforms_with_overrides(graph, &HashMap::new())
+}
+
+/// Apply all transforms, first setting each `overrides` entry as a string
+/// graph attribute (e.g. from CLI `--set key=value`), replacing any value the
+/// DOT file declared.
+pub fn apply_transforms_with_overrides(
+    graph: &mut PipelineGraph,
+    overrides: &HashMap<String, String>,
+) -> attractor_types::Result<()> {
+    for (key, value) in overrides {
+        if key == "goal" {
+            graph.goal = value.clone();
+        }
+        graph
+            .attrs
+            .insert(key.clone(), AttributeValue::String(value.clone()));
+    }
+
     // 1. Apply model stylesheet (if present in graph attrs)
     apply_model_stylesheet(graph)?;
 
     // 2. Expand variables in prompts
     expand_prompt_variables(graph);
 
+    // 3. Inline child pipelines get the same treatment
+    for child in graph.subpipelines_mut() {
+        apply_transforms_with_overrides(child, overrides)?;
+    }
+
     Ok(())
 }
 
@@ -56,6 +91,14 @@ fn expand_prompt_variables(graph: &mut PipelineGraph) {
                 node.prompt = Some(expanded);
             }
         }
+        for value in node.raw_attrs.values_mut() {
+            if let AttributeValue::String(s) = value {
+                let expanded = expand_variables(s, &vars);
+                if expanded != *s {
+                    *s = expanded;
+                }
+            }
+        }
     }
 }
 
@@ -72,6 +115,58 @@ pub fn expand_variables(template: &str, vars: &HashMap<String, String>) -> Strin
     result
 }
 
+fn ctx_pattern() -> &'static regex::Regex {
+    static RE: OnceLock<regex::Regex> = OnceLock::new();
+    RE.get_or_init(|| regex::Regex::new(r"\$\{ctx\.([A-Za-z0-9_.\-]+)\}").expect("valid regex"))
+}
+
+/// Expand `${ctx.key}` patterns in a string from a context snapshot.
+///
+/// String values are inserted without quotes; other JSON values use their
+/// JSON text. Keys missing from the snapshot are left as-is.
+pub fn expand_context_variables(
+    template: &str,
+    snapshot: &HashMap<String, serde_json::Value>,
+) -> String {
+    ctx_pattern()
+        .replace_all(template, |caps: &regex::Captures| {
+            match snapshot.get(&caps[1]) {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(v) => v.to_string(),
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned()
+}
+
+/// The keys of the `${ctx.key}` references in `template`.
+pub fn context_references(template: &str) -> Vec<String> {
+    ctx_pattern()
+        .captures_iter(template)
+        .map(|caps| caps[1].to_string())
+        .collect()
+}
+
+/// Expand `${ctx.key}` references in a node's prompt.
+///
+/// Only the prompt is expanded: context values are often LLM or tool output,
+/// and splicing them into attributes such as `tool_command` would let them
+/// inject shell commands. Borrows the node unchanged when it has nothing to
+/// expand.
+pub fn expand_node_context<'a>(
+    node: &'a PipelineNode,
+    snapshot: &HashMap<String, serde_json::Value>,
+) -> Cow<'a, PipelineNode> {
+    match node.prompt.as_deref() {
+        Some(prompt) if ctx_pattern().is_match(prompt) => {
+            let mut expanded = node.clone();
+            expanded.prompt = Some(expand_context_variables(prompt, snapshot));
+            Cow::Owned(expanded)
+        }
+        _ => Cow::Borrowed(node),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,4 +287,102 @@ mod tests {
         let work = graph.node("work").unwrap();
         assert_eq!(work.prompt.as_deref(), Some("No variables here"));
     }
+
+    #[test]
+    fn apply_transforms_overrides_replace_graph_attrs() {
+        let dot = r#"digraph G {
+            language = "Rust"
+            start [shape="Mdiamond"]
+            work [prompt="Write ${language}", tool_command="echo ${language}"]
+            done [shape="Msquare"]
+            start -> work -> done
+        }"#;
+        let mut graph = parse_and_build(dot);
+        let mut overrides = HashMap::new();
+        overrides.insert("language".to_string(), "Go".to_string());
+        overrides.insert("goal".to_string(), "Port it".to_string());
+        apply_transforms_with_overrides(&mut graph, &overrides).unwrap();
+
+        assert_eq!(graph.goal, "Port it");
+
+        let work = graph.node("work").unwrap();
+        assert_eq!(work.prompt.as_deref(), Some("Write Go"));
+        assert!(matches!(
+            work.raw_attrs.get("tool_command"),
+            Some(AttributeValue::String(s)) if s == "echo Go"
+        ));
+        assert!(matches!(
+            graph.attrs.get("language"),
+            Some(AttributeValue::String(s)) if s == "Go"
+        ));
+    }
+
+    #[test]
+    fn apply_transforms_leaves_ctx_references_for_runtime() {
+        let dot = r#"digraph G {
+            start [shape="Mdiamond"]
+            work [prompt="Use ${ctx.plan.result}"]
+            done [shape="Msquare"]
+            start -> work -> done
+        }"#;
+        let mut graph = parse_and_build(dot);
+        apply_transforms(&mut graph).unwrap();
+
+        let work = graph.node("work").unwrap();
+        assert_eq!(work.prompt.as_deref(), Some("Use ${ctx.plan.result}"));
+    }
+
+    // ---- ${ctx.key} expansion ----
+
+    #[test]
+    fn expand_context_variables_from_snapshot() {
+        let mut snapshot = HashMap::new();
+        snapshot.insert("plan.result".to_string(), serde_json::json!("three steps"));
+        snapshot.insert("attempts".to_string(), serde_json::json!(2));
+        let result = expand_context_variables(
+            "Plan: ${ctx.plan.result} (try ${ctx.attempts}, ${ctx.missing})",
+            &snapshot,
+        );
+        assert_eq!(result, "Plan: three steps (try 2, ${ctx.missing})");
+    }
+
+    #[test]
+    fn context_references_lists_ctx_keys() {
+        assert_eq!(
+            context_references("Plan: ${ctx.plan.result} (try ${ctx.attempts}, ${goal})"),
+            vec!["plan.result", "attempts"]
+        );
+    }
+
+    #[test]
+    fn expand_node_context_borrows_when_nothing_to_expand() {
+        let graph = parse_and_build(r#"digraph G { a [prompt="plain"] }"#);
+        let node = graph.node("a").unwrap();
+        let expanded = expand_node_context(node, &HashMap::new());
+        assert!(matches!(expanded, Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn expand_node_context_rewrites_prompt() {
+        let graph = parse_and_build(r#"digraph G { a [prompt="Build ${ctx.project}"] }"#);
+        let mut snapshot = HashMap::new();
+        snapshot.insert("project".to_string(), serde_json::json!("my-app"));
+        let expanded = expand_node_context(graph.node("a").unwrap(), &snapshot);
+        assert_eq!(expanded.prompt.as_deref(), Some("Build my-app"));
+    }
+
+    #[test]
+    fn expand_node_context_leaves_tool_commands_alone() {
+        let graph = parse_and_build(
+            r#"digraph G { a [prompt="Review ${ctx.plan}", tool_command="echo ${ctx.plan}"] }"#,
+        );
+        let mut snapshot = HashMap::new();
+        snapshot.insert("plan".to_string(), serde_json::json!("x; rm -rf ~"));
+        let expanded = expand_node_context(graph.node("a").unwrap(), &snapshot);
+        assert_eq!(expanded.prompt.as_deref(), Some("Review x; rm -rf ~"));
+        assert!(matches!(
+            expanded.raw_attrs.get("tool_command"),
+            Some(AttributeValue::String(s)) if s == "echo ${ctx.plan}"
+        ));
+    }
 }
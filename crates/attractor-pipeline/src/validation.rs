@@ -1,12 +1,15 @@
 //! Pipeline validation: lint rules and diagnostics.
 //!
-//! Provides 12 built-in rules that check structural and semantic correctness of
+//! Provides 13 built-in rules that check structural and semantic correctness of
 //! a [`PipelineGraph`].  Call [`validate`] for advisory diagnostics or
 //! [`validate_or_raise`] to fail on the first `Error`-severity issue.
 
 use std::collections::{HashSet, VecDeque};
 
+use attractor_dot::AttributeValue;
+
 use crate::graph::PipelineGraph;
+use crate::handlers::wait_human::has_timeout_edge;
 use crate::parse_condition;
 
 // ---------------------------------------------------------------------------
@@ -51,7 +54,8 @@ fn is_terminal_node(id: &str, shape: &str) -> bool {
     shape == "Msquare" || id == "exit" || id == "end" || id == "done"
 }
 
-const VALID_FIDELITY_PREFIXES: &[&str] = &["full", "truncate", "compact", "summary"];
+const VALID_FIDELITY_PREFIXES: &[&str] =
+    &["full", "truncate", "truncate_chars", "compact", "summary"];
 
 fn is_valid_fidelity(val: &str) -> bool {
     let val = val.trim();
@@ -59,13 +63,19 @@ fn is_valid_fidelity(val: &str) -> bool {
         return false;
     }
     // "summary:low", "summary:medium", "truncate:5" etc. or bare prefix
-    if let Some((prefix, _suffix)) = val.split_once(':') {
-        VALID_FIDELITY_PREFIXES.contains(&prefix)
-    } else if let Some((prefix, _suffix)) = val.split_once('(') {
+    let (prefix, arg) = match val.split_once(':') {
+        Some((prefix, arg)) => (prefix, Some(arg)),
         // Also accept "truncate(5)" parenthesized syntax
-        VALID_FIDELITY_PREFIXES.contains(&prefix)
-    } else {
-        VALID_FIDELITY_PREFIXES.contains(&val)
+        None => match val.split_once('(') {
+            Some((prefix, arg)) => (prefix, Some(arg.trim_end_matches(')'))),
+            None => (val, None),
+        },
+    };
+    match (prefix, arg.map(str::trim)) {
+        (_, None) => VALID_FIDELITY_PREFIXES.contains(&prefix),
+        ("truncate" | "truncate_chars", Some(n)) => n.parse::<usize>().is_ok(),
+        ("summary", Some(level)) => attractor_agent::SummaryDetail::parse(level).is_some(),
+        _ => false,
     }
 }
 
@@ -293,6 +303,20 @@ impl LintRule for FidelityValidRule {
     }
     fn apply(&self, graph: &PipelineGraph) -> Vec<Diagnostic> {
         let mut diags = Vec::new();
+        if let Some(AttributeValue::String(f)) = graph.attrs.get("default_fidelity") {
+            if !is_valid_fidelity(f) {
+                diags.push(Diagnostic {
+                    rule: self.name().into(),
+                    severity: Severity::Warning,
+                    message: format!("Graph has invalid default_fidelity value '{f}'"),
+                    node_id: None,
+                    edge: None,
+                    fix: Some(
+                        "Use one of: full, truncate:<turns>, truncate_chars:<chars>, compact, summary, summary:<level>".into(),
+                    ),
+                });
+            }
+        }
         for node in graph.all_nodes() {
             if let Some(ref f) = node.fidelity {
                 if !is_valid_fidelity(f) {
@@ -303,7 +327,7 @@ impl LintRule for FidelityValidRule {
                         node_id: Some(node.id.clone()),
                         edge: None,
                         fix: Some(
-                            "Use one of: full, truncate, compact, summary, summary:<level>".into(),
+                            "Use one of: full, truncate:<turns>, truncate_chars:<chars>, compact, summary, summary:<level>".into(),
                         ),
                     });
                 }
@@ -322,7 +346,7 @@ impl LintRule for FidelityValidRule {
                         node_id: None,
                         edge: Some((edge.from.clone(), edge.to.clone())),
                         fix: Some(
-                            "Use one of: full, truncate, compact, summary, summary:<level>".into(),
+                            "Use one of: full, truncate:<turns>, truncate_chars:<chars>, compact, summary, summary:<level>".into(),
                         ),
                     });
                 }
@@ -341,7 +365,7 @@ impl LintRule for RetryTargetExistsRule {
         let mut diags = Vec::new();
         for node in graph.all_nodes() {
             if let Some(ref target) = node.retry_target {
-                if graph.node(target).is_none() {
+                if graph.resolve_target(target).is_none() {
                     diags.push(Diagnostic {
                         rule: self.name().into(),
                         severity: Severity::Warning,
@@ -356,7 +380,7 @@ impl LintRule for RetryTargetExistsRule {
                 }
             }
             if let Some(ref target) = node.fallback_retry_target {
-                if graph.node(target).is_none() {
+                if graph.resolve_target(target).is_none() {
                     diags.push(Diagnostic {
                         rule: self.name().into(),
                         severity: Severity::Warning,
@@ -439,6 +463,8 @@ impl LintRule for PromptOnLlmNodesRule {
         graph
             .all_nodes()
             .filter(|n| is_llm_node(&n.shape))
+            // Sub-pipeline nodes run a child pipeline, not a prompt
+            .filter(|n| n.node_type.as_deref() != Some("subpipeline"))
             .filter(|n| {
                 // Skip start/terminal nodes — they don't need prompts
                 !is_start_node(&n.id, &n.shape) && !is_terminal_node(&n.id, &n.shape)
@@ -459,6 +485,40 @@ impl LintRule for PromptOnLlmNodesRule {
     }
 }
 
+struct HumanGateTimeoutRule;
+impl LintRule for HumanGateTimeoutRule {
+    fn name(&self) -> &str {
+        "human_gate_timeout"
+    }
+    fn apply(&self, graph: &PipelineGraph) -> Vec<Diagnostic> {
+        graph
+            .all_nodes()
+            .filter(|n| match n.node_type.as_deref() {
+                Some(t) => t == "wait.human",
+                None => n.shape == "hexagon",
+            })
+            // The timeout may come from an enclosing cluster
+            .filter(|n| n.timeout.is_some() && !n.raw_attrs.contains_key("default_choice"))
+            .filter(|n| !has_timeout_edge(n, graph))
+            .map(|n| Diagnostic {
+                rule: self.name().into(),
+                severity: Severity::Warning,
+                message: format!(
+                    "Human gate '{}' has a timeout but no default_choice or edge on {}.timed_out; \
+                     the run fails when it times out",
+                    n.id, n.id
+                ),
+                node_id: Some(n.id.clone()),
+                edge: None,
+                fix: Some(format!(
+                    "Add default_choice, or an edge with condition=\"{}.timed_out=true\"",
+                    n.id
+                )),
+            })
+            .collect()
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -478,6 +538,7 @@ pub fn validate(graph: &PipelineGraph) -> Vec<Diagnostic> {
         Box::new(GoalGateHasRetryRule),
         Box::new(ProviderValidRule),
         Box::new(PromptOnLlmNodesRule),
+        Box::new(HumanGateTimeoutRule),
     ];
 
     let mut diagnostics = Vec::new();
@@ -705,6 +766,18 @@ mod tests {
                 .any(|d| d.rule == "fidelity_valid" && d.severity == Severity::Warning),
             "Expected fidelity_valid warning, got: {diags:?}"
         );
+
+        let pg = parse_and_build(
+            r#"digraph G {
+            default_fidelity="summary:everything"
+            start [shape="Mdiamond"]
+            done [shape="Msquare"]
+            start -> done
+        }"#,
+        );
+        assert!(validate(&pg)
+            .iter()
+            .any(|d| d.rule == "fidelity_valid" && d.message.contains("default_fidelity")));
     }
 
     #[test]
@@ -717,8 +790,13 @@ mod tests {
         assert!(is_valid_fidelity("summary:medium"));
         assert!(is_valid_fidelity("truncate(5)"));
         assert!(is_valid_fidelity("truncate(10)"));
+        assert!(is_valid_fidelity("truncate_chars:2000"));
+        assert!(!is_valid_fidelity("truncate_chars:lots"));
         assert!(!is_valid_fidelity("bogus"));
         assert!(!is_valid_fidelity("bogus(5)"));
+        assert!(!is_valid_fidelity("summary:huge"));
+        assert!(!is_valid_fidelity("truncate:many"));
+        assert!(!is_valid_fidelity("compact:5"));
         assert!(!is_valid_fidelity(""));
     }
 
@@ -741,6 +819,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn human_gate_timeout_needs_a_default_or_escalation() {
+        let pg = parse_and_build(
+            r#"digraph G {
+            start [shape="Mdiamond"]
+            review [shape="hexagon", timeout="30m", default_choice="Approve"]
+            signoff [shape="hexagon", timeout="8h"]
+            done [shape="Msquare"]
+            notify [label="Notify", prompt="Tell the owner"]
+            start -> review
+            review -> signoff [label="Approve"]
+            signoff -> done [label="Approve"]
+            signoff -> notify [condition="signoff.timed_out=true"]
+            notify -> done
+            subgraph cluster_release {
+                timeout = "1h"
+                release [shape="hexagon"]
+            }
+            review -> release [label="Release"]
+            release -> done [label="Ship"]
+        }"#,
+        );
+        let flagged: Vec<_> = validate(&pg)
+            .into_iter()
+            .filter(|d| d.rule == "human_gate_timeout")
+            .collect();
+        // Only the gate that inherits its cluster's timeout has nowhere to go
+        assert_eq!(flagged.len(), 1, "{flagged:?}");
+        assert_eq!(flagged[0].node_id.as_deref(), Some("release"));
+        assert_eq!(flagged[0].severity, Severity::Warning);
+    }
+
     #[test]
     fn provider_valid_warns_on_unknown() {
         let pg = parse_and_build(
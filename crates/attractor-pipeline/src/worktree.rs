@@ -0,0 +1,414 @@
+//! Git worktree isolation for pipeline runs.
+//!
+//! In worktree mode the engine runs the pipeline in a fresh `git worktree`
+//! on its own branch instead of the repository checkout. Every successful
+//! node is committed with its ID and label, so each node's changes are one
+//! commit, and resuming a rewound checkpoint resets the tree to the commit
+//! recorded with it. When the run ends the branch is merged, squashed,
+//! discarded or kept.
+//!
+//! The worktree's location is kept in the run context, so a resumed run
+//! finds it again:
+//!   - `workdir`: where handlers run — the worktree path, or the same
+//!     subdirectory of it that the run's `workdir` was in the repository
+//!   - `worktree.repo` / `worktree.path` / `worktree.branch`
+//!   - `worktree.base`: the commit the branch started from
+//!   - `worktree.head`: the branch tip after the last committed node
+//!   - `<node>.commit`: the commit holding a node's changes
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use attractor_types::{AttractorError, Context, Result};
+
+/// A run's git worktree and the branch checked out in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunWorktree {
+    /// Top level of the repository the worktree belongs to.
+    pub repo: PathBuf,
+    /// Directory the worktree is checked out in.
+    pub path: PathBuf,
+    /// Branch created for the run.
+    pub branch: String,
+    /// Commit the branch started from.
+    pub base: String,
+}
+
+impl RunWorktree {
+    /// Create a worktree for a run of `pipeline_name` off the current `HEAD`
+    /// of the repository containing `dir`. It lives under
+    /// `<repo>/.pas/worktrees/` on a new `pas/<name>-<id>` branch.
+    pub async fn create(dir: &Path, pipeline_name: &str) -> Result<Self> {
+        let repo = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"]).await?);
+        let base = git(&repo, &["rev-parse", "HEAD"]).await?;
+
+        let id = uuid::Uuid::new_v4().simple().to_string();
+        let name = format!("{}-{}", slug(pipeline_name), &id[..8]);
+        let branch = format!("pas/{}", name);
+        let worktrees = repo.join(".pas").join("worktrees");
+        tokio::fs::create_dir_all(&worktrees).await?;
+        // Keep the worktrees out of the main checkout's `git status`
+        let ignore = worktrees.join(".gitignore");
+        if !ignore.exists() {
+            tokio::fs::write(&ignore, "*\n").await?;
+        }
+        let path = worktrees.join(&name);
+
+        git(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                &branch,
+                &path.to_string_lossy(),
+                &base,
+            ],
+        )
+        .await?;
+        tracing::info!(branch = %branch, path = %path.display(), "Created run worktree");
+        Ok(Self {
+            repo,
+            path,
+            branch,
+            base,
+        })
+    }
+
+    /// The worktree recorded in a run's context, if the run uses one.
+    pub fn from_context(values: &HashMap<String, serde_json::Value>) -> Option<Self> {
+        let get = |key: &str| values.get(key).and_then(|v| v.as_str());
+        Some(Self {
+            repo: PathBuf::from(get("worktree.repo")?),
+            path: PathBuf::from(get("worktree.path")?),
+            branch: get("worktree.branch")?.to_string(),
+            base: get("worktree.base")?.to_string(),
+        })
+    }
+
+    /// The directory in the worktree matching `dir` in the main checkout,
+    /// so a run started in a subdirectory of the repository stays in it.
+    /// Creates the directory if it holds nothing git tracks.
+    pub async fn workdir_for(&self, dir: &Path) -> Result<PathBuf> {
+        let top = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"]).await?);
+        let top = top.canonicalize().unwrap_or(top);
+        let dir = dir.canonicalize()?;
+        let rel = dir.strip_prefix(&top).map_err(|_| {
+            AttractorError::Other(format!(
+                "{} is not inside repository {}",
+                dir.display(),
+                top.display()
+            ))
+        })?;
+        let workdir = self.path.join(rel);
+        tokio::fs::create_dir_all(&workdir).await?;
+        Ok(workdir)
+    }
+
+    /// Record the worktree in `context` and point `workdir` at `workdir`,
+    /// a directory inside it.
+    pub async fn record(&self, context: &Context, workdir: &Path) {
+        context
+            .set(
+                "workdir",
+                serde_json::Value::String(workdir.to_string_lossy().into_owned()),
+            )
+            .await;
+        let path = self.path.to_string_lossy().into_owned();
+        context
+            .set(
+                "worktree.repo",
+                serde_json::Value::String(self.repo.to_string_lossy().into_owned()),
+            )
+            .await;
+        context
+            .set("worktree.path", serde_json::Value::String(path))
+            .await;
+        context
+            .set(
+                "worktree.branch",
+                serde_json::Value::String(self.branch.clone()),
+            )
+            .await;
+        context
+            .set(
+                "worktree.base",
+                serde_json::Value::String(self.base.clone()),
+            )
+            .await;
+        context
+            .set(
+                "worktree.head",
+                serde_json::Value::String(self.base.clone()),
+            )
+            .await;
+    }
+
+    /// Commit everything the node changed. Returns the new commit, or `None`
+    /// when the node left the tree untouched.
+    pub async fn commit_node(&self, node_id: &str, label: &str) -> Result<Option<String>> {
+        git(&self.path, &["add", "-A"]).await?;
+        if git(&self.path, &["diff", "--cached", "--quiet"])
+            .await
+            .is_ok()
+        {
+            return Ok(None);
+        }
+        let message = if label.is_empty() || label == node_id {
+            node_id.to_string()
+        } else {
+            format!("{}: {}", node_id, label)
+        };
+        git(
+            &self.path,
+            &[
+                "-c",
+                "user.name=pas",
+                "-c",
+                "user.email=pas@localhost",
+                "commit",
+                "--quiet",
+                "--no-verify",
+                "-m",
+                &message,
+            ],
+        )
+        .await?;
+        Ok(Some(git(&self.path, &["rev-parse", "HEAD"]).await?))
+    }
+
+    /// Reset the worktree to `commit`, dropping later commits and any
+    /// uncommitted changes. Used when resuming so the tree matches the
+    /// checkpoint.
+    pub async fn reset_to(&self, commit: &str) -> Result<()> {
+        if !self.path.exists() {
+            return Err(AttractorError::Other(format!(
+                "Run worktree {} no longer exists — resume with --fresh",
+                self.path.display()
+            )));
+        }
+        git(&self.path, &["reset", "--quiet", "--hard", commit]).await?;
+        git(&self.path, &["clean", "-fdq"]).await?;
+        Ok(())
+    }
+
+    /// One-line summaries of the commits the run added, oldest first.
+    pub async fn log(&self) -> Result<Vec<String>> {
+        let range = format!("{}..{}", self.base, self.branch);
+        let out = git(&self.repo, &["log", "--reverse", "--format=%h %s", &range]).await?;
+        Ok(out.lines().map(str::to_string).collect())
+    }
+
+    /// Finish the run: fold the branch into the repository's current
+    /// checkout, or throw it away. Every action but `Keep` removes the
+    /// worktree and the branch.
+    pub async fn finish(&self, action: WorktreeAction) -> Result<()> {
+        match action {
+            WorktreeAction::Keep => return Ok(()),
+            WorktreeAction::Merge => {
+                let message = format!("Merge pipeline run {}", self.branch);
+                git(
+                    &self.repo,
+                    &[
+                        "merge",
+                        "--no-ff",
+                        "--no-edit",
+                        "-m",
+                        &message,
+                        &self.branch,
+                    ],
+                )
+                .await?;
+            }
+            WorktreeAction::Squash => {
+                git(&self.repo, &["merge", "--squash", &self.branch]).await?;
+                let staged = git(&self.repo, &["diff", "--cached", "--quiet"])
+                    .await
+                    .is_err();
+                if staged {
+                    let message = format!("Pipeline run {}", self.branch);
+                    git(&self.repo, &["commit", "--quiet", "-m", &message]).await?;
+                }
+            }
+            WorktreeAction::Discard => {}
+        }
+        git(
+            &self.repo,
+            &[
+                "worktree",
+                "remove",
+                "--force",
+                &self.path.to_string_lossy(),
+            ],
+        )
+        .await?;
+        git(&self.repo, &["branch", "-D", &self.branch]).await?;
+        Ok(())
+    }
+}
+
+/// What to do with a run's worktree branch once the pipeline finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorktreeAction {
+    /// Merge the branch with a merge commit, keeping per-node commits.
+    Merge,
+    /// Squash the run into a single commit.
+    Squash,
+    /// Delete the worktree and branch without merging.
+    Discard,
+    /// Leave the worktree and branch for manual review.
+    Keep,
+}
+
+impl fmt::Display for WorktreeAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Merge => "merge",
+            Self::Squash => "squash",
+            Self::Discard => "discard",
+            Self::Keep => "keep",
+        })
+    }
+}
+
+impl FromStr for WorktreeAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "merge" => Ok(Self::Merge),
+            "squash" => Ok(Self::Squash),
+            "discard" => Ok(Self::Discard),
+            "keep" => Ok(Self::Keep),
+            other => Err(format!(
+                "unknown worktree action '{}' (expected merge, squash, discard or keep)",
+                other
+            )),
+        }
+    }
+}
+
+/// Run `git` in `dir`, returning trimmed stdout or the stderr as an error.
+async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = tokio::process::Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .map_err(|e| AttractorError::Other(format!("Failed to run git: {}", e)))?;
+    if !output.status.success() {
+        return Err(AttractorError::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Branch-safe form of a pipeline name.
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug.trim_matches('-');
+    if slug.is_empty() {
+        "run".into()
+    } else {
+        slug.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn init_repo() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for args in [
+            &["init", "--quiet", "-b", "main"][..],
+            &["config", "user.name", "test"],
+            &["config", "user.email", "test@localhost"],
+        ] {
+            git(dir.path(), args).await.unwrap();
+        }
+        std::fs::write(dir.path().join("README"), "hello\n").unwrap();
+        git(dir.path(), &["add", "-A"]).await.unwrap();
+        git(dir.path(), &["commit", "--quiet", "-m", "init"])
+            .await
+            .unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn commits_nodes_and_squashes_into_the_checkout() {
+        let repo = init_repo().await;
+        let wt = RunWorktree::create(repo.path(), "My Pipeline!")
+            .await
+            .unwrap();
+        assert!(wt.branch.starts_with("pas/my-pipeline-"));
+        assert!(wt.path.join("README").exists());
+
+        assert_eq!(wt.commit_node("plan", "Plan").await.unwrap(), None);
+        std::fs::write(wt.path.join("plan.md"), "steps\n").unwrap();
+        assert!(wt.commit_node("plan", "Plan").await.unwrap().is_some());
+        assert_eq!(wt.log().await.unwrap().len(), 1);
+        assert!(wt.log().await.unwrap()[0].ends_with("plan: Plan"));
+        // The main checkout is untouched until the run is finished
+        assert!(!repo.path().join("plan.md").exists());
+
+        wt.finish(WorktreeAction::Squash).await.unwrap();
+        assert!(repo.path().join("plan.md").exists());
+        assert!(!wt.path.exists());
+        assert!(git(repo.path(), &["rev-parse", "--verify", &wt.branch])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn reset_and_context_round_trip() {
+        let repo = init_repo().await;
+        let wt = RunWorktree::create(repo.path(), "p").await.unwrap();
+        let ctx = Context::new();
+        wt.record(&ctx, &wt.path).await;
+        assert_eq!(
+            RunWorktree::from_context(&ctx.snapshot().await),
+            Some(wt.clone())
+        );
+
+        std::fs::write(wt.path.join("a.txt"), "a\n").unwrap();
+        wt.commit_node("a", "").await.unwrap();
+        std::fs::write(wt.path.join("b.txt"), "b\n").unwrap();
+        wt.reset_to(&wt.base).await.unwrap();
+        assert!(!wt.path.join("a.txt").exists());
+        assert!(!wt.path.join("b.txt").exists());
+
+        wt.finish(WorktreeAction::Discard).await.unwrap();
+        assert!(!wt.path.exists());
+    }
+
+    #[tokio::test]
+    async fn workdir_keeps_its_place_in_the_repository() {
+        let repo = init_repo().await;
+        let sub = repo.path().join("crates").join("app");
+        std::fs::create_dir_all(&sub).unwrap();
+        let wt = RunWorktree::create(&sub, "p").await.unwrap();
+
+        assert_eq!(wt.workdir_for(repo.path()).await.unwrap(), wt.path);
+        let workdir = wt.workdir_for(&sub).await.unwrap();
+        assert_eq!(workdir, wt.path.join("crates").join("app"));
+        // Untracked in the checkout, so the worktree needs it created
+        assert!(workdir.is_dir());
+
+        wt.finish(WorktreeAction::Discard).await.unwrap();
+    }
+}
@@ -1,20 +1,24 @@
 //! Tool trait, built-in tools, and execution environment for Attractor agents.
 //!
-//! Provides `Tool` trait, `ToolRegistry`, `ExecutionEnvironment` trait,
-//! `LocalEnvironment` implementation, and built-in tools (read_file, write_file,
-//! edit_file, shell, grep, glob).
+//! Provides `Tool` trait, `ToolRegistry`, `ExecutionEnvironment` trait, its
+//! implementations (local, bubblewrap sandbox, copy-on-write overlay), and
+//! built-in tools (read_file, write_file, edit_file, shell, grep, glob).
 
 pub mod builtin;
 pub mod environment;
 pub mod local_env;
+pub mod overlay_env;
 pub mod profiles;
+pub mod sandbox_env;
 pub mod tool;
 pub mod truncation;
 
 pub use builtin::{EditFileTool, GlobTool, GrepTool, ReadFileTool, ShellTool, WriteFileTool};
 pub use environment::{DirEntry, ExecResult, ExecutionEnvironment, GrepOptions};
 pub use local_env::LocalExecutionEnvironment;
+pub use overlay_env::{ChangeKind, FileChange, OverlayExecutionEnvironment};
 pub use profiles::ToolProfile;
+pub use sandbox_env::{SandboxConfig, SandboxExecutionEnvironment};
 pub use tool::{Tool, ToolDefinition, ToolRegistry};
 
 #[cfg(test)]
@@ -8,6 +8,7 @@ use regex::RegexBuilder;
 use crate::environment::{DirEntry, ExecResult, ExecutionEnvironment, GrepOptions};
 
 /// Concrete execution environment that runs on the local filesystem.
+#[derive(Debug, Clone)]
 pub struct LocalExecutionEnvironment {
     working_dir: PathBuf,
     platform: String,
@@ -26,7 +27,7 @@ impl LocalExecutionEnvironment {
     }
 
     /// Resolve a path relative to the working directory if it is not absolute.
-    fn resolve(&self, path: &Path) -> PathBuf {
+    pub(crate) fn resolve(&self, path: &Path) -> PathBuf {
         if path.is_absolute() {
             path.to_path_buf()
         } else {
@@ -35,7 +36,7 @@ impl LocalExecutionEnvironment {
     }
 
     /// Filter environment variables, removing secrets and keeping safe defaults.
-    fn filtered_env() -> HashMap<String, String> {
+    pub(crate) fn filtered_env() -> HashMap<String, String> {
         let exclude_suffixes: &[&str] =
             &["_api_key", "_secret", "_token", "_password", "_credential"];
         let always_include: &[&str] = &["PATH", "HOME", "USER", "SHELL", "LANG", "TERM", "TMPDIR"];
@@ -98,80 +99,11 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
         let mut cmd = tokio::process::Command::new("bash");
         cmd.args(["-c", command])
             .current_dir(&work_dir)
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::piped())
             .envs(Self::filtered_env());
-
-        // On Unix, use process_group for clean kill
-        #[cfg(unix)]
-        {
-            cmd.process_group(0);
-        }
-
         if let Some(vars) = env_vars {
             cmd.envs(vars);
         }
-
-        let start = tokio::time::Instant::now();
-        let mut child = cmd.spawn()?;
-
-        // Take stdout/stderr handles so we can read them while retaining child ownership
-        let mut stdout = child.stdout.take().expect("stdout piped");
-        let mut stderr = child.stderr.take().expect("stderr piped");
-
-        let timeout_dur = std::time::Duration::from_millis(timeout_ms);
-
-        tokio::select! {
-            status = child.wait() => {
-                let status = status?;
-                // Read remaining output
-                let mut stdout_buf = Vec::new();
-                let mut stderr_buf = Vec::new();
-                use tokio::io::AsyncReadExt;
-                let _ = stdout.read_to_end(&mut stdout_buf).await;
-                let _ = stderr.read_to_end(&mut stderr_buf).await;
-
-                let duration_ms = start.elapsed().as_millis() as u64;
-                Ok(ExecResult {
-                    stdout: String::from_utf8_lossy(&stdout_buf).to_string(),
-                    stderr: String::from_utf8_lossy(&stderr_buf).to_string(),
-                    exit_code: status.code().unwrap_or(-1),
-                    timed_out: false,
-                    duration_ms,
-                })
-            }
-            _ = tokio::time::sleep(timeout_dur) => {
-                // Timeout: try graceful termination first
-                #[cfg(unix)]
-                {
-                    if let Some(pid) = child.id() {
-                        // Send SIGTERM to the process group
-                        unsafe { libc::kill(-(pid as i32), libc::SIGTERM); }
-                    }
-                    // Wait 2 seconds for graceful shutdown
-                    tokio::select! {
-                        _ = child.wait() => {}
-                        _ = tokio::time::sleep(std::time::Duration::from_secs(2)) => {
-                            // Force kill
-                            let _ = child.kill().await;
-                        }
-                    }
-                }
-                #[cfg(not(unix))]
-                {
-                    let _ = child.kill().await;
-                }
-
-                let duration_ms = start.elapsed().as_millis() as u64;
-                Ok(ExecResult {
-                    stdout: String::new(),
-                    stderr: format!("Command timed out after {}ms", timeout_ms),
-                    exit_code: -1,
-                    timed_out: true,
-                    duration_ms,
-                })
-            }
-        }
+        run_command(cmd, timeout_ms).await
     }
 
     async fn grep(
@@ -226,6 +158,83 @@ impl ExecutionEnvironment for LocalExecutionEnvironment {
     }
 }
 
+/// Spawn `cmd` with piped output, killing its process group after
+/// `timeout_ms`. Shared by the environments that run shell commands.
+pub(crate) async fn run_command(
+    mut cmd: tokio::process::Command,
+    timeout_ms: u64,
+) -> attractor_types::Result<ExecResult> {
+    cmd.stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped());
+
+    // On Unix, use process_group for clean kill
+    #[cfg(unix)]
+    {
+        cmd.process_group(0);
+    }
+
+    let start = tokio::time::Instant::now();
+    let mut child = cmd.spawn()?;
+
+    // Take stdout/stderr handles so we can read them while retaining child ownership
+    let mut stdout = child.stdout.take().expect("stdout piped");
+    let mut stderr = child.stderr.take().expect("stderr piped");
+
+    let timeout_dur = std::time::Duration::from_millis(timeout_ms);
+
+    tokio::select! {
+        status = child.wait() => {
+            let status = status?;
+            // Read remaining output
+            let mut stdout_buf = Vec::new();
+            let mut stderr_buf = Vec::new();
+            use tokio::io::AsyncReadExt;
+            let _ = stdout.read_to_end(&mut stdout_buf).await;
+            let _ = stderr.read_to_end(&mut stderr_buf).await;
+
+            let duration_ms = start.elapsed().as_millis() as u64;
+            Ok(ExecResult {
+                stdout: String::from_utf8_lossy(&stdout_buf).to_string(),
+                stderr: String::from_utf8_lossy(&stderr_buf).to_string(),
+                exit_code: status.code().unwrap_or(-1),
+                timed_out: false,
+                duration_ms,
+            })
+        }
+        _ = tokio::time::sleep(timeout_dur) => {
+            // Timeout: try graceful termination first
+            #[cfg(unix)]
+            {
+                if let Some(pid) = child.id() {
+                    // Send SIGTERM to the process group
+                    unsafe { libc::kill(-(pid as i32), libc::SIGTERM); }
+                }
+                // Wait 2 seconds for graceful shutdown
+                tokio::select! {
+                    _ = child.wait() => {}
+                    _ = tokio::time::sleep(std::time::Duration::from_secs(2)) => {
+                        // Force kill
+                        let _ = child.kill().await;
+                    }
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = child.kill().await;
+            }
+
+            let duration_ms = start.elapsed().as_millis() as u64;
+            Ok(ExecResult {
+                stdout: String::new(),
+                stderr: format!("Command timed out after {}ms", timeout_ms),
+                exit_code: -1,
+                timed_out: true,
+                duration_ms,
+            })
+        }
+    }
+}
+
 /// Recursively list directory entries up to a given depth.
 async fn list_dir_recursive(
     path: &Path,
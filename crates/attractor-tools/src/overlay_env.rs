@@ -0,0 +1,396 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+
+use crate::environment::{DirEntry, ExecResult, ExecutionEnvironment, GrepOptions};
+use crate::local_env::LocalExecutionEnvironment;
+use crate::sandbox_env::normalize;
+
+/// Top-level entries never compared or applied back: git metadata changes on
+/// every command, and `.pas` holds the pipeline's own logs.
+const IGNORED: &[&str] = &[".git", ".pas"];
+
+/// How a file differs between the overlay and its base directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl ChangeKind {
+    /// One-letter code, as in `git status --short`.
+    pub fn code(self) -> char {
+        match self {
+            ChangeKind::Added => 'A',
+            ChangeKind::Modified => 'M',
+            ChangeKind::Deleted => 'D',
+        }
+    }
+}
+
+/// A file changed in the overlay, relative to the base directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: PathBuf,
+    pub kind: ChangeKind,
+}
+
+/// Execution environment that works on a scratch copy of a directory.
+///
+/// The base directory is copied into a scratch directory up front; every
+/// file tool and shell command then runs against the copy, with absolute
+/// paths under the base redirected into it. The base is untouched until
+/// [`apply`](Self::apply) copies the reviewed [`changes`](Self::changes)
+/// back. Shell commands run unsandboxed, so combine with a sandbox when the
+/// commands themselves are untrusted.
+#[derive(Debug, Clone)]
+pub struct OverlayExecutionEnvironment {
+    base: PathBuf,
+    local: LocalExecutionEnvironment,
+}
+
+impl OverlayExecutionEnvironment {
+    /// Copy `base` into a fresh scratch directory under the system temp dir.
+    pub async fn new(base: impl Into<PathBuf>) -> attractor_types::Result<Self> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+        let scratch =
+            std::env::temp_dir().join(format!("pas-overlay-{}-{}", std::process::id(), nanos));
+        Self::with_scratch(base, scratch).await
+    }
+
+    /// Copy `base` into `scratch`, which must not exist yet.
+    pub async fn with_scratch(
+        base: impl Into<PathBuf>,
+        scratch: impl Into<PathBuf>,
+    ) -> attractor_types::Result<Self> {
+        let base = base.into();
+        let scratch = scratch.into();
+        if scratch.exists() {
+            return Err(attractor_types::AttractorError::ToolError {
+                tool: "overlay".into(),
+                message: format!("scratch directory {} already exists", scratch.display()),
+            });
+        }
+        let (from, to) = (base.clone(), scratch.clone());
+        tokio::task::spawn_blocking(move || copy_tree(&from, &to, true))
+            .await
+            .map_err(|e| attractor_types::AttractorError::Other(e.to_string()))??;
+        Ok(Self {
+            base,
+            local: LocalExecutionEnvironment::new(scratch),
+        })
+    }
+
+    /// The directory the overlay was copied from.
+    pub fn base(&self) -> &Path {
+        &self.base
+    }
+
+    /// The scratch copy commands run in.
+    pub fn scratch(&self) -> &Path {
+        self.local.working_directory()
+    }
+
+    /// Map `path` into the scratch copy: relative paths resolve against it,
+    /// absolute paths under the base are redirected to it.
+    fn resolve(&self, path: &Path) -> PathBuf {
+        let path = normalize(&self.local.resolve(path));
+        match path.strip_prefix(&self.base) {
+            Ok(rel) => self.scratch().join(rel),
+            Err(_) => path,
+        }
+    }
+
+    /// Files added, modified or deleted in the scratch copy, sorted by path.
+    pub async fn changes(&self) -> attractor_types::Result<Vec<FileChange>> {
+        let (base, scratch) = (self.base.clone(), self.scratch().to_path_buf());
+        tokio::task::spawn_blocking(move || diff_trees(&base, &scratch))
+            .await
+            .map_err(|e| attractor_types::AttractorError::Other(e.to_string()))?
+    }
+
+    /// Unified diff of the changes, via `diff -u`. Falls back to a list of
+    /// changed paths when `diff` is unavailable.
+    pub async fn diff(&self) -> attractor_types::Result<String> {
+        let mut out = String::new();
+        for change in self.changes().await? {
+            let rel = change.path.display();
+            let old = match change.kind {
+                ChangeKind::Added => PathBuf::from("/dev/null"),
+                _ => self.base.join(&change.path),
+            };
+            let new = match change.kind {
+                ChangeKind::Deleted => PathBuf::from("/dev/null"),
+                _ => self.scratch().join(&change.path),
+            };
+            let output = tokio::process::Command::new("diff")
+                .arg("-u")
+                .arg(format!("--label=a/{}", rel))
+                .arg(format!("--label=b/{}", rel))
+                .arg(&old)
+                .arg(&new)
+                .output()
+                .await;
+            match output {
+                Ok(o) => out.push_str(&String::from_utf8_lossy(&o.stdout)),
+                Err(_) => out.push_str(&format!("{} {}\n", change.kind.code(), rel)),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Copy the changes back into the base directory and return them.
+    pub async fn apply(&self) -> attractor_types::Result<Vec<FileChange>> {
+        let changes = self.changes().await?;
+        for change in &changes {
+            let target = self.base.join(&change.path);
+            match change.kind {
+                ChangeKind::Deleted => tokio::fs::remove_file(&target).await?,
+                ChangeKind::Added | ChangeKind::Modified => {
+                    if let Some(parent) = target.parent() {
+                        tokio::fs::create_dir_all(parent).await?;
+                    }
+                    let (from, to) = (self.scratch().join(&change.path), target.clone());
+                    tokio::task::spawn_blocking(move || copy_entry(&from, &to))
+                        .await
+                        .map_err(|e| attractor_types::AttractorError::Other(e.to_string()))??;
+                }
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Delete the scratch copy.
+    pub async fn discard(self) -> attractor_types::Result<()> {
+        Ok(tokio::fs::remove_dir_all(self.scratch()).await?)
+    }
+}
+
+#[async_trait]
+impl ExecutionEnvironment for OverlayExecutionEnvironment {
+    async fn read_file(&self, path: &Path) -> attractor_types::Result<String> {
+        self.local.read_file(&self.resolve(path)).await
+    }
+
+    async fn write_file(&self, path: &Path, content: &str) -> attractor_types::Result<()> {
+        self.local.write_file(&self.resolve(path), content).await
+    }
+
+    async fn file_exists(&self, path: &Path) -> attractor_types::Result<bool> {
+        self.local.file_exists(&self.resolve(path)).await
+    }
+
+    async fn list_directory(
+        &self,
+        path: &Path,
+        depth: usize,
+    ) -> attractor_types::Result<Vec<DirEntry>> {
+        self.local.list_directory(&self.resolve(path), depth).await
+    }
+
+    async fn exec_command(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        cwd: Option<&Path>,
+        env_vars: Option<&HashMap<String, String>>,
+    ) -> attractor_types::Result<ExecResult> {
+        let cwd = cwd.map(|p| self.resolve(p));
+        self.local
+            .exec_command(command, timeout_ms, cwd.as_deref(), env_vars)
+            .await
+    }
+
+    async fn grep(
+        &self,
+        pattern: &str,
+        path: &Path,
+        options: &GrepOptions,
+    ) -> attractor_types::Result<String> {
+        self.local.grep(pattern, &self.resolve(path), options).await
+    }
+
+    async fn glob_files(
+        &self,
+        pattern: &str,
+        base: &Path,
+    ) -> attractor_types::Result<Vec<PathBuf>> {
+        self.local.glob_files(pattern, &self.resolve(base)).await
+    }
+
+    fn working_directory(&self) -> &Path {
+        self.scratch()
+    }
+
+    fn platform(&self) -> &str {
+        self.local.platform()
+    }
+}
+
+fn is_ignored(rel: &Path) -> bool {
+    rel.components()
+        .next()
+        .is_some_and(|c| IGNORED.iter().any(|i| c.as_os_str() == *i))
+}
+
+/// Recursively copy `from` into `to`, preserving symlinks. `.pas` is skipped
+/// at the top level when `top` is set; `.git` is copied so git still works.
+fn copy_tree(from: &Path, to: &Path, top: bool) -> std::io::Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        if top && entry.file_name() == ".pas" {
+            continue;
+        }
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &target, false)?;
+        } else {
+            copy_entry(&entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy one file or symlink, replacing whatever is at `to`.
+fn copy_entry(from: &Path, to: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    if std::fs::symlink_metadata(from)?.file_type().is_symlink() {
+        let _ = std::fs::remove_file(to);
+        return std::os::unix::fs::symlink(std::fs::read_link(from)?, to);
+    }
+    std::fs::copy(from, to).map(|_| ())
+}
+
+/// Every file and symlink under `root`, keyed by relative path, minus
+/// ignored entries.
+fn list_files(root: &Path) -> std::io::Result<BTreeMap<PathBuf, PathBuf>> {
+    fn walk(root: &Path, dir: &Path, out: &mut BTreeMap<PathBuf, PathBuf>) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            if is_ignored(&rel) {
+                continue;
+            }
+            if entry.file_type()?.is_dir() {
+                walk(root, &path, out)?;
+            } else {
+                out.insert(rel, path);
+            }
+        }
+        Ok(())
+    }
+    let mut out = BTreeMap::new();
+    walk(root, root, &mut out)?;
+    Ok(out)
+}
+
+fn same_content(a: &Path, b: &Path) -> std::io::Result<bool> {
+    let (ma, mb) = (std::fs::symlink_metadata(a)?, std::fs::symlink_metadata(b)?);
+    if ma.file_type().is_symlink() || mb.file_type().is_symlink() {
+        return Ok(ma.file_type().is_symlink()
+            && mb.file_type().is_symlink()
+            && std::fs::read_link(a)? == std::fs::read_link(b)?);
+    }
+    Ok(ma.len() == mb.len() && std::fs::read(a)? == std::fs::read(b)?)
+}
+
+fn diff_trees(base: &Path, scratch: &Path) -> attractor_types::Result<Vec<FileChange>> {
+    let before = list_files(base)?;
+    let after = list_files(scratch)?;
+    let mut changes = Vec::new();
+    for (rel, path) in &after {
+        let kind = match before.get(rel) {
+            None => ChangeKind::Added,
+            Some(old) if !same_content(old, path)? => ChangeKind::Modified,
+            Some(_) => continue,
+        };
+        changes.push(FileChange {
+            path: rel.clone(),
+            kind,
+        });
+    }
+    for rel in before.keys().filter(|rel| !after.contains_key(*rel)) {
+        changes.push(FileChange {
+            path: rel.clone(),
+            kind: ChangeKind::Deleted,
+        });
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn overlay_of(base: &TempDir, scratch: &TempDir) -> OverlayExecutionEnvironment {
+        OverlayExecutionEnvironment::with_scratch(base.path(), scratch.path().join("copy"))
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn writes_stay_in_scratch_until_applied() {
+        let base = TempDir::new().unwrap();
+        let scratch = TempDir::new().unwrap();
+        std::fs::write(base.path().join("keep.txt"), "same").unwrap();
+        std::fs::write(base.path().join("edit.txt"), "old\n").unwrap();
+        std::fs::write(base.path().join("gone.txt"), "bye").unwrap();
+        let env = overlay_of(&base, &scratch).await;
+
+        env.write_file(Path::new("edit.txt"), "new\n")
+            .await
+            .unwrap();
+        // Absolute paths under the base are redirected too
+        env.write_file(&base.path().join("dir/added.txt"), "hi")
+            .await
+            .unwrap();
+        env.exec_command("rm gone.txt", 5000, None, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(base.path().join("edit.txt")).unwrap(),
+            "old\n"
+        );
+
+        let changes = env.changes().await.unwrap();
+        let summary: Vec<String> = changes
+            .iter()
+            .map(|c| format!("{} {}", c.kind.code(), c.path.display()))
+            .collect();
+        assert_eq!(summary, vec!["A dir/added.txt", "M edit.txt", "D gone.txt"]);
+
+        let diff = env.diff().await.unwrap();
+        assert!(diff.contains("-old") && diff.contains("+new"), "{diff}");
+
+        env.apply().await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(base.path().join("edit.txt")).unwrap(),
+            "new\n"
+        );
+        assert!(base.path().join("dir/added.txt").exists());
+        assert!(!base.path().join("gone.txt").exists());
+        assert!(env.changes().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn pas_logs_are_not_copied_or_compared() {
+        let base = TempDir::new().unwrap();
+        let scratch = TempDir::new().unwrap();
+        std::fs::create_dir_all(base.path().join(".pas/logs")).unwrap();
+        std::fs::write(base.path().join(".pas/logs/x.json"), "{}").unwrap();
+        let env = overlay_of(&base, &scratch).await;
+
+        assert!(!env.scratch().join(".pas").exists());
+        assert!(env.changes().await.unwrap().is_empty());
+        env.discard().await.unwrap();
+    }
+}
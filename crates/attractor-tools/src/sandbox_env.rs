@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+use async_trait::async_trait;
+
+use crate::environment::{DirEntry, ExecResult, ExecutionEnvironment, GrepOptions};
+use crate::local_env::{run_command, LocalExecutionEnvironment};
+
+/// Options for [`SandboxExecutionEnvironment`].
+#[derive(Debug, Clone, Default)]
+pub struct SandboxConfig {
+    /// Keep the host network namespace. Off by default.
+    pub allow_network: bool,
+    /// Directories besides the working directory that stay writable.
+    pub writable_paths: Vec<PathBuf>,
+}
+
+/// Execution environment that runs shell commands inside a bubblewrap
+/// (`bwrap`) sandbox on Linux.
+///
+/// The host root is mounted read-only, the working directory (and any
+/// `writable_paths`) read-write, `/tmp` is a private tmpfs, and every
+/// namespace is unshared — including the network unless `allow_network` is
+/// set. File tools get the same rule: reads go anywhere, writes only inside
+/// the writable directories.
+#[derive(Debug, Clone)]
+pub struct SandboxExecutionEnvironment {
+    local: LocalExecutionEnvironment,
+    config: SandboxConfig,
+    bwrap: PathBuf,
+}
+
+impl SandboxExecutionEnvironment {
+    /// Create a sandbox rooted at `working_dir`. Fails when not on Linux or
+    /// when `bwrap` is not on `PATH`.
+    pub fn new(
+        working_dir: impl Into<PathBuf>,
+        config: SandboxConfig,
+    ) -> attractor_types::Result<Self> {
+        if std::env::consts::OS != "linux" {
+            return Err(sandbox_error("the bubblewrap sandbox requires Linux"));
+        }
+        let bwrap = find_bwrap()
+            .ok_or_else(|| sandbox_error("`bwrap` not found on PATH (install bubblewrap)"))?;
+        Ok(Self {
+            local: LocalExecutionEnvironment::new(working_dir),
+            config,
+            bwrap,
+        })
+    }
+
+    /// Whether `bwrap` is installed, i.e. whether [`Self::new`] can succeed.
+    pub fn is_available() -> bool {
+        std::env::consts::OS == "linux" && find_bwrap().is_some()
+    }
+
+    fn writable_roots(&self) -> impl Iterator<Item = &Path> {
+        std::iter::once(self.local.working_directory())
+            .chain(self.config.writable_paths.iter().map(PathBuf::as_path))
+    }
+
+    /// Arguments for `bwrap` running `command` with `cwd` as its directory.
+    fn bwrap_args(&self, command: &str, cwd: &Path) -> Vec<OsString> {
+        let mut args: Vec<OsString> = ["--ro-bind", "/", "/", "--dev", "/dev", "--proc", "/proc"]
+            .iter()
+            .map(OsString::from)
+            .collect();
+        args.extend(["--tmpfs", "/tmp"].map(OsString::from));
+        for root in self.writable_roots() {
+            args.push("--bind".into());
+            args.push(root.into());
+            args.push(root.into());
+        }
+        args.push("--unshare-all".into());
+        if self.config.allow_network {
+            args.push("--share-net".into());
+        }
+        args.extend(["--die-with-parent", "--new-session", "--chdir"].map(OsString::from));
+        args.push(cwd.into());
+        args.extend(["--", "bash", "-c", command].map(OsString::from));
+        args
+    }
+
+    /// Resolve `path` and reject it unless it lies in a writable directory.
+    ///
+    /// Writes run in the host process, not inside `bwrap`, so symlinks are
+    /// resolved before the check: a link the sandboxed shell planted in the
+    /// working directory must not lead a write out of it. A symlink as the
+    /// final component is refused outright.
+    fn writable_path(&self, path: &Path, tool: &str) -> attractor_types::Result<PathBuf> {
+        let refuse = |message: String| attractor_types::AttractorError::ToolError {
+            tool: tool.into(),
+            message,
+        };
+        let lexical = normalize(&self.local.resolve(path));
+        if std::fs::symlink_metadata(&lexical).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Err(refuse(format!(
+                "{} is a symbolic link; the sandbox does not write through links",
+                lexical.display()
+            )));
+        }
+        let resolved = resolve_existing(&lexical);
+        if self
+            .writable_roots()
+            .any(|root| resolved.starts_with(resolve_existing(root)))
+        {
+            Ok(resolved)
+        } else {
+            Err(refuse(format!(
+                "{} is outside the sandbox's writable directories",
+                resolved.display()
+            )))
+        }
+    }
+}
+
+#[async_trait]
+impl ExecutionEnvironment for SandboxExecutionEnvironment {
+    async fn read_file(&self, path: &Path) -> attractor_types::Result<String> {
+        self.local.read_file(path).await
+    }
+
+    async fn write_file(&self, path: &Path, content: &str) -> attractor_types::Result<()> {
+        let path = self.writable_path(path, "write_file")?;
+        self.local.write_file(&path, content).await
+    }
+
+    async fn file_exists(&self, path: &Path) -> attractor_types::Result<bool> {
+        self.local.file_exists(path).await
+    }
+
+    async fn list_directory(
+        &self,
+        path: &Path,
+        depth: usize,
+    ) -> attractor_types::Result<Vec<DirEntry>> {
+        self.local.list_directory(path, depth).await
+    }
+
+    async fn exec_command(
+        &self,
+        command: &str,
+        timeout_ms: u64,
+        cwd: Option<&Path>,
+        env_vars: Option<&HashMap<String, String>>,
+    ) -> attractor_types::Result<ExecResult> {
+        let work_dir = cwd
+            .map(|p| normalize(&self.local.resolve(p)))
+            .unwrap_or_else(|| self.local.working_directory().to_path_buf());
+
+        let mut cmd = tokio::process::Command::new(&self.bwrap);
+        cmd.args(self.bwrap_args(command, &work_dir))
+            .env_clear()
+            .envs(LocalExecutionEnvironment::filtered_env());
+        if let Some(vars) = env_vars {
+            cmd.envs(vars);
+        }
+        run_command(cmd, timeout_ms).await
+    }
+
+    async fn grep(
+        &self,
+        pattern: &str,
+        path: &Path,
+        options: &GrepOptions,
+    ) -> attractor_types::Result<String> {
+        self.local.grep(pattern, path, options).await
+    }
+
+    async fn glob_files(
+        &self,
+        pattern: &str,
+        base: &Path,
+    ) -> attractor_types::Result<Vec<PathBuf>> {
+        self.local.glob_files(pattern, base).await
+    }
+
+    fn working_directory(&self) -> &Path {
+        self.local.working_directory()
+    }
+
+    fn platform(&self) -> &str {
+        self.local.platform()
+    }
+}
+
+fn sandbox_error(message: &str) -> attractor_types::AttractorError {
+    attractor_types::AttractorError::ToolError {
+        tool: "sandbox".into(),
+        message: message.into(),
+    }
+}
+
+fn find_bwrap() -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join("bwrap"))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Lexically resolve `.` and `..` so `workdir/../etc` cannot pass a prefix
+/// check.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other),
+        }
+    }
+    out
+}
+
+/// `path` with the symlinks in its longest existing prefix resolved; the
+/// components that do not exist yet are appended as they are.
+fn resolve_existing(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(canonical) = std::fs::canonicalize(existing) {
+            return missing.iter().rev().fold(canonical, |p, c| p.join(c));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox(config: SandboxConfig) -> SandboxExecutionEnvironment {
+        // Constructed directly so the tests run without bwrap installed
+        SandboxExecutionEnvironment {
+            local: LocalExecutionEnvironment::new("/work/repo"),
+            config,
+            bwrap: PathBuf::from("/usr/bin/bwrap"),
+        }
+    }
+
+    fn joined(args: &[OsString]) -> String {
+        args.iter()
+            .map(|a| a.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    #[test]
+    fn bwrap_args_mount_root_read_only_and_workdir_writable() {
+        let env = sandbox(SandboxConfig::default());
+        let args = joined(&env.bwrap_args("make test", Path::new("/work/repo/sub")));
+        assert!(args.starts_with("--ro-bind / / --dev /dev --proc /proc --tmpfs /tmp"));
+        assert!(args.contains("--bind /work/repo /work/repo"));
+        assert!(args.contains("--unshare-all"));
+        assert!(!args.contains("--share-net"));
+        assert!(args.ends_with("--chdir /work/repo/sub -- bash -c make test"));
+    }
+
+    #[test]
+    fn bwrap_args_honour_network_and_extra_paths() {
+        let env = sandbox(SandboxConfig {
+            allow_network: true,
+            writable_paths: vec![PathBuf::from("/cache")],
+        });
+        let args = joined(&env.bwrap_args("true", Path::new("/work/repo")));
+        assert!(args.contains("--share-net"));
+        assert!(args.contains("--bind /cache /cache"));
+    }
+
+    #[test]
+    fn writes_outside_writable_dirs_are_rejected() {
+        let env = sandbox(SandboxConfig::default());
+        assert!(env
+            .writable_path(Path::new("src/lib.rs"), "write_file")
+            .is_ok());
+        assert!(env
+            .writable_path(Path::new("/etc/passwd"), "write_file")
+            .is_err());
+        let err = env
+            .writable_path(Path::new("../../etc/passwd"), "write_file")
+            .unwrap_err();
+        assert!(err.to_string().contains("/etc/passwd"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn writes_do_not_follow_symlinks_out_of_the_workdir() {
+        let work = tempfile::tempdir().unwrap();
+        let host = tempfile::tempdir().unwrap();
+        let bashrc = host.path().join(".bashrc");
+        std::fs::write(&bashrc, "original").unwrap();
+        // Links the sandboxed shell could have created
+        std::os::unix::fs::symlink(&bashrc, work.path().join("x")).unwrap();
+        std::os::unix::fs::symlink(host.path(), work.path().join("d")).unwrap();
+        let env = SandboxExecutionEnvironment {
+            local: LocalExecutionEnvironment::new(work.path()),
+            config: SandboxConfig::default(),
+            bwrap: PathBuf::from("/usr/bin/bwrap"),
+        };
+
+        let err = env.write_file(Path::new("x"), "pwned").await.unwrap_err();
+        assert!(err.to_string().contains("symbolic link"), "{err}");
+        let err = env
+            .write_file(Path::new("d/.bashrc"), "pwned")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("outside"), "{err}");
+        let err = env
+            .write_file(Path::new("d/new/file"), "pwned")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("outside"), "{err}");
+        assert_eq!(std::fs::read_to_string(&bashrc).unwrap(), "original");
+
+        env.write_file(Path::new("src/lib.rs"), "ok").await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(work.path().join("src/lib.rs")).unwrap(),
+            "ok"
+        );
+    }
+
+    #[tokio::test]
+    async fn runs_commands_when_bwrap_is_installed() {
+        if !SandboxExecutionEnvironment::is_available() {
+            return;
+        }
+        let dir = tempfile::tempdir().unwrap();
+        let env = SandboxExecutionEnvironment::new(dir.path(), SandboxConfig::default()).unwrap();
+        let result = env
+            .exec_command("echo hi > out.txt && touch /usr/x", 5000, None, None)
+            .await
+            .unwrap();
+        assert_ne!(result.exit_code, 0);
+        assert!(dir.path().join("out.txt").exists());
+    }
+}
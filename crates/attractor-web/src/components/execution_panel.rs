@@ -1,6 +1,7 @@
 use leptos::prelude::*;
 
 use crate::components::execution_node::{ExecutionNode, NodeStatus};
+use crate::components::human_question::{HumanQuestion, PendingQuestion};
 
 #[cfg(feature = "hydrate")]
 use gloo_net::eventsource::futures::EventSource;
@@ -24,6 +25,16 @@ struct PipelineEvent {
     notes: String,
     #[serde(default)]
     message: String,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    question_id: String,
+    #[serde(default)]
+    choices: Vec<String>,
+    #[serde(default)]
+    default: Option<String>,
+    #[serde(default)]
+    timeout_ms: Option<u64>,
 }
 
 #[derive(Clone, Debug)]
@@ -46,6 +57,7 @@ where
     let (total_cost, set_total_cost) = signal(0.0_f64);
     let (is_running, set_is_running) = signal(true);
     let (error, set_error) = signal(Option::<String>::None);
+    let (questions, set_questions) = signal(Vec::<PendingQuestion>::new());
 
     // Connect to SSE stream
     #[cfg(feature = "hydrate")]
@@ -74,6 +86,7 @@ where
                                         set_total_cost,
                                         set_is_running,
                                         set_error,
+                                        set_questions,
                                     );
                                 }
                             }
@@ -114,6 +127,16 @@ where
                 </div>
             })}
 
+            <div class="human-questions">
+                <For
+                    each=move || questions.get()
+                    key=|question| question.question_id.clone()
+                    children=move |question: PendingQuestion| {
+                        view! { <HumanQuestion question=question /> }
+                    }
+                />
+            </div>
+
             <div class="execution-nodes">
                 <For
                     each=move || nodes.get()
@@ -142,6 +165,7 @@ fn process_event(
     set_total_cost: WriteSignal<f64>,
     set_is_running: WriteSignal<bool>,
     set_error: WriteSignal<Option<String>>,
+    set_questions: WriteSignal<Vec<PendingQuestion>>,
 ) {
     match event.event_type.as_str() {
         "node_start" => {
@@ -161,9 +185,8 @@ fn process_event(
             set_nodes.update(|nodes| {
                 if let Some(node) = nodes.iter_mut().find(|n| n.node_id == event.node_id) {
                     node.status = match event.status.as_str() {
-                        "Success" => NodeStatus::Success,
-                        "Failed" | "Fail" => NodeStatus::Failed,
-                        "Skipped" => NodeStatus::Skipped,
+                        "fail" => NodeStatus::Failed,
+                        "skipped" => NodeStatus::Skipped,
                         _ => NodeStatus::Success,
                     };
                     node.cost = event.cost_usd;
@@ -172,12 +195,53 @@ fn process_event(
             });
             set_total_cost.set(event.cost_usd);
         }
+        "node_output" => {
+            // Agent replies stream in until the node completes
+            set_nodes.update(|nodes| {
+                if let Some(node) = nodes.iter_mut().find(|n| n.node_id == event.node_id) {
+                    node.content.push_str(&event.text);
+                }
+            });
+        }
+        "node_failed" => {
+            set_nodes.update(|nodes| {
+                if let Some(node) = nodes.iter_mut().find(|n| n.node_id == event.node_id) {
+                    node.status = NodeStatus::Failed;
+                    node.content = event.message.clone();
+                }
+            });
+        }
+        "node_retry" => {
+            set_nodes.update(|nodes| {
+                if let Some(node) = nodes.iter_mut().find(|n| n.node_id == event.node_id) {
+                    node.content = format!("Retrying: {}", event.message);
+                }
+            });
+        }
+        "human_question" => {
+            set_questions.update(|questions| {
+                questions.push(PendingQuestion {
+                    question_id: event.question_id.clone(),
+                    prompt: event.message.clone(),
+                    choices: event.choices.clone(),
+                    default: event.default.clone(),
+                    timeout_ms: event.timeout_ms,
+                });
+            });
+        }
+        "human_answered" => {
+            set_questions.update(|questions| {
+                questions.retain(|q| q.question_id != event.question_id);
+            });
+        }
         "pipeline_complete" => {
             set_is_running.set(false);
+            set_questions.set(Vec::new());
         }
         "error" => {
             set_error.set(Some(event.message.clone()));
             set_is_running.set(false);
+            set_questions.set(Vec::new());
         }
         _ => {}
     }
@@ -0,0 +1,96 @@
+use leptos::prelude::*;
+
+use crate::server::human::answer_question;
+
+/// A human gate question waiting on the user.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingQuestion {
+    pub question_id: String,
+    pub prompt: String,
+    pub choices: Vec<String>,
+    pub default: Option<String>,
+    pub timeout_ms: Option<u64>,
+}
+
+/// Choice buttons and a free-text box for a pending human gate.
+///
+/// Clicking a choice sends it along with any typed feedback; "Send" submits
+/// the typed text as the answer itself. The card is removed when the server
+/// publishes `human_answered`.
+#[component]
+pub fn HumanQuestion(question: PendingQuestion) -> impl IntoView {
+    let (feedback, set_feedback) = signal(String::new());
+    let (error, set_error) = signal(Option::<String>::None);
+
+    let question_id = question.question_id.clone();
+    let answer_action = Action::new(move |(choice, text): &(String, Option<String>)| {
+        let question_id = question_id.clone();
+        let choice = choice.clone();
+        let text = text.clone();
+        async move { answer_question(question_id, choice, text).await }
+    });
+
+    Effect::new(move || {
+        if let Some(Err(e)) = answer_action.value().get() {
+            set_error.set(Some(e.to_string()));
+        }
+    });
+
+    let is_sending = move || answer_action.pending().get();
+    let typed = move || {
+        let text = feedback.get();
+        (!text.trim().is_empty()).then_some(text)
+    };
+
+    let timeout_hint = question.timeout_ms.map(|ms| match question.default {
+        Some(ref default) => format!("Defaults to \"{}\" after {}s", default, ms / 1000),
+        None => format!("Times out after {}s", ms / 1000),
+    });
+
+    view! {
+        <div class="human-question">
+            <p class="human-question-prompt">{question.prompt.clone()}</p>
+            {timeout_hint.map(|hint| view! { <p class="human-question-timeout">{hint}</p> })}
+            <textarea
+                class="human-question-feedback"
+                placeholder="Optional feedback for the next step"
+                prop:value=feedback
+                on:input=move |ev| set_feedback.set(event_target_value(&ev))
+                disabled=is_sending
+            ></textarea>
+            <div class="human-question-choices">
+                {question
+                    .choices
+                    .iter()
+                    .map(|choice| {
+                        let label = choice.clone();
+                        let choice = choice.clone();
+                        view! {
+                            <button
+                                class="btn btn-secondary"
+                                disabled=is_sending
+                                on:click=move |_| {
+                                    answer_action.dispatch((choice.clone(), typed()));
+                                }
+                            >
+                                {label}
+                            </button>
+                        }
+                    })
+                    .collect_view()}
+                <button
+                    class="btn btn-approve"
+                    disabled=move || is_sending() || typed().is_none()
+                    on:click=move |_| {
+                        if let Some(text) = typed() {
+                            answer_action.dispatch((text.clone(), Some(text)));
+                        }
+                    }
+                >
+                    "Send"
+                </button>
+            </div>
+            {move || error.get().map(|err| view! { <div class="error-message">{err}</div> })}
+        </div>
+    }
+}
@@ -3,6 +3,7 @@ use leptos::prelude::*;
 use crate::components::approval_bar::ApprovalBar;
 use crate::components::document_viewer::DocumentViewer;
 use crate::components::execution_panel::ExecutionPanel;
+use crate::components::run_history::RunHistory;
 use crate::components::terminal::Terminal;
 use crate::server::projects::Project;
 
@@ -11,6 +12,7 @@ use crate::server::projects::Project;
 pub enum RightPanel {
     Documents,
     Execution,
+    History,
 }
 
 /// Project-scoped two-column layout: Terminal (left) + Document/Execution viewer (right)
@@ -48,6 +50,12 @@ pub fn ProjectView(#[prop(into)] project: Project) -> impl IntoView {
                     {move || match panel.get() {
                         RightPanel::Documents => {
                             view! {
+                                <button
+                                    class="btn btn-secondary"
+                                    on:click=move |_| set_panel.set(RightPanel::History)
+                                >
+                                    "Run History"
+                                </button>
                                 <ApprovalBar
                                     project_id=project_id
                                     enabled=can_approve
@@ -55,7 +63,7 @@ pub fn ProjectView(#[prop(into)] project: Project) -> impl IntoView {
                                 />
                             }.into_any()
                         }
-                        RightPanel::Execution => {
+                        RightPanel::Execution | RightPanel::History => {
                             view! {
                                 <button class="btn btn-secondary" on:click=on_back_to_docs>
                                     "Back to Docs"
@@ -96,6 +104,13 @@ pub fn ProjectView(#[prop(into)] project: Project) -> impl IntoView {
                             </div>
                         }.into_any()
                     }
+                    RightPanel::History => {
+                        view! {
+                            <div class="panel-right">
+                                <RunHistory project_id=project_id />
+                            </div>
+                        }.into_any()
+                    }
                 }}
             </div>
         </div>
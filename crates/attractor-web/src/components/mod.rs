@@ -5,7 +5,9 @@ pub mod execution_node;
 #[allow(unused_variables)]
 pub mod execution_panel;
 pub mod folder_picker;
+pub mod human_question;
 pub mod layout;
 pub mod markdown_render;
 pub mod project_sidebar;
+pub mod run_history;
 pub mod terminal;
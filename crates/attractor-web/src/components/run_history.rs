@@ -0,0 +1,127 @@
+use leptos::prelude::*;
+
+use crate::server::runs::{get_run_detail, list_project_runs, RunDetail, RunRecord};
+
+/// Past pipeline runs for a project, with per-node outcomes for the selected
+/// run. Reads the same history database as `pas runs`.
+#[component]
+pub fn RunHistory(project_id: i64) -> impl IntoView {
+    let runs = Resource::new(|| (), move |_| list_project_runs(project_id));
+    let (selected, set_selected) = signal(Option::<i64>::None);
+    let detail = Resource::new(
+        move || selected.get(),
+        |run_id| async move {
+            match run_id {
+                Some(id) => get_run_detail(id).await.map(Some),
+                None => Ok(None),
+            }
+        },
+    );
+
+    view! {
+        <div class="run-history">
+            <div class="execution-header">
+                <h2>"Run History"</h2>
+                <button class="btn btn-secondary" on:click=move |_| runs.refetch()>
+                    "Refresh"
+                </button>
+            </div>
+
+            <Suspense fallback=|| view! { <p>"Loading runs..."</p> }>
+                {move || runs.get().map(|result| match result {
+                    Ok(list) if list.is_empty() => {
+                        view! { <p class="run-history-empty">"No runs recorded yet."</p> }
+                            .into_any()
+                    }
+                    Ok(list) => view! { <RunTable runs=list set_selected=set_selected /> }.into_any(),
+                    Err(e) => view! { <div class="execution-error">{e.to_string()}</div> }.into_any(),
+                })}
+            </Suspense>
+
+            <Suspense fallback=|| ()>
+                {move || detail.get().map(|result| match result {
+                    Ok(Some(d)) => view! { <RunDetailView detail=d /> }.into_any(),
+                    Ok(None) => ().into_any(),
+                    Err(e) => view! { <div class="execution-error">{e.to_string()}</div> }.into_any(),
+                })}
+            </Suspense>
+        </div>
+    }
+}
+
+#[component]
+fn RunTable(runs: Vec<RunRecord>, set_selected: WriteSignal<Option<i64>>) -> impl IntoView {
+    view! {
+        <table class="run-table">
+            <thead>
+                <tr>
+                    <th>"#"</th>
+                    <th>"Pipeline"</th>
+                    <th>"Status"</th>
+                    <th>"Started"</th>
+                    <th>"Cost"</th>
+                    <th>"Hash"</th>
+                </tr>
+            </thead>
+            <tbody>
+                {runs
+                    .into_iter()
+                    .map(|run| {
+                        let id = run.id;
+                        let hash: String = run.pipeline_hash.chars().take(8).collect();
+                        view! {
+                            <tr class="run-row" on:click=move |_| set_selected.set(Some(id))>
+                                <td>{run.id}</td>
+                                <td>{run.pipeline_name}</td>
+                                <td class=format!("run-status {}", run.status)>{run.status.clone()}</td>
+                                <td>{run.started_at}</td>
+                                <td>{format!("${:.2}", run.total_cost_usd)}</td>
+                                <td class="run-hash">{hash}</td>
+                            </tr>
+                        }
+                    })
+                    .collect_view()}
+            </tbody>
+        </table>
+    }
+}
+
+#[component]
+fn RunDetailView(detail: RunDetail) -> impl IntoView {
+    let run = detail.run;
+    view! {
+        <div class="run-detail">
+            <h3>{format!("Run #{} — {}", run.id, run.pipeline_name)}</h3>
+            <p class="run-detail-path">{run.pipeline_path}</p>
+            {run.error.map(|e| view! { <div class="execution-error">{e}</div> })}
+            <table class="run-table">
+                <thead>
+                    <tr>
+                        <th>"Node"</th>
+                        <th>"Outcome"</th>
+                        <th>"Duration"</th>
+                        <th>"Cost"</th>
+                        <th>"Turns"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {detail
+                        .nodes
+                        .into_iter()
+                        .map(|node| {
+                            view! {
+                                <tr title=node.result>
+                                    <td>{node.node_id}</td>
+                                    <td class=format!("run-status {}", node.status)>{node.status.clone()}</td>
+                                    <td>{format!("{:.1}s", node.duration_ms as f64 / 1000.0)}</td>
+                                    <td>{node.cost_usd.map(|c| format!("${:.2}", c)).unwrap_or_default()}</td>
+                                    <td>{node.turns.map(|t| t.to_string()).unwrap_or_default()}</td>
+                                </tr>
+                            }
+                        })
+                        .collect_view()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
@@ -131,27 +131,30 @@ pub async fn start_execution(
         ServerFnError::<NoCustomError>::ServerError(format!("Failed to read pipeline file: {}", e))
     })?;
 
-    let parsed = attractor_dot::parse(&dot_source).map_err(|e| {
-        ServerFnError::<NoCustomError>::ServerError(format!("Failed to parse pipeline: {}", e))
+    // Load the way `pas run` does, so relative `sub_pipeline` and manager
+    // child paths resolve against the pipeline file
+    let graph = attractor_pipeline::PipelineGraph::load(
+        &full_pipeline_path,
+        &std::collections::HashMap::new(),
+    )
+    .map_err(|e| {
+        ServerFnError::<NoCustomError>::ServerError(format!("Failed to load pipeline: {}", e))
     })?;
 
-    let graph = attractor_pipeline::PipelineGraph::from_dot(parsed).map_err(|e| {
-        ServerFnError::<NoCustomError>::ServerError(format!("Failed to build graph: {}", e))
-    })?;
-
-    // Spawn background execution task
+    // Spawn background execution task. Failures reach the browser through
+    // the engine's PipelineFailed event.
     let sid = session_id.clone();
+    let logs_dir = project_dir.join(".pas/logs").join(&epic_id);
     tokio::spawn(async move {
-        if let Err(e) = run_pipeline_with_streaming(&graph, &sid).await {
+        let recorder = RecorderStart {
+            pipeline_name: &graph.name,
+            pipeline_path: &full_pipeline_path,
+            source: &dot_source,
+        };
+        if let Err(e) =
+            run_pipeline_with_streaming(&graph, &sid, &project_dir, &logs_dir, recorder).await
+        {
             tracing::error!("Pipeline execution failed: {:?}", e);
-            crate::server::stream::publish_event(
-                &sid,
-                serde_json::to_string(&serde_json::json!({
-                    "type": "error",
-                    "message": format!("Pipeline failed: {}", e),
-                }))
-                .unwrap_or_default(),
-            );
         }
         crate::server::stream::clear_session_state(&sid);
     });
@@ -164,177 +167,428 @@ pub async fn start_execution(
 }
 
 /// Execute a pipeline graph with streaming progress events.
+///
+/// Runs the graph through [`attractor_pipeline::PipelineExecutor`] — the same
+/// engine `pas run` uses — and forwards its events to the session's SSE
+/// stream. Human gates ask through a [`crate::server::human::WebInterviewer`]
+/// on the same stream. Checkpoints are written under `logs_dir`, and the run
+/// is recorded in the shared run history when `recorder` is given.
 #[cfg(feature = "ssr")]
 async fn run_pipeline_with_streaming(
     graph: &attractor_pipeline::PipelineGraph,
     session_id: &str,
+    workdir: &std::path::Path,
+    logs_dir: &std::path::Path,
+    recorder: RecorderStart<'_>,
 ) -> Result<attractor_pipeline::PipelineResult, attractor_types::AttractorError> {
-    use attractor_pipeline::{default_registry, select_edge};
-    use attractor_types::{AttractorError, Context};
-    use std::collections::HashMap;
+    use attractor_pipeline::{
+        default_registry_with_interviewer, AgentHandler, EventEmitter, PipelineExecutor,
+    };
+    use attractor_types::Context;
+
+    // Human gates are answered from the execution panel
+    let interviewer = std::sync::Arc::new(crate::server::human::WebInterviewer::new(session_id));
+    let emitter = EventEmitter::default();
+    let recorder = recorder.start(&emitter).await;
+    let mut registry = default_registry_with_interviewer(interviewer);
+    registry.register(AgentHandler::from_env().with_event_emitter(emitter.clone()));
+    let executor = PipelineExecutor::new(registry).with_event_emitter(emitter.clone());
+
+    let labels = graph
+        .all_nodes()
+        .map(|n| (n.id.clone(), n.label.clone()))
+        .collect();
+    let forwarder = tokio::spawn(forward_events(
+        emitter.subscribe(),
+        session_id.to_string(),
+        labels,
+    ));
 
-    let registry = default_registry();
     let context = Context::new();
-
-    // Set workdir to current directory
-    if let Ok(cwd) = std::env::current_dir() {
-        context
-            .set(
-                "workdir",
-                serde_json::Value::String(cwd.to_string_lossy().into()),
-            )
-            .await;
+    context
+        .set(
+            "workdir",
+            serde_json::Value::String(workdir.to_string_lossy().into()),
+        )
+        .await;
+
+    let result = executor.run_with_checkpoint(graph, context, logs_dir).await;
+    if let Some(recorder) = recorder {
+        if let Err(e) = recorder.finish(&result).await {
+            tracing::warn!("Failed to finish run record: {}", e);
+        }
     }
 
-    // Initialize context from graph attrs
-    for (key, val) in &graph.attrs {
-        context.set(key, attr_to_json(val)).await;
+    // Dropping every sender closes the channel, letting the forwarder drain
+    // the remaining events and exit.
+    drop(executor);
+    drop(emitter);
+    if let Err(e) = forwarder.await {
+        tracing::warn!("Event forwarder task failed: {}", e);
     }
 
-    let mut completed_nodes = Vec::new();
-    let mut node_outcomes = HashMap::new();
-    let mut current_node = graph
-        .start_node()
-        .ok_or_else(|| AttractorError::Other("No start node found".into()))?;
-    let mut total_cost = 0.0;
-
-    loop {
-        // Emit node_start event
-        crate::server::stream::publish_event(
-            session_id,
-            serde_json::to_string(&serde_json::json!({
-                "type": "node_start",
-                "node_id": current_node.id,
-                "label": current_node.label,
-            }))
-            .unwrap_or_default(),
-        );
-
-        // Execute node
-        let handler_type = registry.resolve_type(current_node);
-        let handler = registry
-            .get(&handler_type)
-            .ok_or_else(|| AttractorError::HandlerError {
-                handler: handler_type.clone(),
-                node: current_node.id.clone(),
-                message: format!("No handler for '{}'", handler_type),
-            })?;
-
-        let outcome = handler.execute(current_node, &context, graph).await?;
-
-        // Track cost
-        if let Some(cost) = outcome
-            .context_updates
-            .get(&format!("{}.cost_usd", current_node.id))
-        {
-            if let Some(c) = cost.as_f64() {
-                total_cost += c;
-            }
-        }
-
-        // Emit node_complete event
-        crate::server::stream::publish_event(
-            session_id,
-            serde_json::to_string(&serde_json::json!({
-                "type": "node_complete",
-                "node_id": current_node.id,
-                "status": format!("{:?}", outcome.status),
-                "cost_usd": total_cost,
-                "notes": outcome.notes,
-            }))
-            .unwrap_or_default(),
-        );
+    result
+}
 
-        completed_nodes.push(current_node.id.clone());
-        node_outcomes.insert(current_node.id.clone(), outcome.clone());
+/// What the run history needs to record a pipeline run.
+#[cfg(feature = "ssr")]
+struct RecorderStart<'a> {
+    pipeline_name: &'a str,
+    pipeline_path: &'a std::path::Path,
+    source: &'a str,
+}
 
-        // Apply context updates
-        context.apply_updates(outcome.context_updates.clone()).await;
-        context
-            .set(
-                "outcome",
-                serde_json::Value::String(status_str(outcome.status)),
+#[cfg(feature = "ssr")]
+impl RecorderStart<'_> {
+    /// Open the run store and start recording. Failures disable history for
+    /// this run rather than failing it.
+    async fn start(
+        self,
+        emitter: &attractor_pipeline::EventEmitter,
+    ) -> Option<attractor_pipeline::RunRecorder> {
+        // Paths are stored canonicalized, matching `pas run`
+        let canonical = std::fs::canonicalize(self.pipeline_path)
+            .unwrap_or_else(|_| self.pipeline_path.to_path_buf());
+        let started = async {
+            let store = attractor_pipeline::RunStore::open_default().await?;
+            attractor_pipeline::RunRecorder::start(
+                store,
+                emitter,
+                self.pipeline_name,
+                &canonical.to_string_lossy(),
+                self.source,
             )
-            .await;
-        if let Some(ref label) = outcome.preferred_label {
-            context
-                .set("preferred_label", serde_json::Value::String(label.clone()))
-                .await;
+            .await
+        };
+        match started.await {
+            Ok(recorder) => Some(recorder),
+            Err(e) => {
+                tracing::warn!("Run history disabled: {}", e);
+                None
+            }
         }
+    }
+}
 
-        // Terminal check
-        if current_node.shape == "Msquare" {
-            break;
-        }
+/// Forward engine events to a session's SSE stream until the channel closes.
+#[cfg(feature = "ssr")]
+async fn forward_events(
+    mut rx: tokio::sync::broadcast::Receiver<attractor_pipeline::PipelineEvent>,
+    session_id: String,
+    labels: std::collections::HashMap<String, String>,
+) {
+    use tokio::sync::broadcast::error::RecvError;
 
-        // Select next edge
-        let resolve = |key: &str| -> String {
-            match key {
-                "outcome" => format!("{:?}", outcome.status),
-                "preferred_label" => outcome.preferred_label.clone().unwrap_or_default(),
-                _ => String::new(),
-            }
-        };
-        let next_edge = select_edge(&current_node.id, &outcome, &resolve, graph);
-
-        match next_edge {
-            Some(edge) => {
-                let next_id = edge.to.clone();
-                current_node = graph.node(&next_id).ok_or_else(|| {
-                    AttractorError::Other(format!("Edge target '{}' not found", next_id))
-                })?;
+    let mut total_cost = 0.0;
+    loop {
+        match rx.recv().await {
+            Ok(event) => {
+                if let Some(payload) = sse_payload(event, &labels, &mut total_cost) {
+                    let text = serde_json::to_string(&payload).unwrap_or_default();
+                    if payload["type"] == "node_output" {
+                        crate::server::stream::publish_live_event(&session_id, text);
+                    } else {
+                        crate::server::stream::publish_event(&session_id, text);
+                    }
+                }
             }
-            None => {
-                return Err(AttractorError::Other(
-                    "No outgoing edge from non-terminal node".into(),
-                ));
+            Err(RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    "SSE forwarder for {} lagged, skipped {} events",
+                    session_id,
+                    skipped
+                );
             }
+            Err(RecvError::Closed) => break,
         }
     }
+}
 
-    // Emit pipeline_complete
-    crate::server::stream::publish_event(
-        session_id,
-        serde_json::to_string(&serde_json::json!({
+/// Map an engine event onto the JSON message shape the execution panel reads.
+///
+/// `total_cost` accumulates node costs so `node_complete` and
+/// `pipeline_complete` can report the running total.
+#[cfg(feature = "ssr")]
+fn sse_payload(
+    event: attractor_pipeline::PipelineEvent,
+    labels: &std::collections::HashMap<String, String>,
+    total_cost: &mut f64,
+) -> Option<serde_json::Value> {
+    use attractor_pipeline::PipelineEvent;
+    use serde_json::json;
+
+    let payload = match event {
+        PipelineEvent::PipelineStarted {
+            pipeline_name,
+            node_count,
+        } => json!({
+            "type": "pipeline_start",
+            "pipeline_name": pipeline_name,
+            "node_count": node_count,
+        }),
+        PipelineEvent::PipelineCompleted {
+            completed_nodes,
+            duration_ms,
+            ..
+        } => json!({
             "type": "pipeline_complete",
-            "total_cost_usd": total_cost,
+            "total_cost_usd": *total_cost,
             "completed_nodes": completed_nodes,
-        }))
-        .unwrap_or_default(),
-    );
-
-    Ok(attractor_pipeline::PipelineResult {
-        completed_nodes,
-        node_outcomes,
-        final_context: context.snapshot().await,
-    })
+            "duration_ms": duration_ms,
+        }),
+        PipelineEvent::PipelineFailed { error, .. } => json!({
+            "type": "error",
+            "message": format!("Pipeline failed: {}", error),
+        }),
+        PipelineEvent::StageStarted {
+            node_id,
+            handler_type,
+        } => json!({
+            "type": "node_start",
+            "label": labels.get(&node_id).cloned().unwrap_or_else(|| node_id.clone()),
+            "node_id": node_id,
+            "handler_type": handler_type,
+        }),
+        PipelineEvent::StageCompleted {
+            node_id,
+            status,
+            duration_ms,
+            cost_usd,
+            notes,
+            ..
+        } => {
+            *total_cost += cost_usd.unwrap_or(0.0);
+            json!({
+                "type": "node_complete",
+                "node_id": node_id,
+                "status": status,
+                "cost_usd": *total_cost,
+                "notes": notes,
+                "duration_ms": duration_ms,
+            })
+        }
+        PipelineEvent::StageFailed {
+            node_id,
+            error,
+            duration_ms,
+        } => json!({
+            "type": "node_failed",
+            "node_id": node_id,
+            "message": error,
+            "duration_ms": duration_ms,
+        }),
+        PipelineEvent::StageRetrying {
+            node_id,
+            attempt,
+            delay_ms,
+            reason,
+        } => json!({
+            "type": "node_retry",
+            "node_id": node_id,
+            "attempt": attempt,
+            "delay_ms": delay_ms,
+            "message": reason,
+        }),
+        PipelineEvent::EdgeSelected {
+            from_node,
+            to_node,
+            edge_label,
+        } => json!({
+            "type": "edge_selected",
+            "from": from_node,
+            "to": to_node,
+            "label": edge_label,
+        }),
+        PipelineEvent::GoalGateChecked { node_id, satisfied } => json!({
+            "type": "goal_gate",
+            "node_id": node_id,
+            "satisfied": satisfied,
+        }),
+        PipelineEvent::CheckpointSaved { node_id } => json!({
+            "type": "checkpoint",
+            "node_id": node_id,
+        }),
+        PipelineEvent::TextDelta { node_id, text } => json!({
+            "type": "node_output",
+            "node_id": node_id,
+            "text": text,
+        }),
+        // Context key churn is too noisy for the UI.
+        PipelineEvent::ContextUpdated { .. } => return None,
+        // Child runs are reported, with their cost, by their parent node.
+        PipelineEvent::Child { .. } => return None,
+    };
+    Some(payload)
 }
 
-#[cfg(feature = "ssr")]
-fn attr_to_json(attr: &attractor_dot::AttributeValue) -> serde_json::Value {
-    use attractor_dot::AttributeValue;
-    match attr {
-        AttributeValue::String(s) => serde_json::Value::String(s.clone()),
-        AttributeValue::Integer(i) => serde_json::Value::Number(serde_json::Number::from(*i)),
-        AttributeValue::Float(f) => serde_json::Number::from_f64(*f)
-            .map(serde_json::Value::Number)
-            .unwrap_or(serde_json::Value::Null),
-        AttributeValue::Boolean(b) => serde_json::Value::Bool(*b),
-        AttributeValue::Duration(d) => {
-            serde_json::Value::Number(serde_json::Number::from(d.as_secs()))
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+    use attractor_pipeline::PipelineEvent;
+    use std::collections::HashMap;
+
+    fn completed(node_id: &str, cost_usd: Option<f64>) -> PipelineEvent {
+        PipelineEvent::StageCompleted {
+            node_id: node_id.into(),
+            status: "success".into(),
+            duration_ms: 12,
+            cost_usd,
+            turns: None,
+            notes: format!("{} done", node_id),
         }
     }
-}
 
-#[cfg(feature = "ssr")]
-fn status_str(status: attractor_types::StageStatus) -> String {
-    use attractor_types::StageStatus;
-    match status {
-        StageStatus::Success => "success",
-        StageStatus::PartialSuccess => "partial_success",
-        StageStatus::Retry => "retry",
-        StageStatus::Fail => "fail",
-        StageStatus::Skipped => "skipped",
+    #[test]
+    fn stage_events_map_to_node_messages() {
+        let labels = HashMap::from([("plan".to_string(), "Plan it".to_string())]);
+        let mut total_cost = 0.0;
+
+        let started = sse_payload(
+            PipelineEvent::StageStarted {
+                node_id: "plan".into(),
+                handler_type: "codergen".into(),
+            },
+            &labels,
+            &mut total_cost,
+        )
+        .unwrap();
+        assert_eq!(started["type"], "node_start");
+        assert_eq!(started["label"], "Plan it");
+        assert_eq!(started["handler_type"], "codergen");
+
+        // Unlabelled nodes fall back to their ID
+        let started = sse_payload(
+            PipelineEvent::StageStarted {
+                node_id: "build".into(),
+                handler_type: "tool".into(),
+            },
+            &labels,
+            &mut total_cost,
+        )
+        .unwrap();
+        assert_eq!(started["label"], "build");
+
+        let failed = sse_payload(
+            PipelineEvent::StageFailed {
+                node_id: "build".into(),
+                error: "exit 1".into(),
+                duration_ms: 5,
+            },
+            &labels,
+            &mut total_cost,
+        )
+        .unwrap();
+        assert_eq!(failed["type"], "node_failed");
+        assert_eq!(failed["message"], "exit 1");
+    }
+
+    #[test]
+    fn costs_accumulate_into_the_running_total() {
+        let labels = HashMap::new();
+        let mut total_cost = 0.0;
+
+        let first = sse_payload(completed("plan", Some(0.25)), &labels, &mut total_cost).unwrap();
+        assert_eq!(first["type"], "node_complete");
+        assert_eq!(first["cost_usd"], 0.25);
+        assert_eq!(first["notes"], "plan done");
+        let second = sse_payload(completed("start", None), &labels, &mut total_cost).unwrap();
+        assert_eq!(second["cost_usd"], 0.25);
+        sse_payload(completed("build", Some(0.5)), &labels, &mut total_cost);
+
+        let done = sse_payload(
+            PipelineEvent::PipelineCompleted {
+                pipeline_name: "p".into(),
+                completed_nodes: vec!["plan".into(), "build".into()],
+                duration_ms: 100,
+            },
+            &labels,
+            &mut total_cost,
+        )
+        .unwrap();
+        assert_eq!(done["type"], "pipeline_complete");
+        assert_eq!(done["total_cost_usd"], 0.75);
+        assert_eq!(
+            done["completed_nodes"],
+            serde_json::json!(["plan", "build"])
+        );
+    }
+
+    #[test]
+    fn text_deltas_map_to_node_output() {
+        let mut total_cost = 0.0;
+        let delta = PipelineEvent::TextDelta {
+            node_id: "impl".into(),
+            text: "Reading the ".into(),
+        };
+        let output = sse_payload(delta, &HashMap::new(), &mut total_cost).unwrap();
+        assert_eq!(output["type"], "node_output");
+        assert_eq!(output["node_id"], "impl");
+        assert_eq!(output["text"], "Reading the ");
+    }
+
+    #[test]
+    fn child_and_context_events_are_not_forwarded() {
+        let labels = HashMap::new();
+        let mut total_cost = 0.0;
+
+        let child = PipelineEvent::Child {
+            node_id: "mgr".into(),
+            event: Box::new(completed("task", Some(0.5))),
+        };
+        assert!(sse_payload(child, &labels, &mut total_cost).is_none());
+        let context = PipelineEvent::ContextUpdated {
+            node_id: "plan".into(),
+            keys: vec!["plan.result".into()],
+        };
+        assert!(sse_payload(context, &labels, &mut total_cost).is_none());
+        assert_eq!(total_cost, 0.0);
+    }
+
+    #[test]
+    fn failures_and_routing_map_to_their_messages() {
+        let labels = HashMap::new();
+        let mut total_cost = 0.0;
+
+        let failed = sse_payload(
+            PipelineEvent::PipelineFailed {
+                pipeline_name: "p".into(),
+                error: "budget".into(),
+            },
+            &labels,
+            &mut total_cost,
+        )
+        .unwrap();
+        assert_eq!(failed["type"], "error");
+        assert_eq!(failed["message"], "Pipeline failed: budget");
+
+        let edge = sse_payload(
+            PipelineEvent::EdgeSelected {
+                from_node: "plan".into(),
+                to_node: "build".into(),
+                edge_label: Some("ok".into()),
+            },
+            &labels,
+            &mut total_cost,
+        )
+        .unwrap();
+        assert_eq!(edge["type"], "edge_selected");
+        assert_eq!(edge["from"], "plan");
+        assert_eq!(edge["to"], "build");
+        assert_eq!(edge["label"], "ok");
+
+        let retry = sse_payload(
+            PipelineEvent::StageRetrying {
+                node_id: "build".into(),
+                attempt: 2,
+                delay_ms: 400,
+                reason: "timeout".into(),
+            },
+            &labels,
+            &mut total_cost,
+        )
+        .unwrap();
+        assert_eq!(retry["type"], "node_retry");
+        assert_eq!(retry["attempt"], 2);
+        assert_eq!(retry["message"], "timeout");
     }
-    .to_string()
 }
@@ -0,0 +1,264 @@
+//! Human gates answered from the browser.
+//!
+//! [`WebInterviewer`] publishes a `human_question` event on the session's SSE
+//! stream and waits for the execution panel to post the answer back through
+//! [`answer_question`]. Timeouts are enforced by the `TimeoutInterviewer`
+//! that `WaitHumanHandler` wraps around every interviewer.
+
+use leptos::prelude::*;
+use leptos::server_fn::error::NoCustomError;
+
+#[cfg(feature = "ssr")]
+use attractor_pipeline::{Answer, Interviewer, Question};
+#[cfg(feature = "ssr")]
+use std::collections::HashMap;
+#[cfg(feature = "ssr")]
+use tokio::sync::oneshot;
+
+// Questions waiting for an answer, keyed by question_id.
+#[cfg(feature = "ssr")]
+lazy_static::lazy_static! {
+    static ref PENDING: std::sync::Mutex<HashMap<String, oneshot::Sender<Answer>>> =
+        std::sync::Mutex::new(HashMap::new());
+}
+
+/// Answer a pending human gate question.
+///
+/// `choice` is the clicked option, or the typed text when the user submits
+/// free text without picking one. Blank `custom_text` is dropped.
+#[server]
+pub async fn answer_question(
+    question_id: String,
+    choice: String,
+    custom_text: Option<String>,
+) -> Result<(), ServerFnError<NoCustomError>> {
+    let answer = Answer {
+        choice,
+        custom_text: custom_text.filter(|t| !t.trim().is_empty()),
+        timed_out: false,
+    };
+    if deliver_answer(&question_id, answer) {
+        Ok(())
+    } else {
+        Err(ServerFnError::<NoCustomError>::ServerError(format!(
+            "Question {} is no longer waiting for an answer",
+            question_id
+        )))
+    }
+}
+
+/// Hand `answer` to the interviewer waiting on `question_id`.
+///
+/// Returns false if the question was already answered or timed out.
+#[cfg(feature = "ssr")]
+fn deliver_answer(question_id: &str, answer: Answer) -> bool {
+    let sender = PENDING.lock().unwrap().remove(question_id);
+    sender.is_some_and(|tx| tx.send(answer).is_ok())
+}
+
+/// [`Interviewer`] that asks questions through a session's SSE stream.
+#[cfg(feature = "ssr")]
+pub struct WebInterviewer {
+    session_id: String,
+}
+
+#[cfg(feature = "ssr")]
+impl WebInterviewer {
+    pub fn new(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: session_id.into(),
+        }
+    }
+
+    fn publish(&self, payload: serde_json::Value) {
+        crate::server::stream::publish_event(
+            &self.session_id,
+            serde_json::to_string(&payload).unwrap_or_default(),
+        );
+    }
+}
+
+/// How a pending question was settled.
+#[cfg(feature = "ssr")]
+#[derive(Debug, Clone, PartialEq)]
+enum Resolution {
+    Answered(String),
+    TimedOut,
+    Cancelled,
+}
+
+/// Clears a pending question when `ask` returns or is cancelled.
+///
+/// `WaitHumanHandler` wraps every interviewer in a `TimeoutInterviewer`, which
+/// drops the `ask` future on timeout; the guard then withdraws the question
+/// from the browser. A future dropped before the question's deadline belongs
+/// to a cancelled run, not a timeout.
+#[cfg(feature = "ssr")]
+struct PendingGuard<'a> {
+    interviewer: &'a WebInterviewer,
+    question_id: String,
+    deadline: Option<std::time::Instant>,
+    resolution: Option<Resolution>,
+}
+
+#[cfg(feature = "ssr")]
+impl PendingGuard<'_> {
+    fn resolution(&self) -> Resolution {
+        self.resolution
+            .clone()
+            .unwrap_or_else(|| match self.deadline {
+                Some(deadline) if std::time::Instant::now() >= deadline => Resolution::TimedOut,
+                _ => Resolution::Cancelled,
+            })
+    }
+}
+
+#[cfg(feature = "ssr")]
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        PENDING.lock().unwrap().remove(&self.question_id);
+        let resolution = self.resolution();
+        let (choice, reason) = match resolution {
+            Resolution::Answered(ref choice) => (Some(choice.as_str()), "answered"),
+            Resolution::TimedOut => (None, "timed_out"),
+            Resolution::Cancelled => (None, "cancelled"),
+        };
+        self.interviewer.publish(serde_json::json!({
+            "type": "human_answered",
+            "question_id": self.question_id,
+            "choice": choice,
+            "reason": reason,
+            "timed_out": resolution == Resolution::TimedOut,
+        }));
+    }
+}
+
+#[cfg(feature = "ssr")]
+#[async_trait::async_trait]
+impl Interviewer for WebInterviewer {
+    async fn ask(&self, question: &Question) -> attractor_types::Result<Answer> {
+        let question_id = uuid::Uuid::new_v4().to_string();
+        let (tx, rx) = oneshot::channel();
+        PENDING.lock().unwrap().insert(question_id.clone(), tx);
+
+        let mut guard = PendingGuard {
+            interviewer: self,
+            question_id: question_id.clone(),
+            deadline: question.timeout.map(|t| std::time::Instant::now() + t),
+            resolution: None,
+        };
+
+        self.publish(serde_json::json!({
+            "type": "human_question",
+            "question_id": question_id,
+            "message": question.prompt,
+            "choices": question.choices,
+            "default": question.default,
+            "timeout_ms": question.timeout.map(|t| t.as_millis() as u64),
+        }));
+
+        let answer = match rx.await {
+            Ok(answer) => answer,
+            Err(_) => {
+                guard.resolution = Some(Resolution::Cancelled);
+                return Err(attractor_types::AttractorError::Other(format!(
+                    "Question {} was dropped without an answer",
+                    question_id
+                )));
+            }
+        };
+        guard.resolution = Some(Resolution::Answered(answer.choice.clone()));
+        Ok(answer)
+    }
+}
+
+#[cfg(all(test, feature = "ssr"))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn question(timeout: Option<Duration>) -> Question {
+        Question {
+            prompt: "Ship it?".into(),
+            choices: vec!["Approve".into(), "Reject".into()],
+            default: None,
+            timeout,
+        }
+    }
+
+    fn pending_ids() -> Vec<String> {
+        PENDING.lock().unwrap().keys().cloned().collect()
+    }
+
+    #[tokio::test]
+    async fn answer_resolves_pending_question() {
+        let interviewer = Arc::new(WebInterviewer::new("human-test-answer"));
+        let before = pending_ids();
+        let asking = tokio::spawn({
+            let interviewer = interviewer.clone();
+            async move { interviewer.ask(&question(None)).await }
+        });
+
+        // Wait for the question to be registered
+        let question_id = loop {
+            if let Some(id) = pending_ids().into_iter().find(|id| !before.contains(id)) {
+                break id;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        };
+
+        let delivered = deliver_answer(
+            &question_id,
+            Answer {
+                choice: "Reject".into(),
+                custom_text: Some("Missing an index".into()),
+                timed_out: false,
+            },
+        );
+        assert!(delivered);
+
+        let answer = asking.await.unwrap().unwrap();
+        assert_eq!(answer.choice, "Reject");
+        assert_eq!(answer.custom_text.as_deref(), Some("Missing an index"));
+        assert!(!deliver_answer(&question_id, answer));
+    }
+
+    #[tokio::test]
+    async fn timeout_answers_with_default() {
+        use attractor_pipeline::TimeoutInterviewer;
+
+        let interviewer =
+            TimeoutInterviewer::new(Arc::new(WebInterviewer::new("human-test-timeout")));
+        let mut q = question(Some(Duration::from_millis(20)));
+        q.default = Some("Reject".into());
+
+        let answer = interviewer.ask(&q).await.unwrap();
+        assert!(answer.timed_out);
+        assert_eq!(answer.choice, "Reject");
+    }
+
+    #[test]
+    fn guard_tells_timeouts_from_cancelled_runs() {
+        let interviewer = WebInterviewer::new("human-test-guard");
+        let guard = |deadline, resolution| PendingGuard {
+            interviewer: &interviewer,
+            question_id: "q".into(),
+            deadline,
+            resolution,
+        };
+        let now = std::time::Instant::now();
+
+        let passed = guard(Some(now - Duration::from_millis(1)), None);
+        assert_eq!(passed.resolution(), Resolution::TimedOut);
+        let pending = guard(Some(now + Duration::from_secs(60)), None);
+        assert_eq!(pending.resolution(), Resolution::Cancelled);
+        let untimed = guard(None, None);
+        assert_eq!(untimed.resolution(), Resolution::Cancelled);
+        let answered = guard(None, Some(Resolution::Answered("Approve".into())));
+        assert_eq!(
+            answered.resolution(),
+            Resolution::Answered("Approve".into())
+        );
+    }
+}
@@ -1,7 +1,9 @@
 pub mod execute;
+pub mod human;
 
 // Leptos server functions: available to both SSR and WASM (for RPC stubs)
 pub mod projects;
+pub mod runs;
 
 // SSR-only modules (no client stubs needed)
 #[cfg(feature = "ssr")]
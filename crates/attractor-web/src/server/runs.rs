@@ -0,0 +1,96 @@
+//! Run history server functions, backed by the pipeline crate's run store
+//! (`~/.pas/runs.db`, shared with `pas runs`).
+
+use leptos::prelude::*;
+use leptos::server_fn::error::NoCustomError;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "ssr")]
+pub use attractor_pipeline::{NodeRecord, RunRecord};
+
+// Client-side type definitions (when not SSR)
+#[cfg(not(feature = "ssr"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: i64,
+    pub pipeline_name: String,
+    pub pipeline_path: String,
+    pub pipeline_hash: String,
+    pub status: String,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    pub total_cost_usd: f64,
+    pub error: Option<String>,
+}
+
+#[cfg(not(feature = "ssr"))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeRecord {
+    pub node_id: String,
+    pub status: String,
+    pub duration_ms: u64,
+    pub cost_usd: Option<f64>,
+    pub turns: Option<u64>,
+    pub result: String,
+    pub finished_at: String,
+}
+
+/// A run together with its node records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunDetail {
+    pub run: RunRecord,
+    pub nodes: Vec<NodeRecord>,
+}
+
+#[cfg(feature = "ssr")]
+async fn open_store() -> Result<attractor_pipeline::RunStore, ServerFnError<NoCustomError>> {
+    attractor_pipeline::RunStore::open_default()
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))
+}
+
+/// Recent runs of pipelines inside a project's folder, newest first.
+#[server]
+pub async fn list_project_runs(
+    project_id: i64,
+) -> Result<Vec<RunRecord>, ServerFnError<NoCustomError>> {
+    let pool = use_context::<sqlx::SqlitePool>()
+        .ok_or_else(|| ServerFnError::<NoCustomError>::ServerError("No database pool".into()))?;
+    let project = crate::server::db::get_project(&pool, project_id)
+        .await
+        .map_err(|e| {
+            ServerFnError::<NoCustomError>::ServerError(format!("Failed to get project: {}", e))
+        })?;
+
+    // Run paths are stored canonicalized
+    let folder = std::fs::canonicalize(&project.folder_path)
+        .map(|p| p.to_string_lossy().into_owned())
+        .unwrap_or(project.folder_path);
+    let runs = open_store()
+        .await?
+        .list_runs(None, 200)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    Ok(runs
+        .into_iter()
+        .filter(|r| std::path::Path::new(&r.pipeline_path).starts_with(&folder))
+        .collect())
+}
+
+/// A run with its per-node records.
+#[server]
+pub async fn get_run_detail(run_id: i64) -> Result<RunDetail, ServerFnError<NoCustomError>> {
+    let store = open_store().await?;
+    let run = store
+        .get_run(run_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?
+        .ok_or_else(|| {
+            ServerFnError::<NoCustomError>::ServerError(format!("No run with id {}", run_id))
+        })?;
+    let nodes = store
+        .run_nodes(run_id)
+        .await
+        .map_err(|e| ServerFnError::<NoCustomError>::ServerError(e.to_string()))?;
+    Ok(RunDetail { run, nodes })
+}
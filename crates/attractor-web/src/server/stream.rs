@@ -142,7 +142,13 @@ pub fn publish_event(session_id: &str, event: String) {
         }
     }
 
-    // Publish to broadcast channel
+    publish_live_event(session_id, event);
+}
+
+/// Publish an event to the clients connected to a session's SSE stream
+/// without keeping it for reconnecting browsers. Used for streamed text,
+/// which would otherwise crowd the node events out of the cache.
+pub fn publish_live_event(session_id: &str, event: String) {
     let channels = CHANNELS.read().unwrap();
     if let Some(sender) = channels.get(session_id) {
         match sender.send(event) {